@@ -59,14 +59,14 @@ fn test_registry_module_name_lookup() {
     registry.insert(BytecodeEntry {
         name: "mypackage".to_string(),
         source_path: temp.path().join("mypackage/__init__.py"),
-        bytecode: vec![0xe3, 1, 2, 3],
+        bytecode: vec![0xe3, 1, 2, 3].into(),
         is_package: true,
     });
 
     registry.insert(BytecodeEntry {
         name: "mypackage.submodule".to_string(),
         source_path: temp.path().join("mypackage/submodule.py"),
-        bytecode: vec![0xe3, 4, 5, 6],
+        bytecode: vec![0xe3, 4, 5, 6].into(),
         is_package: false,
     });
 
@@ -337,7 +337,7 @@ fn test_registry_concurrent_insert() {
                 reg.insert(BytecodeEntry {
                     name: format!("module_{}", i),
                     source_path: temp_path.join(format!("module_{}.py", i)),
-                    bytecode: vec![0xe3, i as u8],
+                    bytecode: vec![0xe3, i as u8].into(),
                     is_package: false,
                 });
             })
@@ -454,7 +454,7 @@ fn test_registry_source_path_retrieval() {
     registry.insert(BytecodeEntry {
         name: "mymodule".to_string(),
         source_path: expected_path.clone(),
-        bytecode: vec![1, 2, 3],
+        bytecode: vec![1, 2, 3].into(),
         is_package: false,
     });
 
@@ -475,7 +475,7 @@ fn test_registry_empty_and_len() {
     registry.insert(BytecodeEntry {
         name: "test".to_string(),
         source_path: temp.path().join("test.py"),
-        bytecode: vec![1],
+        bytecode: vec![1].into(),
         is_package: false,
     });
 
@@ -287,22 +287,27 @@ fn test_physics_check_memory_reset() {
             kill(child, Signal::SIGCONT).expect("Failed to SIGCONT worker");
             eprintln!("[supervisor] Worker resumed - waiting for UFFD faults...");
 
-            // Polling loop: handle UFFD faults while worker runs
+            // Event-driven loop: block in epoll_wait for UFFD readiness
+            // instead of alternating a WNOHANG poll with a 1ms sleep.
+            use tach_core::snapshot::FaultLoopResult;
             loop {
-                // Check if worker has exited using non-blocking wait
-                match waitpid(child, Some(WaitPidFlag::WNOHANG)) {
-                    Ok(WaitStatus::Exited(_, code)) => {
-                        eprintln!("[supervisor] Worker exited with code {}", code);
-                        if code == 0 {
-                            eprintln!("[supervisor] ✓ Physics Check PASSED!");
-                        } else {
-                            eprintln!("[supervisor] ✗ Physics Check FAILED (exit code: {})!", code);
-                        }
+                match snapshot_mgr.run_fault_loop(&[worker_nix_pid], Some(Duration::from_secs(5))) {
+                    Ok(FaultLoopResult::WorkerExited(_)) => {
+                        // The worker's UFFD hung up because it exited; reap it.
                         break;
                     }
-                    Ok(WaitStatus::StillAlive) => {
-                        // Worker still running, poll for UFFD events
+                    Ok(FaultLoopResult::TimedOut) | Ok(FaultLoopResult::Idle) => {
+                        // No fault activity in the window; fall through to
+                        // check whether the worker has exited anyway.
+                    }
+                    Err(e) => {
+                        eprintln!("[supervisor] Fault loop error: {}", e);
+                        break;
                     }
+                }
+
+                match waitpid(child, Some(WaitPidFlag::WNOHANG)) {
+                    Ok(WaitStatus::StillAlive) => continue,
                     Ok(status) => {
                         eprintln!("[supervisor] Worker status: {:?}", status);
                         break;
@@ -312,20 +317,23 @@ fn test_physics_check_memory_reset() {
                         break;
                     }
                 }
+            }
 
-                // Poll UFFD for pending page faults
-                match snapshot_mgr.handle_pending_faults(worker_nix_pid) {
-                    Ok(handled) if handled > 0 => {
-                        eprintln!("[supervisor] Handled {} page faults", handled);
-                    }
-                    Ok(_) => {}
-                    Err(e) => {
-                        eprintln!("[supervisor] Fault handling error: {}", e);
+            match waitpid(child, Some(WaitPidFlag::WNOHANG)) {
+                Ok(WaitStatus::Exited(_, code)) => {
+                    eprintln!("[supervisor] Worker exited with code {}", code);
+                    if code == 0 {
+                        eprintln!("[supervisor] ✓ Physics Check PASSED!");
+                    } else {
+                        eprintln!("[supervisor] ✗ Physics Check FAILED (exit code: {})!", code);
                     }
                 }
-
-                // Brief sleep to avoid busy-waiting
-                std::thread::sleep(Duration::from_millis(1));
+                Ok(status) => {
+                    eprintln!("[supervisor] Worker status after fault loop: {:?}", status);
+                }
+                Err(e) => {
+                    eprintln!("[supervisor] waitpid error: {}", e);
+                }
             }
 
             // Cleanup
@@ -6,14 +6,15 @@ use clap::{Parser, Subcommand, ValueEnum};
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 // =============================================================================
 // CLI Configuration (Phase 5.1)
 // =============================================================================
 
 /// Output format for tach results
-#[derive(ValueEnum, Clone, Debug, Default, PartialEq)]
+#[derive(ValueEnum, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     /// Human-readable CLI output (to stderr)
     #[default]
@@ -22,26 +23,121 @@ pub enum OutputFormat {
     Json,
 }
 
+/// Reporter style for the human-facing side of a run. Orthogonal to
+/// `--format`: `--format=json` always emits NDJSON regardless of this.
+#[derive(ValueEnum, Clone, Debug, Default, PartialEq)]
+pub enum ReporterKind {
+    /// One line per test with ✓/✗ (the existing `HumanReporter`)
+    #[default]
+    Pretty,
+    /// One character per test result, wrapped at 80 columns
+    Dot,
+    /// TAP v13 (`ok`/`not ok` lines) to stdout, for CI harnesses that ingest TAP
+    Tap,
+    /// JUnit XML only, to the path given by --junit-xml (default: junit.xml)
+    Junit,
+    /// Newline-delimited JSON events (the same stream `--format=json` emits)
+    /// to stdout, for editors/CI that want incremental structured results
+    /// without switching `--format`.
+    Ndjson,
+}
+
 /// Tach CLI - Fast Python Test Runner
 #[derive(Parser)]
 #[command(name = "tach", version, about = "Fast Python Test Runner")]
 pub struct Cli {
-    /// Output format (also: TACH_FORMAT env var)
-    #[arg(long, value_enum, default_value_t = OutputFormat::Human, env = "TACH_FORMAT")]
-    pub format: OutputFormat,
-
-    /// Path to generate JUnit XML report (also: TACH_JUNIT_XML env var)
+    /// Output format (also: TACH_FORMAT env var, or [tool.tach] format in
+    /// pyproject.toml; an explicit flag here wins over both)
+    #[arg(long, value_enum, env = "TACH_FORMAT")]
+    pub format: Option<OutputFormat>,
+
+    /// Human-facing reporter style: pretty (default), dot, tap, junit, or
+    /// ndjson. Has no effect when --format=json (which always emits the same
+    /// ndjson stream `--reporter=ndjson` does).
+    #[arg(long, value_enum, default_value_t = ReporterKind::Pretty)]
+    pub reporter: ReporterKind,
+
+    /// Path to generate JUnit XML report, or `-` to write the document to
+    /// stdout instead (also: TACH_JUNIT_XML env var, or [tool.tach]
+    /// junit_xml in pyproject.toml)
     #[arg(long, env = "TACH_JUNIT_XML")]
     pub junit_xml: Option<std::path::PathBuf>,
 
+    /// Collect per-test line coverage and write an LCOV report
+    /// (`<dir>/lcov.info`). Each test's fork-per-test isolation keeps traces
+    /// from bleeding between tests, so the merged report is exact.
+    #[arg(long)]
+    pub coverage: Option<std::path::PathBuf>,
+
     /// Watch for changes and re-run tests automatically
     #[arg(long, short = 'w')]
     pub watch: bool,
 
-    /// Disable filesystem and network isolation (runs without CAP_SYS_ADMIN)
+    /// Disable filesystem and network isolation (runs without CAP_SYS_ADMIN).
+    /// Also settable via [tool.tach] no_isolation in pyproject.toml.
     #[arg(long)]
     pub no_isolation: bool,
 
+    /// Number of parallel worker slots (default: number of CPUs). Also
+    /// settable via [tool.tach] workers in pyproject.toml.
+    #[arg(long, env = "TACH_WORKERS")]
+    pub workers: Option<usize>,
+
+    /// Run tests in a randomized order. For `tach list`, shuffles the
+    /// printed node ids instead, to preview the order `tach test --shuffle`
+    /// would use without running anything.
+    #[arg(long)]
+    pub shuffle: bool,
+
+    /// Seed for --shuffle; re-run with the seed printed by a previous run to
+    /// reproduce its exact test order. Implies --shuffle.
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Stream worker stdout/stderr live instead of only showing it once a
+    /// test finishes. Useful for watching a long-running test's progress.
+    #[arg(long)]
+    pub stream_logs: bool,
+
+    /// Only run tests whose fully-qualified id (`path::test_name`) contains
+    /// this substring. Applied after path filtering. For `tach list`, this
+    /// is instead evaluated as a pytest `-k`-style boolean expression
+    /// (`and`/`or`/`not`/parens) of case-insensitive id substrings.
+    #[arg(long)]
+    pub filter: Option<String>,
+
+    /// `tach list` only: a pytest `-m`-style boolean expression
+    /// (`and`/`or`/`not`/parens) over marker names, e.g. `"slow and not
+    /// skip"`. Each identifier must exactly match a `@pytest.mark.<name>`.
+    #[arg(long)]
+    pub markers: Option<String>,
+
+    /// Like --filter, but matches the fully-qualified test id against a
+    /// regular expression instead of a plain substring.
+    #[arg(long)]
+    pub filter_regex: Option<String>,
+
+    /// Stop scheduling new tests after N failures (bare --fail-fast means 1)
+    #[arg(long, num_args = 0..=1, default_missing_value = "1")]
+    pub fail_fast: Option<usize>,
+
+    /// Only select tests whose file path matches this glob (repeatable). A
+    /// test is selected if it matches any --include (or none are given).
+    /// Also settable via [tool.tach] include in pyproject.toml.
+    #[arg(long)]
+    pub include: Vec<String>,
+
+    /// Exclude tests whose file path matches this glob (repeatable), e.g.
+    /// `tests/**/*_slow.py`. Also settable via [tool.tach] exclude.
+    #[arg(long)]
+    pub ignore: Vec<String>,
+
+    /// Also collect doctests (`>>>` blocks in module/class/function
+    /// docstrings) as discoverable tests. Only affects `tach list`; has no
+    /// effect on `tach test` yet, since doctests aren't executable tests.
+    #[arg(long)]
+    pub doctest: bool,
+
     /// Test directory or file pattern
     #[arg(default_value = ".")]
     pub path: String,
@@ -71,33 +167,55 @@ struct PyProject {
 #[derive(Deserialize, Default)]
 struct ToolConfig {
     pytest_env: Option<HashMap<String, String>>,
+    tach: Option<TachConfig>,
 }
 
-/// Load environment variables from pyproject.toml and apply to current process.
+/// Project-level defaults read from `[tool.tach]` in pyproject.toml.
 ///
-/// This function reads `[tool.pytest_env]` section from pyproject.toml and
-/// sets each key-value pair as an environment variable. Must be called
-/// BEFORE forking the Zygote so workers inherit the environment.
-pub fn load_env_from_pyproject(root: &Path) {
+/// Every field is optional: an unset field falls back to the CLI flag's own
+/// default. An explicit CLI flag always overrides the value found here.
+#[derive(Deserialize, Default, Debug, Clone, PartialEq)]
+pub struct TachConfig {
+    pub format: Option<OutputFormat>,
+    pub junit_xml: Option<PathBuf>,
+    pub no_isolation: Option<bool>,
+    pub workers: Option<usize>,
+    pub include: Option<Vec<String>>,
+    pub exclude: Option<Vec<String>>,
+}
+
+/// Read and parse pyproject.toml at `root`, if it exists.
+fn read_pyproject(root: &Path) -> Option<PyProject> {
     let config_path = root.join("pyproject.toml");
     if !config_path.exists() {
-        return;
+        return None;
     }
 
     let contents = match fs::read_to_string(&config_path) {
         Ok(c) => c,
         Err(e) => {
             eprintln!("[config] Failed to read pyproject.toml: {}", e);
-            return;
+            return None;
         }
     };
 
-    let pyproject: PyProject = match toml::from_str(&contents) {
-        Ok(p) => p,
+    match toml::from_str(&contents) {
+        Ok(p) => Some(p),
         Err(e) => {
             eprintln!("[config] Failed to parse pyproject.toml: {}", e);
-            return;
+            None
         }
+    }
+}
+
+/// Load environment variables from pyproject.toml and apply to current process.
+///
+/// This function reads `[tool.pytest_env]` section from pyproject.toml and
+/// sets each key-value pair as an environment variable. Must be called
+/// BEFORE forking the Zygote so workers inherit the environment.
+pub fn load_env_from_pyproject(root: &Path) {
+    let Some(pyproject) = read_pyproject(root) else {
+        return;
     };
 
     if let Some(tool) = pyproject.tool {
@@ -110,6 +228,17 @@ pub fn load_env_from_pyproject(root: &Path) {
     }
 }
 
+/// Load project-level CLI defaults from the `[tool.tach]` section of
+/// pyproject.toml. Unlike `load_env_from_pyproject`, this does not mutate
+/// the environment - it returns parsed defaults for the caller to merge
+/// with CLI flags (precedence: CLI flag > pyproject.toml > built-in default).
+pub fn load_tach_config(root: &Path) -> TachConfig {
+    read_pyproject(root)
+        .and_then(|p| p.tool)
+        .and_then(|t| t.tach)
+        .unwrap_or_default()
+}
+
 // =============================================================================
 // Unit Tests
 // =============================================================================
@@ -240,4 +369,44 @@ select = ["E", "F"]
         let env_vars = pyproject.tool.unwrap().pytest_env.unwrap();
         assert!(env_vars.is_empty());
     }
+
+    #[test]
+    fn test_load_tach_config_parses_tool_tach_section() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("pyproject.toml");
+
+        let toml_content = r#"
+[tool.tach]
+format = "json"
+no_isolation = true
+workers = 4
+include = ["tests/**/*.py"]
+exclude = ["tests/**/*_slow.py"]
+"#;
+        std::fs::write(&config_path, toml_content).unwrap();
+
+        let config = load_tach_config(temp_dir.path());
+        assert_eq!(config.format, Some(OutputFormat::Json));
+        assert_eq!(config.no_isolation, Some(true));
+        assert_eq!(config.workers, Some(4));
+        assert_eq!(config.include, Some(vec!["tests/**/*.py".to_string()]));
+        assert_eq!(config.exclude, Some(vec!["tests/**/*_slow.py".to_string()]));
+    }
+
+    #[test]
+    fn test_load_tach_config_defaults_when_section_absent() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("pyproject.toml");
+        std::fs::write(&config_path, "[tool.pytest_env]\n").unwrap();
+
+        let config = load_tach_config(temp_dir.path());
+        assert_eq!(config, TachConfig::default());
+    }
+
+    #[test]
+    fn test_load_tach_config_defaults_when_file_absent() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = load_tach_config(temp_dir.path());
+        assert_eq!(config, TachConfig::default());
+    }
 }
@@ -0,0 +1,150 @@
+//! Per-test line coverage aggregation and LCOV output.
+//!
+//! Fed from `protocol::CoverageReport`s as they arrive on `TestResult`s
+//! (see `Scheduler::try_collect_result_for_reporter`). Because each test
+//! runs in its own fork of the Zygote, one test's trace can't bleed into
+//! another's, so merging is just a per-line hit-count sum across every
+//! report - no attribution ambiguity the way a threaded/in-process tracer
+//! would have.
+
+use crate::protocol::CoverageReport;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Accumulates execution counts per line per file across every test's
+/// `CoverageReport`, then renders them as a standard LCOV file.
+#[derive(Debug, Default)]
+pub struct CoverageMerger {
+    /// file path -> line number -> execution count
+    files: BTreeMap<String, BTreeMap<u32, u32>>,
+}
+
+impl CoverageMerger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fold one test's report into the running totals.
+    pub fn merge(&mut self, report: &CoverageReport) {
+        for (file, lines) in &report.files {
+            let counts = self.files.entry(file.clone()).or_default();
+            for &line in lines {
+                *counts.entry(line).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Render the accumulated counts as LCOV `tracefile` text: one
+    /// `SF:`/`DA:`*/`end_of_record` block per file, files in path order and
+    /// lines in ascending order within each file.
+    pub fn to_lcov(&self) -> String {
+        let mut out = String::new();
+        for (file, lines) in &self.files {
+            out.push_str(&format!("SF:{}\n", file));
+            for (&line, &count) in lines {
+                out.push_str(&format!("DA:{},{}\n", line, count));
+            }
+            out.push_str("end_of_record\n");
+        }
+        out
+    }
+
+    /// Write the merged report to `<dir>/lcov.info`, creating `dir` if it
+    /// doesn't exist yet.
+    pub fn write_lcov(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join("lcov.info");
+        let mut file = fs::File::create(path)?;
+        file.write_all(self.to_lcov().as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(test_id: u32, files: Vec<(&str, Vec<u32>)>) -> CoverageReport {
+        CoverageReport {
+            test_id,
+            files: files
+                .into_iter()
+                .map(|(f, lines)| (f.to_string(), lines))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_merge_counts_single_report() {
+        let mut merger = CoverageMerger::new();
+        merger.merge(&report(0, vec![("app.py", vec![1, 2, 2, 3])]));
+
+        let lcov = merger.to_lcov();
+        assert!(lcov.contains("SF:app.py\n"));
+        assert!(lcov.contains("DA:1,1\n"));
+        assert!(lcov.contains("DA:2,2\n"));
+        assert!(lcov.contains("DA:3,1\n"));
+        assert!(lcov.contains("end_of_record\n"));
+    }
+
+    #[test]
+    fn test_merge_sums_across_multiple_tests() {
+        let mut merger = CoverageMerger::new();
+        merger.merge(&report(0, vec![("app.py", vec![5])]));
+        merger.merge(&report(1, vec![("app.py", vec![5, 5])]));
+
+        let lcov = merger.to_lcov();
+        assert!(lcov.contains("DA:5,3\n"));
+    }
+
+    #[test]
+    fn test_merge_keeps_files_separate() {
+        let mut merger = CoverageMerger::new();
+        merger.merge(&report(0, vec![("a.py", vec![1])]));
+        merger.merge(&report(1, vec![("b.py", vec![1])]));
+
+        let lcov = merger.to_lcov();
+        assert!(lcov.contains("SF:a.py\n"));
+        assert!(lcov.contains("SF:b.py\n"));
+        // Each file gets its own end_of_record, not a shared one.
+        assert_eq!(lcov.matches("end_of_record").count(), 2);
+    }
+
+    #[test]
+    fn test_to_lcov_orders_files_and_lines() {
+        let mut merger = CoverageMerger::new();
+        merger.merge(&report(0, vec![("z.py", vec![9, 2])]));
+        merger.merge(&report(1, vec![("a.py", vec![1])]));
+
+        let lcov = merger.to_lcov();
+        let a_pos = lcov.find("SF:a.py").unwrap();
+        let z_pos = lcov.find("SF:z.py").unwrap();
+        assert!(a_pos < z_pos, "files should be sorted by path");
+
+        let da2_pos = lcov.find("DA:2,").unwrap();
+        let da9_pos = lcov.find("DA:9,").unwrap();
+        assert!(da2_pos < da9_pos, "lines should be sorted ascending");
+    }
+
+    #[test]
+    fn test_empty_merger_produces_empty_lcov() {
+        let merger = CoverageMerger::new();
+        assert_eq!(merger.to_lcov(), "");
+    }
+
+    #[test]
+    fn test_write_lcov_creates_directory_and_file() {
+        let dir = std::env::temp_dir().join(format!("tach_coverage_test_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut merger = CoverageMerger::new();
+        merger.merge(&report(0, vec![("app.py", vec![1])]));
+        merger.write_lcov(&dir).unwrap();
+
+        let contents = fs::read_to_string(dir.join("lcov.info")).unwrap();
+        assert!(contents.contains("SF:app.py"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -5,38 +5,69 @@
 //!
 //! ## Architecture
 //!
-//! 1. **DebugServer**: Unix socket listener at `/tmp/tach_debug_{pid}.sock`
+//! 1. **DebugServer**: Unix socket listener at `/tmp/tach_debug_{pid}.sock`, plus a
+//!    line-oriented control socket at `/tmp/tach_ctl_{pid}.sock` for a `tachctl`
+//!    client to `list`/`attach`/`signal` against.
 //! 2. **TerminalManager**: Switches terminal between Raw/Cooked modes
-//! 3. **Session Loop**: Bidirectional pipe: stdin <-> socket, socket <-> stdout
+//! 3. **Session Loop**: Bidirectional pipe, preferring a real PTY (`openpty()`) handed
+//!    off to the worker over `ScmRights` so `isatty()`/readline/job control work on its
+//!    end; falls back to tunneling raw bytes over the `UnixStream` when `openpty` isn't
+//!    available.
+//!
+//! ## Multiple workers, one operator
+//!
+//! A worker that hits `breakpoint()` connects to the debug socket and sends its
+//! worker id as the first four bytes (little-endian `u32`) before any PTY/tunnel
+//! traffic. `DebugServer` stashes the connection in a `pending` table keyed by
+//! that id instead of servicing it inline, so several workers can be waiting at
+//! once. An operator (local, or a `tachctl` client connected to the control
+//! socket over SSH/port-forwarding) issues `list` to see who's waiting and
+//! `attach <worker_id>` to bind that worker's stream to their own terminal
+//! for the duration of the session; `signal <worker_id> <SIG>` pokes a
+//! worker without attaching at all. `detach` is deliberately not a real
+//! command: the control socket answers it with an explanation rather than
+//! silently falling into "unknown command", but `attach` still holds the
+//! connection until the tunnel itself closes. Doing a real detach would need
+//! an out-of-band escape sequence that unwinds `tunnel_sockets`/`run_tunnel`
+//! without closing the worker's end and re-queues it in `pending` - enough
+//! surface area (and enough ways to wedge a session half-detached) that it's
+//! out of scope until there's an actual need for it.
 //!
 //! ## Safety
 //!
-//! - Only one worker can be debugged at a time (exclusive locking via socket accept)
+//! - Only one worker can be actively attached-to at a time (the control session
+//!   blocks for the duration of `attach`), but others may queue in `pending`
 //! - Panic hook restores terminal on crash to prevent corruption
-//! - Socket file cleaned up on Drop
+//! - Socket files cleaned up on Drop
 
 use anyhow::{Context, Result};
-use nix::sys::signal::{kill, Signal};
+use nix::pty::{openpty, OpenptyResult};
+use nix::sys::signal::{kill, signal, SigHandler, Signal};
+use nix::sys::socket::{sendmsg, ControlMessage, MsgFlags};
 use nix::sys::termios::{cfmakeraw, tcgetattr, tcsetattr, SetArg, Termios};
 use nix::unistd::Pid;
-use std::fs;
-use std::io::{self, Read, Write};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, IoSlice, Read, Write};
 use std::os::unix::io::{AsRawFd, RawFd};
 use std::os::unix::net::{UnixListener, UnixStream};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
 /// Pause all workers by sending SIGSTOP
 ///
 /// This freezes workers to prevent their logs from interleaving with pdb output.
-/// The debugging worker is excluded from pausing.
-fn pause_workers(worker_pids: &[i32], debug_worker_pid: Option<i32>) {
+/// `excluded` is skipped - the worker being debugged, plus any other worker
+/// already parked at a breakpoint of its own (it's blocked on its own debug
+/// connection, not running a test, so SIGSTOP would be pointless and SIGCONT
+/// at resume time would be wrong).
+fn pause_workers(worker_pids: &[i32], excluded: &[i32]) {
     for &pid in worker_pids {
-        if Some(pid) == debug_worker_pid {
-            continue; // Don't stop the worker we're debugging!
+        if excluded.contains(&pid) {
+            continue;
         }
         if pid > 0 {
             let _ = kill(Pid::from_raw(pid), Signal::SIGSTOP);
@@ -44,9 +75,14 @@ fn pause_workers(worker_pids: &[i32], debug_worker_pid: Option<i32>) {
     }
 }
 
-/// Resume all paused workers by sending SIGCONT
-fn resume_workers(worker_pids: &[i32]) {
+/// Resume paused workers by sending SIGCONT, skipping `excluded` for the same
+/// reason `pause_workers` does - a worker still parked at its own breakpoint
+/// should stay parked, not be woken up just because a sibling's session ended.
+fn resume_workers(worker_pids: &[i32], excluded: &[i32]) {
     for &pid in worker_pids {
+        if excluded.contains(&pid) {
+            continue;
+        }
         if pid > 0 {
             let _ = kill(Pid::from_raw(pid), Signal::SIGCONT);
         }
@@ -153,6 +189,33 @@ impl TerminalManager {
     pub fn mode(&self) -> TerminalMode {
         self.current_mode
     }
+
+    /// Read the operator's current terminal size via `TIOCGWINSZ` on stdin.
+    pub fn window_size(&self) -> Result<libc::winsize> {
+        query_window_size()
+    }
+}
+
+/// Issue `TIOCGWINSZ` on stdin to read the real terminal's current
+/// `{ws_row, ws_col, ws_xpixel, ws_ypixel}`.
+fn query_window_size() -> Result<libc::winsize> {
+    let mut ws: libc::winsize = unsafe { std::mem::zeroed() };
+    let rc = unsafe { libc::ioctl(libc::STDIN_FILENO, libc::TIOCGWINSZ, &mut ws) };
+    if rc != 0 {
+        return Err(io::Error::last_os_error()).context("TIOCGWINSZ on stdin failed");
+    }
+    Ok(ws)
+}
+
+/// Apply a window size to `fd` (the PTY master or slave - either updates the
+/// pair) via `TIOCSWINSZ`, so the child's terminal dimensions track the
+/// operator's real one.
+fn apply_window_size(fd: RawFd, ws: &libc::winsize) -> Result<()> {
+    let rc = unsafe { libc::ioctl(fd, libc::TIOCSWINSZ, ws) };
+    if rc != 0 {
+        return Err(io::Error::last_os_error()).context("TIOCSWINSZ failed");
+    }
+    Ok(())
 }
 
 impl Drop for TerminalManager {
@@ -162,40 +225,310 @@ impl Drop for TerminalManager {
     }
 }
 
+/// A duplex I/O handle that can be split into an independent read half and
+/// write half via cloning, the way `UnixStream` and `File` both already do.
+trait DuplexEndpoint: Read + Write + Send + 'static {
+    fn try_clone_endpoint(&self) -> io::Result<Self>
+    where
+        Self: Sized;
+}
+
+impl DuplexEndpoint for UnixStream {
+    fn try_clone_endpoint(&self) -> io::Result<Self> {
+        self.try_clone()
+    }
+}
+
+impl DuplexEndpoint for File {
+    fn try_clone_endpoint(&self) -> io::Result<Self> {
+        self.try_clone()
+    }
+}
+
+/// Allocate a PTY and hand the slave end off to the worker over `stream` via
+/// `SCM_RIGHTS`, so the worker can `dup2` it onto its stdio and get a real
+/// controlling terminal (`isatty()` true, readline/job control working).
+///
+/// Returns the PTY master, which the supervisor tunnels local stdin/stdout
+/// through in place of the raw socket. Fails (falling back to the raw-socket
+/// path in [`DebugServer::handle_session`]) if `openpty()` or the fd handoff
+/// is unavailable, e.g. a sandboxed environment with no `/dev/ptmx`.
+fn open_worker_pty(stream: &UnixStream) -> Result<File> {
+    let OpenptyResult { master, slave } =
+        openpty(None, None).context("openpty() unavailable")?;
+
+    let slave_fd = slave.as_raw_fd();
+    let iov = [IoSlice::new(b"PTY")];
+    let cmsg = [ControlMessage::ScmRights(&[slave_fd])];
+    sendmsg::<()>(stream.as_raw_fd(), &iov, &cmsg, MsgFlags::empty(), None)
+        .context("failed to hand PTY slave fd to worker")?;
+
+    // The worker now holds its own dup of the slave fd from the SCM_RIGHTS
+    // handoff; our copy can close once `slave` drops here.
+    Ok(File::from(master))
+}
+
+/// Tunnel local stdin/stdout through `endpoint` until it's closed from the
+/// other end (EOF) or stdin closes. Used for both the PTY master and the
+/// raw-socket fallback - the loop itself doesn't care which one it's given.
+fn run_tunnel(endpoint: impl DuplexEndpoint) -> Result<()> {
+    let mut write_half = endpoint;
+    let mut read_half = write_half
+        .try_clone_endpoint()
+        .context("failed to clone debug tunnel endpoint")?;
+
+    let running = Arc::new(AtomicBool::new(true));
+    let running_clone = running.clone();
+
+    // Thread 1: Read from the endpoint, write to stdout
+    let stdout_thread = thread::spawn(move || {
+        let mut stdout = io::stdout();
+        let mut buf = [0u8; 1024];
+
+        while running_clone.load(Ordering::SeqCst) {
+            match read_half.read(&mut buf) {
+                Ok(0) => {
+                    // EOF - other end closed
+                    running_clone.store(false, Ordering::SeqCst);
+                    break;
+                }
+                Ok(n) => {
+                    if stdout.write_all(&buf[..n]).is_err() {
+                        break;
+                    }
+                    let _ = stdout.flush();
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(10));
+                }
+                Err(_) => {
+                    running_clone.store(false, Ordering::SeqCst);
+                    break;
+                }
+            }
+        }
+    });
+
+    // Main thread: Read from stdin, write to the endpoint
+    let mut stdin = io::stdin();
+    let mut buf = [0u8; 1];
+
+    while running.load(Ordering::SeqCst) {
+        match stdin.read(&mut buf) {
+            Ok(0) => {
+                // EOF on stdin
+                break;
+            }
+            Ok(n) => {
+                // Forward to the endpoint (including Ctrl+C as 0x03)
+                if write_half.write_all(&buf[..n]).is_err() {
+                    break;
+                }
+                let _ = write_half.flush();
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(10));
+            }
+            Err(_) => break,
+        }
+    }
+
+    // Signal reader thread to stop
+    running.store(false, Ordering::SeqCst);
+
+    // Wait for reader thread (with timeout)
+    let _ = stdout_thread.join();
+
+    Ok(())
+}
+
+/// Set by `on_sigwinch` when the operator's terminal is resized mid-session;
+/// cleared by the watcher thread in [`run_pty_tunnel`] once it re-applies
+/// the new size.
+static WINCH_RECEIVED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_sigwinch(_: libc::c_int) {
+    WINCH_RECEIVED.store(true, Ordering::SeqCst);
+}
+
+/// Tunnel stdin/stdout through a PTY master like [`run_tunnel`], but also
+/// keep the worker's terminal dimensions in sync with the operator's real
+/// one: push the current size once at session start and again on every
+/// `SIGWINCH`.
+///
+/// The `SIGWINCH` handler only sets a flag; a dedicated watcher thread polls
+/// it and re-applies the size, so a resize never has to interrupt the
+/// blocking stdin/stdout reads in `run_tunnel` (no risk of deadlocking those
+/// threads waiting on a read that a signal can't unblock).
+fn run_pty_tunnel(master: File) -> Result<()> {
+    let master_fd = master.as_raw_fd();
+
+    if let Ok(ws) = query_window_size() {
+        let _ = apply_window_size(master_fd, &ws);
+    }
+
+    let prior_handler = unsafe { signal(Signal::SIGWINCH, SigHandler::Handler(on_sigwinch)) }
+        .context("failed to install SIGWINCH handler")?;
+    WINCH_RECEIVED.store(false, Ordering::SeqCst);
+
+    let watch_running = Arc::new(AtomicBool::new(true));
+    let watch_running_clone = watch_running.clone();
+    let watcher = thread::spawn(move || {
+        while watch_running_clone.load(Ordering::SeqCst) {
+            if WINCH_RECEIVED.swap(false, Ordering::SeqCst) {
+                if let Ok(ws) = query_window_size() {
+                    let _ = apply_window_size(master_fd, &ws);
+                }
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    });
+
+    let result = run_tunnel(master);
+
+    watch_running.store(false, Ordering::SeqCst);
+    let _ = watcher.join();
+
+    // Restore whatever SIGWINCH disposition was registered before us.
+    let _ = unsafe { signal(Signal::SIGWINCH, prior_handler) };
+
+    result
+}
+
+/// Read the 4-byte little-endian worker id a worker sends immediately after
+/// connecting to the debug socket, before any PTY/tunnel bytes flow. This is
+/// the only framing the debug protocol needs: everything after it is opaque
+/// PTY/socket bytes tunneled straight through.
+fn read_worker_id(stream: &mut UnixStream) -> Result<u32> {
+    let mut buf = [0u8; 4];
+    stream
+        .read_exact(&mut buf)
+        .context("failed to read worker id from debug connection")?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+/// Who the worker's tunnel is being shown to: the supervisor's own terminal
+/// (the original, local-only behavior), or a remote `tachctl` client attached
+/// over the control socket.
+enum DebugPeer {
+    LocalTerminal,
+    Remote(UnixStream),
+}
+
+/// Tunnel two arbitrary duplex endpoints into each other until either side
+/// closes. Used for the remote-attach case, where neither end is the
+/// supervisor's own stdin/stdout - compare [`run_tunnel`], which is the
+/// stdin/stdout-specific sibling of this for the local case.
+fn tunnel_sockets(a: impl DuplexEndpoint, b: impl DuplexEndpoint) -> Result<()> {
+    let mut a_write = a;
+    let mut a_read = a_write
+        .try_clone_endpoint()
+        .context("failed to clone debug tunnel endpoint")?;
+    let mut b_write = b;
+    let mut b_read = b_write
+        .try_clone_endpoint()
+        .context("failed to clone debug tunnel endpoint")?;
+
+    let running = Arc::new(AtomicBool::new(true));
+    let running_clone = running.clone();
+
+    let forward = thread::spawn(move || {
+        let mut buf = [0u8; 1024];
+        while running_clone.load(Ordering::SeqCst) {
+            match a_read.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    if b_write.write_all(&buf[..n]).is_err() {
+                        break;
+                    }
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(10));
+                }
+                Err(_) => break,
+            }
+        }
+        running_clone.store(false, Ordering::SeqCst);
+    });
+
+    let mut buf = [0u8; 1024];
+    while running.load(Ordering::SeqCst) {
+        match b_read.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                if a_write.write_all(&buf[..n]).is_err() {
+                    break;
+                }
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(10));
+            }
+            Err(_) => break,
+        }
+    }
+    running.store(false, Ordering::SeqCst);
+
+    let _ = forward.join();
+    Ok(())
+}
+
 /// The Debug Server accepting worker connections
 ///
-/// Listens on a Unix socket for workers that hit breakpoints.
-/// When a connection is received, switches to raw mode and tunnels I/O.
+/// Listens on a Unix socket for workers that hit breakpoints, and a second
+/// line-oriented control socket for an operator (local or remote, via
+/// `tachctl`) to list/attach/signal against them.
 pub struct DebugServer {
     socket_path: PathBuf,
     listener: UnixListener,
+    ctl_socket_path: PathBuf,
+    ctl_listener: UnixListener,
+    /// Worker connections that have hit a breakpoint and are waiting for an
+    /// operator to debug them, in the order they arrived.
+    pending: Mutex<Vec<PendingSession>>,
+}
+
+/// A worker parked at a breakpoint, waiting for an operator to attach.
+struct PendingSession {
+    worker_pid: u32,
+    stream: UnixStream,
 }
 
 impl DebugServer {
     /// Create a new debug server
     ///
-    /// Creates socket at `/tmp/tach_debug_{supervisor_pid}.sock`
+    /// Creates the debug socket at `/tmp/tach_debug_{supervisor_pid}.sock`
+    /// and the control socket at `/tmp/tach_ctl_{supervisor_pid}.sock`.
     pub fn new() -> Result<Self> {
         let pid = std::process::id();
         let socket_path = PathBuf::from(format!("/tmp/tach_debug_{}.sock", pid));
+        let ctl_socket_path = PathBuf::from(format!("/tmp/tach_ctl_{}.sock", pid));
 
-        // Clean up any stale socket file
-        if socket_path.exists() {
-            fs::remove_file(&socket_path).context("Failed to remove stale debug socket")?;
+        for path in [&socket_path, &ctl_socket_path] {
+            if path.exists() {
+                fs::remove_file(path).context("Failed to remove stale debug socket")?;
+            }
         }
 
         let listener = UnixListener::bind(&socket_path).context("Failed to bind debug socket")?;
-
-        // Set non-blocking so we can check for connections without blocking scheduler
         listener
             .set_nonblocking(true)
             .context("Failed to set socket non-blocking")?;
 
+        let ctl_listener =
+            UnixListener::bind(&ctl_socket_path).context("Failed to bind debug control socket")?;
+        ctl_listener
+            .set_nonblocking(true)
+            .context("Failed to set control socket non-blocking")?;
+
         eprintln!("[debugger] Listening on {}", socket_path.display());
+        eprintln!("[debugger] Control socket at {}", ctl_socket_path.display());
 
         Ok(Self {
             socket_path,
             listener,
+            ctl_socket_path,
+            ctl_listener,
+            pending: Mutex::new(Vec::new()),
         })
     }
 
@@ -204,129 +537,237 @@ impl DebugServer {
         &self.socket_path
     }
 
-    /// Check if a worker is waiting to connect (non-blocking)
-    pub fn try_accept(&self) -> Option<UnixStream> {
-        match self.listener.accept() {
+    /// Get the control socket path for `tachctl` to connect
+    pub fn ctl_socket_path(&self) -> &Path {
+        &self.ctl_socket_path
+    }
+
+    /// Poll the debug socket for every worker currently waiting to report a
+    /// breakpoint hit (non-blocking), accepting all of them rather than just
+    /// one: in a parallel run several workers can hit `breakpoint()` nearly
+    /// simultaneously, and none of them should block on `accept()` with no
+    /// visibility while an operator works through an earlier one. Each
+    /// accepted connection's worker id handshake is read and the session is
+    /// queued in `pending` rather than serviced inline. Returns the ids
+    /// queued by this call, in arrival order.
+    pub fn poll_pending(&self) -> Vec<u32> {
+        let mut accepted_ids = Vec::new();
+        loop {
+            let (mut stream, _) = match self.listener.accept() {
+                Ok(accepted) => accepted,
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    eprintln!("[debugger] Accept error: {}", e);
+                    break;
+                }
+            };
+            stream
+                .set_nonblocking(false)
+                .expect("failed to set accepted debug stream blocking");
+            match read_worker_id(&mut stream) {
+                Ok(worker_pid) => {
+                    self.pending
+                        .lock()
+                        .unwrap()
+                        .push(PendingSession { worker_pid, stream });
+                    accepted_ids.push(worker_pid);
+                }
+                Err(e) => {
+                    eprintln!("[debugger] Dropping connection with bad handshake: {}", e);
+                }
+            }
+        }
+        accepted_ids
+    }
+
+    /// Worker ids currently parked at a breakpoint, waiting for an operator,
+    /// in the order they queued up.
+    pub fn pending(&self) -> Vec<u32> {
+        self.pending.lock().unwrap().iter().map(|s| s.worker_pid).collect()
+    }
+
+    /// Check if a client is waiting on the control socket (non-blocking).
+    pub fn try_accept_ctl(&self) -> Option<UnixStream> {
+        match self.ctl_listener.accept() {
             Ok((stream, _)) => Some(stream),
             Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => None,
             Err(e) => {
-                eprintln!("[debugger] Accept error: {}", e);
+                eprintln!("[debugger] Control accept error: {}", e);
                 None
             }
         }
     }
 
+    /// Service one control-socket client until it disconnects, dispatching
+    /// `list`, `attach <worker_id>`, and `signal <worker_id> <SIG>` commands.
+    /// `attach` blocks for the duration of the debug session - the client's
+    /// own terminal (not the supervisor's) is tunneled to the worker.
+    /// `detach` is recognized but intentionally unsupported: it answers with
+    /// an explanation instead of either pretending to work (the old
+    /// behavior) or falling through to "unknown command" (indistinguishable
+    /// from a typo). Doing it for real needs an out-of-band escape signal
+    /// that unwinds the tunnel without closing the worker's connection and
+    /// re-queues it in `pending`, which doesn't exist yet - see the module
+    /// docs.
+    pub fn handle_ctl_session(&self, ctl_stream: UnixStream, worker_pids: &[i32]) -> Result<()> {
+        let mut writer = ctl_stream.try_clone().context("failed to clone control stream")?;
+        let reader = BufReader::new(ctl_stream);
+
+        for line in reader.lines() {
+            let line = line.context("failed to read control command")?;
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("list") => {
+                    let ids = self.pending();
+                    writeln!(writer, "{} worker(s) waiting: {:?}", ids.len(), ids)?;
+                }
+                Some("attach") => {
+                    let Some(id_str) = parts.next() else {
+                        writeln!(writer, "usage: attach <worker_id>")?;
+                        continue;
+                    };
+                    let Ok(worker_id) = id_str.parse::<u32>() else {
+                        writeln!(writer, "invalid worker id: {id_str}")?;
+                        continue;
+                    };
+                    let Some(stream) = self.take_pending(worker_id) else {
+                        writeln!(writer, "no worker {worker_id} waiting")?;
+                        continue;
+                    };
+                    let client = writer.try_clone().context("failed to clone control stream")?;
+                    writeln!(writer, "attached to worker {worker_id}, tunneling")?;
+                    self.handle_session(
+                        stream,
+                        worker_pids,
+                        Some(worker_id as i32),
+                        DebugPeer::Remote(client),
+                    )?;
+                    writeln!(writer, "detached from worker {worker_id}")?;
+
+                    // Don't blindly resume everyone and leave the rest of the
+                    // queue to silently sit there - tell the operator who's
+                    // still waiting so they can pick up the next one.
+                    let remaining = self.pending();
+                    if !remaining.is_empty() {
+                        writeln!(
+                            writer,
+                            "{} more worker(s) waiting: {:?}",
+                            remaining.len(),
+                            remaining
+                        )?;
+                    }
+                }
+                Some("signal") => {
+                    let (Some(id_str), Some(sig_str)) = (parts.next(), parts.next()) else {
+                        writeln!(writer, "usage: signal <worker_id> <SIG>")?;
+                        continue;
+                    };
+                    let Ok(worker_id) = id_str.parse::<i32>() else {
+                        writeln!(writer, "invalid worker id: {id_str}")?;
+                        continue;
+                    };
+                    match Signal::from_str(sig_str) {
+                        Ok(sig) => {
+                            let _ = kill(Pid::from_raw(worker_id), sig);
+                            writeln!(writer, "sent {sig_str} to worker {worker_id}")?;
+                        }
+                        Err(_) => writeln!(writer, "unknown signal: {sig_str}")?,
+                    }
+                }
+                Some("detach") => {
+                    writeln!(
+                        writer,
+                        "detach is not supported: attach holds the connection for the \
+                         duration of the session, there is no out-of-band escape to park \
+                         it back in the pending queue"
+                    )?;
+                }
+                Some(other) => writeln!(writer, "unknown command: {other}")?,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+
     /// Handle a full debug session (blocking)
     ///
     /// This function:
     /// 1. Pauses all other workers (SIGSTOP) to prevent log interleaving
-    /// 2. Enters raw terminal mode
-    /// 3. Pipes stdin <-> socket and socket <-> stdout bidirectionally
-    /// 4. Restores cooked mode and resumes workers (SIGCONT) when socket closes
+    /// 2. For a local session, enters raw terminal mode on the supervisor's own tty
+    /// 3. Hands the worker a real PTY over the socket (falling back to raw
+    ///    socket bytes if unavailable) and tunnels it to `peer` - the
+    ///    supervisor's stdin/stdout, or a remote control client's socket
+    /// 4. Restores cooked mode and resumes workers (SIGCONT) when the session ends
     ///
     /// # Arguments
     /// * `stream` - Connected socket from worker hitting breakpoint
     /// * `worker_pids` - PIDs of all active workers (for pausing)
     /// * `debug_worker_pid` - PID of the worker being debugged (won't be paused)
-    pub fn handle_session(
+    fn handle_session(
         &self,
-        mut stream: UnixStream,
+        stream: UnixStream,
         worker_pids: &[i32],
         debug_worker_pid: Option<i32>,
+        peer: DebugPeer,
     ) -> Result<()> {
         // Phase 4.2: Mark that we're debugging (affects signal handling)
         // SIGINT will be ignored by signal handler - raw mode handles Ctrl+C
         crate::lifecycle::IS_DEBUGGING.store(true, Ordering::SeqCst);
 
+        // Exclude the worker we're debugging plus anyone else already parked
+        // at their own breakpoint - they're blocked on their own debug
+        // connection, not running a test, so neither SIGSTOP nor the
+        // matching SIGCONT below should touch them.
+        let mut excluded: Vec<i32> = self.pending().into_iter().map(|pid| pid as i32).collect();
+        excluded.extend(debug_worker_pid);
+
         // BOSS REFINEMENT #1: Pause other workers to prevent log interleaving
-        pause_workers(worker_pids, debug_worker_pid);
+        pause_workers(worker_pids, &excluded);
 
         eprintln!("\n[tach] Worker hit breakpoint. Entering Debug Mode...");
         eprintln!("[tach] Type 'c' to continue, 'q' to quit pdb.\n");
 
-        // Create terminal manager and enter raw mode
-        let mut terminal = TerminalManager::new()?;
-        terminal.enter_raw_mode()?;
+        // Only the local-terminal case touches the supervisor's own tty.
+        let mut terminal = match peer {
+            DebugPeer::LocalTerminal => {
+                let mut t = TerminalManager::new()?;
+                t.enter_raw_mode()?;
+                Some(t)
+            }
+            DebugPeer::Remote(_) => None,
+        };
 
         // Set stream to blocking for the debug session
         stream
             .set_nonblocking(false)
             .context("Failed to set stream blocking")?;
 
-        // Clone stream for the reader thread
-        let stream_for_reader = stream.try_clone().context("Failed to clone stream")?;
-
-        // Flag to signal threads to stop
-        let running = Arc::new(AtomicBool::new(true));
-        let running_clone = running.clone();
-
-        // Thread 1: Read from socket, write to stdout
-        let stdout_thread = thread::spawn(move || {
-            let mut stream = stream_for_reader;
-            let mut stdout = io::stdout();
-            let mut buf = [0u8; 1024];
-
-            while running_clone.load(Ordering::SeqCst) {
-                match stream.read(&mut buf) {
-                    Ok(0) => {
-                        // EOF - socket closed
-                        running_clone.store(false, Ordering::SeqCst);
-                        break;
-                    }
-                    Ok(n) => {
-                        if stdout.write_all(&buf[..n]).is_err() {
-                            break;
-                        }
-                        let _ = stdout.flush();
-                    }
-                    Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
-                        thread::sleep(Duration::from_millis(10));
-                    }
-                    Err(_) => {
-                        running_clone.store(false, Ordering::SeqCst);
-                        break;
-                    }
-                }
+        // Prefer a real PTY so the worker's stdio is a terminal (ECHO/ICANON,
+        // isatty() == true, job-control signals flow). Fall back to tunneling
+        // raw bytes over the socket if openpty()/the fd handoff isn't available.
+        let pty = open_worker_pty(&stream);
+        match (pty, peer) {
+            (Ok(master), DebugPeer::LocalTerminal) => run_pty_tunnel(master)?,
+            (Ok(master), DebugPeer::Remote(client)) => tunnel_sockets(master, client)?,
+            (Err(e), DebugPeer::LocalTerminal) => {
+                eprintln!("[debugger] PTY unavailable ({e}), falling back to raw socket tunnel");
+                run_tunnel(stream)?;
             }
-        });
-
-        // Main thread: Read from stdin, write to socket
-        let mut stdin = io::stdin();
-        let mut buf = [0u8; 1];
-
-        // Set stdin to non-blocking for graceful shutdown
-        // Note: We're in raw mode, so reads are character-by-character
-        while running.load(Ordering::SeqCst) {
-            match stdin.read(&mut buf) {
-                Ok(0) => {
-                    // EOF on stdin
-                    break;
-                }
-                Ok(n) => {
-                    // Forward to socket (including Ctrl+C as 0x03)
-                    if stream.write_all(&buf[..n]).is_err() {
-                        break;
-                    }
-                    let _ = stream.flush();
-                }
-                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
-                    thread::sleep(Duration::from_millis(10));
-                }
-                Err(_) => break,
+            (Err(e), DebugPeer::Remote(client)) => {
+                eprintln!("[debugger] PTY unavailable ({e}), falling back to raw socket tunnel");
+                tunnel_sockets(stream, client)?;
             }
         }
 
-        // Signal reader thread to stop
-        running.store(false, Ordering::SeqCst);
-
-        // Wait for reader thread (with timeout)
-        let _ = stdout_thread.join();
-
         // Restore terminal
-        terminal.restore()?;
+        if let Some(terminal) = terminal.as_mut() {
+            terminal.restore()?;
+        }
 
-        // BOSS REFINEMENT #1: Resume all paused workers
-        resume_workers(worker_pids);
+        // BOSS REFINEMENT #1: Resume paused workers, but not any new arrival
+        // that hit a breakpoint of its own while we were debugging this one.
+        let still_excluded: Vec<i32> = self.pending().into_iter().map(|pid| pid as i32).collect();
+        resume_workers(worker_pids, &still_excluded);
 
         // Phase 4.2: Clear debugging flag (affects signal handling)
         crate::lifecycle::IS_DEBUGGING.store(false, Ordering::SeqCst);
@@ -336,10 +777,47 @@ impl DebugServer {
         Ok(())
     }
 
-    /// Cleanup socket file
+    /// Remove and return a specific pending session by worker id, if queued.
+    fn take_pending(&self, worker_pid: u32) -> Option<UnixStream> {
+        let mut pending = self.pending.lock().unwrap();
+        let idx = pending.iter().position(|s| s.worker_pid == worker_pid)?;
+        Some(pending.remove(idx).stream)
+    }
+
+    /// Debug the longest-waiting pending worker on the supervisor's own
+    /// terminal - the local-debugging entry point, used when there's no
+    /// `tachctl` client in the picture.
+    pub fn debug_next(&self, worker_pids: &[i32]) -> Result<()> {
+        let session = {
+            let mut pending = self.pending.lock().unwrap();
+            if pending.is_empty() {
+                anyhow::bail!("no worker waiting");
+            }
+            pending.remove(0)
+        };
+        self.handle_session(
+            session.stream,
+            worker_pids,
+            Some(session.worker_pid as i32),
+            DebugPeer::LocalTerminal,
+        )
+    }
+
+    /// Debug one specific pending worker by id on the supervisor's own
+    /// terminal, out of arrival order if needed.
+    pub fn debug(&self, worker_pid: u32, worker_pids: &[i32]) -> Result<()> {
+        let Some(stream) = self.take_pending(worker_pid) else {
+            anyhow::bail!("no worker {worker_pid} waiting");
+        };
+        self.handle_session(stream, worker_pids, Some(worker_pid as i32), DebugPeer::LocalTerminal)
+    }
+
+    /// Cleanup socket files
     fn cleanup(&self) {
-        if self.socket_path.exists() {
-            let _ = fs::remove_file(&self.socket_path);
+        for path in [&self.socket_path, &self.ctl_socket_path] {
+            if path.exists() {
+                let _ = fs::remove_file(path);
+            }
         }
     }
 }
@@ -408,4 +886,35 @@ mod tests {
         IN_RAW_MODE.store(false, Ordering::SeqCst);
         assert!(!IN_RAW_MODE.load(Ordering::SeqCst));
     }
+
+    #[test]
+    fn test_ctl_socket_path_format() {
+        let pid = std::process::id();
+        let expected_path = format!("/tmp/tach_ctl_{}.sock", pid);
+        assert!(expected_path.starts_with("/tmp/tach_ctl_"));
+        assert!(expected_path.ends_with(".sock"));
+    }
+
+    #[test]
+    fn test_worker_id_handshake_round_trip() {
+        let (mut a, mut b) = UnixStream::pair().unwrap();
+        a.write_all(&42u32.to_le_bytes()).unwrap();
+        assert_eq!(read_worker_id(&mut b).unwrap(), 42);
+    }
+
+    #[test]
+    fn test_pending_is_fifo_and_take_pending_removes_by_id() {
+        let server = DebugServer::new().unwrap();
+        let (a, _keep_a) = UnixStream::pair().unwrap();
+        let (b, _keep_b) = UnixStream::pair().unwrap();
+        server.pending.lock().unwrap().push(PendingSession { worker_pid: 7, stream: a });
+        server.pending.lock().unwrap().push(PendingSession { worker_pid: 3, stream: b });
+
+        // Arrival order, not sorted by id.
+        assert_eq!(server.pending(), vec![7, 3]);
+
+        assert!(server.take_pending(7).is_some());
+        assert_eq!(server.pending(), vec![3]);
+        assert!(server.take_pending(7).is_none());
+    }
 }
@@ -16,6 +16,7 @@ pub enum FixtureScope {
     Function,
     Class,
     Module,
+    Package,
     Session,
 }
 
@@ -25,6 +26,20 @@ impl Default for FixtureScope {
     }
 }
 
+impl FixtureScope {
+    /// Nesting order from narrowest to widest, matching pytest's setup order
+    /// (widest scope is set up first, torn down last).
+    pub fn rank(&self) -> u8 {
+        match self {
+            FixtureScope::Function => 0,
+            FixtureScope::Class => 1,
+            FixtureScope::Module => 2,
+            FixtureScope::Package => 3,
+            FixtureScope::Session => 4,
+        }
+    }
+}
+
 /// A pytest fixture definition
 #[derive(Debug, Clone)]
 pub struct FixtureDefinition {
@@ -36,6 +51,25 @@ pub struct FixtureDefinition {
     /// Some([]) = empty params list
     /// Some(["a", "b"]) = static params extracted from AST
     pub params: Option<Vec<String>>,
+    /// Name of the enclosing `Test*` class, for a fixture defined as a method
+    /// rather than at module level. `None` for module/conftest-level fixtures.
+    pub class_scope: Option<String>,
+}
+
+/// A single `@pytest.mark.parametrize(argnames, argvalues)` decorator on a
+/// test. `argnames` is either a single name or a comma-separated/listed set
+/// for multi-arg parametrization.
+#[derive(Debug, Clone)]
+pub struct ParametrizeArg {
+    pub names: Vec<String>,
+    /// One entry per row in `argvalues`, already rendered the way pytest
+    /// would show it in a node id: a single name's own literal string form,
+    /// or a multi-name row's per-name segments joined with `-`. A dynamic
+    /// (non-literal) value's segment is `<argname><rowindex>`, matching
+    /// pytest's own id-generation fallback rather than bailing the row out.
+    /// `None` if `argvalues` itself isn't a static list (e.g. a function
+    /// call) or argnames/argvalues don't line up.
+    pub rows: Option<Vec<String>>,
 }
 
 /// A test case (function)
@@ -45,10 +79,52 @@ pub struct TestCase {
     pub dependencies: Vec<String>,
     pub is_async: bool,
     pub line_number: usize,
+    /// Present if annotated with `@pytest.mark.xfail(...)`.
+    pub xfail: Option<XfailMarker>,
+    /// `@pytest.mark.parametrize(...)` decorators, one entry per decorator.
+    /// Multiple decorators stack (pytest takes their Cartesian product).
+    pub parametrized_args: Vec<ParametrizeArg>,
+    /// Concrete pytest node-id suffixes after expanding `parametrized_args`'
+    /// Cartesian product (e.g. `test_foo[sqlite-utc]` -> `["sqlite-utc"]`).
+    /// Empty if the test isn't parametrized, or if any decorator's
+    /// `argvalues` couldn't be resolved statically.
+    pub param_sets: Vec<Vec<String>>,
+    /// Every `@pytest.mark.<name>(...)` decorator on the test, `xfail`
+    /// included (alongside the dedicated `xfail` field above, which callers
+    /// needing just the expected-failure fast path can keep using).
+    pub markers: Vec<Marker>,
+    /// `true` for a synthetic entry generated from a docstring's `>>>` block
+    /// rather than an actual `def test_*`/`Test*` method - see
+    /// `collect_doctests`. Always `false` unless discovery was run with
+    /// `include_doctests`.
+    pub is_doctest: bool,
+}
+
+/// A single `@pytest.mark.<name>(...)` or bare `@mark.<name>(...)` decorator,
+/// covering skip/xfail/skipif/custom markers alike. Only static-literal
+/// positional and keyword arguments are recorded (e.g. the reason string for
+/// `skip`/`xfail`, the `condition`/`strict` kwargs) - a dynamic argument
+/// (a variable, a call) is simply omitted rather than bailing the whole
+/// marker out, since most markers are consulted for their name alone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marker {
+    pub name: String,
+    pub args: Vec<String>,
+    pub kwargs: Vec<(String, String)>,
+}
+
+/// Marks a test as an expected failure (`@pytest.mark.xfail`).
+///
+/// `strict` mirrors the decorator's `strict=` kwarg: when `true`, the test
+/// unexpectedly passing (`xpass`) is treated as a run failure rather than
+/// just being surfaced as a warning.
+#[derive(Debug, Clone, Default)]
+pub struct XfailMarker {
+    pub strict: bool,
 }
 
 /// A Python test module (.py file)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TestModule {
     pub path: PathBuf,
     pub tests: Vec<TestCase>,
@@ -69,6 +145,49 @@ impl DiscoveryResult {
     pub fn fixture_count(&self) -> usize {
         self.modules.iter().map(|m| m.fixtures.len()).sum()
     }
+
+    /// Keep only tests whose node id matches `expr` under pytest's `-k`
+    /// semantics: a bare identifier is a case-insensitive substring match
+    /// against the id (see `node_ids`), combined with `and`/`or`/`not`.
+    pub fn filter_by_keyword(&self, expr: &crate::selection::SelectionExpr) -> DiscoveryResult {
+        self.filter_modules(|file, test| {
+            node_ids(file, test).iter().any(|id| {
+                let id = id.to_lowercase();
+                expr.eval(&|ident| id.contains(&ident.to_lowercase()))
+            })
+        })
+    }
+
+    /// Keep only tests whose markers match `expr` under pytest's `-m`
+    /// semantics: a bare identifier must exactly equal one of the test's
+    /// `@pytest.mark.<name>` marker names.
+    pub fn filter_by_markers(&self, expr: &crate::selection::SelectionExpr) -> DiscoveryResult {
+        self.filter_modules(|_file, test| {
+            expr.eval(&|ident| test.markers.iter().any(|m| m.name == ident))
+        })
+    }
+
+    fn filter_modules(&self, mut keep: impl FnMut(&str, &TestCase) -> bool) -> DiscoveryResult {
+        let modules = self
+            .modules
+            .iter()
+            .filter_map(|module| {
+                let file = module.path.to_string_lossy().to_string();
+                let tests: Vec<TestCase> =
+                    module.tests.iter().filter(|test| keep(&file, test)).cloned().collect();
+                if tests.is_empty() {
+                    None
+                } else {
+                    Some(TestModule {
+                        path: module.path.clone(),
+                        tests,
+                        fixtures: module.fixtures.clone(),
+                    })
+                }
+            })
+            .collect();
+        DiscoveryResult { modules }
+    }
 }
 
 /// Convert byte offset to line number (1-indexed)
@@ -87,6 +206,13 @@ pub struct JsonTestInfo {
     pub file: String,
     pub line: usize,
     pub is_async: bool,
+    /// Marker names only (e.g. `["skip", "xfail"]`) - enough for a consumer
+    /// like an editor plugin to report skip/xfail status without needing the
+    /// full `Marker` argument shape.
+    pub markers: Vec<String>,
+    /// `true` for a synthetic doctest entry (see `TestCase::is_doctest`)
+    /// rather than an actual `def test_*`/`Test*` method.
+    pub is_doctest: bool,
 }
 
 /// JSON output for discovery listing
@@ -96,6 +222,35 @@ struct JsonDiscoveryOutput {
     tests: Vec<JsonTestInfo>,
 }
 
+/// JSON output for a live incremental-rescan diff (`version: 2`), emitted
+/// alongside `JsonDiscoveryOutput` so a long-lived watch-mode consumer can
+/// patch its in-memory test tree instead of replacing it wholesale on
+/// every change - see `incremental::IncrementalState::rescan`.
+#[derive(Serialize)]
+struct JsonDiscoveryDiff {
+    version: u32,
+    added: Vec<String>,
+    removed: Vec<String>,
+    /// `(old_id, new_id)` pairs for a test whose file changed but whose
+    /// bare name didn't.
+    moved: Vec<(String, String)>,
+}
+
+/// Dump an `IncrementalState::rescan` diff as JSON to stdout, in the same
+/// envelope style as `dump_json` but `version: 2` so consumers can tell a
+/// full listing from a live diff.
+pub fn dump_json_diff(diff: &crate::incremental::TestIdDiff) -> Result<()> {
+    let output = JsonDiscoveryDiff {
+        version: 2,
+        added: diff.added.clone(),
+        removed: diff.removed.clone(),
+        moved: diff.moved.clone(),
+    };
+    // ONLY dump_json/dump_json_diff touch stdout with JSON
+    println!("{}", serde_json::to_string(&output)?);
+    Ok(())
+}
+
 /// Dump discovery result as JSON to stdout
 ///
 /// Used by `tach list --format=json` for IDE integration.
@@ -108,14 +263,20 @@ pub fn dump_json(result: &DiscoveryResult) -> Result<()> {
         .modules
         .iter()
         .flat_map(|module| {
-            module.tests.iter().map(move |test| {
+            module.tests.iter().flat_map(move |test| {
                 let file = module.path.to_string_lossy().to_string();
-                JsonTestInfo {
-                    id: format!("{}::{}", file, test.name),
-                    file,
-                    line: test.line_number,
-                    is_async: test.is_async,
-                }
+                let markers: Vec<String> = test.markers.iter().map(|m| m.name.clone()).collect();
+                node_ids(&file, test)
+                    .into_iter()
+                    .map(move |id| JsonTestInfo {
+                        id,
+                        file: file.clone(),
+                        line: test.line_number,
+                        is_async: test.is_async,
+                        markers: markers.clone(),
+                        is_doctest: test.is_doctest,
+                    })
+                    .collect::<Vec<_>>()
             })
         })
         .collect();
@@ -127,8 +288,48 @@ pub fn dump_json(result: &DiscoveryResult) -> Result<()> {
     Ok(())
 }
 
+/// Flatten every module's tests into a single ordered list of fully
+/// qualified node ids (one per `param_sets` row, via `node_ids`). This is
+/// the list `tach list` prints in text mode, and what `--shuffle` reorders
+/// to preview the run order `tach test --shuffle` would use.
+pub fn flatten_node_ids(result: &DiscoveryResult) -> Vec<String> {
+    result
+        .modules
+        .iter()
+        .flat_map(|module| {
+            let file = module.path.to_string_lossy().to_string();
+            module
+                .tests
+                .iter()
+                .flat_map(move |test| node_ids(&file, test))
+        })
+        .collect()
+}
+
+/// Build a test's pytest-style node ids: one per `param_sets` row, or a
+/// single unparametrized id if it isn't parametrized. The exact ids
+/// `dump_json` emits, and what `-k` selection (see `selection.rs`) matches
+/// a keyword expression's identifiers against.
+pub(crate) fn node_ids(file: &str, test: &TestCase) -> Vec<String> {
+    if test.param_sets.is_empty() {
+        vec![format!("{}::{}", file, test.name)]
+    } else {
+        test.param_sets
+            .iter()
+            .map(|row| format!("{}::{}[{}]", file, test.name, row.join("-")))
+            .collect()
+    }
+}
+
 /// Scan project for test files and parse them in parallel
 pub fn discover(root: &Path) -> Result<DiscoveryResult> {
+    discover_with_options(root, false)
+}
+
+/// Like `discover`, but with `include_doctests` controlling whether module,
+/// class, and function docstrings are scanned for `>>>` doctest blocks.
+/// Off by default (via `discover`) so plain test collection is unchanged.
+pub fn discover_with_options(root: &Path, include_doctests: bool) -> Result<DiscoveryResult> {
     let paths: Vec<PathBuf> = WalkBuilder::new(root)
         .standard_filters(true)
         .build()
@@ -145,14 +346,14 @@ pub fn discover(root: &Path) -> Result<DiscoveryResult> {
 
     let modules: Vec<TestModule> = paths
         .par_iter()
-        .filter_map(|path| parse_module(path).ok())
+        .filter_map(|path| parse_module(path, include_doctests).ok())
         .filter(|m| !m.tests.is_empty() || !m.fixtures.is_empty())
         .collect();
 
     Ok(DiscoveryResult { modules })
 }
 
-fn is_test_file(path: &Path) -> bool {
+pub(crate) fn is_test_file(path: &Path) -> bool {
     if !path.is_file() {
         return false;
     }
@@ -164,7 +365,11 @@ fn is_test_file(path: &Path) -> bool {
     name.starts_with("test_") || name.ends_with("_test.py") || name == "conftest.py"
 }
 
-fn parse_module(path: &Path) -> Result<TestModule> {
+/// Parse a single module. `pub(crate)` so watch-mode incremental
+/// re-discovery (see `incremental.rs`) can re-parse one changed file
+/// without re-walking the whole project. `include_doctests` gates scanning
+/// docstrings for `>>>` blocks - most callers pass `false`.
+pub(crate) fn parse_module(path: &Path, include_doctests: bool) -> Result<TestModule> {
     let source = fs::read_to_string(path)?;
     let path_str = path.to_string_lossy();
 
@@ -182,6 +387,11 @@ fn parse_module(path: &Path) -> Result<TestModule> {
     let mut tests = vec![];
     let mut fixtures = vec![];
 
+    if include_doctests {
+        let module_name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("module");
+        collect_doctests(&suite, &source, module_name, &mut tests);
+    }
+
     for stmt in suite {
         match stmt {
             ast::Stmt::FunctionDef(func) => {
@@ -191,12 +401,13 @@ fn parse_module(path: &Path) -> Result<TestModule> {
                 let name = func.name.as_str();
                 if name.starts_with("test_") {
                     let line_number = get_line_number(&source, func.range.start().to_usize());
-                    tests.push(TestCase {
-                        name: name.to_string(),
-                        dependencies: extract_args_from_arguments(&func.args),
-                        is_async: true,
+                    tests.push(build_test_case(
+                        name.to_string(),
+                        extract_args_from_arguments(&func.args),
+                        true,
                         line_number,
-                    });
+                        &func.decorator_list,
+                    ));
                 }
                 if has_fixture_decorator(&func.decorator_list) {
                     fixtures.push(FixtureDefinition {
@@ -204,6 +415,7 @@ fn parse_module(path: &Path) -> Result<TestModule> {
                         scope: extract_scope_from_decorators(&func.decorator_list),
                         dependencies: extract_args_from_arguments(&func.args),
                         params: extract_params_from_decorators(&func.decorator_list),
+                        class_scope: None,
                     });
                 }
             }
@@ -216,24 +428,26 @@ fn parse_module(path: &Path) -> Result<TestModule> {
                             if method_name.starts_with("test_") {
                                 let line_number =
                                     get_line_number(&source, func.range.start().to_usize());
-                                tests.push(TestCase {
-                                    name: format!("{}::{}", class_name, method_name),
-                                    dependencies: extract_args_from_arguments(&func.args),
-                                    is_async: false,
+                                tests.push(build_test_case(
+                                    format!("{}::{}", class_name, method_name),
+                                    extract_args_from_arguments(&func.args),
+                                    false,
                                     line_number,
-                                });
+                                    &func.decorator_list,
+                                ));
                             }
                         } else if let ast::Stmt::AsyncFunctionDef(func) = stmt {
                             let method_name = func.name.as_str();
                             if method_name.starts_with("test_") {
                                 let line_number =
                                     get_line_number(&source, func.range.start().to_usize());
-                                tests.push(TestCase {
-                                    name: format!("{}::{}", class_name, method_name),
-                                    dependencies: extract_args_from_arguments(&func.args),
-                                    is_async: true,
+                                tests.push(build_test_case(
+                                    format!("{}::{}", class_name, method_name),
+                                    extract_args_from_arguments(&func.args),
+                                    true,
                                     line_number,
-                                });
+                                    &func.decorator_list,
+                                ));
                             }
                         }
                     }
@@ -250,6 +464,108 @@ fn parse_module(path: &Path) -> Result<TestModule> {
     })
 }
 
+/// Build a `TestCase`, deriving `parametrized_args` and its expanded
+/// `param_sets` from the same decorator list so every call site stays in
+/// sync with each other.
+fn build_test_case(
+    name: String,
+    dependencies: Vec<String>,
+    is_async: bool,
+    line_number: usize,
+    decorators: &[ast::Expr],
+) -> TestCase {
+    let parametrized_args = extract_parametrize_args(decorators);
+    let param_sets = expand_param_sets(&parametrized_args);
+    TestCase {
+        name,
+        dependencies,
+        is_async,
+        line_number,
+        xfail: extract_xfail_marker(decorators),
+        parametrized_args,
+        param_sets,
+        markers: extract_markers(decorators),
+        is_doctest: false,
+    }
+}
+
+/// Extract doctest `TestCase`s from module/function/class docstrings - one
+/// entry per docstring containing a `>>>` interactive block, matching
+/// pytest's doctest module (which runs a whole docstring as a single test
+/// rather than one per example). Recurses one level into classes for method
+/// docstrings, mirroring pytest's `ClassName.method` doctest qualnames.
+fn collect_doctests(stmts: &[ast::Stmt], source: &str, module_name: &str, out: &mut Vec<TestCase>) {
+    if let Some(case) = doctest_case(stmts, source, module_name) {
+        out.push(case);
+    }
+    for stmt in stmts {
+        match stmt {
+            ast::Stmt::FunctionDef(func) => {
+                let qualname = format!("{}.{}", module_name, func.name.as_str());
+                if let Some(case) = doctest_case(&func.body, source, &qualname) {
+                    out.push(case);
+                }
+            }
+            ast::Stmt::AsyncFunctionDef(func) => {
+                let qualname = format!("{}.{}", module_name, func.name.as_str());
+                if let Some(case) = doctest_case(&func.body, source, &qualname) {
+                    out.push(case);
+                }
+            }
+            ast::Stmt::ClassDef(class) => {
+                let class_qualname = format!("{}.{}", module_name, class.name.as_str());
+                if let Some(case) = doctest_case(&class.body, source, &class_qualname) {
+                    out.push(case);
+                }
+                for method in &class.body {
+                    let (func_name, func_body) = match method {
+                        ast::Stmt::FunctionDef(func) => (func.name.as_str(), &func.body),
+                        ast::Stmt::AsyncFunctionDef(func) => (func.name.as_str(), &func.body),
+                        _ => continue,
+                    };
+                    let qualname = format!("{}.{}", class_qualname, func_name);
+                    if let Some(case) = doctest_case(func_body, source, &qualname) {
+                        out.push(case);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Build a doctest `TestCase` for `body`'s docstring (its first statement,
+/// if it's a string literal) when that docstring contains a `>>>`
+/// interactive line. `line_number` points at the `>>>` line itself - the
+/// docstring's own starting line plus the number of newlines before the
+/// first `>>>` within it.
+fn doctest_case(body: &[ast::Stmt], source: &str, qualname: &str) -> Option<TestCase> {
+    let ast::Stmt::Expr(expr_stmt) = body.first()? else {
+        return None;
+    };
+    let ast::Expr::Constant(constant) = expr_stmt.value.as_ref() else {
+        return None;
+    };
+    let ast::Constant::Str(text) = &constant.value else {
+        return None;
+    };
+    let doctest_offset = text.find(">>>")?;
+    let line_offset = text[..doctest_offset].matches('\n').count();
+    let line_number = get_line_number(source, constant.range.start().to_usize()) + line_offset;
+
+    Some(TestCase {
+        name: qualname.to_string(),
+        dependencies: vec![],
+        is_async: false,
+        line_number,
+        xfail: None,
+        parametrized_args: vec![],
+        param_sets: vec![],
+        markers: vec![],
+        is_doctest: true,
+    })
+}
+
 fn analyze_function(
     func: &ast::StmtFunctionDef,
     source: &str,
@@ -261,12 +577,13 @@ fn analyze_function(
 
     if name.starts_with("test_") {
         let line_number = get_line_number(source, func.range.start().to_usize());
-        tests.push(TestCase {
-            name: name.to_string(),
-            dependencies: extract_args_from_arguments(&func.args),
+        tests.push(build_test_case(
+            name.to_string(),
+            extract_args_from_arguments(&func.args),
             is_async,
             line_number,
-        });
+            &func.decorator_list,
+        ));
     }
 
     if has_fixture_decorator(&func.decorator_list) {
@@ -275,6 +592,7 @@ fn analyze_function(
             scope: extract_scope_from_decorators(&func.decorator_list),
             dependencies: extract_args_from_arguments(&func.args),
             params: extract_params_from_decorators(&func.decorator_list),
+            class_scope: None,
         });
     }
 }
@@ -314,6 +632,7 @@ fn extract_scope_from_decorators(decorators: &[ast::Expr]) -> FixtureScope {
                                 return match s.as_str() {
                                     "class" => FixtureScope::Class,
                                     "module" => FixtureScope::Module,
+                                    "package" => FixtureScope::Package,
                                     "session" => FixtureScope::Session,
                                     _ => FixtureScope::Function,
                                 };
@@ -327,6 +646,238 @@ fn extract_scope_from_decorators(decorators: &[ast::Expr]) -> FixtureScope {
     FixtureScope::Function
 }
 
+/// Detect `@pytest.mark.xfail(...)` and extract its `strict` kwarg (if any)
+fn extract_xfail_marker(decorators: &[ast::Expr]) -> Option<XfailMarker> {
+    for decorator in decorators {
+        if !is_xfail_decorator(decorator) {
+            continue;
+        }
+        let strict = if let ast::Expr::Call(call) = decorator {
+            call.keywords.iter().any(|kw| {
+                kw.arg.as_deref() == Some("strict")
+                    && matches!(
+                        &kw.value,
+                        ast::Expr::Constant(c) if matches!(c.value, ast::Constant::Bool(true))
+                    )
+            })
+        } else {
+            false
+        };
+        return Some(XfailMarker { strict });
+    }
+    None
+}
+
+fn is_xfail_decorator(expr: &ast::Expr) -> bool {
+    match expr {
+        ast::Expr::Call(call) => is_xfail_decorator(&call.func),
+        ast::Expr::Attribute(attr) => attr.attr.as_str() == "xfail",
+        ast::Expr::Name(name) => name.id.as_str() == "xfail",
+        _ => false,
+    }
+}
+
+/// Extract the marker name from a `pytest.mark.<name>` or bare `mark.<name>`
+/// decorator (attribute access or call), e.g. `@pytest.mark.skip` or
+/// `@mark.parametrize(...)`. Returns `None` for anything else, including
+/// `@pytest.fixture` and non-mark custom decorators.
+fn mark_decorator_name(expr: &ast::Expr) -> Option<String> {
+    match expr {
+        ast::Expr::Call(call) => mark_decorator_name(&call.func),
+        ast::Expr::Attribute(attr) => match &*attr.value {
+            ast::Expr::Attribute(inner) if inner.attr.as_str() == "mark" => {
+                Some(attr.attr.to_string())
+            }
+            ast::Expr::Name(name) if name.id.as_str() == "mark" => Some(attr.attr.to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Extract every `@pytest.mark.<name>(...)` / `@mark.<name>(...)` decorator
+/// on a test - skip, xfail, skipif, and any project-defined custom marker
+/// alike. See `Marker` for which arguments are captured.
+fn extract_markers(decorators: &[ast::Expr]) -> Vec<Marker> {
+    let mut markers = Vec::new();
+    for decorator in decorators {
+        let Some(name) = mark_decorator_name(decorator) else {
+            continue;
+        };
+        let (args, kwargs) = match decorator {
+            ast::Expr::Call(call) => (
+                call.args.iter().filter_map(expr_to_literal_string).collect(),
+                call.keywords
+                    .iter()
+                    .filter_map(|kw| {
+                        let key = kw.arg.as_ref()?.to_string();
+                        let value = expr_to_literal_string(&kw.value)?;
+                        Some((key, value))
+                    })
+                    .collect(),
+            ),
+            _ => (Vec::new(), Vec::new()),
+        };
+        markers.push(Marker { name, args, kwargs });
+    }
+    markers
+}
+
+/// Extract `@pytest.mark.parametrize(argnames, argvalues)` decorators.
+/// Multiple decorators on the same test are all collected (pytest stacks
+/// them, taking their Cartesian product - see `expand_param_sets`).
+fn extract_parametrize_args(decorators: &[ast::Expr]) -> Vec<ParametrizeArg> {
+    let mut result = Vec::new();
+    for decorator in decorators {
+        let ast::Expr::Call(call) = decorator else { continue };
+        if !is_parametrize_decorator(&call.func) {
+            continue;
+        }
+        let (Some(names_arg), Some(values_arg)) = (call.args.first(), call.args.get(1)) else {
+            continue;
+        };
+        let Some(names) = extract_argnames(names_arg) else { continue };
+        let rows = extract_param_rows(&names, values_arg);
+        result.push(ParametrizeArg { names, rows });
+    }
+    result
+}
+
+/// Parse `argnames`: either a comma-separated string (`"a,b"`) or a
+/// `List`/`Tuple` of name string literals.
+fn extract_argnames(expr: &ast::Expr) -> Option<Vec<String>> {
+    let names = match expr {
+        ast::Expr::Constant(c) => match &c.value {
+            ast::Constant::Str(s) => s
+                .split(',')
+                .map(|n| n.trim().to_string())
+                .filter(|n| !n.is_empty())
+                .collect(),
+            _ => return None,
+        },
+        ast::Expr::List(list) => list.elts.iter().map(argname_literal).collect::<Option<Vec<_>>>()?,
+        ast::Expr::Tuple(tuple) => tuple
+            .elts
+            .iter()
+            .map(argname_literal)
+            .collect::<Option<Vec<_>>>()?,
+        _ => return None,
+    };
+    if names.is_empty() {
+        None
+    } else {
+        Some(names)
+    }
+}
+
+fn argname_literal(expr: &ast::Expr) -> Option<String> {
+    match expr {
+        ast::Expr::Constant(c) => match &c.value {
+            ast::Constant::Str(s) => Some(s.to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Render `argvalues` (a `List`/`Tuple` of rows) as each row's pytest
+/// node-id segment. For a single name, a row is the bare literal itself;
+/// for multiple names, a row must itself be a `Tuple`/`List` whose elements
+/// line up with `names`, joined with `-`. Any individual non-literal value
+/// (a `Name`, call, etc.) becomes `<argname><rowindex>` rather than bailing
+/// the whole row out - pytest's own id-generation fallback. Returns `None`
+/// if `argvalues` itself isn't a static list, e.g. `parametrize("x",
+/// load_cases())`.
+fn extract_param_rows(names: &[String], argvalues: &ast::Expr) -> Option<Vec<String>> {
+    let elts: &[ast::Expr] = match argvalues {
+        ast::Expr::List(list) => &list.elts,
+        ast::Expr::Tuple(tuple) => &tuple.elts,
+        _ => return None,
+    };
+
+    let mut rows = Vec::with_capacity(elts.len());
+    for (row_index, elt) in elts.iter().enumerate() {
+        let values: Vec<&ast::Expr> = if names.len() > 1 {
+            match elt {
+                ast::Expr::Tuple(t) => t.elts.iter().collect(),
+                ast::Expr::List(l) => l.elts.iter().collect(),
+                _ => return None,
+            }
+        } else {
+            vec![elt]
+        };
+        if values.len() != names.len() {
+            return None;
+        }
+        let segment = values
+            .iter()
+            .zip(names)
+            .map(|(value_expr, name)| param_id_segment(value_expr, name, row_index))
+            .collect::<Vec<_>>()
+            .join("-");
+        rows.push(segment);
+    }
+    Some(rows)
+}
+
+/// A single parametrize value's pytest node-id segment: its own literal
+/// string form, or `<argname><rowindex>` for anything dynamic.
+fn param_id_segment(expr: &ast::Expr, name: &str, row_index: usize) -> String {
+    expr_to_literal_string(expr).unwrap_or_else(|| format!("{name}{row_index}"))
+}
+
+/// Like `expr_to_string`, but stricter: only true Python literals count, not
+/// a bare `Name` (which `expr_to_string` accepts for a fixture's `params=`,
+/// e.g. a reference to an exception class). Parametrize id generation needs
+/// to tell literals and dynamic expressions apart, so a `Name` here counts
+/// as dynamic.
+fn expr_to_literal_string(expr: &ast::Expr) -> Option<String> {
+    match expr {
+        ast::Expr::Constant(_) => expr_to_string(expr),
+        _ => None,
+    }
+}
+
+/// Expand a test's stacked `@pytest.mark.parametrize` decorators into
+/// concrete pytest node-id suffixes via their Cartesian product. Decorators
+/// nest the way pytest does: the first-listed (outermost, applied last)
+/// decorator is the slowest-varying loop and its segment comes last in the
+/// id, while one closer to the function varies fastest and comes first -
+/// e.g. stacking `@parametrize("x", [0, 1])` over `@parametrize("y", ["a",
+/// "b"])` yields `[a-0, b-0, a-1, b-1]`. Returns `vec![]` if the test isn't
+/// parametrized, or if any decorator's rows couldn't be resolved statically
+/// (the caller then falls back to treating it as a single unparametrized
+/// test).
+fn expand_param_sets(parametrized_args: &[ParametrizeArg]) -> Vec<Vec<String>> {
+    if parametrized_args.is_empty() {
+        return Vec::new();
+    }
+
+    let mut rows: Vec<Vec<String>> = vec![Vec::new()];
+    for arg in parametrized_args {
+        let Some(arg_rows) = &arg.rows else { return Vec::new() };
+        let mut next = Vec::with_capacity(rows.len() * arg_rows.len());
+        for existing in &rows {
+            for segment in arg_rows {
+                let mut combined = vec![segment.clone()];
+                combined.extend(existing.iter().cloned());
+                next.push(combined);
+            }
+        }
+        rows = next;
+    }
+    rows
+}
+
+fn is_parametrize_decorator(expr: &ast::Expr) -> bool {
+    match expr {
+        ast::Expr::Call(call) => is_parametrize_decorator(&call.func),
+        ast::Expr::Attribute(attr) => attr.attr.as_str() == "parametrize",
+        ast::Expr::Name(name) => name.id.as_str() == "parametrize",
+        _ => false,
+    }
+}
+
 /// Extract params from @pytest.fixture(params=[...]) decorator
 /// Returns None if:
 /// - No params keyword
@@ -411,7 +962,14 @@ mod tests {
     fn parse_source(source: &str) -> TestModule {
         let mut file = NamedTempFile::new().unwrap();
         file.write_all(source.as_bytes()).unwrap();
-        parse_module(file.path()).unwrap()
+        parse_module(file.path(), false).unwrap()
+    }
+
+    // Like `parse_source`, but with doctest discovery enabled.
+    fn parse_source_with_doctests(source: &str) -> TestModule {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(source.as_bytes()).unwrap();
+        parse_module(file.path(), true).unwrap()
     }
 
     #[test]
@@ -431,12 +989,22 @@ mod tests {
                             dependencies: vec![],
                             is_async: false,
                             line_number: 1,
+                            xfail: None,
+                            parametrized_args: vec![],
+                            param_sets: vec![],
+                            markers: vec![],
+                            is_doctest: false,
                         },
                         TestCase {
                             name: "test_2".into(),
                             dependencies: vec![],
                             is_async: true,
                             line_number: 1,
+                            xfail: None,
+                            parametrized_args: vec![],
+                            param_sets: vec![],
+                            markers: vec![],
+                            is_doctest: false,
                         },
                     ],
                     fixtures: vec![FixtureDefinition {
@@ -444,6 +1012,7 @@ mod tests {
                         scope: FixtureScope::Module,
                         dependencies: vec![],
                         params: None,
+                        class_scope: None,
                     }],
                 },
                 TestModule {
@@ -453,6 +1022,11 @@ mod tests {
                         dependencies: vec!["db".into()],
                         is_async: false,
                         line_number: 1,
+                        xfail: None,
+                        parametrized_args: vec![],
+                        param_sets: vec![],
+                        markers: vec![],
+                        is_doctest: false,
                     }],
                     fixtures: vec![],
                 },
@@ -474,10 +1048,19 @@ mod tests {
         assert_eq!(FixtureScope::Function, FixtureScope::Function);
         assert_eq!(FixtureScope::Class, FixtureScope::Class);
         assert_eq!(FixtureScope::Module, FixtureScope::Module);
+        assert_eq!(FixtureScope::Package, FixtureScope::Package);
         assert_eq!(FixtureScope::Session, FixtureScope::Session);
         assert_ne!(FixtureScope::Function, FixtureScope::Session);
     }
 
+    #[test]
+    fn test_fixture_scope_rank_orders_narrow_to_wide() {
+        assert!(FixtureScope::Function.rank() < FixtureScope::Class.rank());
+        assert!(FixtureScope::Class.rank() < FixtureScope::Module.rank());
+        assert!(FixtureScope::Module.rank() < FixtureScope::Package.rank());
+        assert!(FixtureScope::Package.rank() < FixtureScope::Session.rank());
+    }
+
     // =========================================================================
     // AST Parsing Tests
     // =========================================================================
@@ -549,14 +1132,19 @@ def session_fixture():
 @pytest.fixture(scope="class")
 def class_fixture():
     return "class"
+
+@pytest.fixture(scope="package")
+def package_fixture():
+    return "package"
 "#;
         let module = parse_source(source);
-        assert_eq!(module.fixtures.len(), 3);
+        assert_eq!(module.fixtures.len(), 4);
 
         let scopes: Vec<_> = module.fixtures.iter().map(|f| f.scope.clone()).collect();
         assert!(scopes.contains(&FixtureScope::Module));
         assert!(scopes.contains(&FixtureScope::Session));
         assert!(scopes.contains(&FixtureScope::Class));
+        assert!(scopes.contains(&FixtureScope::Package));
     }
 
     #[test]
@@ -711,4 +1299,424 @@ def bare_fixture():
         assert_eq!(module.fixtures.len(), 1);
         assert_eq!(module.fixtures[0].name, "bare_fixture");
     }
+
+    #[test]
+    fn test_parse_xfail_marker() {
+        let source = r#"
+import pytest
+
+@pytest.mark.xfail
+def test_known_broken():
+    assert False
+"#;
+        let module = parse_source(source);
+        assert_eq!(module.tests.len(), 1);
+        let marker = module.tests[0].xfail.as_ref().expect("should be marked xfail");
+        assert!(!marker.strict);
+    }
+
+    #[test]
+    fn test_parse_xfail_strict() {
+        let source = r#"
+import pytest
+
+@pytest.mark.xfail(strict=True)
+def test_strict_broken():
+    assert False
+"#;
+        let module = parse_source(source);
+        let marker = module.tests[0].xfail.as_ref().expect("should be marked xfail");
+        assert!(marker.strict);
+    }
+
+    #[test]
+    fn test_parse_no_xfail_marker() {
+        let source = r#"
+def test_normal():
+    pass
+"#;
+        let module = parse_source(source);
+        assert!(module.tests[0].xfail.is_none());
+    }
+
+    #[test]
+    fn test_parse_parametrize_static_values() {
+        let source = r#"
+import pytest
+
+@pytest.mark.parametrize("backend", ["sqlite", "postgres"])
+def test_backend(backend):
+    pass
+"#;
+        let module = parse_source(source);
+        assert_eq!(module.tests[0].parametrized_args.len(), 1);
+        let arg = &module.tests[0].parametrized_args[0];
+        assert_eq!(arg.names, vec!["backend".to_string()]);
+        assert_eq!(arg.rows, Some(vec!["sqlite".to_string(), "postgres".to_string()]));
+        assert_eq!(
+            module.tests[0].param_sets,
+            vec![vec!["sqlite".to_string()], vec!["postgres".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_parse_parametrize_stacked_decorators() {
+        // pytest stacks decorators outer-to-inner as the slowest- to
+        // fastest-varying axis: "tz" (innermost) should vary fastest.
+        let source = r#"
+import pytest
+
+@pytest.mark.parametrize("backend", ["sqlite", "postgres"])
+@pytest.mark.parametrize("tz", ["utc", "local"])
+def test_combo(backend, tz):
+    pass
+"#;
+        let module = parse_source(source);
+        let names: Vec<_> = module.tests[0]
+            .parametrized_args
+            .iter()
+            .flat_map(|a| a.names.iter().map(String::as_str))
+            .collect();
+        assert_eq!(names, vec!["backend", "tz"]);
+        assert_eq!(
+            module.tests[0].param_sets,
+            vec![
+                vec!["utc".to_string(), "sqlite".to_string()],
+                vec!["local".to_string(), "sqlite".to_string()],
+                vec!["utc".to_string(), "postgres".to_string()],
+                vec!["local".to_string(), "postgres".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_parametrize_dynamic_values_yields_none() {
+        let source = r#"
+import pytest
+
+@pytest.mark.parametrize("backend", load_backends())
+def test_dynamic(backend):
+    pass
+"#;
+        let module = parse_source(source);
+        assert_eq!(module.tests[0].parametrized_args[0].rows, None);
+        assert!(module.tests[0].param_sets.is_empty());
+    }
+
+    #[test]
+    fn test_parse_parametrize_dynamic_row_falls_back_to_argname_index() {
+        let source = r#"
+import pytest
+
+@pytest.mark.parametrize("backend", ["sqlite", get_default_backend()])
+def test_backend(backend):
+    pass
+"#;
+        let module = parse_source(source);
+        assert_eq!(
+            module.tests[0].parametrized_args[0].rows,
+            Some(vec!["sqlite".to_string(), "backend1".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_parse_parametrize_multi_name() {
+        let source = r#"
+import pytest
+
+@pytest.mark.parametrize("a,b", [(1, 2), (3, 4)])
+def test_multi(a, b):
+    pass
+"#;
+        let module = parse_source(source);
+        assert_eq!(module.tests[0].parametrized_args.len(), 1);
+        let arg = &module.tests[0].parametrized_args[0];
+        assert_eq!(arg.names, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(arg.rows, Some(vec!["1-2".to_string(), "3-4".to_string()]));
+        assert_eq!(
+            module.tests[0].param_sets,
+            vec![vec!["1-2".to_string()], vec!["3-4".to_string()]]
+        );
+    }
+
+    #[test]
+    fn test_parse_markers_skip_with_reason() {
+        let source = r#"
+import pytest
+
+@pytest.mark.skip("not ready yet")
+def test_unfinished():
+    pass
+"#;
+        let module = parse_source(source);
+        assert_eq!(module.tests[0].markers.len(), 1);
+        let marker = &module.tests[0].markers[0];
+        assert_eq!(marker.name, "skip");
+        assert_eq!(marker.args, vec!["not ready yet".to_string()]);
+        assert!(marker.kwargs.is_empty());
+    }
+
+    #[test]
+    fn test_parse_markers_skipif_with_static_kwargs() {
+        let source = r#"
+import pytest
+
+@pytest.mark.skipif(True, reason="unsupported on this platform")
+def test_platform_specific():
+    pass
+"#;
+        let module = parse_source(source);
+        let marker = &module.tests[0].markers[0];
+        assert_eq!(marker.name, "skipif");
+        assert_eq!(marker.args, vec!["True".to_string()]);
+        assert_eq!(
+            marker.kwargs,
+            vec![("reason".to_string(), "unsupported on this platform".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_markers_bare_mark_attribute() {
+        let source = r#"
+from pytest import mark
+
+@mark.smoke
+def test_quick():
+    pass
+"#;
+        let module = parse_source(source);
+        assert_eq!(module.tests[0].markers.len(), 1);
+        assert_eq!(module.tests[0].markers[0].name, "smoke");
+    }
+
+    #[test]
+    fn test_parse_markers_includes_xfail_alongside_dedicated_field() {
+        let source = r#"
+import pytest
+
+@pytest.mark.xfail(strict=True)
+def test_known_broken():
+    assert False
+"#;
+        let module = parse_source(source);
+        assert!(module.tests[0].xfail.is_some());
+        assert_eq!(module.tests[0].markers.len(), 1);
+        assert_eq!(module.tests[0].markers[0].name, "xfail");
+        assert_eq!(
+            module.tests[0].markers[0].kwargs,
+            vec![("strict".to_string(), "True".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_markers_empty_without_mark_decorators() {
+        let source = r#"
+def test_plain():
+    pass
+"#;
+        let module = parse_source(source);
+        assert!(module.tests[0].markers.is_empty());
+    }
+
+    #[test]
+    fn test_doctests_not_collected_by_default() {
+        let source = r#"
+def add(a, b):
+    """
+    >>> add(1, 2)
+    3
+    """
+    return a + b
+"#;
+        let module = parse_source(source);
+        assert!(module.tests.is_empty());
+    }
+
+    #[test]
+    fn test_doctest_collected_from_function_docstring() {
+        let source = r#"
+def add(a, b):
+    """
+    >>> add(1, 2)
+    3
+    """
+    return a + b
+"#;
+        let module = parse_source_with_doctests(source);
+        assert_eq!(module.tests.len(), 1);
+        let case = &module.tests[0];
+        assert!(case.is_doctest);
+        assert!(case.name.ends_with(".add"));
+        assert_eq!(case.line_number, 4);
+    }
+
+    #[test]
+    fn test_doctest_collected_from_module_and_class_docstrings() {
+        let source = r#"
+"""
+>>> 1 + 1
+2
+"""
+
+class Calculator:
+    """A simple calculator."""
+
+    def multiply(self, a, b):
+        """
+        >>> Calculator().multiply(2, 3)
+        6
+        """
+        return a * b
+"#;
+        let module = parse_source_with_doctests(source);
+        assert_eq!(module.tests.len(), 2);
+        let module_name = module.path.file_stem().unwrap().to_str().unwrap().to_string();
+        assert!(module.tests.iter().any(|t| t.name == module_name));
+        assert!(module
+            .tests
+            .iter()
+            .any(|t| t.name.ends_with(".Calculator.multiply")));
+    }
+
+    #[test]
+    fn test_no_doctest_case_without_interactive_block() {
+        let source = r#"
+def plain(a, b):
+    """Just a normal docstring, no examples."""
+    return a + b
+"#;
+        let module = parse_source_with_doctests(source);
+        assert!(module.tests.is_empty());
+    }
+
+    #[test]
+    fn test_filter_by_keyword_matches_case_insensitive_substring() {
+        let source = r#"
+def test_login_succeeds():
+    pass
+
+def test_logout_fails():
+    pass
+"#;
+        let result = DiscoveryResult { modules: vec![parse_source(source)] };
+        let expr = crate::selection::parse_selection("LOGIN").unwrap();
+        let filtered = result.filter_by_keyword(&expr);
+        assert_eq!(filtered.test_count(), 1);
+        assert_eq!(filtered.modules[0].tests[0].name, "test_login_succeeds");
+    }
+
+    #[test]
+    fn test_filter_by_keyword_supports_boolean_expression() {
+        let source = r#"
+def test_login_succeeds():
+    pass
+
+def test_login_fails():
+    pass
+
+def test_logout_fails():
+    pass
+"#;
+        let result = DiscoveryResult { modules: vec![parse_source(source)] };
+        let expr = crate::selection::parse_selection("login and not fails").unwrap();
+        let filtered = result.filter_by_keyword(&expr);
+        assert_eq!(filtered.test_count(), 1);
+        assert_eq!(filtered.modules[0].tests[0].name, "test_login_succeeds");
+    }
+
+    #[test]
+    fn test_filter_by_markers_matches_exact_marker_name() {
+        let source = r#"
+import pytest
+
+@pytest.mark.slow
+def test_a():
+    pass
+
+@pytest.mark.skip
+def test_b():
+    pass
+
+def test_c():
+    pass
+"#;
+        let result = DiscoveryResult { modules: vec![parse_source(source)] };
+        let expr = crate::selection::parse_selection("slow or skip").unwrap();
+        let filtered = result.filter_by_markers(&expr);
+        let names: Vec<&str> = filtered.modules[0].tests.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["test_a", "test_b"]);
+    }
+
+    #[test]
+    fn test_filter_by_markers_drops_modules_with_no_surviving_tests() {
+        let source = r#"
+def test_plain():
+    pass
+"#;
+        let result = DiscoveryResult { modules: vec![parse_source(source)] };
+        let expr = crate::selection::parse_selection("slow").unwrap();
+        let filtered = result.filter_by_markers(&expr);
+        assert!(filtered.modules.is_empty());
+    }
+
+    #[test]
+    fn test_flatten_node_ids_orders_by_module_then_test() {
+        let result = DiscoveryResult {
+            modules: vec![
+                TestModule {
+                    path: PathBuf::from("test_a.py"),
+                    tests: vec![
+                        TestCase {
+                            name: "test_1".into(),
+                            dependencies: vec![],
+                            is_async: false,
+                            line_number: 1,
+                            xfail: None,
+                            parametrized_args: vec![],
+                            param_sets: vec![],
+                            markers: vec![],
+                            is_doctest: false,
+                        },
+                        TestCase {
+                            name: "test_2".into(),
+                            dependencies: vec![],
+                            is_async: false,
+                            line_number: 2,
+                            xfail: None,
+                            parametrized_args: vec![],
+                            param_sets: vec![vec!["1".into()], vec!["2".into()]],
+                            markers: vec![],
+                            is_doctest: false,
+                        },
+                    ],
+                    fixtures: vec![],
+                },
+                TestModule {
+                    path: PathBuf::from("test_b.py"),
+                    tests: vec![TestCase {
+                        name: "test_3".into(),
+                        dependencies: vec![],
+                        is_async: false,
+                        line_number: 1,
+                        xfail: None,
+                        parametrized_args: vec![],
+                        param_sets: vec![],
+                        markers: vec![],
+                        is_doctest: false,
+                    }],
+                    fixtures: vec![],
+                },
+            ],
+        };
+
+        assert_eq!(
+            flatten_node_ids(&result),
+            vec![
+                "test_a.py::test_1".to_string(),
+                "test_a.py::test_2[1]".to_string(),
+                "test_a.py::test_2[2]".to_string(),
+                "test_b.py::test_3".to_string(),
+            ]
+        );
+    }
 }
@@ -0,0 +1,300 @@
+//! trybuild-style snapshot assertions for expected-failure messages
+//!
+//! `protocol::TestResult::message` is a raw, 4KB-truncated string - for an
+//! `@pytest.mark.xfail` test that's asserting on its own traceback, that
+//! text is full of machine-specific noise (absolute paths, object
+//! addresses, line numbers that shift with a patch release, durations).
+//! This module normalizes that noise away the way `trybuild` does for
+//! compiler output, then snapshots the result to `.tach/snapshots/<id>.txt`
+//! and diffs later runs against it - so the assertion is "does this test
+//! still fail the same way", not "does this test fail with byte-identical
+//! text".
+//!
+//! Deliberately its own module rather than folded into `snapshot` - that
+//! module is the UFFD/process_vm_readv golden-memory-snapshot machinery for
+//! fork-server warm starts, a completely different "snapshot" from the
+//! testing sense used here. Sharing a name with it would be the confusing
+//! part, not reusing it.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// What comparing a freshly-normalized message against the on-disk snapshot
+/// produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// No snapshot existed yet - one was written.
+    Created,
+    /// `TACH_BLESS=1` was set - the snapshot was (re)written unconditionally.
+    Blessed,
+    /// Normalized message matches the snapshot on disk.
+    Matched,
+    /// Normalized message differs - carries a unified-style diff.
+    Mismatch(String),
+}
+
+/// Directory snapshots live under - `project_root/.tach/snapshots`.
+pub fn snapshot_dir(project_root: &Path) -> PathBuf {
+    project_root.join(".tach").join("snapshots")
+}
+
+/// Normalize, then compare against (or write) the on-disk snapshot for
+/// `test_id`. `TACH_BLESS=1` always rewrites the snapshot in place.
+pub fn check_or_bless(snapshot_dir: &Path, test_id: &str, message: &str, project_root: &Path) -> Result<Outcome> {
+    let normalized = normalize(message, project_root);
+    let path = snapshot_dir.join(format!("{}.txt", sanitize_filename(test_id)));
+
+    let bless = std::env::var("TACH_BLESS").as_deref() == Ok("1");
+
+    if bless {
+        write_snapshot(&path, &normalized)?;
+        return Ok(Outcome::Blessed);
+    }
+
+    match fs::read_to_string(&path) {
+        Ok(existing) if existing == normalized => Ok(Outcome::Matched),
+        Ok(existing) => Ok(Outcome::Mismatch(unified_diff(&existing, &normalized))),
+        Err(_) => {
+            write_snapshot(&path, &normalized)?;
+            Ok(Outcome::Created)
+        }
+    }
+}
+
+fn write_snapshot(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("creating {}", parent.display()))?;
+    }
+    fs::write(path, contents).with_context(|| format!("writing snapshot to {}", path.display()))
+}
+
+/// Turn a qualified test id (`tests/foo.py::test_bar[1]`) into a filesystem-
+/// safe name - every character that isn't alphanumeric, `.`, `-` or `_`
+/// becomes `_`.
+fn sanitize_filename(test_id: &str) -> String {
+    test_id
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect()
+}
+
+fn hex_address_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"0[xX][0-9a-fA-F]+").unwrap())
+}
+
+fn traceback_line_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    // `File "foo.py", line 42, in bar` - the line number shifts with every
+    // unrelated edit above it, so it's not part of what the assertion means.
+    RE.get_or_init(|| Regex::new(r"line \d+").unwrap())
+}
+
+fn file_pos_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    // `foo.py:42:7` - pytest/ruff-style `path:line:col` position markers.
+    RE.get_or_init(|| Regex::new(r":(\d+):(\d+)\b").unwrap())
+}
+
+fn duration_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\b\d+(\.\d+)?\s?(ns|us|µs|ms|s)\b").unwrap())
+}
+
+/// Normalize a raw failure message the way `trybuild` normalizes compiler
+/// output before comparing: absolute paths collapse to project-relative,
+/// addresses and line/column numbers that vary machine-to-machine or
+/// patch-to-patch collapse to placeholders, and durations collapse to a
+/// `<DURATION>` placeholder since they're never meaningful to the assertion.
+pub fn normalize(message: &str, project_root: &Path) -> String {
+    let mut text = message.to_string();
+
+    if let Ok(canonical) = project_root.canonicalize() {
+        let prefix = format!("{}/", canonical.display());
+        text = text.replace(&prefix, "");
+    }
+    let raw_prefix = format!("{}/", project_root.display());
+    text = text.replace(&raw_prefix, "");
+
+    text = hex_address_re().replace_all(&text, "0xADDR").into_owned();
+    text = traceback_line_re().replace_all(&text, "line N").into_owned();
+    text = file_pos_re().replace_all(&text, ":N:N").into_owned();
+    text = duration_re().replace_all(&text, "<DURATION>").into_owned();
+
+    text
+}
+
+/// Minimal unified-style diff: every line in `old` not also in `new`
+/// (by longest-common-subsequence alignment) is emitted prefixed `-`, every
+/// line in `new` not in `old` is emitted prefixed `+`, unchanged lines get a
+/// leading space - same idea as `diff -u`, without the hunk-header grouping,
+/// since snapshot messages are small enough that one contiguous block reads
+/// fine.
+fn unified_diff(old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    let n = old_lines.len();
+    let m = new_lines.len();
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_lines[i] == new_lines[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut out = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old_lines[i] == new_lines[j] {
+            out.push(format!("  {}", old_lines[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push(format!("- {}", old_lines[i]));
+            i += 1;
+        } else {
+            out.push(format!("+ {}", new_lines[j]));
+            j += 1;
+        }
+    }
+    while i < n {
+        out.push(format!("- {}", old_lines[i]));
+        i += 1;
+    }
+    while j < m {
+        out.push(format!("+ {}", new_lines[j]));
+        j += 1;
+    }
+
+    out.join("\n")
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::sync::Mutex;
+
+    /// `TACH_BLESS` is process-wide state, but `cargo test` runs this file's
+    /// tests concurrently by default - if `test_tach_bless_env_rewrites_existing_snapshot`'s
+    /// `set_var` lands while another test is mid-`check_or_bless`, that test
+    /// spuriously takes the `Blessed` branch instead of comparing. Every
+    /// test that calls `check_or_bless` holds this for its duration, same as
+    /// `jobserver`'s `MAKEFLAGS_LOCK`.
+    static TACH_BLESS_LOCK: Mutex<()> = Mutex::new(());
+
+    fn tmp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tach_failure_snapshot_test_{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_normalize_collapses_hex_addresses() {
+        let project_root = tmp_dir("normalize_hex");
+        let msg = "object at 0x7f3a4c0b1d90 raised";
+        assert_eq!(normalize(msg, &project_root), "object at 0xADDR raised");
+    }
+
+    #[test]
+    fn test_normalize_collapses_traceback_line_numbers() {
+        let project_root = tmp_dir("normalize_line");
+        let msg = "File \"foo.py\", line 42, in bar";
+        assert_eq!(normalize(msg, &project_root), "File \"foo.py\", line N, in bar");
+    }
+
+    #[test]
+    fn test_normalize_collapses_file_positions() {
+        let project_root = tmp_dir("normalize_pos");
+        let msg = "foo.py:42:7: assertion failed";
+        assert_eq!(normalize(msg, &project_root), "foo.py:N:N: assertion failed");
+    }
+
+    #[test]
+    fn test_normalize_erases_durations() {
+        let project_root = tmp_dir("normalize_duration");
+        let msg = "test took 123.4ms to fail";
+        assert_eq!(normalize(msg, &project_root), "test took <DURATION> to fail");
+    }
+
+    #[test]
+    fn test_normalize_strips_project_root_prefix() {
+        let project_root = tmp_dir("normalize_prefix");
+        let canonical = project_root.canonicalize().unwrap();
+        let msg = format!("{}/tests/test_foo.py failed", canonical.display());
+        assert_eq!(normalize(&msg, &project_root), "tests/test_foo.py failed");
+    }
+
+    #[test]
+    fn test_check_or_bless_creates_missing_snapshot() {
+        let _guard = TACH_BLESS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let root = tmp_dir("create_root");
+        let dir = snapshot_dir(&root);
+        let outcome = check_or_bless(&dir, "tests/foo.py::test_bar", "boom", &root).unwrap();
+        assert_eq!(outcome, Outcome::Created);
+        assert!(dir.join("tests_foo.py__test_bar.txt").exists());
+    }
+
+    #[test]
+    fn test_check_or_bless_matches_identical_snapshot() {
+        let _guard = TACH_BLESS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let root = tmp_dir("match_root");
+        let dir = snapshot_dir(&root);
+        check_or_bless(&dir, "tests/foo.py::test_bar", "boom", &root).unwrap();
+        let outcome = check_or_bless(&dir, "tests/foo.py::test_bar", "boom", &root).unwrap();
+        assert_eq!(outcome, Outcome::Matched);
+    }
+
+    #[test]
+    fn test_check_or_bless_reports_mismatch_with_diff() {
+        let _guard = TACH_BLESS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let root = tmp_dir("mismatch_root");
+        let dir = snapshot_dir(&root);
+        check_or_bless(&dir, "tests/foo.py::test_bar", "expected: 1", &root).unwrap();
+        let outcome = check_or_bless(&dir, "tests/foo.py::test_bar", "expected: 2", &root).unwrap();
+        match outcome {
+            Outcome::Mismatch(diff) => {
+                assert!(diff.contains("- expected: 1"));
+                assert!(diff.contains("+ expected: 2"));
+            }
+            other => panic!("expected Mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_tach_bless_env_rewrites_existing_snapshot() {
+        let _guard = TACH_BLESS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let root = tmp_dir("bless_root");
+        let dir = snapshot_dir(&root);
+        check_or_bless(&dir, "tests/foo.py::test_bar", "old text", &root).unwrap();
+
+        std::env::set_var("TACH_BLESS", "1");
+        let outcome = check_or_bless(&dir, "tests/foo.py::test_bar", "new text", &root).unwrap();
+        std::env::remove_var("TACH_BLESS");
+
+        assert_eq!(outcome, Outcome::Blessed);
+        let path = dir.join("tests_foo.py__test_bar.txt");
+        assert_eq!(fs::read_to_string(path).unwrap(), "new text");
+    }
+
+    #[test]
+    fn test_sanitize_filename_escapes_path_and_separator_characters() {
+        assert_eq!(
+            sanitize_filename("tests/foo.py::test_bar[1]"),
+            "tests_foo.py__test_bar_1_"
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Gitignore-style matching for watch mode.
+//!
+//! `watch::is_ignored_path` used to hardcode a handful of substring checks
+//! (`__pycache__`, `.venv`, ...), which misfires on projects with unusual
+//! layouts. This builds a real gitignore matcher instead, using the same
+//! `ignore` crate `discovery::discover` already relies on for directory
+//! walking: every `.gitignore` under the watched root, plus a top-level
+//! `.tachignore` for tach-specific excludes, combined into one matcher so
+//! `/foo`, `bar/`, `**`, and `!` negation all behave exactly like git.
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::{Match, WalkBuilder};
+use std::path::Path;
+
+/// Built once when watch mode starts; cheap to query per file-change event
+/// afterwards since the `ignore` crate precompiles every pattern into a
+/// `Gitignore`.
+pub struct WatchIgnore {
+    gitignore: Gitignore,
+}
+
+impl WatchIgnore {
+    /// Walk `project_root` collecting every `.gitignore` file plus a
+    /// top-level `.tachignore`, and compile them into a single matcher.
+    /// Files are added shallowest-first so that, matching git's own
+    /// precedence, a more specific (deeper) file's rules - including
+    /// re-including negations - take priority over a shallower one's.
+    pub fn load(project_root: &Path) -> Self {
+        let mut builder = GitignoreBuilder::new(project_root);
+
+        // Not finding a .tachignore is the common case, not a warning.
+        let _ = builder.add(project_root.join(".tachignore"));
+
+        let mut gitignore_files: Vec<_> = WalkBuilder::new(project_root)
+            // `.gitignore` files are themselves dotfiles, and we can't use
+            // ignore rules to find the ignore rules - so walk everything.
+            .hidden(false)
+            .standard_filters(false)
+            .build()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_str() == Some(".gitignore"))
+            .map(|entry| entry.into_path())
+            .collect();
+        gitignore_files.sort_by_key(|p| p.components().count());
+
+        for path in gitignore_files {
+            if let Some(err) = builder.add(&path) {
+                eprintln!("[tach] Warning: failed to parse {}: {}", path.display(), err);
+            }
+        }
+
+        let gitignore = builder.build().unwrap_or_else(|e| {
+            eprintln!("[tach] Warning: failed to compile gitignore rules: {}", e);
+            Gitignore::empty()
+        });
+
+        Self { gitignore }
+    }
+
+    /// Whether `path` should be skipped by the watcher: matched against
+    /// every collected `.gitignore`/`.tachignore` rule first (nearest file
+    /// wins, negation can re-include), falling back to a small built-in
+    /// default ruleset - at lowest precedence - for projects that ship no
+    /// ignore files of their own at all.
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        match self.gitignore.matched_path_or_any_parents(path, false) {
+            Match::Ignore(_) => true,
+            Match::Whitelist(_) => false,
+            Match::None => default_is_ignored(path),
+        }
+    }
+}
+
+/// Fallback ruleset for projects with no `.gitignore`/`.tachignore` at all -
+/// the same substring checks this module replaces, now consulted only once
+/// every real ignore rule has had a chance to match (or re-include) a path.
+fn default_is_ignored(path: &Path) -> bool {
+    let path_str = path.to_string_lossy();
+    path_str.contains("__pycache__")
+        || path_str.contains(".pytest_cache")
+        || path_str.contains(".mypy_cache")
+        || path_str.contains(".git")
+        || path_str.contains(".venv")
+        || path_str.contains("/venv/")
+        || path_str.contains("/env/")
+        || path_str.contains("/node_modules/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Fresh, empty temp directory for one test, cleaned up on return.
+    fn tmp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "tach_ignorefile_test_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_gitignore_pattern_is_respected() {
+        let dir = tmp_dir("basic");
+        fs::write(dir.join(".gitignore"), "generated/\n").unwrap();
+
+        let ignore = WatchIgnore::load(&dir);
+        assert!(ignore.is_ignored(&dir.join("generated").join("models.py")));
+        assert!(!ignore.is_ignored(&dir.join("src").join("models.py")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_negation_reincludes_file() {
+        let dir = tmp_dir("negate");
+        fs::write(dir.join(".gitignore"), "*.py\n!keep_me.py\n").unwrap();
+
+        let ignore = WatchIgnore::load(&dir);
+        assert!(ignore.is_ignored(&dir.join("scratch.py")));
+        assert!(!ignore.is_ignored(&dir.join("keep_me.py")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_nested_gitignore_overrides_root() {
+        let dir = tmp_dir("nested");
+        fs::write(dir.join(".gitignore"), "*.py\n").unwrap();
+        fs::create_dir_all(dir.join("keep")).unwrap();
+        fs::write(dir.join("keep").join(".gitignore"), "!*.py\n").unwrap();
+
+        let ignore = WatchIgnore::load(&dir);
+        assert!(ignore.is_ignored(&dir.join("other.py")));
+        assert!(!ignore.is_ignored(&dir.join("keep").join("wanted.py")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_tachignore_is_honored() {
+        let dir = tmp_dir("tachignore");
+        fs::write(dir.join(".tachignore"), "fixtures_data/\n").unwrap();
+
+        let ignore = WatchIgnore::load(&dir);
+        assert!(ignore.is_ignored(&dir.join("fixtures_data").join("big.py")));
+        assert!(!ignore.is_ignored(&dir.join("src").join("big.py")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_falls_back_to_builtin_defaults_with_no_ignore_files() {
+        let dir = tmp_dir("fallback");
+
+        let ignore = WatchIgnore::load(&dir);
+        assert!(ignore.is_ignored(&dir.join("__pycache__").join("mod.py")));
+        assert!(ignore.is_ignored(&dir.join(".venv").join("lib").join("site.py")));
+        assert!(!ignore.is_ignored(&dir.join("src").join("mod.py")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_default_is_ignored_cache_dirs() {
+        assert!(default_is_ignored(Path::new("foo/__pycache__/bar.py")));
+        assert!(default_is_ignored(Path::new(
+            "project/.pytest_cache/v/cache.py"
+        )));
+        assert!(default_is_ignored(Path::new(
+            "project/.mypy_cache/3.10/module.py"
+        )));
+    }
+
+    #[test]
+    fn test_default_is_ignored_venv_variations() {
+        assert!(default_is_ignored(Path::new(".git/hooks/pre-commit.py")));
+        assert!(default_is_ignored(Path::new(".venv/lib/python3.10/site.py")));
+        assert!(default_is_ignored(Path::new("/home/user/.venv/lib/site.py")));
+        assert!(default_is_ignored(Path::new("/project/venv/bin/activate.py")));
+        assert!(default_is_ignored(Path::new("/project/env/lib/python.py")));
+    }
+
+    #[test]
+    fn test_default_is_ignored_node_modules() {
+        assert!(default_is_ignored(Path::new(
+            "/project/node_modules/something.py"
+        )));
+    }
+
+    #[test]
+    fn test_default_not_ignored_normal_paths() {
+        assert!(!default_is_ignored(Path::new("tests/test_unit.py")));
+        assert!(!default_is_ignored(Path::new("src/app/models.py")));
+        assert!(!default_is_ignored(Path::new("conftest.py")));
+        assert!(!default_is_ignored(Path::new("test_integration.py")));
+    }
+}
@@ -0,0 +1,366 @@
+//! Import-Graph Change Impact
+//!
+//! `resolver::affected_by_changes` can already narrow a watch-mode rerun to
+//! tests touched by their own file or a resolved fixture's source file, but
+//! falls back to a full run for any other changed file since - short of a
+//! real import graph - there's no way to prove nothing depends on it. This
+//! builds that graph: every project `.py` file's `import`/`from ... import`
+//! statements are parsed and resolved to the project file they name, giving
+//! a reverse edge set (`imported file -> files that import it`) that
+//! `transitive_dependents` walks to answer "what, transitively, imports
+//! this file?" for an arbitrary changed path - the same question Deno's
+//! `has_graph_root_local_dependent_changed` answers off its module graph.
+//!
+//! Resolution is best-effort static analysis, same spirit as
+//! `reachability::prune_unreachable`: dynamic imports (`importlib.import_module`,
+//! `__import__`), namespace packages, and `sys.path` tricks aren't modeled.
+//! An import that can't be resolved to a file under the project root is
+//! simply dropped rather than guessed at - the caller's existing "unknown
+//! dependents -> full run" fallback covers anything this misses.
+
+use rustpython_ast as ast;
+use rustpython_parser::parse_program;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Reverse import edges over a project's `.py` files: `imported file ->
+/// files that directly import it`, project-relative throughout.
+#[derive(Debug, Default)]
+pub struct ImportGraph {
+    importers: HashMap<PathBuf, HashSet<PathBuf>>,
+}
+
+impl ImportGraph {
+    /// An empty graph - `transitive_dependents` always returns just the
+    /// queried path itself. Cheap placeholder for call sites (e.g. a
+    /// single, non-watch run) that never actually consult the graph.
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Walk every `.py` file under `project_root`, parse its imports, and
+    /// resolve each to a project-relative file path, building the reverse
+    /// edge set `build` returns.
+    pub fn build(project_root: &Path) -> Self {
+        let py_files: Vec<PathBuf> = ignore::WalkBuilder::new(project_root)
+            .standard_filters(true)
+            .build()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension() == Some(OsStr::new("py")))
+            .map(|e| {
+                e.path()
+                    .strip_prefix(project_root)
+                    .unwrap_or(e.path())
+                    .to_path_buf()
+            })
+            .collect();
+
+        let mut importers: HashMap<PathBuf, HashSet<PathBuf>> = HashMap::new();
+        for file in &py_files {
+            let abs = project_root.join(file);
+            let Ok(source) = fs::read_to_string(&abs) else {
+                continue;
+            };
+            let Ok(suite) = parse_program(&source, &abs.to_string_lossy()) else {
+                continue;
+            };
+
+            let mut targets = Vec::new();
+            collect_imports(&suite, file, project_root, &mut targets);
+            for target in targets {
+                importers.entry(target).or_default().insert(file.clone());
+            }
+        }
+
+        Self { importers }
+    }
+
+    /// Every project file that transitively imports `changed` (BFS over the
+    /// reverse edge set), including `changed` itself.
+    pub fn transitive_dependents(&self, changed: &Path) -> HashSet<PathBuf> {
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+        seen.insert(changed.to_path_buf());
+        queue.push_back(changed.to_path_buf());
+
+        while let Some(file) = queue.pop_front() {
+            if let Some(direct_importers) = self.importers.get(&file) {
+                for importer in direct_importers {
+                    if seen.insert(importer.clone()) {
+                        queue.push_back(importer.clone());
+                    }
+                }
+            }
+        }
+        seen
+    }
+}
+
+/// Recursively walk a statement list for `import`/`from ... import`,
+/// descending into the same set of blocks `reachability::collect_imports`
+/// does, resolving each to a project-relative file path.
+fn collect_imports(stmts: &[ast::Stmt], file: &Path, project_root: &Path, out: &mut Vec<PathBuf>) {
+    for stmt in stmts {
+        match stmt {
+            ast::Stmt::Import(import) => {
+                for alias in &import.names {
+                    if let Some(p) = resolve_dotted(project_root, alias.name.as_str()) {
+                        out.push(p);
+                    }
+                }
+            }
+            ast::Stmt::ImportFrom(import_from) => {
+                collect_import_from(import_from, file, project_root, out);
+            }
+            ast::Stmt::FunctionDef(f) => collect_imports(&f.body, file, project_root, out),
+            ast::Stmt::AsyncFunctionDef(f) => collect_imports(&f.body, file, project_root, out),
+            ast::Stmt::ClassDef(c) => collect_imports(&c.body, file, project_root, out),
+            ast::Stmt::If(s) => {
+                collect_imports(&s.body, file, project_root, out);
+                collect_imports(&s.orelse, file, project_root, out);
+            }
+            ast::Stmt::For(s) => {
+                collect_imports(&s.body, file, project_root, out);
+                collect_imports(&s.orelse, file, project_root, out);
+            }
+            ast::Stmt::AsyncFor(s) => {
+                collect_imports(&s.body, file, project_root, out);
+                collect_imports(&s.orelse, file, project_root, out);
+            }
+            ast::Stmt::While(s) => {
+                collect_imports(&s.body, file, project_root, out);
+                collect_imports(&s.orelse, file, project_root, out);
+            }
+            ast::Stmt::With(s) => collect_imports(&s.body, file, project_root, out),
+            ast::Stmt::AsyncWith(s) => collect_imports(&s.body, file, project_root, out),
+            ast::Stmt::Try(s) => {
+                collect_imports(&s.body, file, project_root, out);
+                for handler in &s.handlers {
+                    let ast::ExceptHandler::ExceptHandler(h) = handler;
+                    collect_imports(&h.body, file, project_root, out);
+                }
+                collect_imports(&s.orelse, file, project_root, out);
+                collect_imports(&s.finalbody, file, project_root, out);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Resolve a single `from ... import ...` statement to the project file(s)
+/// it names: the target module/package itself, plus any imported name that
+/// also resolves to a project submodule (`from pkg import sub` where
+/// `pkg/sub.py` exists, not just an attribute of `pkg`).
+fn collect_import_from(
+    import_from: &ast::StmtImportFrom,
+    file: &Path,
+    project_root: &Path,
+    out: &mut Vec<PathBuf>,
+) {
+    let level = import_from
+        .level
+        .as_ref()
+        .map(|l| l.to_string().parse::<usize>().unwrap_or(0))
+        .unwrap_or(0);
+
+    // `file`'s own containing directory is its package, for both a regular
+    // module (`pkg/leaf.py`, package `pkg`) and a package itself
+    // (`pkg/__init__.py`, package `pkg`) - in path space, `.parent()` lands
+    // on the right directory in both cases without needing to special-case
+    // which kind `file` is, unlike the dotted-name arithmetic
+    // `reachability::relative_base` has to do.
+    let base_dir: Option<PathBuf> = if level > 0 {
+        let mut dir = match file.parent() {
+            Some(d) => d.to_path_buf(),
+            None => return,
+        };
+        for _ in 0..level.saturating_sub(1) {
+            dir = match dir.parent() {
+                Some(d) => d.to_path_buf(),
+                None => return,
+            };
+        }
+        Some(dir)
+    } else {
+        None
+    };
+
+    let base_dir = match (&base_dir, &import_from.module) {
+        (Some(dir), Some(m)) => dir.join(m.to_string().replace('.', "/")),
+        (Some(dir), None) => dir.clone(),
+        (None, Some(m)) => PathBuf::from(m.to_string().replace('.', "/")),
+        (None, None) => return,
+    };
+
+    if let Some(p) = resolve_as_module(project_root, &base_dir) {
+        out.push(p);
+    }
+    for alias in &import_from.names {
+        if alias.name.as_str() == "*" {
+            continue; // whole module is already kept above
+        }
+        if let Some(p) = resolve_as_module(project_root, &base_dir.join(alias.name.as_str())) {
+            out.push(p);
+        }
+    }
+}
+
+/// Resolve a dotted absolute import (`import a.b.c`) to a project file.
+fn resolve_dotted(project_root: &Path, dotted: &str) -> Option<PathBuf> {
+    resolve_as_module(project_root, &PathBuf::from(dotted.replace('.', "/")))
+}
+
+/// `candidate` is project-relative with dots already turned into path
+/// separators; try it as a plain module (`candidate.py`) then as a package
+/// (`candidate/__init__.py`), returning whichever actually exists.
+fn resolve_as_module(project_root: &Path, candidate: &Path) -> Option<PathBuf> {
+    let as_module = candidate.with_extension("py");
+    if project_root.join(&as_module).is_file() {
+        return Some(as_module);
+    }
+    let as_package = candidate.join("__init__.py");
+    if project_root.join(&as_package).is_file() {
+        return Some(as_package);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tmp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "tach_importgraph_test_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write(dir: &Path, rel: &str, source: &str) {
+        let path = dir.join(rel);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(path, source).unwrap();
+    }
+
+    #[test]
+    fn test_absolute_import_resolves_to_project_file() {
+        let dir = tmp_dir("absolute");
+        write(&dir, "test_a.py", "import helper\n");
+        write(&dir, "helper.py", "x = 1\n");
+
+        let graph = ImportGraph::build(&dir);
+        let dependents = graph.transitive_dependents(Path::new("helper.py"));
+        assert!(dependents.contains(Path::new("test_a.py")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_from_import_resolves_to_project_file() {
+        let dir = tmp_dir("from_import");
+        write(&dir, "test_a.py", "from helpers import util\n");
+        write(&dir, "helpers.py", "def util(): pass\n");
+
+        let graph = ImportGraph::build(&dir);
+        let dependents = graph.transitive_dependents(Path::new("helpers.py"));
+        assert!(dependents.contains(Path::new("test_a.py")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_from_import_submodule_resolves_to_submodule_file() {
+        let dir = tmp_dir("submodule");
+        write(&dir, "test_a.py", "from pkg import sub\n");
+        write(&dir, "pkg/__init__.py", "");
+        write(&dir, "pkg/sub.py", "x = 1\n");
+
+        let graph = ImportGraph::build(&dir);
+        let dependents = graph.transitive_dependents(Path::new("pkg/sub.py"));
+        assert!(dependents.contains(Path::new("test_a.py")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_relative_import_resolves_within_package() {
+        let dir = tmp_dir("relative");
+        write(&dir, "pkg/__init__.py", "");
+        write(&dir, "pkg/test_a.py", "from . import helper\n");
+        write(&dir, "pkg/helper.py", "x = 1\n");
+
+        let graph = ImportGraph::build(&dir);
+        let dependents = graph.transitive_dependents(Path::new("pkg/helper.py"));
+        assert!(dependents.contains(Path::new("pkg/test_a.py")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_transitive_dependents_follows_import_chain() {
+        let dir = tmp_dir("transitive");
+        write(&dir, "test_a.py", "import middle\n");
+        write(&dir, "middle.py", "import leaf\n");
+        write(&dir, "leaf.py", "x = 1\n");
+
+        let graph = ImportGraph::build(&dir);
+        let dependents = graph.transitive_dependents(Path::new("leaf.py"));
+        assert!(dependents.contains(Path::new("leaf.py")));
+        assert!(dependents.contains(Path::new("middle.py")));
+        assert!(dependents.contains(Path::new("test_a.py")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_unrelated_file_not_included() {
+        let dir = tmp_dir("unrelated");
+        write(&dir, "test_a.py", "import helper\n");
+        write(&dir, "helper.py", "x = 1\n");
+        write(&dir, "unrelated.py", "y = 2\n");
+
+        let graph = ImportGraph::build(&dir);
+        let dependents = graph.transitive_dependents(Path::new("helper.py"));
+        assert!(!dependents.contains(Path::new("unrelated.py")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_empty_graph_only_contains_queried_path() {
+        let graph = ImportGraph::empty();
+        let dependents = graph.transitive_dependents(Path::new("anything.py"));
+        assert_eq!(dependents.len(), 1);
+        assert!(dependents.contains(Path::new("anything.py")));
+    }
+
+    #[test]
+    fn test_finds_imports_inside_try_except() {
+        let dir = tmp_dir("try_except");
+        write(
+            &dir,
+            "test_a.py",
+            "try:\n    import fast_impl\nexcept ImportError:\n    import slow_impl\n",
+        );
+        write(&dir, "fast_impl.py", "x = 1\n");
+        write(&dir, "slow_impl.py", "x = 2\n");
+
+        let graph = ImportGraph::build(&dir);
+        assert!(graph
+            .transitive_dependents(Path::new("fast_impl.py"))
+            .contains(Path::new("test_a.py")));
+        assert!(graph
+            .transitive_dependents(Path::new("slow_impl.py"))
+            .contains(Path::new("test_a.py")));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,769 @@
+//! Incremental Watch Resolution
+//!
+//! Builds a reverse fixture-dependency graph from a `DiscoveryResult` so
+//! that, when a single file changes, watch mode can re-resolve and re-run
+//! only the tests transitively affected by the change instead of the
+//! whole suite.
+
+use crate::discovery::{is_test_file, parse_module, DiscoveryResult, TestModule};
+use anyhow::Result;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// Reverse dependency graph over a `DiscoveryResult`: maps each fixture to
+/// the fixtures and tests that (directly) depend on it, plus a
+/// file -> {fixtures, tests} index for diffing a single changed file.
+#[derive(Debug, Default)]
+pub struct ReverseDepGraph {
+    /// fixture name -> names of fixtures that directly depend on it
+    fixture_to_fixtures: HashMap<String, HashSet<String>>,
+    /// fixture name -> test ids that directly depend on it
+    fixture_to_tests: HashMap<String, HashSet<String>>,
+    /// file path -> (fixture names defined there, test ids defined there)
+    file_index: HashMap<PathBuf, (HashSet<String>, HashSet<String>)>,
+}
+
+impl ReverseDepGraph {
+    /// Build the graph from a full discovery pass.
+    pub fn build(result: &DiscoveryResult) -> Self {
+        let mut graph = Self::default();
+        for module in &result.modules {
+            graph.index_module(module);
+        }
+        graph
+    }
+
+    /// Fold a single module's fixtures/tests into the graph. Used both by
+    /// `build` and to re-index a file after it's been re-discovered.
+    fn index_module(&mut self, module: &TestModule) {
+        let mut fixture_names = HashSet::new();
+        let mut test_names = HashSet::new();
+
+        for fixture in &module.fixtures {
+            fixture_names.insert(fixture.name.clone());
+            for dep in &fixture.dependencies {
+                self.fixture_to_fixtures
+                    .entry(dep.clone())
+                    .or_default()
+                    .insert(fixture.name.clone());
+            }
+        }
+        for test in &module.tests {
+            test_names.insert(test.name.clone());
+            for dep in &test.dependencies {
+                self.fixture_to_tests
+                    .entry(dep.clone())
+                    .or_default()
+                    .insert(test.name.clone());
+            }
+        }
+
+        self.file_index
+            .insert(module.path.clone(), (fixture_names, test_names));
+    }
+
+    /// Remove a file's prior contribution to the graph (used before
+    /// re-indexing it with freshly re-discovered definitions).
+    fn remove_module(&mut self, path: &Path) {
+        let Some((fixtures, tests)) = self.file_index.remove(path) else {
+            return;
+        };
+        for set in self.fixture_to_fixtures.values_mut() {
+            for f in &fixtures {
+                set.remove(f);
+            }
+        }
+        for set in self.fixture_to_tests.values_mut() {
+            for t in &tests {
+                set.remove(t);
+            }
+        }
+        self.fixture_to_fixtures.retain(|_, v| !v.is_empty());
+        self.fixture_to_tests.retain(|_, v| !v.is_empty());
+    }
+
+    /// All fixtures transitively depending on `fixture_name` (BFS over the
+    /// reverse edge set), including `fixture_name` itself.
+    fn transitive_fixtures(&self, fixture_name: &str) -> HashSet<String> {
+        let mut seen = HashSet::new();
+        let mut queue = vec![fixture_name.to_string()];
+        seen.insert(fixture_name.to_string());
+
+        while let Some(name) = queue.pop() {
+            if let Some(dependents) = self.fixture_to_fixtures.get(&name) {
+                for dependent in dependents {
+                    if seen.insert(dependent.clone()) {
+                        queue.push(dependent.clone());
+                    }
+                }
+            }
+        }
+        seen
+    }
+
+    /// Test ids in `file_path`, for re-resolving just that file.
+    pub fn tests_in_file(&self, file_path: &Path) -> HashSet<String> {
+        self.file_index
+            .get(file_path)
+            .map(|(_, tests)| tests.clone())
+            .unwrap_or_default()
+    }
+
+    /// Compute the transitive closure of test ids impacted by a set of
+    /// changed fixture names and a set of directly-changed test ids.
+    pub fn impacted_tests(
+        &self,
+        changed_fixtures: &HashSet<String>,
+        changed_tests: &HashSet<String>,
+    ) -> HashSet<String> {
+        let mut impacted = changed_tests.clone();
+        for fixture in changed_fixtures {
+            for affected_fixture in self.transitive_fixtures(fixture) {
+                if let Some(tests) = self.fixture_to_tests.get(&affected_fixture) {
+                    impacted.extend(tests.iter().cloned());
+                }
+            }
+        }
+        impacted
+    }
+}
+
+/// What changed about a single re-discovered file, relative to its last
+/// known definitions.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FileDiff {
+    pub added_fixtures: HashSet<String>,
+    pub removed_fixtures: HashSet<String>,
+    /// Fixture kept its name but its own dependency set changed - its
+    /// existing dependents must be re-resolved (and re-checked for new
+    /// cycles) even though the fixture itself wasn't added or removed.
+    pub changed_fixtures: HashSet<String>,
+    pub added_tests: HashSet<String>,
+    pub removed_tests: HashSet<String>,
+}
+
+impl FileDiff {
+    fn is_empty(&self) -> bool {
+        self.added_fixtures.is_empty()
+            && self.removed_fixtures.is_empty()
+            && self.changed_fixtures.is_empty()
+            && self.added_tests.is_empty()
+            && self.removed_tests.is_empty()
+    }
+
+    /// All fixture names this diff requires re-resolving dependents for.
+    fn touched_fixtures(&self) -> HashSet<String> {
+        self.added_fixtures
+            .iter()
+            .chain(&self.removed_fixtures)
+            .chain(&self.changed_fixtures)
+            .cloned()
+            .collect()
+    }
+
+    /// Test ids that changed shape in this file (added/removed). Does not
+    /// include tests whose fixtures moved - those are picked up via
+    /// `touched_fixtures` in `ReverseDepGraph::impacted_tests`.
+    fn touched_tests(&self) -> HashSet<String> {
+        self.added_tests
+            .iter()
+            .chain(&self.removed_tests)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Diff a file's previous definitions against its freshly re-parsed ones.
+/// `old` is `None` for a brand-new file.
+pub fn diff_module(old: Option<&TestModule>, new: &TestModule) -> FileDiff {
+    let empty = TestModule {
+        path: new.path.clone(),
+        tests: vec![],
+        fixtures: vec![],
+    };
+    let old = old.unwrap_or(&empty);
+
+    let old_fixtures: HashMap<_, _> = old
+        .fixtures
+        .iter()
+        .map(|f| (f.name.clone(), f.dependencies.clone()))
+        .collect();
+    let new_fixtures: HashMap<_, _> = new
+        .fixtures
+        .iter()
+        .map(|f| (f.name.clone(), f.dependencies.clone()))
+        .collect();
+
+    let mut diff = FileDiff::default();
+    for (name, deps) in &new_fixtures {
+        match old_fixtures.get(name) {
+            None => {
+                diff.added_fixtures.insert(name.clone());
+            }
+            Some(old_deps) if old_deps != deps => {
+                diff.changed_fixtures.insert(name.clone());
+            }
+            Some(_) => {}
+        }
+    }
+    for name in old_fixtures.keys() {
+        if !new_fixtures.contains_key(name) {
+            diff.removed_fixtures.insert(name.clone());
+        }
+    }
+
+    let old_tests: HashSet<_> = old.tests.iter().map(|t| t.name.clone()).collect();
+    let new_tests: HashSet<_> = new.tests.iter().map(|t| t.name.clone()).collect();
+    diff.added_tests = new_tests.difference(&old_tests).cloned().collect();
+    diff.removed_tests = old_tests.difference(&new_tests).cloned().collect();
+
+    diff
+}
+
+/// Cheap content fingerprint used to skip re-parsing a file whose text
+/// hasn't actually changed since the last scan (a burst of saves from an
+/// editor, or an unrelated filesystem event touching the same path).
+/// Collisions only cost a missed skip, never a missed re-parse - the next
+/// scan always re-hashes from the file's current contents.
+fn hash_source(source: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Every test id (`file::name`, matching `dump_json`'s id format) defined
+/// in a module, for diffing one rescan's test tree against the last.
+fn test_ids(module: &TestModule) -> HashSet<String> {
+    let file = module.path.to_string_lossy().to_string();
+    module
+        .tests
+        .iter()
+        .flat_map(|t| crate::discovery::node_ids(&file, t))
+        .collect()
+}
+
+/// The last component of a `file::name` test id, used to pair a removed id
+/// with an added one that's really the same test having moved files.
+fn bare_name(id: &str) -> &str {
+    id.rsplit("::").next().unwrap_or(id)
+}
+
+/// Test ids that appeared, disappeared, or moved to a different file
+/// across a single `IncrementalState::rescan`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TestIdDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// (old id, new id) pairs: a test whose bare name reappeared in exactly
+    /// one other file this scan, reported as a move rather than an
+    /// unrelated add/remove pair.
+    pub moved: Vec<(String, String)>,
+}
+
+/// Pair up added/removed ids that share a bare test name and came from a
+/// file that no longer (or not yet) defines it, reporting them as a move.
+/// Only pairs a 1:1 match - an ambiguous rename (the same bare name
+/// appearing/disappearing in more than one file this scan) is left as
+/// plain adds/removes rather than guessed at.
+fn pair_moves(added: HashSet<String>, removed: HashSet<String>) -> TestIdDiff {
+    let mut removed_by_name: HashMap<&str, Vec<&String>> = HashMap::new();
+    for id in &removed {
+        removed_by_name.entry(bare_name(id)).or_default().push(id);
+    }
+    let mut added_per_name: HashMap<&str, usize> = HashMap::new();
+    for id in &added {
+        *added_per_name.entry(bare_name(id)).or_default() += 1;
+    }
+
+    let mut moved = Vec::new();
+    let mut still_added = Vec::new();
+    let mut paired_removed = HashSet::new();
+    for id in &added {
+        let candidates = removed_by_name.get(bare_name(id)).map(Vec::as_slice).unwrap_or(&[]);
+        if candidates.len() == 1 && added_per_name[bare_name(id)] == 1 {
+            let old_id = candidates[0].clone();
+            moved.push((old_id.clone(), id.clone()));
+            paired_removed.insert(old_id);
+        } else {
+            still_added.push(id.clone());
+        }
+    }
+
+    let still_removed = removed
+        .into_iter()
+        .filter(|id| !paired_removed.contains(id))
+        .collect();
+
+    still_added.sort();
+    moved.sort();
+    let mut still_removed: Vec<String> = still_removed;
+    still_removed.sort();
+
+    TestIdDiff {
+        added: still_added,
+        removed: still_removed,
+        moved,
+    }
+}
+
+/// Tracks the last-known `DiscoveryResult` plus its reverse dependency
+/// graph across watch-mode iterations, so unaffected tests keep their
+/// prior resolution instead of being re-walked from scratch.
+pub struct IncrementalState {
+    modules: HashMap<PathBuf, TestModule>,
+    graph: ReverseDepGraph,
+    /// Content hash of each known file as of the last scan, so an
+    /// unchanged file is skipped without re-parsing it.
+    hashes: HashMap<PathBuf, u64>,
+}
+
+impl IncrementalState {
+    pub fn new(result: DiscoveryResult) -> Self {
+        let graph = ReverseDepGraph::build(&result);
+        let mut hashes = HashMap::new();
+        for module in &result.modules {
+            if let Ok(source) = fs::read_to_string(&module.path) {
+                hashes.insert(module.path.clone(), hash_source(&source));
+            }
+        }
+        let modules = result
+            .modules
+            .into_iter()
+            .map(|m| (m.path.clone(), m))
+            .collect();
+        Self { modules, graph, hashes }
+    }
+
+    /// Walk `root` for test files (the same filters `discovery::discover`
+    /// uses), re-parsing only files whose content hash changed since the
+    /// last scan and dropping entries for files that disappeared, so a long
+    /// burst of saves costs one re-parse per actually-changed file rather
+    /// than a full tree re-walk. Returns which test ids appeared,
+    /// disappeared, or moved to a different file.
+    pub fn rescan(&mut self, root: &Path) -> Result<TestIdDiff> {
+        let paths: Vec<PathBuf> = ignore::WalkBuilder::new(root)
+            .standard_filters(true)
+            .build()
+            .filter_map(|e| e.ok())
+            .filter(|e| is_test_file(e.path()))
+            .map(|e| {
+                e.path()
+                    .strip_prefix(root)
+                    .unwrap_or(e.path())
+                    .to_path_buf()
+            })
+            .collect();
+
+        let mut on_disk = HashSet::new();
+        let mut added_ids = HashSet::new();
+        let mut removed_ids = HashSet::new();
+
+        for path in &paths {
+            on_disk.insert(path.clone());
+            let Ok(source) = fs::read_to_string(path) else {
+                continue;
+            };
+            let hash = hash_source(&source);
+            if self.hashes.get(path) == Some(&hash) {
+                continue;
+            }
+            self.hashes.insert(path.clone(), hash);
+
+            let old = self.modules.get(path).cloned();
+            let Ok(new_module) = parse_module(path, false) else {
+                continue;
+            };
+
+            let old_ids = old.as_ref().map(test_ids).unwrap_or_default();
+            let new_ids = test_ids(&new_module);
+            removed_ids.extend(old_ids.difference(&new_ids).cloned());
+            added_ids.extend(new_ids.difference(&old_ids).cloned());
+
+            self.graph.remove_module(path);
+            self.graph.index_module(&new_module);
+            self.modules.insert(path.clone(), new_module);
+        }
+
+        let gone: Vec<PathBuf> = self
+            .modules
+            .keys()
+            .filter(|p| !on_disk.contains(*p))
+            .cloned()
+            .collect();
+        for path in gone {
+            if let Some(module) = self.modules.remove(&path) {
+                removed_ids.extend(test_ids(&module));
+            }
+            self.graph.remove_module(&path);
+            self.hashes.remove(&path);
+        }
+
+        Ok(pair_moves(added_ids, removed_ids))
+    }
+
+    /// Snapshot the current per-file modules back into a `DiscoveryResult`,
+    /// e.g. to hand to `Resolver` after `rescan` without re-walking or
+    /// re-parsing anything `rescan` decided was unchanged. Sorted by path so
+    /// repeated snapshots are stable even though the backing map isn't.
+    pub fn current_result(&self) -> DiscoveryResult {
+        let mut modules: Vec<TestModule> = self.modules.values().cloned().collect();
+        modules.sort_by(|a, b| a.path.cmp(&b.path));
+        DiscoveryResult { modules }
+    }
+
+    /// Re-parse `path` (relative to the project root, matching
+    /// `DiscoveryResult` paths) and fold the change into the graph.
+    ///
+    /// Returns the set of test ids that must be re-resolved and re-run: the
+    /// tests added to or removed from `path`, plus every test transitively
+    /// depending on a fixture that was added, removed, or changed there. A
+    /// removed fixture's former dependents are included here too, so
+    /// re-resolving them surfaces a `MissingFixture` error instead of
+    /// silently keeping their stale resolution. Only names and dependency
+    /// lists are compared, so an edit that leaves both unchanged (e.g. a
+    /// test body) yields an empty set.
+    pub fn handle_change(&mut self, path: &Path) -> Result<HashSet<String>> {
+        let old = self.modules.get(path).cloned();
+        let new_module = parse_module(path, false)?;
+        let diff = diff_module(old.as_ref(), &new_module);
+
+        if diff.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let impacted = self
+            .graph
+            .impacted_tests(&diff.touched_fixtures(), &diff.touched_tests());
+
+        self.graph.remove_module(path);
+        self.graph.index_module(&new_module);
+        self.modules.insert(path.to_path_buf(), new_module);
+
+        Ok(impacted)
+    }
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::discovery::{FixtureDefinition, FixtureScope, TestCase};
+
+    fn fixture(name: &str, deps: Vec<&str>) -> FixtureDefinition {
+        FixtureDefinition {
+            name: name.to_string(),
+            scope: FixtureScope::Function,
+            dependencies: deps.into_iter().map(String::from).collect(),
+            params: None,
+            class_scope: None,
+        }
+    }
+
+    fn test_case(name: &str, deps: Vec<&str>) -> TestCase {
+        TestCase {
+            name: name.to_string(),
+            dependencies: deps.into_iter().map(String::from).collect(),
+            is_async: false,
+            line_number: 1,
+            xfail: None,
+            parametrized_args: vec![],
+            param_sets: vec![],
+            markers: vec![],
+            is_doctest: false,
+        }
+    }
+
+    fn discovery(modules: Vec<TestModule>) -> DiscoveryResult {
+        DiscoveryResult { modules }
+    }
+
+    #[test]
+    fn test_impacted_tests_direct_dependency() {
+        let result = discovery(vec![TestModule {
+            path: PathBuf::from("conftest.py"),
+            tests: vec![],
+            fixtures: vec![fixture("db", vec![])],
+        }, TestModule {
+            path: PathBuf::from("test_a.py"),
+            tests: vec![test_case("test_uses_db", vec!["db"])],
+            fixtures: vec![],
+        }]);
+
+        let graph = ReverseDepGraph::build(&result);
+        let mut changed = HashSet::new();
+        changed.insert("db".to_string());
+        let impacted = graph.impacted_tests(&changed, &HashSet::new());
+
+        assert!(impacted.contains("test_uses_db"));
+    }
+
+    #[test]
+    fn test_impacted_tests_transitive_through_fixture_chain() {
+        let result = discovery(vec![
+            TestModule {
+                path: PathBuf::from("conftest.py"),
+                tests: vec![],
+                fixtures: vec![fixture("base", vec![]), fixture("db", vec!["base"])],
+            },
+            TestModule {
+                path: PathBuf::from("test_a.py"),
+                tests: vec![test_case("test_uses_db", vec!["db"])],
+                fixtures: vec![],
+            },
+        ]);
+
+        let graph = ReverseDepGraph::build(&result);
+        let mut changed = HashSet::new();
+        changed.insert("base".to_string());
+        let impacted = graph.impacted_tests(&changed, &HashSet::new());
+
+        // Changing `base` must ripple through `db` to reach the test.
+        assert!(impacted.contains("test_uses_db"));
+    }
+
+    #[test]
+    fn test_impacted_tests_unrelated_fixture_not_included() {
+        let result = discovery(vec![
+            TestModule {
+                path: PathBuf::from("conftest.py"),
+                tests: vec![],
+                fixtures: vec![fixture("db", vec![]), fixture("cache", vec![])],
+            },
+            TestModule {
+                path: PathBuf::from("test_a.py"),
+                tests: vec![
+                    test_case("test_uses_db", vec!["db"]),
+                    test_case("test_uses_cache", vec!["cache"]),
+                ],
+                fixtures: vec![],
+            },
+        ]);
+
+        let graph = ReverseDepGraph::build(&result);
+        let mut changed = HashSet::new();
+        changed.insert("db".to_string());
+        let impacted = graph.impacted_tests(&changed, &HashSet::new());
+
+        assert!(impacted.contains("test_uses_db"));
+        assert!(!impacted.contains("test_uses_cache"));
+    }
+
+    #[test]
+    fn test_diff_module_detects_added_and_removed_fixture() {
+        let old = TestModule {
+            path: PathBuf::from("conftest.py"),
+            tests: vec![],
+            fixtures: vec![fixture("db", vec![])],
+        };
+        let new = TestModule {
+            path: PathBuf::from("conftest.py"),
+            tests: vec![],
+            fixtures: vec![fixture("cache", vec![])],
+        };
+
+        let diff = diff_module(Some(&old), &new);
+        assert!(diff.added_fixtures.contains("cache"));
+        assert!(diff.removed_fixtures.contains("db"));
+    }
+
+    #[test]
+    fn test_diff_module_detects_changed_dependencies() {
+        let old = TestModule {
+            path: PathBuf::from("conftest.py"),
+            tests: vec![],
+            fixtures: vec![fixture("db", vec!["base"])],
+        };
+        let new = TestModule {
+            path: PathBuf::from("conftest.py"),
+            tests: vec![],
+            fixtures: vec![fixture("db", vec!["base", "extra"])],
+        };
+
+        let diff = diff_module(Some(&old), &new);
+        assert!(diff.changed_fixtures.contains("db"));
+        assert!(diff.added_fixtures.is_empty());
+        assert!(diff.removed_fixtures.is_empty());
+    }
+
+    #[test]
+    fn test_diff_module_new_file_is_all_additions() {
+        let new = TestModule {
+            path: PathBuf::from("test_new.py"),
+            tests: vec![test_case("test_foo", vec![])],
+            fixtures: vec![fixture("db", vec![])],
+        };
+
+        let diff = diff_module(None, &new);
+        assert!(diff.added_tests.contains("test_foo"));
+        assert!(diff.added_fixtures.contains("db"));
+        assert!(diff.removed_tests.is_empty());
+    }
+
+    #[test]
+    fn test_diff_module_unchanged_file_is_empty_diff() {
+        let module = TestModule {
+            path: PathBuf::from("test_a.py"),
+            tests: vec![test_case("test_foo", vec!["db"])],
+            fixtures: vec![fixture("db", vec![])],
+        };
+
+        let diff = diff_module(Some(&module), &module);
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_removed_fixture_keeps_dependent_impacted_for_missing_fixture_reresolution() {
+        // A fixture's dependent must be re-surfaced (not silently dropped)
+        // once the fixture it relies on disappears from the file.
+        let result = discovery(vec![
+            TestModule {
+                path: PathBuf::from("conftest.py"),
+                tests: vec![],
+                fixtures: vec![fixture("db", vec![])],
+            },
+            TestModule {
+                path: PathBuf::from("test_a.py"),
+                tests: vec![test_case("test_uses_db", vec!["db"])],
+                fixtures: vec![],
+            },
+        ]);
+
+        let graph = ReverseDepGraph::build(&result);
+        let old = TestModule {
+            path: PathBuf::from("conftest.py"),
+            tests: vec![],
+            fixtures: vec![fixture("db", vec![])],
+        };
+        let new = TestModule {
+            path: PathBuf::from("conftest.py"),
+            tests: vec![],
+            fixtures: vec![],
+        };
+        let diff = diff_module(Some(&old), &new);
+        let impacted = graph.impacted_tests(&diff.touched_fixtures(), &diff.touched_tests());
+
+        assert!(impacted.contains("test_uses_db"));
+    }
+
+    #[test]
+    fn test_tests_in_file() {
+        let result = discovery(vec![TestModule {
+            path: PathBuf::from("test_a.py"),
+            tests: vec![test_case("test_foo", vec![]), test_case("test_bar", vec![])],
+            fixtures: vec![],
+        }]);
+
+        let graph = ReverseDepGraph::build(&result);
+        let tests = graph.tests_in_file(Path::new("test_a.py"));
+        assert_eq!(tests.len(), 2);
+        assert!(tests.contains("test_foo"));
+    }
+
+    #[test]
+    fn test_remove_module_clears_prior_contribution() {
+        let result = discovery(vec![TestModule {
+            path: PathBuf::from("conftest.py"),
+            tests: vec![],
+            fixtures: vec![fixture("db", vec!["base"]), fixture("base", vec![])],
+        }]);
+
+        let mut graph = ReverseDepGraph::build(&result);
+        assert!(!graph.transitive_fixtures("base").is_empty());
+
+        graph.remove_module(Path::new("conftest.py"));
+        // Only "base" itself remains in its own transitive closure once its
+        // sole dependent ("db") has been removed from the graph.
+        assert_eq!(graph.transitive_fixtures("base"), {
+            let mut s = HashSet::new();
+            s.insert("base".to_string());
+            s
+        });
+    }
+
+    #[test]
+    fn test_pair_moves_detects_single_file_rename() {
+        let mut added = HashSet::new();
+        added.insert("tests/test_b.py::test_foo".to_string());
+        let mut removed = HashSet::new();
+        removed.insert("tests/test_a.py::test_foo".to_string());
+
+        let diff = pair_moves(added, removed);
+        assert_eq!(
+            diff.moved,
+            vec![(
+                "tests/test_a.py::test_foo".to_string(),
+                "tests/test_b.py::test_foo".to_string()
+            )]
+        );
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+    }
+
+    #[test]
+    fn test_pair_moves_leaves_ambiguous_rename_as_add_and_remove() {
+        // "test_foo" disappeared from two files and appeared in one - no
+        // single pairing is unambiguous, so nothing gets guessed at.
+        let mut added = HashSet::new();
+        added.insert("tests/test_c.py::test_foo".to_string());
+        let mut removed = HashSet::new();
+        removed.insert("tests/test_a.py::test_foo".to_string());
+        removed.insert("tests/test_b.py::test_foo".to_string());
+
+        let diff = pair_moves(added, removed);
+        assert!(diff.moved.is_empty());
+        assert_eq!(diff.added, vec!["tests/test_c.py::test_foo".to_string()]);
+        assert_eq!(diff.removed.len(), 2);
+    }
+
+    #[test]
+    fn test_pair_moves_unrelated_add_and_remove_stay_separate() {
+        let mut added = HashSet::new();
+        added.insert("tests/test_a.py::test_new".to_string());
+        let mut removed = HashSet::new();
+        removed.insert("tests/test_a.py::test_old".to_string());
+
+        let diff = pair_moves(added, removed);
+        assert!(diff.moved.is_empty());
+        assert_eq!(diff.added, vec!["tests/test_a.py::test_new".to_string()]);
+        assert_eq!(diff.removed, vec!["tests/test_a.py::test_old".to_string()]);
+    }
+
+    #[test]
+    fn test_current_result_reflects_rescanned_modules_sorted_by_path() {
+        let result = discovery(vec![
+            TestModule {
+                path: PathBuf::from("test_b.py"),
+                tests: vec![test_case("test_one", vec![])],
+                fixtures: vec![],
+            },
+            TestModule {
+                path: PathBuf::from("test_a.py"),
+                tests: vec![test_case("test_two", vec![])],
+                fixtures: vec![],
+            },
+        ]);
+
+        let state = IncrementalState::new(result);
+        let snapshot = state.current_result();
+        let paths: Vec<&Path> = snapshot.modules.iter().map(|m| m.path.as_path()).collect();
+        assert_eq!(paths, vec![Path::new("test_a.py"), Path::new("test_b.py")]);
+    }
+
+    #[test]
+    fn test_test_ids_formats_as_file_double_colon_name() {
+        let module = TestModule {
+            path: PathBuf::from("tests/test_a.py"),
+            tests: vec![test_case("test_foo", vec![]), test_case("test_bar", vec![])],
+            fixtures: vec![],
+        };
+        let ids = test_ids(&module);
+        assert!(ids.contains("tests/test_a.py::test_foo"));
+        assert!(ids.contains("tests/test_a.py::test_bar"));
+    }
+}
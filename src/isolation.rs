@@ -2,10 +2,17 @@
 //!
 //! Each worker gets:
 //! - Private /tmp via Copy-on-Write overlay
-//! - Private network namespace with its own localhost
+//! - Private network namespace, posture driven by `Permissions::net`
 //! - READ-ONLY root filesystem (Iron Dome protection)
-//! - Writable overlay on project directory
+//! - Writable overlay on project directory, plus any `Permissions::write_paths`
+//! - Any `Permissions::read_paths` bind-mounted in read-only
+//!
+//! This used to be one fixed posture for every worker. `Permissions` (see
+//! `protocol::Permissions`), derived per test from its pytest marks (see
+//! `resolver::permissions_from_markers`), now lets a test opt into a looser
+//! or tighter sandbox than the default.
 
+use crate::protocol::{NetPolicy, Permissions};
 use anyhow::{Context, Result};
 use nix::mount::{mount, MsFlags};
 use nix::sched::{unshare, CloneFlags};
@@ -22,8 +29,10 @@ use std::process::Command;
 /// 4. Remount root as RO
 /// 5. Mount tmpfs (allowed over RO dir)
 /// 6. Mount overlays
-pub fn setup_filesystem(worker_id: u32, project_root: &Path) -> Result<()> {
-    // 1. Create new mount AND network namespaces
+pub fn setup_filesystem(worker_id: u32, project_root: &Path, permissions: &Permissions) -> Result<()> {
+    // 1. Create new mount AND network namespaces. The namespace itself is
+    // always isolated from the host regardless of policy - `NetPolicy` only
+    // controls what's reachable *inside* it (see step 3).
     unshare(CloneFlags::CLONE_NEWNS | CloneFlags::CLONE_NEWNET)
         .context("unshare(CLONE_NEWNS | CLONE_NEWNET) failed - requires CAP_SYS_ADMIN")?;
 
@@ -31,8 +40,33 @@ pub fn setup_filesystem(worker_id: u32, project_root: &Path) -> Result<()> {
     mount::<str, str, str, str>(None, "/", None, MsFlags::MS_REC | MsFlags::MS_PRIVATE, None)
         .context("Failed to mark root as MS_PRIVATE")?;
 
-    // 3. Bring up loopback interface
-    setup_loopback().context("Failed to configure loopback interface")?;
+    // 3. Configure network access per policy.
+    match &permissions.net {
+        NetPolicy::None => {
+            // Namespace has no interfaces at all - not even loopback.
+        }
+        NetPolicy::Loopback => {
+            setup_loopback().context("Failed to configure loopback interface")?;
+        }
+        NetPolicy::AllowHosts(hosts) => {
+            setup_loopback().context("Failed to configure loopback interface")?;
+            // Real egress to `hosts` needs a veth pair bridged to the host
+            // network plus host-side routing (and likely NAT) - that's
+            // host-cooperative setup this function can't safely do on its
+            // own, so for now `tach_allow_net` grants loopback only and
+            // warns rather than silently pretending egress is allowed.
+            let hosts_desc = if hosts.is_empty() {
+                "any host".to_string()
+            } else {
+                hosts.join(", ")
+            };
+            eprintln!(
+                "[tach] Warning: worker {} requested network access to {} via tach_allow_net, \
+                 but egress bridging isn't implemented yet - falling back to loopback only.",
+                worker_id, hosts_desc
+            );
+        }
+    }
 
     // 4. PREPARE MOUNT POINTS (while root is still writable!)
     let base = PathBuf::from(format!("/run/tach/worker_{}", worker_id));
@@ -112,6 +146,58 @@ pub fn setup_filesystem(worker_id: u32, project_root: &Path) -> Result<()> {
     )
     .context("Failed to mount overlay on project root")?;
 
+    // 10. Extra writable zones beyond /tmp and the project root.
+    for (i, extra_path) in permissions.write_paths.iter().enumerate() {
+        let extra_path = Path::new(extra_path);
+        let upper = base.join(format!("extra_write_upper_{}", i));
+        let work = base.join(format!("extra_write_work_{}", i));
+        fs::create_dir_all(&upper)
+            .with_context(|| format!("Failed to create upperdir for {}", extra_path.display()))?;
+        fs::create_dir_all(&work)
+            .with_context(|| format!("Failed to create workdir for {}", extra_path.display()))?;
+
+        let opts = format!(
+            "lowerdir={},upperdir={},workdir={}",
+            extra_path.display(),
+            upper.display(),
+            work.display()
+        );
+
+        mount::<str, Path, str, str>(
+            Some("overlay"),
+            extra_path,
+            Some("overlay"),
+            MsFlags::empty(),
+            Some(&opts),
+        )
+        .with_context(|| format!("Failed to mount overlay on {}", extra_path.display()))?;
+    }
+
+    // 11. Extra read-only bind mounts. Each path is bind-mounted over
+    // itself and remounted read-only, which also makes it read-only where
+    // it lies inside one of the writable overlays above (/tmp, the project
+    // root, a `write_paths` entry).
+    for read_path in &permissions.read_paths {
+        let read_path = Path::new(read_path);
+        mount::<Path, Path, str, str>(
+            Some(read_path),
+            read_path,
+            None,
+            MsFlags::MS_BIND | MsFlags::MS_REC,
+            None,
+        )
+        .with_context(|| format!("Failed to bind-mount {}", read_path.display()))?;
+
+        mount::<Path, Path, str, str>(
+            Some(read_path),
+            read_path,
+            None,
+            MsFlags::MS_BIND | MsFlags::MS_REMOUNT | MsFlags::MS_RDONLY | MsFlags::MS_REC,
+            None,
+        )
+        .with_context(|| format!("Failed to remount {} as read-only", read_path.display()))?;
+    }
+
     Ok(())
 }
 
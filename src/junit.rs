@@ -42,10 +42,30 @@ fn strip_ansi_codes(s: &str) -> String {
 #[derive(Serialize)]
 #[serde(rename = "testsuites")]
 struct TestSuites {
+    #[serde(rename = "@tests")]
+    tests: usize,
+    #[serde(rename = "@failures")]
+    failures: usize,
+    #[serde(rename = "@errors")]
+    errors: usize,
+    #[serde(rename = "@skipped")]
+    skipped: usize,
+    #[serde(rename = "@time")]
+    time: f64,
     #[serde(rename = "testsuite")]
     suites: Vec<TestSuite>,
 }
 
+/// Sum each `<testsuite>`'s counts into the root `<testsuites>` attributes.
+fn aggregate(suites: &[TestSuite]) -> (usize, usize, usize, usize, f64) {
+    let tests = suites.iter().map(|s| s.tests).sum();
+    let failures = suites.iter().map(|s| s.failures).sum();
+    let errors = suites.iter().map(|s| s.errors).sum();
+    let skipped = suites.iter().map(|s| s.skipped).sum();
+    let time = suites.iter().map(|s| s.time).sum();
+    (tests, failures, errors, skipped, time)
+}
+
 #[derive(Serialize)]
 struct TestSuite {
     #[serde(rename = "@name")]
@@ -60,20 +80,57 @@ struct TestSuite {
     skipped: usize,
     #[serde(rename = "@time")]
     time: f64,
+    /// ISO-8601, captured once at `on_run_start` so every suite in the same
+    /// run reports the same moment.
+    #[serde(rename = "@timestamp")]
+    timestamp: String,
+    #[serde(rename = "@hostname")]
+    hostname: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    properties: Option<Properties>,
     #[serde(rename = "testcase")]
     cases: Vec<TestCase>,
 }
 
+/// `<properties>` block: environment metadata (tach version, Python version,
+/// git SHA, CI job id, ...) so Jenkins/GitLab can group historical results
+/// and attribute flaky tests to the environment that produced them.
+#[derive(Serialize, Clone)]
+struct Properties {
+    #[serde(rename = "property")]
+    properties: Vec<Property>,
+}
+
+#[derive(Serialize, Clone)]
+struct Property {
+    #[serde(rename = "@name")]
+    name: String,
+    #[serde(rename = "@value")]
+    value: String,
+}
+
 #[derive(Serialize)]
 struct TestCase {
     #[serde(rename = "@name")]
     name: String,
     #[serde(rename = "@classname")]
     classname: String,
+    #[serde(rename = "@file")]
+    file: String,
+    #[serde(rename = "@line")]
+    line: usize,
     #[serde(rename = "@time")]
     time: f64,
     #[serde(skip_serializing_if = "Option::is_none")]
     failure: Option<Failure>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Error>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skipped: Option<Skipped>,
+    #[serde(rename = "system-out", skip_serializing_if = "Option::is_none")]
+    system_out: Option<CapturedOutput>,
+    #[serde(rename = "system-err", skip_serializing_if = "Option::is_none")]
+    system_err: Option<CapturedOutput>,
 }
 
 #[derive(Serialize)]
@@ -84,33 +141,227 @@ struct Failure {
     body: String,
 }
 
+/// Worker crash (panic, segfault, stale-worker timeout) - JUnit's `<error>`
+/// element, kept distinct from `<failure>` so CI can tell a crash from an
+/// ordinary assertion failure.
+#[derive(Serialize)]
+struct Error {
+    #[serde(rename = "@message")]
+    message: String,
+    #[serde(rename = "$text")]
+    body: String,
+}
+
+/// Empty `<skipped/>` marker element (JUnit has no body/attributes for this case)
+#[derive(Serialize)]
+struct Skipped {}
+
+/// Body of a `<system-out>`/`<system-err>` element: captured output lines
+/// joined with newlines, one element per test case.
+#[derive(Serialize)]
+struct CapturedOutput {
+    #[serde(rename = "$text")]
+    body: String,
+}
+
+/// Group cases into one `<testsuite>` per source file, in file-name order.
+/// `timestamp`/`hostname`/`properties` are run-level metadata, duplicated
+/// onto every suite since JUnit's schema attaches them per-`<testsuite>`.
+fn group_cases_by_file(
+    cases: Vec<TestCase>,
+    timestamp: &str,
+    hostname: &str,
+    properties: &[(String, String)],
+) -> Vec<TestSuite> {
+    let mut by_file: std::collections::BTreeMap<String, Vec<TestCase>> =
+        std::collections::BTreeMap::new();
+    for case in cases {
+        by_file.entry(case.file.clone()).or_default().push(case);
+    }
+
+    let properties = (!properties.is_empty()).then(|| Properties {
+        properties: properties
+            .iter()
+            .map(|(name, value)| Property {
+                name: name.clone(),
+                value: value.clone(),
+            })
+            .collect(),
+    });
+
+    by_file
+        .into_iter()
+        .map(|(file, cases)| {
+            let failures = cases.iter().filter(|c| c.failure.is_some()).count();
+            let errors = cases.iter().filter(|c| c.error.is_some()).count();
+            let skipped = cases.iter().filter(|c| c.skipped.is_some()).count();
+            let time = cases.iter().map(|c| c.time).sum();
+            TestSuite {
+                name: file,
+                tests: cases.len(),
+                failures,
+                errors,
+                skipped,
+                time,
+                timestamp: timestamp.to_string(),
+                hostname: hostname.to_string(),
+                properties: properties.clone(),
+                cases,
+            }
+        })
+        .collect()
+}
+
 // =============================================================================
 // JunitReporter
 // =============================================================================
 
+/// Where a `JunitReporter` writes its XML document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JunitTarget {
+    /// Write to stdout, e.g. for piping straight into a CI step that reads
+    /// the JUnit document from the job's captured output rather than a
+    /// fixed path. No `[tach] JUnit report written to ...` status line in
+    /// this mode - that would land in the same stream as the XML.
+    Stdout,
+    File(PathBuf),
+}
+
+impl JunitTarget {
+    /// `-` (the conventional "use stdout" placeholder for a path argument)
+    /// selects `Stdout`; anything else is a file path.
+    fn from_path(path: PathBuf) -> Self {
+        if path == PathBuf::from("-") {
+            JunitTarget::Stdout
+        } else {
+            JunitTarget::File(path)
+        }
+    }
+}
+
 /// Reporter that buffers results and writes JUnit XML on completion
 pub struct JunitReporter {
-    output_path: PathBuf,
+    output: JunitTarget,
+    /// Working directory test files are made relative to before their path
+    /// is turned into a `classname` - see `relative_classname`.
+    cwd: PathBuf,
+    /// `@hostname` on every `<testsuite>`, resolved once at construction.
+    hostname: String,
+    /// `<property>` entries attached to every `<testsuite>` - e.g. tach
+    /// version, Python version, git SHA, CI job id. Empty by default; set
+    /// via `with_properties`.
+    properties: Vec<(String, String)>,
+    /// `@timestamp` on every `<testsuite>`, captured at `on_run_start` so
+    /// every suite in a run reports the same moment.
+    timestamp: String,
     cases: Vec<TestCase>,
     start_time: Instant,
     error_message: Option<String>,
+    /// Source line of each in-flight test, set by `on_test_location` and
+    /// consumed when the case is built in `on_test_finished`.
+    lines: std::collections::HashMap<String, usize>,
+    /// Captured output lines per in-flight test id, keyed like
+    /// `HumanReporter::captured` so concurrent tests don't interleave.
+    captured: std::collections::HashMap<String, Vec<(String, String)>>,
 }
 
 impl JunitReporter {
-    pub fn new(path: PathBuf) -> Self {
+    /// `path == "-"` writes the XML document to stdout instead of a file -
+    /// see `JunitTarget::Stdout`. `cwd` is used to make each test file's
+    /// classname relative rather than dependent on whether tach was invoked
+    /// with an absolute or relative path - see `relative_classname`.
+    pub fn new(path: PathBuf, cwd: PathBuf) -> Self {
         Self {
-            output_path: path,
+            output: JunitTarget::from_path(path),
+            cwd,
+            hostname: hostname(),
+            properties: Vec::new(),
+            timestamp: iso8601_now(),
             cases: Vec::new(),
             start_time: Instant::now(),
             error_message: None,
+            lines: std::collections::HashMap::new(),
+            captured: std::collections::HashMap::new(),
         }
     }
+
+    /// Attach `<property name=".." value=".."/>` entries to every
+    /// `<testsuite>` - e.g. tach version, Python version, git SHA, CI job id.
+    pub fn with_properties(mut self, properties: Vec<(String, String)>) -> Self {
+        self.properties = properties;
+        self
+    }
+}
+
+/// Best-effort local hostname for `@hostname`, via the same raw-libc
+/// approach `provenance.rs`/`zygote.rs` use elsewhere in this crate.
+/// Falls back to "unknown" rather than failing the whole report.
+fn hostname() -> String {
+    let mut buf = [0u8; 256];
+    let rc = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) };
+    if rc != 0 {
+        return "unknown".to_string();
+    }
+    let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..end]).into_owned()
+}
+
+/// Current UTC time as an ISO-8601 timestamp (`YYYY-MM-DDTHH:MM:SSZ`), hand
+/// rolled since this crate has no date/time dependency - same tradeoff
+/// `failure_snapshot.rs` makes by hand-rolling its unified diff rather than
+/// pulling in a crate for one function.
+fn iso8601_now() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    civil_from_unix(secs)
+}
+
+/// Howard Hinnant's `civil_from_days` algorithm, adapted to also break out
+/// the time-of-day, to convert a Unix timestamp to a UTC `YYYY-MM-DDTHH:MM:SSZ`
+/// string without pulling in a date/time crate.
+fn civil_from_unix(unix_secs: u64) -> String {
+    let days = (unix_secs / 86400) as i64;
+    let time_of_day = unix_secs % 86400;
+    let (hour, min, sec) = (time_of_day / 3600, (time_of_day / 60) % 60, time_of_day % 60);
+
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = (z - era * 146097) as u64; // [0, 146096]
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365; // [0, 399]
+    let y = yoe as i64 + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100); // [0, 365]
+    let mp = (5 * doy + 2) / 153; // [0, 11]
+    let d = doy - (153 * mp + 2) / 5 + 1; // [1, 31]
+    let m = if mp < 10 { mp + 3 } else { mp - 9 }; // [1, 12]
+    let y = if m <= 2 { y + 1 } else { y };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        y, m, d, hour, min, sec
+    )
+}
+
+/// Normalize a test file path into a dotted JUnit classname: relative to
+/// `cwd` (falling back to the path as-is when it isn't under `cwd`, e.g. a
+/// path outside the project), separators turned into dots, and only a
+/// trailing `.py` extension stripped - not every `.py` substring, which
+/// `replace(".py", "")` would also mangle (e.g. `copyright.py_helpers`).
+fn relative_classname(file: &str, cwd: &std::path::Path) -> String {
+    let path = std::path::Path::new(file);
+    let relative = path.strip_prefix(cwd).unwrap_or(path);
+    let dotted = relative.to_string_lossy().replace(['/', '\\'], ".");
+    dotted.strip_suffix(".py").unwrap_or(&dotted).to_string()
 }
 
 impl Reporter for JunitReporter {
-    fn on_run_start(&mut self, _count: usize) {
+    fn on_run_start(&mut self, _count: usize, _seed: Option<u64>) {
         self.start_time = Instant::now();
+        self.timestamp = iso8601_now();
         self.cases.clear();
+        self.lines.clear();
+        self.captured.clear();
         self.error_message = None;
     }
 
@@ -118,6 +369,17 @@ impl Reporter for JunitReporter {
         // JUnit doesn't have a test_start event - we buffer results
     }
 
+    fn on_test_location(&mut self, id: &str, line: usize) {
+        self.lines.insert(id.to_string(), line);
+    }
+
+    fn on_test_output(&mut self, id: &str, stream: &str, line: &str) {
+        self.captured
+            .entry(id.to_string())
+            .or_default()
+            .push((stream.to_string(), line.to_string()));
+    }
+
     fn on_test_finished(
         &mut self,
         id: &str,
@@ -125,77 +387,157 @@ impl Reporter for JunitReporter {
         duration_ms: u64,
         message: Option<&str>,
     ) {
-        // Parse id "path/to/file.py::test_name" -> classname, name
+        // Parse id "path/to/file.py::test_name" -> classname, name. Deeper
+        // ids ("path/to/file.py::TestClass::test::[param]") come from
+        // parametrized or class-grouped tests; keep the file as classname
+        // and join every remaining segment into one dotted name
+        // ("TestClass.test.[param]") so the hierarchy survives as a single
+        // flat `<testcase>` - Jenkins/GitLab only understand that layer,
+        // not nested tags.
         let parts: Vec<&str> = id.splitn(2, "::").collect();
-        let classname = parts
-            .first()
-            .unwrap_or(&"unknown")
-            .replace('/', ".")
-            .replace(".py", "");
-        let name = parts.get(1).unwrap_or(&id).to_string();
-
-        let failure = if status != "pass" {
-            let raw_msg = message.unwrap_or("Test failed");
-            let clean_msg = strip_ansi_codes(raw_msg);
-            Some(Failure {
-                message: "Test failed".to_string(),
-                body: clean_msg,
-            })
-        } else {
-            None
+        let file = parts.first().unwrap_or(&"unknown").to_string();
+        let classname = relative_classname(&file, &self.cwd);
+        let rest = parts.get(1).copied().unwrap_or(id);
+        let name = rest.split("::").collect::<Vec<_>>().join(".");
+        let line = self.lines.remove(id).unwrap_or(0);
+
+        let (failure, error, skipped) = match status {
+            "skip" | "xfail" => (None, None, Some(Skipped {})),
+            "pass" => (None, None, None),
+            "crash" => {
+                let raw_msg = message.unwrap_or("Worker crashed");
+                let clean_msg = strip_ansi_codes(raw_msg);
+                (
+                    None,
+                    Some(Error {
+                        message: "Worker crashed".to_string(),
+                        body: clean_msg,
+                    }),
+                    None,
+                )
+            }
+            // Infra problems (collection errors, harness faults) - distinct
+            // from an assertion failure, so they serialize as <error> too.
+            "error" => {
+                let raw_msg = message.unwrap_or("Test infrastructure error");
+                let clean_msg = strip_ansi_codes(raw_msg);
+                (
+                    None,
+                    Some(Error {
+                        message: "Test infrastructure error".to_string(),
+                        body: clean_msg,
+                    }),
+                    None,
+                )
+            }
+            _ => {
+                let raw_msg = message.unwrap_or("Test failed");
+                let clean_msg = strip_ansi_codes(raw_msg);
+                (
+                    Some(Failure {
+                        message: "Test failed".to_string(),
+                        body: clean_msg,
+                    }),
+                    None,
+                    None,
+                )
+            }
         };
 
+        let mut stdout_lines = Vec::new();
+        let mut stderr_lines = Vec::new();
+        for (stream, line) in self.captured.remove(id).into_iter().flatten() {
+            match stream.as_str() {
+                "stderr" => stderr_lines.push(line),
+                _ => stdout_lines.push(line),
+            }
+        }
+        let system_out = (!stdout_lines.is_empty()).then(|| CapturedOutput {
+            body: stdout_lines.join("\n"),
+        });
+        let system_err = (!stderr_lines.is_empty()).then(|| CapturedOutput {
+            body: stderr_lines.join("\n"),
+        });
+
         self.cases.push(TestCase {
             name,
             classname,
+            file,
+            line,
             time: duration_ms as f64 / 1000.0,
             failure,
+            error,
+            skipped,
+            system_out,
+            system_err,
         });
     }
 
-    fn on_run_finished(&mut self, passed: usize, failed: usize, skipped: usize, duration_ms: u64) {
-        let suite = TestSuite {
-            name: "tach".to_string(),
-            tests: passed + failed + skipped,
-            failures: failed,
-            errors: 0,
+    #[allow(clippy::too_many_arguments)]
+    fn on_run_finished(
+        &mut self,
+        _passed: usize,
+        _failed: usize,
+        _skipped: usize,
+        _xfailed: usize,
+        _xpassed: usize,
+        _crashed: usize,
+        _duration_ms: u64,
+    ) {
+        // JUnit has no native xfail/xpass attributes: xfail renders as
+        // <skipped/> (see on_test_finished), xpass renders as a <failure/>.
+        // One <testsuite> per source file rather than a single flat "tach"
+        // suite, so each test module's cases (including parametrized/subtest
+        // expansions) are grouped the way CI dashboards expect.
+        let suites = group_cases_by_file(
+            std::mem::take(&mut self.cases),
+            &self.timestamp,
+            &self.hostname,
+            &self.properties,
+        );
+        let (tests, failures, errors, skipped, time) = aggregate(&suites);
+        let root = TestSuites {
+            tests,
+            failures,
+            errors,
             skipped,
-            time: duration_ms as f64 / 1000.0,
-            cases: std::mem::take(&mut self.cases),
+            time,
+            suites,
         };
 
-        let root = TestSuites {
-            suites: vec![suite],
+        use std::io::Write;
+
+        let xml = match quick_xml::se::to_string(&root) {
+            Ok(xml) => xml,
+            Err(e) => {
+                eprintln!("[tach] Failed to serialize JUnit report: {}", e);
+                return;
+            }
         };
 
-        // Write to file
-        match File::create(&self.output_path) {
-            Ok(file) => {
-                let mut writer = BufWriter::new(file);
-                // Write XML declaration
-                use std::io::Write;
+        match &self.output {
+            JunitTarget::Stdout => {
+                // No status line here - it would land in the same stream as
+                // the XML itself, breaking a pipeline reading it from stdout.
+                let stdout = std::io::stdout();
+                let mut writer = stdout.lock();
                 let _ = writer.write_all(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
-
-                // Serialize to string first, then write
-                match quick_xml::se::to_string(&root) {
-                    Ok(xml) => {
-                        if let Err(e) = writer.write_all(xml.as_bytes()) {
-                            eprintln!("[tach] Failed to write JUnit report: {}", e);
-                        } else {
-                            eprintln!(
-                                "[tach] JUnit report written to {}",
-                                self.output_path.display()
-                            );
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("[tach] Failed to serialize JUnit report: {}", e);
+                let _ = writer.write_all(xml.as_bytes());
+            }
+            JunitTarget::File(path) => match File::create(path) {
+                Ok(file) => {
+                    let mut writer = BufWriter::new(file);
+                    let _ = writer.write_all(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+                    if let Err(e) = writer.write_all(xml.as_bytes()) {
+                        eprintln!("[tach] Failed to write JUnit report: {}", e);
+                    } else {
+                        eprintln!("[tach] JUnit report written to {}", path.display());
                     }
                 }
-            }
-            Err(e) => {
-                eprintln!("[tach] Failed to create JUnit report: {}", e);
-            }
+                Err(e) => {
+                    eprintln!("[tach] Failed to create JUnit report: {}", e);
+                }
+            },
         }
     }
 
@@ -253,15 +595,15 @@ mod tests {
 
     #[test]
     fn test_junit_reporter_creation() {
-        let reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"));
+        let reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
         assert!(reporter.cases.is_empty());
     }
 
     #[test]
     fn test_junit_reporter_buffers_tests() {
-        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"));
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
 
-        reporter.on_run_start(2);
+        reporter.on_run_start(2, None);
         reporter.on_test_start("test.py::test_foo", "test.py");
         reporter.on_test_finished("test.py::test_foo", "pass", 42, None);
         reporter.on_test_start("test.py::test_bar", "test.py");
@@ -276,8 +618,8 @@ mod tests {
 
     #[test]
     fn test_junit_classname_parsing() {
-        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"));
-        reporter.on_run_start(1);
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(1, None);
         reporter.on_test_finished("path/to/test_module.py::test_func", "pass", 10, None);
 
         // path/to/test_module.py -> path.to.test_module
@@ -285,10 +627,68 @@ mod tests {
         assert_eq!(reporter.cases[0].name, "test_func");
     }
 
+    #[test]
+    fn test_junit_classname_is_relative_to_cwd_for_absolute_paths() {
+        let mut reporter = JunitReporter::new(
+            PathBuf::from("/tmp/test.xml"),
+            PathBuf::from("/home/user/project"),
+        );
+        reporter.on_run_start(1, None);
+        reporter.on_test_finished(
+            "/home/user/project/path/to/test_module.py::test_func",
+            "pass",
+            10,
+            None,
+        );
+
+        assert_eq!(reporter.cases[0].classname, "path.to.test_module");
+    }
+
+    #[test]
+    fn test_junit_classname_only_strips_trailing_py_extension() {
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(1, None);
+        reporter.on_test_finished("copyright.py_helpers.py::test_func", "pass", 10, None);
+
+        // Only the trailing ".py" is an extension - the one in the middle of
+        // the filename is part of the name and must survive.
+        assert_eq!(reporter.cases[0].classname, "copyright.py_helpers");
+    }
+
+    #[test]
+    fn test_junit_class_based_test_keeps_file_as_classname_and_dots_the_rest() {
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(1, None);
+        reporter.on_test_finished(
+            "path/to/test_module.py::TestWidget::test_create",
+            "pass",
+            10,
+            None,
+        );
+
+        assert_eq!(reporter.cases[0].classname, "path.to.test_module");
+        assert_eq!(reporter.cases[0].name, "TestWidget.test_create");
+    }
+
+    #[test]
+    fn test_junit_parametrized_class_test_joins_full_hierarchy_into_name() {
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(1, None);
+        reporter.on_test_finished(
+            "path/to/test_module.py::TestWidget::test_create::[param]",
+            "pass",
+            10,
+            None,
+        );
+
+        assert_eq!(reporter.cases[0].classname, "path.to.test_module");
+        assert_eq!(reporter.cases[0].name, "TestWidget.test_create.[param]");
+    }
+
     #[test]
     fn test_junit_time_conversion() {
-        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"));
-        reporter.on_run_start(1);
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(1, None);
         reporter.on_test_finished("test.py::test_a", "pass", 1500, None); // 1500ms = 1.5s
 
         assert!((reporter.cases[0].time - 1.5).abs() < 0.001);
@@ -296,8 +696,8 @@ mod tests {
 
     #[test]
     fn test_junit_failure_strips_ansi() {
-        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"));
-        reporter.on_run_start(1);
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(1, None);
         reporter.on_test_finished(
             "test.py::test_fail",
             "fail",
@@ -310,22 +710,209 @@ mod tests {
         assert!(!failure.body.contains("\x1b"));
     }
 
+    #[test]
+    fn test_junit_skip_renders_as_skipped_not_failure() {
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(1, None);
+        reporter.on_test_finished("test.py::test_skipped", "skip", 0, None);
+
+        assert!(reporter.cases[0].failure.is_none());
+        assert!(reporter.cases[0].skipped.is_some());
+    }
+
+    #[test]
+    fn test_junit_crash_renders_as_error_not_failure() {
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(1, None);
+        reporter.on_test_finished(
+            "test.py::test_crashed",
+            "crash",
+            0,
+            Some("segfault in worker"),
+        );
+
+        assert!(reporter.cases[0].failure.is_none());
+        assert!(reporter.cases[0].skipped.is_none());
+        let error = reporter.cases[0].error.as_ref().unwrap();
+        assert_eq!(error.body, "segfault in worker");
+    }
+
+    #[test]
+    fn test_junit_error_status_renders_as_error_not_failure() {
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(1, None);
+        reporter.on_test_finished(
+            "test.py::test_broken",
+            "error",
+            0,
+            Some("collection failed: ModuleNotFoundError"),
+        );
+
+        assert!(reporter.cases[0].failure.is_none());
+        assert!(reporter.cases[0].skipped.is_none());
+        let error = reporter.cases[0].error.as_ref().unwrap();
+        assert_eq!(error.body, "collection failed: ModuleNotFoundError");
+    }
+
+    #[test]
+    fn test_group_cases_by_file_counts_errors_separately_from_failures() {
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(2, None);
+        reporter.on_test_finished("a.py::test_1", "fail", 10, Some("boom"));
+        reporter.on_test_finished("a.py::test_2", "crash", 10, Some("died"));
+
+        let suites = group_cases_by_file(std::mem::take(&mut reporter.cases), "2024-01-01T00:00:00Z", "testhost", &[]);
+        assert_eq!(suites[0].failures, 1);
+        assert_eq!(suites[0].errors, 1);
+    }
+
+    #[test]
+    fn test_aggregate_sums_counts_and_time_across_suites() {
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(3, None);
+        reporter.on_test_finished("a.py::test_1", "pass", 1000, None);
+        reporter.on_test_finished("a.py::test_2", "fail", 1000, Some("boom"));
+        reporter.on_test_finished("b.py::test_3", "crash", 1000, Some("died"));
+
+        let suites = group_cases_by_file(std::mem::take(&mut reporter.cases), "2024-01-01T00:00:00Z", "testhost", &[]);
+        let (tests, failures, errors, skipped, time) = aggregate(&suites);
+        assert_eq!(tests, 3);
+        assert_eq!(failures, 1);
+        assert_eq!(errors, 1);
+        assert_eq!(skipped, 0);
+        assert!((time - 3.0).abs() < 0.001);
+    }
+
     #[test]
     fn test_junit_on_error_stores_message() {
-        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"));
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
         reporter.on_error("Zygote crashed");
         assert_eq!(reporter.error_message, Some("Zygote crashed".to_string()));
     }
 
     #[test]
     fn test_junit_run_start_clears_state() {
-        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"));
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
         reporter.on_test_finished("test.py::test_a", "pass", 10, None);
         reporter.on_error("some error");
 
         // Start new run should clear
-        reporter.on_run_start(0);
+        reporter.on_run_start(0, None);
         assert!(reporter.cases.is_empty());
         assert!(reporter.error_message.is_none());
     }
+
+    #[test]
+    fn test_junit_on_test_location_populates_file_and_line() {
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(1, None);
+        reporter.on_test_location("path/to/test_module.py::test_func", 42);
+        reporter.on_test_finished("path/to/test_module.py::test_func", "pass", 5, None);
+
+        assert_eq!(reporter.cases[0].file, "path/to/test_module.py");
+        assert_eq!(reporter.cases[0].line, 42);
+    }
+
+    #[test]
+    fn test_junit_missing_location_defaults_line_to_zero() {
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(1, None);
+        reporter.on_test_finished("test.py::test_a", "pass", 5, None);
+
+        assert_eq!(reporter.cases[0].line, 0);
+    }
+
+    #[test]
+    fn test_junit_captures_system_out_and_err_per_test() {
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(1, None);
+        reporter.on_test_output("test.py::test_a", "stdout", "hello");
+        reporter.on_test_output("test.py::test_a", "stderr", "uh oh");
+        reporter.on_test_finished("test.py::test_a", "fail", 5, Some("boom"));
+
+        assert_eq!(reporter.cases[0].system_out.as_ref().unwrap().body, "hello");
+        assert_eq!(reporter.cases[0].system_err.as_ref().unwrap().body, "uh oh");
+    }
+
+    #[test]
+    fn test_junit_no_captured_output_omits_system_sections() {
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(1, None);
+        reporter.on_test_finished("test.py::test_a", "pass", 5, None);
+
+        assert!(reporter.cases[0].system_out.is_none());
+        assert!(reporter.cases[0].system_err.is_none());
+    }
+
+    #[test]
+    fn test_group_cases_by_file_splits_into_one_testsuite_per_file() {
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(2, None);
+        reporter.on_test_finished("a.py::test_1", "pass", 10, None);
+        reporter.on_test_finished("b.py::test_2", "fail", 10, Some("boom"));
+
+        let suites = group_cases_by_file(std::mem::take(&mut reporter.cases), "2024-01-01T00:00:00Z", "testhost", &[]);
+        assert_eq!(suites.len(), 2);
+        assert_eq!(suites[0].name, "a.py");
+        assert_eq!(suites[0].failures, 0);
+        assert_eq!(suites[1].name, "b.py");
+        assert_eq!(suites[1].failures, 1);
+    }
+
+    #[test]
+    fn test_group_cases_by_file_stamps_timestamp_and_hostname_on_every_suite() {
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(2, None);
+        reporter.on_test_finished("a.py::test_1", "pass", 10, None);
+        reporter.on_test_finished("b.py::test_2", "pass", 10, None);
+
+        let suites = group_cases_by_file(
+            std::mem::take(&mut reporter.cases),
+            "2024-06-01T12:00:00Z",
+            "ci-runner-7",
+            &[],
+        );
+        for suite in &suites {
+            assert_eq!(suite.timestamp, "2024-06-01T12:00:00Z");
+            assert_eq!(suite.hostname, "ci-runner-7");
+            assert!(suite.properties.is_none());
+        }
+    }
+
+    #[test]
+    fn test_group_cases_by_file_attaches_properties_to_every_suite() {
+        let mut reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        reporter.on_run_start(1, None);
+        reporter.on_test_finished("a.py::test_1", "pass", 10, None);
+
+        let properties = vec![
+            ("tach.version".to_string(), "1.2.3".to_string()),
+            ("git.sha".to_string(), "deadbeef".to_string()),
+        ];
+        let suites = group_cases_by_file(
+            std::mem::take(&mut reporter.cases),
+            "2024-06-01T12:00:00Z",
+            "ci-runner-7",
+            &properties,
+        );
+        let props = &suites[0].properties.as_ref().unwrap().properties;
+        assert_eq!(props.len(), 2);
+        assert_eq!(props[0].name, "tach.version");
+        assert_eq!(props[0].value, "1.2.3");
+        assert_eq!(props[1].name, "git.sha");
+        assert_eq!(props[1].value, "deadbeef");
+    }
+
+    #[test]
+    fn test_junit_reporter_resolves_a_nonempty_hostname_by_default() {
+        let reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new());
+        assert!(!reporter.hostname.is_empty());
+    }
+
+    #[test]
+    fn test_junit_with_properties_overrides_empty_default() {
+        let reporter = JunitReporter::new(PathBuf::from("/tmp/test.xml"), PathBuf::new())
+            .with_properties(vec![("ci.job_id".to_string(), "42".to_string())]);
+        assert_eq!(reporter.properties, vec![("ci.job_id".to_string(), "42".to_string())]);
+    }
 }
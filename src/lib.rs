@@ -4,18 +4,29 @@
 //! The binary entry point is in main.rs.
 
 pub mod config;
+pub mod coverage;
 pub mod debugger;
 pub mod discovery;
 pub mod environment;
+pub mod failure_snapshot;
+pub mod ignorefile;
+pub mod importgraph;
+pub mod incremental;
 pub mod isolation;
+pub mod jobserver;
 pub mod junit;
 pub mod lifecycle;
 pub mod loader;
 pub mod logcapture;
+pub mod logstream;
+pub mod manifest;
 pub mod protocol;
+pub mod provenance;
+pub mod reachability;
 pub mod reporter;
 pub mod resolver;
 pub mod scheduler;
+pub mod selection;
 pub mod signals;
 pub mod snapshot;
 pub mod watch;
@@ -10,10 +10,21 @@
 //! - **Mutex Poison Immunity**: Cleanup works even after panic-while-holding-lock
 
 use nix::sys::signal::{kill, Signal};
+use nix::sys::wait::{waitpid, WaitPidFlag, WaitStatus};
 use nix::unistd::Pid;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Default grace window `kill_workers` waits for a `SIGTERM`ed worker to
+/// exit on its own (letting it flush coverage/teardown) before escalating
+/// to `SIGKILL`. Overridable via `set_shutdown_grace`.
+const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_millis(200);
+
+/// How often `kill_workers` polls `waitpid(WNOHANG)` while waiting out the
+/// grace window.
+const REAP_POLL_INTERVAL: Duration = Duration::from_millis(10);
 
 /// Global flag to track if we're in debugging mode
 /// Used by signal handler to decide behavior:
@@ -38,6 +49,9 @@ pub struct CleanupGuard {
     socket_paths: Mutex<Vec<PathBuf>>,
     /// The Zygote PID for explicit cleanup
     zygote_pid: Mutex<Option<i32>>,
+    /// How long to wait after `SIGTERM` before escalating to `SIGKILL`.
+    /// Defaults to `DEFAULT_SHUTDOWN_GRACE`; see `set_shutdown_grace`.
+    shutdown_grace: Mutex<Duration>,
 }
 
 impl CleanupGuard {
@@ -47,9 +61,17 @@ impl CleanupGuard {
             worker_pids: Mutex::new(Vec::new()),
             socket_paths: Mutex::new(Vec::new()),
             zygote_pid: Mutex::new(None),
+            shutdown_grace: Mutex::new(DEFAULT_SHUTDOWN_GRACE),
         }
     }
 
+    /// Override the `SIGTERM` -> `SIGKILL` grace window (default 200ms).
+    pub fn set_shutdown_grace(&self, grace: Duration) {
+        // BOSS REFINEMENT: Ignore mutex poison
+        let mut g = self.shutdown_grace.lock().unwrap_or_else(|e| e.into_inner());
+        *g = grace;
+    }
+
     /// Track the Zygote PID
     pub fn set_zygote_pid(&self, pid: i32) {
         // BOSS REFINEMENT: Ignore mutex poison
@@ -85,27 +107,70 @@ impl CleanupGuard {
         pids.clone()
     }
 
-    /// Force kill all tracked workers
+    /// Reap all tracked workers (and the Zygote) via a two-phase escalation:
+    /// `SIGTERM` first so a worker gets a chance to flush coverage/teardown,
+    /// then `SIGKILL` for anything still alive after `shutdown_grace`, then
+    /// a final blocking `waitpid` on everyone so none of them linger as
+    /// zombies. Previously this only ever sent `SIGKILL` and never
+    /// `waitpid`ed at all, so every teardown path leaked zombies.
     fn kill_workers(&self) {
         // BOSS REFINEMENT: Ignore mutex poison - we MUST kill workers even after panic
         let pids = self.worker_pids.lock().unwrap_or_else(|e| e.into_inner());
+        let zygote = self.zygote_pid.lock().unwrap_or_else(|e| e.into_inner());
+        let grace = *self.shutdown_grace.lock().unwrap_or_else(|e| e.into_inner());
 
-        for &pid in pids.iter() {
+        let mut targets: Vec<i32> = pids.iter().copied().filter(|&pid| pid > 0).collect();
+        if let Some(pid) = *zygote {
             if pid > 0 {
-                // Try to kill entire process group first (catches any children)
-                let _ = kill(Pid::from_raw(-pid), Signal::SIGKILL);
-                // Also kill the process directly
-                let _ = kill(Pid::from_raw(pid), Signal::SIGKILL);
+                targets.push(pid);
             }
         }
+        drop(pids);
+        drop(zygote);
 
-        // Kill the Zygote too
-        let zygote = self.zygote_pid.lock().unwrap_or_else(|e| e.into_inner());
-        if let Some(pid) = *zygote {
-            if pid > 0 {
-                let _ = kill(Pid::from_raw(pid), Signal::SIGKILL);
+        if targets.is_empty() {
+            return;
+        }
+
+        // Phase 1: SIGTERM every tracked process (and its process group, to
+        // catch any children it spawned) and give it a chance to exit
+        // cleanly.
+        for &pid in &targets {
+            let _ = kill(Pid::from_raw(-pid), Signal::SIGTERM);
+            let _ = kill(Pid::from_raw(pid), Signal::SIGTERM);
+        }
+
+        let mut remaining: Vec<i32> = targets.clone();
+        let deadline = Instant::now() + grace;
+        while !remaining.is_empty() && Instant::now() < deadline {
+            remaining.retain(|&pid| !Self::try_reap(pid));
+            if !remaining.is_empty() {
+                std::thread::sleep(REAP_POLL_INTERVAL);
             }
         }
+
+        // Phase 2: anything still alive after the grace window gets SIGKILL.
+        for &pid in &remaining {
+            let _ = kill(Pid::from_raw(-pid), Signal::SIGKILL);
+            let _ = kill(Pid::from_raw(pid), Signal::SIGKILL);
+        }
+
+        // Final blocking reap so no PID we signalled is left a zombie.
+        for &pid in &targets {
+            let _ = waitpid(Pid::from_raw(pid), None);
+        }
+    }
+
+    /// Non-blocking check for whether `pid` has already exited, reaping it
+    /// if so. Returns `true` once the PID is gone (exited, or never existed
+    /// in the first place), `false` if it's still alive.
+    fn try_reap(pid: i32) -> bool {
+        match waitpid(Pid::from_raw(pid), Some(WaitPidFlag::WNOHANG)) {
+            Ok(WaitStatus::StillAlive) => false,
+            Ok(_) => true,
+            Err(nix::errno::Errno::ECHILD) => true,
+            Err(_) => false,
+        }
     }
 
     /// Remove socket files
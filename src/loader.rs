@@ -10,12 +10,19 @@
 //! - `PyImport_ExecCodeModuleObject`: Execute code, register in sys.modules
 
 use anyhow::{anyhow, Result};
+use dashmap::mapref::one::Ref;
 use dashmap::DashMap;
+use glob::Pattern;
+use ignore::WalkBuilder;
+use memmap2::Mmap;
 use pyo3::ffi;
 use pyo3::prelude::*;
-use pyo3::types::PyList;
+use pyo3::types::{PyDict, PyList};
+use rayon::prelude::*;
+use std::borrow::Cow;
 use std::fs;
 use std::io::Read;
+use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::OnceLock;
@@ -23,8 +30,22 @@ use std::time::SystemTime;
 
 /// .pyc header size for Python 3.7+ (PEP 552)
 /// Format: Magic (4) + BitField (4) + Timestamp (4) + Size (4) = 16 bytes
+/// (the last 8 bytes hold a source hash instead, when bit 0 of BitField is set)
 const PYC_HEADER_SIZE: usize = 16;
 
+/// BitField bit 0: cache is hash-based rather than timestamp-based.
+const PYC_FLAG_HASH_BASED: u32 = 1 << 0;
+/// BitField bit 1 (hash-based caches only): re-hash the source and compare
+/// before trusting the cache. Unset means trust it unconditionally.
+const PYC_FLAG_CHECK_SOURCE: u32 = 1 << 1;
+
+/// Magic bytes identifying a packed-registry blob written by
+/// [`ModuleRegistry::pack`], distinct from the Python bytecode magic
+/// (`expected_magic`) also recorded in the header.
+const PACKED_REGISTRY_MAGIC: [u8; 4] = *b"TPK1";
+/// Packed-registry blob format version; bump when the index layout changes.
+const PACKED_REGISTRY_VERSION: u32 = 1;
+
 /// Global registry instance (initialized once at startup)
 static REGISTRY: OnceLock<ModuleRegistry> = OnceLock::new();
 
@@ -36,11 +57,67 @@ static CACHED_PYTHON_EXE: OnceLock<PathBuf> = OnceLock::new();
 /// Without caching, parallel tests would spawn many Python processes, causing OOM.
 static CACHED_MAGIC: OnceLock<[u8; 4]> = OnceLock::new();
 
+/// Global cache for Python's PEP 3147 cache tag (e.g. "cpython-311"), used
+/// to locate CPython's own `__pycache__/<stem>.<cache_tag>.pyc` artifacts.
+/// Same "ask once, reuse forever" rationale as `CACHED_MAGIC`.
+static CACHED_CACHE_TAG: OnceLock<String> = OnceLock::new();
+
+/// Whether `PhaseTimer` emits its structured debug logs. Gated behind the
+/// `TACH_LOADER_TIMING` env var so the common case (no one profiling
+/// compilation) doesn't pay for an `Instant::now()`/`eprintln!` pair per
+/// file per phase. Checked once and cached, same rationale as `CACHED_MAGIC`.
+static TIMING_ENABLED: OnceLock<bool> = OnceLock::new();
+
+fn timing_enabled() -> bool {
+    *TIMING_ENABLED.get_or_init(|| std::env::var_os("TACH_LOADER_TIMING").is_some())
+}
+
+/// RAII scoped timer for compilation phase instrumentation.
+///
+/// On drop, emits a single structured debug line - `module`, `phase`, and
+/// elapsed milliseconds - so a user profiling a slow `compile_batch` can see
+/// where the time actually goes (e.g. stuck in `read_and_strip_header`
+/// because of a cold disk cache, rather than in `compile` itself). A no-op
+/// unless `TACH_LOADER_TIMING` is set.
+struct PhaseTimer {
+    module: String,
+    phase: &'static str,
+    start: std::time::Instant,
+}
+
+impl PhaseTimer {
+    fn start(module: impl Into<String>, phase: &'static str) -> Self {
+        Self {
+            module: module.into(),
+            phase,
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+impl Drop for PhaseTimer {
+    fn drop(&mut self) {
+        if timing_enabled() {
+            eprintln!(
+                "[loader] DEBUG module={} phase={} elapsed_ms={}",
+                self.module,
+                self.phase,
+                self.start.elapsed().as_millis()
+            );
+        }
+    }
+}
+
 // =============================================================================
 // BytecodeEntry: Registry entry for a compiled module
 // =============================================================================
 
 /// A compiled Python module ready for injection
+///
+/// `bytecode` is `Owned` for modules compiled straight into memory (the
+/// normal path) and `Borrowed` for entries rebacked by [`ModuleRegistry::freeze`]
+/// onto a memory-mapped packed cache file, so the hot load path can hand
+/// CPython a slice straight out of the mapping instead of a heap copy.
 #[derive(Clone)]
 pub struct BytecodeEntry {
     /// Python module name (e.g., "foo.bar")
@@ -48,9 +125,15 @@ pub struct BytecodeEntry {
     /// Absolute path to source .py file
     pub source_path: PathBuf,
     /// Header-stripped bytecode (bytes 16+ of .pyc)
-    pub bytecode: Vec<u8>,
+    pub bytecode: Cow<'static, [u8]>,
     /// True if this is a package (__init__.py)
     pub is_package: bool,
+    /// True if this is a synthesized PEP 420 namespace package - a
+    /// directory in the dotted-name chain that has no `__init__.py` of its
+    /// own. Carries empty bytecode; exists only so the directory resolves
+    /// as a package parent. Overwritten in place if a real `__init__.py`
+    /// for the same name is discovered later.
+    pub is_namespace: bool,
 }
 
 // =============================================================================
@@ -66,6 +149,10 @@ pub struct ModuleRegistry {
     /// Project root for path resolution (reserved for future use)
     #[allow(dead_code)]
     project_root: PathBuf,
+    /// Packed-cache mapping created by `freeze()`, kept around so the
+    /// `'static` slices handed out by `get_bytecode` stay valid for the life
+    /// of the registry. `None` until `freeze()` is called.
+    frozen_mmap: OnceLock<&'static Mmap>,
 }
 
 impl ModuleRegistry {
@@ -74,6 +161,7 @@ impl ModuleRegistry {
         Self {
             entries: DashMap::new(),
             project_root,
+            frozen_mmap: OnceLock::new(),
         }
     }
 
@@ -82,9 +170,51 @@ impl ModuleRegistry {
         self.entries.insert(entry.name.clone(), entry);
     }
 
-    /// Get bytecode for a module by name
-    pub fn get_bytecode(&self, name: &str) -> Option<Vec<u8>> {
-        self.entries.get(name).map(|e| e.bytecode.clone())
+    /// Borrow bytecode for a module by name.
+    ///
+    /// Derefs straight through to the stored bytes - mmap-backed after
+    /// `freeze()`, heap-owned before it - without an intermediate copy.
+    pub fn get_bytecode(&self, name: &str) -> Option<BytecodeRef<'_>> {
+        self.entries.get(name).map(BytecodeRef)
+    }
+
+    /// Write every current entry into one packed cache file, memory-map it
+    /// read-only, and rewrite each entry's bytecode as a borrowed slice into
+    /// that mapping.
+    ///
+    /// The mapping is leaked for the process lifetime so the resulting
+    /// `&'static` slices are sound: the registry is populated once at
+    /// startup via the "Push" model and never emptied, so this matches its
+    /// existing lifecycle rather than introducing a new one.
+    pub fn freeze(&self, packed_path: &Path) -> Result<()> {
+        let mut buffer = Vec::new();
+        let mut spans: Vec<(String, usize, usize)> = Vec::new();
+
+        for entry in self.entries.iter() {
+            let start = buffer.len();
+            buffer.extend_from_slice(entry.bytecode.as_ref());
+            spans.push((entry.key().clone(), start, entry.bytecode.len()));
+        }
+
+        if let Some(parent) = packed_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(packed_path, &buffer)?;
+
+        let file = fs::File::open(packed_path)?;
+        // SAFETY: `packed_path` was just written by us and is not modified
+        // by any other process while this mapping is alive.
+        let mmap = unsafe { Mmap::map(&file)? };
+        let leaked: &'static Mmap = Box::leak(Box::new(mmap));
+        let _ = self.frozen_mmap.set(leaked);
+
+        for (name, start, len) in spans {
+            if let Some(mut entry) = self.entries.get_mut(&name) {
+                entry.bytecode = Cow::Borrowed(&leaked[start..start + len]);
+            }
+        }
+
+        Ok(())
     }
 
     /// Get source path for a module by name
@@ -97,6 +227,18 @@ impl ModuleRegistry {
         self.entries.get(name).map(|e| e.is_package)
     }
 
+    /// Check if a module is a synthesized PEP 420 namespace package (a
+    /// package directory with no `__init__.py` of its own).
+    pub fn is_namespace_package(&self, name: &str) -> Option<bool> {
+        self.entries.get(name).map(|e| e.is_namespace)
+    }
+
+    /// Check whether any entry - real or synthesized - is registered under
+    /// `name` already.
+    fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
     /// Get number of entries in registry
     pub fn len(&self) -> usize {
         self.entries.len()
@@ -106,6 +248,343 @@ impl ModuleRegistry {
     pub fn is_empty(&self) -> bool {
         self.entries.is_empty()
     }
+
+    /// Iterate over every entry currently in the registry.
+    pub fn iter_entries(&self) -> impl Iterator<Item = BytecodeEntry> + '_ {
+        self.entries.iter().map(|e| e.value().clone())
+    }
+
+    /// Keep only entries whose name is in `keep`, dropping the rest.
+    /// Returns the number of entries removed.
+    ///
+    /// Used by [`crate::reachability`] to tree-shake modules that aren't
+    /// transitively imported from a set of entry points.
+    pub fn retain_reachable(&self, keep: &std::collections::HashSet<String>) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|name, _| keep.contains(name));
+        before - self.entries.len()
+    }
+
+    /// Serialize every current entry into one self-describing packed blob:
+    /// a small index (module name, is_package/is_namespace flags, source
+    /// path, offset, length) followed by the concatenated marshalled
+    /// bytecode, prefixed with a magic/version header and `python_magic` so
+    /// a later cold-start `load_packed` call - possibly in a different
+    /// process - can validate it before trusting the contents.
+    ///
+    /// Unlike `freeze`, which repoints *this* registry's own in-memory
+    /// entries onto a leaked mmap for the rest of this process's lifetime,
+    /// `pack` writes an artifact meant to be reloaded from scratch, letting
+    /// many worker processes share one read-only mapping of all compiled
+    /// bytecode instead of each re-reading thousands of small `.pyc` files.
+    pub fn pack(&self, packed_path: &Path, python_magic: [u8; 4]) -> Result<()> {
+        let mut index = Vec::new();
+        let mut data = Vec::new();
+
+        for entry in self.entries.iter() {
+            let name_bytes = entry.key().as_bytes();
+            let path_bytes = entry.source_path.to_string_lossy().into_owned().into_bytes();
+            let offset = data.len() as u64;
+            let length = entry.bytecode.len() as u64;
+            data.extend_from_slice(entry.bytecode.as_ref());
+
+            index.extend_from_slice(&(name_bytes.len() as u32).to_le_bytes());
+            index.extend_from_slice(name_bytes);
+            index.extend_from_slice(&(path_bytes.len() as u32).to_le_bytes());
+            index.extend_from_slice(&path_bytes);
+            index.push(entry.is_package as u8);
+            index.push(entry.is_namespace as u8);
+            index.extend_from_slice(&offset.to_le_bytes());
+            index.extend_from_slice(&length.to_le_bytes());
+        }
+
+        let mut buffer = Vec::with_capacity(16 + index.len() + data.len());
+        buffer.extend_from_slice(&PACKED_REGISTRY_MAGIC);
+        buffer.extend_from_slice(&PACKED_REGISTRY_VERSION.to_le_bytes());
+        buffer.extend_from_slice(&python_magic);
+        buffer.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        buffer.extend_from_slice(&index);
+        buffer.extend_from_slice(&data);
+
+        if let Some(parent) = packed_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(packed_path, &buffer)?;
+
+        Ok(())
+    }
+
+    /// Bounds-checked `&bytes[start..start+len]`, instead of indexing
+    /// straight off attacker/corruption-controlled lengths read from the
+    /// blob itself - a truncated `registry.pack` (e.g. `pack`'s `fs::write`
+    /// cut short by a killed process) must come back as an `Err`, not a
+    /// slice-index panic.
+    fn checked_slice(bytes: &[u8], start: usize, len: usize) -> Result<&[u8]> {
+        let end = start
+            .checked_add(len)
+            .ok_or_else(|| anyhow!("packed registry length overflow"))?;
+        bytes
+            .get(start..end)
+            .ok_or_else(|| anyhow!("packed registry blob truncated"))
+    }
+
+    /// Bounds-checked little-endian `u32` read at `cursor`.
+    fn read_u32(bytes: &[u8], cursor: usize) -> Result<u32> {
+        let slice = Self::checked_slice(bytes, cursor, 4)?;
+        Ok(u32::from_le_bytes(slice.try_into().unwrap()))
+    }
+
+    /// Bounds-checked little-endian `u64` read at `cursor`.
+    fn read_u64(bytes: &[u8], cursor: usize) -> Result<u64> {
+        let slice = Self::checked_slice(bytes, cursor, 8)?;
+        Ok(u64::from_le_bytes(slice.try_into().unwrap()))
+    }
+
+    /// Load a registry back from a blob written by `pack`, `mmap`-ing the
+    /// file read-only and building `BytecodeEntry`s whose `bytecode` is a
+    /// borrowed slice straight into that mapping - no per-entry heap
+    /// allocation, and many worker processes can share the page cache for
+    /// the same mapping. Validates the blob's own magic/version and the
+    /// embedded Python bytecode magic against `expected_magic` before
+    /// trusting the contents.
+    pub fn load_packed(
+        packed_path: &Path,
+        project_root: PathBuf,
+        expected_magic: [u8; 4],
+    ) -> Result<Self> {
+        let file = fs::File::open(packed_path)?;
+        // SAFETY: the packed file is written once by `pack` and not
+        // modified concurrently by another process while mapped - same
+        // assumption `freeze` makes for its own mapping.
+        let mmap = unsafe { Mmap::map(&file)? };
+        let leaked: &'static Mmap = Box::leak(Box::new(mmap));
+        let bytes: &'static [u8] = &leaked[..];
+
+        if bytes.len() < 16 {
+            return Err(anyhow!(
+                "packed registry blob too short: {}",
+                packed_path.display()
+            ));
+        }
+        if bytes[0..4] != PACKED_REGISTRY_MAGIC {
+            return Err(anyhow!(
+                "not a packed registry blob: {}",
+                packed_path.display()
+            ));
+        }
+        let version = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+        if version != PACKED_REGISTRY_VERSION {
+            return Err(anyhow!("unsupported packed registry version {version}"));
+        }
+        let python_magic: [u8; 4] = bytes[8..12].try_into().unwrap();
+        if python_magic != expected_magic {
+            return Err(anyhow!(
+                "packed registry {} was built for a different Python interpreter (magic mismatch)",
+                packed_path.display()
+            ));
+        }
+        let entry_count = u32::from_le_bytes(bytes[12..16].try_into().unwrap()) as usize;
+
+        let mut cursor = 16usize;
+        let mut records = Vec::with_capacity(entry_count);
+        for _ in 0..entry_count {
+            let name_len = Self::read_u32(bytes, cursor)? as usize;
+            cursor += 4;
+            let name = String::from_utf8(Self::checked_slice(bytes, cursor, name_len)?.to_vec())
+                .map_err(|e| anyhow!("invalid module name in packed registry: {e}"))?;
+            cursor += name_len;
+
+            let path_len = Self::read_u32(bytes, cursor)? as usize;
+            cursor += 4;
+            let source_path = PathBuf::from(
+                String::from_utf8(Self::checked_slice(bytes, cursor, path_len)?.to_vec())
+                    .map_err(|e| anyhow!("invalid source path in packed registry: {e}"))?,
+            );
+            cursor += path_len;
+
+            let flags = Self::checked_slice(bytes, cursor, 2)?;
+            let is_package = flags[0] != 0;
+            let is_namespace = flags[1] != 0;
+            cursor += 2;
+
+            let offset = Self::read_u64(bytes, cursor)? as usize;
+            cursor += 8;
+            let length = Self::read_u64(bytes, cursor)? as usize;
+            cursor += 8;
+
+            records.push((name, source_path, is_package, is_namespace, offset, length));
+        }
+
+        let registry = Self::new(project_root);
+        let data_start = cursor;
+        for (name, source_path, is_package, is_namespace, offset, length) in records {
+            let start = data_start + offset;
+            let end = start + length;
+            if end > bytes.len() {
+                return Err(anyhow!("packed registry data out of bounds for {name}"));
+            }
+
+            registry.insert(BytecodeEntry {
+                name,
+                source_path,
+                bytecode: Cow::Borrowed(&bytes[start..end]),
+                is_package,
+                is_namespace,
+            });
+        }
+
+        let _ = registry.frozen_mmap.set(leaked);
+
+        Ok(registry)
+    }
+}
+
+/// A borrowed handle to a registry entry's bytecode, returned by
+/// [`ModuleRegistry::get_bytecode`].
+///
+/// Derefs to `&[u8]` so most callers can use it exactly like a slice; call
+/// `.to_vec()` only at an actual ownership boundary (e.g. handing bytes to
+/// Python across the FFI).
+pub struct BytecodeRef<'a>(Ref<'a, String, BytecodeEntry>);
+
+impl Deref for BytecodeRef<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.0.bytecode.as_ref()
+    }
+}
+
+// =============================================================================
+// PEP 552 header helpers: hash-based .pyc invalidation
+// =============================================================================
+
+/// Parsed first 16 bytes of a .pyc file.
+struct PycHeader {
+    flags: u32,
+    /// Bytes 8..16, meaningful only when `flags` has `PYC_FLAG_HASH_BASED` set.
+    source_hash: u64,
+}
+
+impl PycHeader {
+    fn is_hash_based(&self) -> bool {
+        self.flags & PYC_FLAG_HASH_BASED != 0
+    }
+}
+
+/// Read and parse the PEP 552 header of a .pyc file (magic number is
+/// skipped here; callers that need it use `validate_magic` separately).
+fn read_pyc_header(pyc_path: &Path) -> Result<PycHeader> {
+    let mut file = fs::File::open(pyc_path)?;
+    let mut header = [0u8; PYC_HEADER_SIZE];
+    file.read_exact(&mut header)?;
+
+    Ok(PycHeader {
+        flags: u32::from_le_bytes(header[4..8].try_into().unwrap()),
+        source_hash: u64::from_le_bytes(header[8..16].try_into().unwrap()),
+    })
+}
+
+/// Overwrite bytes 4..16 of `pyc_path` with a hash-based PEP 552 header,
+/// leaving the magic number and the code object untouched.
+fn write_hash_header(pyc_path: &Path, magic: [u8; 4], source: &[u8], checked: bool) -> Result<()> {
+    use std::io::{Seek, SeekFrom, Write};
+
+    let mut flags = PYC_FLAG_HASH_BASED;
+    if checked {
+        flags |= PYC_FLAG_CHECK_SOURCE;
+    }
+
+    let mut header = [0u8; 12];
+    header[0..4].copy_from_slice(&flags.to_le_bytes());
+    header[4..12].copy_from_slice(&source_hash(magic, source).to_le_bytes());
+
+    let mut file = fs::OpenOptions::new().write(true).open(pyc_path)?;
+    file.seek(SeekFrom::Start(4))?;
+    file.write_all(&header)?;
+    Ok(())
+}
+
+/// CPython's PEP 552 source hash: SipHash-1-3 keyed on the interpreter's
+/// magic number, as computed by `_imp.source_hash` and written by
+/// `py_compile.compile(..., invalidation_mode=CHECKED_HASH)`. Matching the
+/// real algorithm (rather than an arbitrary one) keeps the header we hand-
+/// write in `write_hash_header` byte-compatible with `.pyc` files CPython
+/// itself would produce.
+fn source_hash(magic: [u8; 4], source: &[u8]) -> u64 {
+    let key = u32::from_le_bytes(magic) as u64;
+    siphash13(key, 0, source)
+}
+
+/// SipHash-1-3 (1 compression round, 3 finalization rounds), as used by
+/// CPython's `_Py_KeyedHash` for `.pyc` source hashing.
+fn siphash13(k0: u64, k1: u64, data: &[u8]) -> u64 {
+    let mut v0 = 0x736f6d6570736575u64 ^ k0;
+    let mut v1 = 0x646f72616e646f6du64 ^ k1;
+    let mut v2 = 0x6c7967656e657261u64 ^ k0;
+    let mut v3 = 0x7465646279746573u64 ^ k1;
+
+    macro_rules! sipround {
+        () => {
+            v0 = v0.wrapping_add(v1);
+            v1 = v1.rotate_left(13);
+            v1 ^= v0;
+            v0 = v0.rotate_left(32);
+            v2 = v2.wrapping_add(v3);
+            v3 = v3.rotate_left(16);
+            v3 ^= v2;
+            v0 = v0.wrapping_add(v3);
+            v3 = v3.rotate_left(21);
+            v3 ^= v0;
+            v2 = v2.wrapping_add(v1);
+            v1 = v1.rotate_left(17);
+            v1 ^= v2;
+            v2 = v2.rotate_left(32);
+        };
+    }
+
+    let len = data.len();
+    let chunks = data.chunks_exact(8);
+    let remainder = chunks.remainder();
+
+    for chunk in chunks {
+        let m = u64::from_le_bytes(chunk.try_into().unwrap());
+        v3 ^= m;
+        sipround!();
+        v0 ^= m;
+    }
+
+    let mut last_block = [0u8; 8];
+    last_block[..remainder.len()].copy_from_slice(remainder);
+    last_block[7] = (len & 0xff) as u8;
+    let m = u64::from_le_bytes(last_block);
+
+    v3 ^= m;
+    sipround!();
+    v0 ^= m;
+
+    v2 ^= 0xff;
+    sipround!();
+    sipround!();
+    sipround!();
+
+    v0 ^ v1 ^ v2 ^ v3
+}
+
+/// Every strict package ancestor of a dotted module name, narrowest last
+/// (`a.b.c` -> `["a", "a.b"]`). Shared by namespace-package synthesis here
+/// and by [`crate::reachability`]'s reachability walk.
+pub(crate) fn package_ancestors(name: &str) -> Vec<String> {
+    let segments: Vec<&str> = name.split('.').collect();
+    (1..segments.len())
+        .map(|i| segments[..i].join("."))
+        .collect()
+}
+
+/// Compile a `discover_tree` include/exclude glob, same error-wrapping
+/// convention as `manifest::compile_pattern`.
+fn compile_discovery_glob(pattern: &str) -> Result<Pattern> {
+    Pattern::new(pattern).map_err(|e| anyhow!("invalid glob '{}': {}", pattern, e))
 }
 
 // =============================================================================
@@ -116,7 +595,7 @@ impl ModuleRegistry {
 ///
 /// Features:
 /// - Persistent cache in `.tach/cache/`
-/// - mtime-based staleness detection
+/// - mtime-based or hash-based (PEP 552) staleness detection
 /// - Magic number validation
 pub struct BytecodeCompiler {
     /// Cache directory (.tach/cache)
@@ -127,15 +606,89 @@ pub struct BytecodeCompiler {
     python_exe: PathBuf,
     /// Expected magic number (from running Python)
     expected_magic: Option<[u8; 4]>,
+    /// Worker thread count for `compile_batch_parallel`
+    thread_count: usize,
+    /// How cache staleness is decided (default: mtime)
+    invalidation: CacheInvalidation,
+    /// `-O`/`-OO` level baked into compiled artifacts (default: zero)
+    optimization_level: BytecodeOptimizationLevel,
+    /// When set, `compile`/`compile_batch` never read an existing cache
+    /// entry - not the `.tach/cache` one, not a reused `__pycache__` hit -
+    /// and recompile from source unconditionally. A compiled artifact is
+    /// still written to `cache_path` as usual so later stages (packing,
+    /// namespace ancestor sourcing) keep working the same way; it just
+    /// never gets consulted to skip the recompile. (default: false)
+    no_cache: bool,
+    /// Whether `discover_tree` honors `.gitignore` (and nested ignore files
+    /// up the tree, plus `.git/info/exclude` and the global gitignore) while
+    /// walking a project. (default: true)
+    respect_gitignore: bool,
+    /// `discover_tree` drops any path that doesn't match at least one of
+    /// these, relative to the walked root. Empty (the default) means no
+    /// restriction.
+    include_globs: Vec<Pattern>,
+    /// `discover_tree` drops any path matching one of these, relative to
+    /// the walked root, even one `.gitignore` wouldn't have excluded.
+    exclude_globs: Vec<Pattern>,
+}
+
+/// Bytecode optimization level, mirroring CPython's `-O`/`-OO` flags and the
+/// `opt-N` component of a PEP 3147 `__pycache__` filename. Only `cache_path`
+/// reads it, giving each level its own cache file so artifacts for
+/// different levels never clobber each other. Neither
+/// `compile_to_cache_in_process` nor `compile_to_cache_subprocess` passes it
+/// to the compiler, so what gets stripped from the compiled code is set by
+/// the interpreter's own `-O` flag, whatever the level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BytecodeOptimizationLevel {
+    /// `-O`/`-OO` unset: assertions and docstrings kept.
+    Zero,
+    /// `-O`: `assert` statements and `__debug__`-gated blocks stripped.
+    One,
+    /// `-OO`: everything `One` strips, plus docstrings.
+    Two,
+}
+
+impl BytecodeOptimizationLevel {
+    /// The numeric level CPython itself uses: `py_compile.compile`'s
+    /// `optimize` argument, `sys.flags.optimize`, and the PEP 3147 `opt-N`
+    /// cache tag (level zero omits the tag entirely).
+    fn as_u8(self) -> u8 {
+        match self {
+            Self::Zero => 0,
+            Self::One => 1,
+            Self::Two => 2,
+        }
+    }
+}
+
+/// Cache invalidation strategy, mirroring PEP 552's timestamp-based vs.
+/// hash-based `.pyc` headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheInvalidation {
+    /// Compare source mtime against cache mtime (the original behavior).
+    /// Fragile across checkouts/containers/reproducible builds that don't
+    /// preserve timestamps.
+    Mtime,
+    /// Compare a content hash of the source bytes, ignoring mtime entirely.
+    /// `checked: false` trusts the cache without re-hashing at all, for
+    /// deploy-time immutability (PEP 552's "check_source" flag unset).
+    Hash { checked: bool },
 }
 
 impl BytecodeCompiler {
-    /// Create a new compiler with cache in project_root/.tach/cache
+    /// Create a new compiler with cache in project_root/.tach/cache, or in
+    /// `TACH_CACHE_DIR` if set - e.g. for CI runs against a read-only
+    /// checkout, or to isolate caches for concurrent runs against the same
+    /// source tree the way `TMPDIR`-style overrides do for other tools.
     ///
     /// This uses global caches for Python path and magic number to avoid
     /// spawning multiple Python subprocesses during parallel test execution.
     pub fn new(project_root: &Path) -> Result<Self> {
-        let cache_dir = project_root.join(".tach").join("cache");
+        let cache_dir = match std::env::var_os("TACH_CACHE_DIR") {
+            Some(dir) => PathBuf::from(dir),
+            None => project_root.join(".tach").join("cache"),
+        };
         fs::create_dir_all(&cache_dir)?;
 
         // Find Python executable (cached globally)
@@ -144,14 +697,105 @@ impl BytecodeCompiler {
         // Get expected magic number from running Python (cached globally)
         let expected_magic = Self::get_python_magic_cached(&python_exe)?;
 
+        let thread_count = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4);
+
         Ok(Self {
             cache_dir,
             project_root: project_root.to_path_buf(),
             python_exe,
             expected_magic: Some(expected_magic),
+            thread_count,
+            invalidation: CacheInvalidation::Mtime,
+            optimization_level: BytecodeOptimizationLevel::Zero,
+            no_cache: false,
+            respect_gitignore: true,
+            include_globs: Vec::new(),
+            exclude_globs: Vec::new(),
         })
     }
 
+    /// Override the worker thread count used by `compile_batch_parallel`
+    /// (defaults to the available parallelism).
+    pub fn with_thread_count(mut self, thread_count: usize) -> Self {
+        self.thread_count = thread_count.max(1);
+        self
+    }
+
+    /// Override the cache invalidation strategy (defaults to mtime).
+    pub fn with_invalidation(mut self, invalidation: CacheInvalidation) -> Self {
+        self.invalidation = invalidation;
+        self
+    }
+
+    /// Override the optimization level used in `compile`/`compile_batch`
+    /// cache filenames (the `.opt-N` tag; defaults to zero, i.e. `-O`/`-OO`
+    /// both unset). The compiled code itself is the same at every level -
+    /// see [`BytecodeOptimizationLevel`].
+    pub fn with_optimization_level(mut self, optimization_level: BytecodeOptimizationLevel) -> Self {
+        self.optimization_level = optimization_level;
+        self
+    }
+
+    /// Override the cache root (defaults to `project_root/.tach/cache`, or
+    /// `TACH_CACHE_DIR` if set). `cache_path` joins each source's path
+    /// relative to `project_root` onto this root, so an out-of-tree root
+    /// still gets one `__pycache__` per source directory rather than
+    /// collapsing everything into one flat namespace.
+    pub fn with_cache_dir(mut self, cache_dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&cache_dir)?;
+        self.cache_dir = cache_dir;
+        Ok(self)
+    }
+
+    /// Disable cache reads entirely: every `compile`/`compile_batch` call
+    /// recompiles from source regardless of `is_cache_stale`, and skips the
+    /// PEP 3147 `__pycache__` reuse probe too. Mirrors the `--no-cache`
+    /// knob formatters/linters expose for reproducible CI runs against a
+    /// read-only source tree. Artifacts are still written to `cache_path`
+    /// afterwards (default: false, i.e. caching is on).
+    pub fn with_no_cache(mut self, no_cache: bool) -> Self {
+        self.no_cache = no_cache;
+        self
+    }
+
+    /// Toggle whether `discover_tree` honors `.gitignore` (default: true).
+    /// Turn off when generated or vendored sources that happen to be
+    /// gitignored must still be compiled.
+    pub fn with_gitignore(mut self, respect: bool) -> Self {
+        self.respect_gitignore = respect;
+        self
+    }
+
+    /// Restrict `discover_tree` to paths matching at least one of these
+    /// glob patterns, relative to the walked root. Empty (the default)
+    /// matches everything.
+    pub fn with_include_globs<I: IntoIterator<Item = S>, S: AsRef<str>>(
+        mut self,
+        patterns: I,
+    ) -> Result<Self> {
+        self.include_globs = patterns
+            .into_iter()
+            .map(|p| compile_discovery_glob(p.as_ref()))
+            .collect::<Result<_>>()?;
+        Ok(self)
+    }
+
+    /// Drop any path `discover_tree` finds that matches one of these glob
+    /// patterns, relative to the walked root - even one `.gitignore`
+    /// wouldn't have excluded, and even one an include glob also matches.
+    pub fn with_exclude_globs<I: IntoIterator<Item = S>, S: AsRef<str>>(
+        mut self,
+        patterns: I,
+    ) -> Result<Self> {
+        self.exclude_globs = patterns
+            .into_iter()
+            .map(|p| compile_discovery_glob(p.as_ref()))
+            .collect::<Result<_>>()?;
+        Ok(self)
+    }
+
     /// Find the Python interpreter (cached globally)
     ///
     /// Uses CACHED_PYTHON_EXE to ensure we only spawn `which` once
@@ -196,14 +840,22 @@ impl BytecodeCompiler {
     /// CRITICAL: This uses CACHED_MAGIC to ensure we only spawn Python ONCE
     /// regardless of how many tests run in parallel. Without this cache,
     /// parallel tests would spawn many Python processes, potentially causing OOM.
+    ///
+    /// Prefers reading `importlib.util.MAGIC_NUMBER` straight out of an
+    /// already-embedded interpreter (see `is_interpreter_embedded`) over
+    /// spawning a subprocess for it.
     fn get_python_magic_cached(python_exe: &Path) -> Result<[u8; 4]> {
         // Try to get from cache first
         if let Some(cached) = CACHED_MAGIC.get() {
             return Ok(*cached);
         }
 
-        // Not cached yet, fetch from Python
-        let magic = Self::get_python_magic_impl(python_exe)?;
+        // Not cached yet, fetch it
+        let magic = if Self::is_interpreter_embedded() {
+            Self::get_python_magic_in_process()?
+        } else {
+            Self::get_python_magic_impl(python_exe)?
+        };
 
         // Try to store it (may fail if another thread beat us)
         let _ = CACHED_MAGIC.set(magic);
@@ -230,6 +882,33 @@ impl BytecodeCompiler {
         Ok(magic)
     }
 
+    /// In-process variant of `get_python_magic_impl`: reads
+    /// `importlib.util.MAGIC_NUMBER` out of the already-embedded interpreter
+    /// under the GIL instead of spawning `python -c`.
+    fn get_python_magic_in_process() -> Result<[u8; 4]> {
+        Python::with_gil(|py| -> Result<[u8; 4]> {
+            let magic_number: Vec<u8> = py
+                .import("importlib.util")?
+                .getattr("MAGIC_NUMBER")?
+                .extract()?;
+
+            if magic_number.len() < 4 {
+                return Err(anyhow!("Invalid magic number length"));
+            }
+
+            let mut magic = [0u8; 4];
+            magic.copy_from_slice(&magic_number[..4]);
+            Ok(magic)
+        })
+    }
+
+    /// Whether a Python interpreter is already running in this process
+    /// (e.g. a forked worker that has called `Python::with_gil` before),
+    /// as opposed to the driver process, which never embeds one.
+    fn is_interpreter_embedded() -> bool {
+        unsafe { ffi::Py_IsInitialized() != 0 }
+    }
+
     /// Convert a file path to a Python module name
     fn path_to_module_name(&self, path: &Path) -> String {
         let relative = path.strip_prefix(&self.project_root).unwrap_or(path);
@@ -247,26 +926,290 @@ impl BytecodeCompiler {
         name
     }
 
-    /// Get cache path for a source file
+    /// Synthesize PEP 420 namespace-package entries for every ancestor of
+    /// `module_name` that isn't already registered under any form (real
+    /// package, namespace package, or plain module). A later real
+    /// `__init__.py` for the same name naturally overwrites the
+    /// synthesized entry on its own `registry.insert`, so no upgrade logic
+    /// is needed here - only "don't insert if something is already there".
+    fn register_namespace_ancestors(&self, module_name: &str, registry: &ModuleRegistry) {
+        for ancestor in package_ancestors(module_name) {
+            if registry.contains(&ancestor) {
+                continue;
+            }
+
+            let source_path = self.project_root.join(ancestor.replace('.', &std::path::MAIN_SEPARATOR.to_string()));
+
+            registry.insert(BytecodeEntry {
+                name: ancestor,
+                source_path,
+                bytecode: Cow::Borrowed(&[]),
+                is_package: true,
+                is_namespace: true,
+            });
+        }
+    }
+
+    /// Walk `root`, classifying every resource it finds instead of
+    /// requiring the caller to hand-list files like `compile_batch` does.
+    /// Always skips `.tach` (our own cache), `__pycache__`, `.git`, and any
+    /// other dot-directory; on top of that, honors `.gitignore` (and nested
+    /// ignore files up the tree, plus `.git/info/exclude` and the global
+    /// gitignore) unless `with_gitignore(false)` disabled it, and applies
+    /// `with_include_globs`/`with_exclude_globs` if configured.
+    ///
+    /// Returns a [`DiscoveredTree`] the caller can both pull a
+    /// `compile_batch`-ready file list from (via `source_files`, which
+    /// doubles as the observable "what will be compiled" set) and `ingest`
+    /// directly into a [`ModuleRegistry`] in one call - this is what turns
+    /// the compiler from "compile these files" into "index and compile a
+    /// project".
+    pub fn discover_tree(&self, root: &Path) -> Result<DiscoveredTree> {
+        let mut tree = DiscoveredTree::default();
+        let mut dirs_with_descendants: std::collections::HashSet<PathBuf> =
+            std::collections::HashSet::new();
+        let mut dirs_with_own_init: std::collections::HashSet<PathBuf> =
+            std::collections::HashSet::new();
+
+        let mut builder = WalkBuilder::new(root);
+        builder
+            .hidden(true)
+            .git_ignore(self.respect_gitignore)
+            .git_global(self.respect_gitignore)
+            .git_exclude(self.respect_gitignore)
+            .parents(self.respect_gitignore)
+            .filter_entry(|entry| {
+                entry.file_type().map_or(true, |t| {
+                    !t.is_dir() || !Self::is_skipped_dir(&entry.file_name().to_string_lossy())
+                })
+            });
+
+        for entry in builder.build() {
+            let Ok(entry) = entry else { continue };
+            let path = entry.path();
+            if path == root || entry.file_type().map_or(true, |t| t.is_dir()) {
+                continue;
+            }
+            if !self.passes_discovery_globs(root, path) {
+                continue;
+            }
+            if !self.classify_discovered_file(path, &mut tree, &mut dirs_with_own_init) {
+                continue;
+            }
+
+            // Mark every ancestor directory between this file's parent and
+            // `self.project_root` (exclusive) as having a descendant
+            // resource, same as the recursive walk this replaced: a
+            // directory anywhere on the dotted-name chain to a resource is
+            // itself a namespace-package candidate.
+            let mut ancestor = path.parent();
+            while let Some(dir) = ancestor {
+                if dir == self.project_root {
+                    break;
+                }
+                dirs_with_descendants.insert(dir.to_path_buf());
+                ancestor = dir.parent();
+            }
+        }
+
+        // A directory is a PEP 420 namespace package iff it has at least
+        // one descendant module/extension/package (directly or nested) and
+        // no `__init__.py` of its own - a real package already got its own
+        // `Source { is_package: true, .. }` entry above.
+        for dir in dirs_with_descendants {
+            if dir == root || dirs_with_own_init.contains(&dir) {
+                continue;
+            }
+            let name = self.path_to_module_name(&dir);
+            if name.is_empty() {
+                continue;
+            }
+            tree.modules.push(DiscoveredModule::Namespace { name, path: dir });
+        }
+
+        Ok(tree)
+    }
+
+    /// Whether `path` (absolute, under `root`) survives `discover_tree`'s
+    /// include/exclude glob filters, matched against its path relative to
+    /// `root`. An exclude match always wins, even over an include match;
+    /// empty `include_globs` means no restriction.
+    fn passes_discovery_globs(&self, root: &Path, path: &Path) -> bool {
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        if self.exclude_globs.iter().any(|p| p.matches_path(relative)) {
+            return false;
+        }
+        if self.include_globs.is_empty() {
+            return true;
+        }
+        self.include_globs.iter().any(|p| p.matches_path(relative))
+    }
+
+    /// Classify a single file found by `discover_tree`'s walk, pushing the
+    /// appropriate `DiscoveredModule` onto `tree` and recording `dir` in
+    /// `dirs_with_own_init` for `__init__.py`. Returns whether the file
+    /// became a resource at all (plain non-Python, non-extension files are
+    /// silently skipped), which the caller uses to decide whether to mark
+    /// the file's ancestor directories as namespace-package candidates.
+    fn classify_discovered_file(
+        &self,
+        path: &Path,
+        tree: &mut DiscoveredTree,
+        dirs_with_own_init: &mut std::collections::HashSet<PathBuf>,
+    ) -> bool {
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        if file_name == "__init__.py" {
+            tree.modules.push(DiscoveredModule::Source {
+                name: self.path_to_module_name(path),
+                path: path.to_path_buf(),
+                is_package: true,
+            });
+            if let Some(dir) = path.parent() {
+                dirs_with_own_init.insert(dir.to_path_buf());
+            }
+            true
+        } else if path.extension().map_or(false, |e| e == "py") {
+            tree.modules.push(DiscoveredModule::Source {
+                name: self.path_to_module_name(path),
+                path: path.to_path_buf(),
+                is_package: false,
+            });
+            true
+        } else if let Some(name) = self.extension_module_name(path) {
+            tree.modules.push(DiscoveredModule::Extension {
+                name,
+                path: path.to_path_buf(),
+            });
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Directories `discover_tree` never descends into: our own bytecode
+    /// cache, CPython's own cache, VCS metadata, and dot-directories in
+    /// general (venvs, editor/tooling state).
+    fn is_skipped_dir(file_name: &str) -> bool {
+        file_name == ".tach" || file_name == "__pycache__" || file_name.starts_with('.')
+    }
+
+    /// Derive a dotted module name for a native extension file
+    /// (`foo.cpython-311-x86_64-linux-gnu.so`, `foo.abi3.so`, `foo.pyd`),
+    /// whose real module name is everything before its *first* `.`, unlike
+    /// a plain `.py` file where `with_extension("")` (just the last `.`) is
+    /// enough. Returns `None` for anything not recognized as an extension.
+    fn extension_module_name(&self, path: &Path) -> Option<String> {
+        let file_name = path.file_name()?.to_str()?;
+        let is_extension = file_name.ends_with(".so") || file_name.ends_with(".pyd");
+        if !is_extension {
+            return None;
+        }
+
+        let stem = file_name.split('.').next()?;
+        let relative_dir = path
+            .parent()
+            .and_then(|p| p.strip_prefix(&self.project_root).ok())
+            .unwrap_or_else(|| Path::new(""));
+
+        let mut name = relative_dir
+            .to_string_lossy()
+            .replace(std::path::MAIN_SEPARATOR, ".");
+        if !name.is_empty() {
+            name.push('.');
+        }
+        name.push_str(stem);
+        Some(name)
+    }
+
+    /// Canonicalize `source` and verify it actually resolves under
+    /// `self.project_root`, returning the path relative to the root.
+    ///
+    /// `cache_path` used to just `strip_prefix(&self.project_root)` and
+    /// silently fall back to the raw (possibly absolute, possibly
+    /// `..`-laden) source path when that failed - which could make a cache
+    /// write land outside `self.cache_dir` entirely, or collide with an
+    /// unrelated source that normalizes to the same relative path. Doing
+    /// the `canonicalize` + `strip_prefix` here instead, as a hard error,
+    /// is what lets `compile` refuse such a source rather than silently
+    /// misplacing its artifact.
+    fn audit_source_path(&self, source: &Path) -> Result<PathBuf> {
+        let root = self
+            .project_root
+            .canonicalize()
+            .unwrap_or_else(|_| self.project_root.clone());
+        let canonical_source = source
+            .canonicalize()
+            .map_err(|e| anyhow!("cannot resolve source path {}: {}", source.display(), e))?;
+
+        canonical_source.strip_prefix(&root).map(PathBuf::from).map_err(|_| {
+            anyhow!(
+                "source {} resolves outside project root {} - refusing to write a cache artifact for it",
+                source.display(),
+                self.project_root.display()
+            )
+        })
+    }
+
+    /// Get cache path for a source file.
+    ///
+    /// `compile` passes the canonicalized path `audit_source_path` already
+    /// produced, so this only needs to fall back to stripping
+    /// `self.project_root` itself for callers (tests, mostly) that hand it a
+    /// path directly. Mirrors `source`'s directory structure under
+    /// `self.cache_dir` and names the artifact the PEP 3147 way -
+    /// `<dir>/__pycache__/<stem>-<fingerprint>.<cache_tag>[.opt-N].pyc` -
+    /// rather than the old flattened `<flattened_path>.pyc`, so the layout
+    /// (if not the location) matches what a real CPython runtime would
+    /// write. The `<fingerprint>` component is a SipHash-1-3 of the whole
+    /// path relative to `project_root` (not just the stem), so two sources
+    /// that happen to share a file stem - or whose `strip_prefix` fallback
+    /// collapses onto the same relative path - still land on distinct,
+    /// deterministic cache files instead of clobbering each other. The
+    /// `opt-N` tag keeps different optimization levels from sharing a file;
+    /// level zero omits it, same as CPython itself.
     fn cache_path(&self, source: &Path) -> PathBuf {
         let relative = source.strip_prefix(&self.project_root).unwrap_or(source);
 
-        let mut cache_name = relative
-            .to_string_lossy()
-            .replace(std::path::MAIN_SEPARATOR, "_");
-        cache_name.push_str(".pyc");
+        let stem = relative
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let fingerprint = siphash13(0, 0, relative.to_string_lossy().as_bytes());
+        let cache_tag = Self::get_cache_tag_cached().unwrap_or_else(|_| "unknown".to_string());
+        let opt_suffix = match self.optimization_level {
+            BytecodeOptimizationLevel::Zero => String::new(),
+            level => format!(".opt-{}", level.as_u8()),
+        };
+        let cache_name = format!("{stem}-{fingerprint:016x}.{cache_tag}{opt_suffix}.pyc");
 
-        self.cache_dir.join(cache_name)
+        let parent = relative.parent().unwrap_or_else(|| Path::new(""));
+        self.cache_dir.join(parent).join("__pycache__").join(cache_name)
     }
 
-    /// Check if cached .pyc is stale (source mtime > cache mtime)
+    /// Check if cached .pyc is stale, per `self.invalidation`.
+    ///
+    /// `cache` already encodes the optimization level via `cache_path`'s
+    /// `opt-N` tag, so artifacts for different levels never share a path
+    /// and therefore never clobber or stale-check against each other.
     fn is_cache_stale(&self, source: &Path, cache: &Path) -> bool {
-        // If cache doesn't exist, it's stale
         if !cache.exists() {
             return true;
         }
 
-        // Compare mtimes
+        match self.invalidation {
+            CacheInvalidation::Mtime => self.is_cache_stale_mtime(source, cache),
+            CacheInvalidation::Hash { checked } => {
+                self.is_cache_stale_hash(source, cache, checked)
+            }
+        }
+    }
+
+    /// mtime staleness check: source mtime > cache mtime.
+    fn is_cache_stale_mtime(&self, source: &Path, cache: &Path) -> bool {
         let source_mtime = fs::metadata(source)
             .and_then(|m| m.modified())
             .unwrap_or(SystemTime::UNIX_EPOCH);
@@ -278,8 +1221,43 @@ impl BytecodeCompiler {
         source_mtime > cache_mtime
     }
 
+    /// Hash staleness check (PEP 552). Reads the cache header rather than
+    /// comparing timestamps:
+    /// - If the header isn't hash-based (e.g. written under `Mtime` mode by
+    ///   an earlier run), treat it as stale so it gets rewritten with a hash.
+    /// - If `checked` is false, an existing hash-based cache is never stale -
+    ///   we trust it without touching the source at all.
+    /// - If `checked` is true, recompute the source hash and compare against
+    ///   the one stored in the header.
+    fn is_cache_stale_hash(&self, source: &Path, cache: &Path, checked: bool) -> bool {
+        let header = match read_pyc_header(cache) {
+            Ok(header) => header,
+            Err(_) => return true,
+        };
+
+        if !header.is_hash_based() {
+            return true;
+        }
+
+        if !checked {
+            return false;
+        }
+
+        let Some(expected_magic) = self.expected_magic else {
+            return true;
+        };
+
+        let source_bytes = match fs::read(source) {
+            Ok(bytes) => bytes,
+            Err(_) => return true,
+        };
+
+        header.source_hash != source_hash(expected_magic, &source_bytes)
+    }
+
     /// Validate magic number of a .pyc file
     fn validate_magic(&self, pyc_path: &Path) -> Result<bool> {
+        let _timer = PhaseTimer::start(pyc_path.to_string_lossy(), "validate_magic");
         let mut file = fs::File::open(pyc_path)?;
         let mut magic = [0u8; 4];
         file.read_exact(&mut magic)?;
@@ -291,15 +1269,127 @@ impl BytecodeCompiler {
         }
     }
 
+    /// Probe for a PEP 3147 `__pycache__/<stem>.<cache_tag>.pyc` artifact
+    /// next to `source` and reuse it if it's a clean hit: present, matching
+    /// `expected_magic`, and not stale per `self.invalidation`. Returns
+    /// `None` on anything less than that - including a cache tag we
+    /// couldn't determine - so the caller falls back to the normal
+    /// `.tach/cache` compile path rather than erroring.
+    fn try_reuse_pycache(&self, source: &Path) -> Option<Vec<u8>> {
+        let cache_tag = Self::get_cache_tag_cached().ok()?;
+        let stem = source.file_stem()?.to_str()?;
+        let pycache_path = source
+            .parent()?
+            .join("__pycache__")
+            .join(format!("{stem}.{cache_tag}.pyc"));
+
+        if !pycache_path.exists() {
+            return None;
+        }
+        if !self.validate_magic(&pycache_path).unwrap_or(false) {
+            return None;
+        }
+        if self.is_cache_stale(source, &pycache_path) {
+            return None;
+        }
+
+        self.read_and_strip_header(&pycache_path).ok()
+    }
+
+    /// Get `sys.implementation.cache_tag` (e.g. `"cpython-311"`), cached
+    /// globally alongside `CACHED_MAGIC`/`CACHED_PYTHON_EXE` so repeated
+    /// lookups (one per compiled file, in the worst case) don't each spawn
+    /// or round-trip through Python.
+    fn get_cache_tag_cached() -> Result<String> {
+        if let Some(cached) = CACHED_CACHE_TAG.get() {
+            return Ok(cached.clone());
+        }
+
+        let tag = if Self::is_interpreter_embedded() {
+            Self::get_cache_tag_in_process()?
+        } else {
+            let python_exe = Self::find_python_cached()?;
+            Self::get_cache_tag_impl(&python_exe)?
+        };
+
+        let _ = CACHED_CACHE_TAG.set(tag.clone());
+        Ok(tag)
+    }
+
+    /// Internal: actually get the cache tag by spawning Python
+    fn get_cache_tag_impl(python_exe: &Path) -> Result<String> {
+        let output = Command::new(python_exe)
+            .args(["-c", "import sys; print(sys.implementation.cache_tag)"])
+            .output()?;
+
+        if !output.status.success() {
+            return Err(anyhow!("Failed to get Python cache tag"));
+        }
+
+        let tag = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if tag.is_empty() {
+            return Err(anyhow!("Empty Python cache tag"));
+        }
+        Ok(tag)
+    }
+
+    /// In-process variant of `get_cache_tag_impl`: reads
+    /// `sys.implementation.cache_tag` out of the already-embedded
+    /// interpreter instead of spawning `python -c`.
+    fn get_cache_tag_in_process() -> Result<String> {
+        Python::with_gil(|py| -> Result<String> {
+            let tag: String = py
+                .import("sys")?
+                .getattr("implementation")?
+                .getattr("cache_tag")?
+                .extract()?;
+
+            if tag.is_empty() {
+                return Err(anyhow!("Empty Python cache tag"));
+            }
+            Ok(tag)
+        })
+    }
+
     /// Compile a single source file, returning header-stripped bytecode
     ///
     /// Uses persistent cache with mtime-based invalidation.
     /// Validates magic number and recompiles on mismatch.
+    ///
+    /// Before touching `.tach/cache` at all, probes for a PEP 3147
+    /// `__pycache__/<stem>.<cache_tag>.pyc` CPython may have already written
+    /// for this file (e.g. the project was already run/imported normally)
+    /// and reuses it directly when it's fresh, skipping compilation entirely.
+    ///
+    /// Both of those reads are skipped when `self.no_cache` is set - every
+    /// call recompiles from source, independent of `is_cache_stale` or
+    /// anything CPython already wrote to `__pycache__`.
+    ///
+    /// Audits `source` against `self.project_root` first (see
+    /// `audit_source_path`) and returns an error rather than compiling if it
+    /// resolves outside the root - a `..`-laden or symlinked path must not
+    /// be allowed to make `cache_path` write a `.pyc` somewhere unexpected.
     pub fn compile(&self, source: &Path) -> Result<Vec<u8>> {
-        let cache_path = self.cache_path(source);
+        let _timer = PhaseTimer::start(source.to_string_lossy(), "compile");
+        let canonical_relative = self.audit_source_path(source)?;
+
+        if !self.no_cache {
+            if let Some(reused) = self.try_reuse_pycache(source) {
+                return Ok(reused);
+            }
+        }
+
+        // Use the already-canonicalized relative path from the audit above
+        // rather than re-deriving it from `source` here - recomputing it
+        // independently is what let two symlink-equivalent spellings of the
+        // same file land on different fingerprints despite both passing the
+        // audit.
+        let cache_path = self.cache_path(&canonical_relative);
 
         // Check if we need to recompile
-        let needs_compile = if self.is_cache_stale(source, &cache_path) {
+        let needs_compile = if self.no_cache {
+            true
+        } else if self.is_cache_stale(source, &cache_path) {
             true
         } else {
             // Cache exists and is fresh, but check magic number
@@ -324,17 +1414,63 @@ impl BytecodeCompiler {
         self.read_and_strip_header(&cache_path)
     }
 
-    /// Compile source to cache using py_compile
+    /// Compile source to cache.
+    ///
+    /// Prefers the in-process path (GIL + `Py_CompileStringObject` +
+    /// `PyMarshal_WriteObjectToString`) when an interpreter is already
+    /// embedded in this process, falling back to spawning
+    /// `python -c "py_compile..."` otherwise - see `is_interpreter_embedded`.
     fn compile_to_cache(&self, source: &Path, cache: &Path) -> Result<()> {
         // Ensure parent directory exists
         if let Some(parent) = cache.parent() {
             fs::create_dir_all(parent)?;
         }
 
+        if Self::is_interpreter_embedded() {
+            self.compile_to_cache_in_process(source, cache)?;
+
+            // The in-process path always writes a timestamp-based header -
+            // `PyMarshal_WriteObjectToString` has no `invalidation_mode`
+            // equivalent to hand it. Patch it into a PEP 552 hash-based
+            // header afterwards when hash invalidation is configured.
+            if let CacheInvalidation::Hash { checked } = self.invalidation {
+                if let Some(magic) = self.expected_magic {
+                    let source_bytes = fs::read(source)?;
+                    write_hash_header(cache, magic, &source_bytes, checked)?;
+                }
+            }
+        } else {
+            // The subprocess path asks py_compile for the right header
+            // directly via `invalidation_mode`, so no post-hoc patch needed.
+            self.compile_to_cache_subprocess(source, cache)?;
+        }
+
+        Ok(())
+    }
+
+    /// Compile `source` to `cache` by spawning `python -c "py_compile..."`.
+    ///
+    /// This is the original path: one subprocess per file, which dominates
+    /// discovery time on large trees and risks OOM under parallelism (the
+    /// reason `CACHED_MAGIC`/`CACHED_PYTHON_EXE` exist in the first place).
+    /// Used only as a fallback when no interpreter is embedded in this
+    /// process for `compile_to_cache_in_process` to ride on.
+    fn compile_to_cache_subprocess(&self, source: &Path, cache: &Path) -> Result<()> {
+        let invalidation_mode = match self.invalidation {
+            CacheInvalidation::Mtime => "py_compile.PycInvalidationMode.TIMESTAMP",
+            CacheInvalidation::Hash { checked: true } => {
+                "py_compile.PycInvalidationMode.CHECKED_HASH"
+            }
+            CacheInvalidation::Hash { checked: false } => {
+                "py_compile.PycInvalidationMode.UNCHECKED_HASH"
+            }
+        };
+
         let script = format!(
-            "import py_compile; py_compile.compile('{}', '{}', doraise=True)",
+            "import py_compile; py_compile.compile('{}', '{}', doraise=True, invalidation_mode={})",
             source.display(),
-            cache.display()
+            cache.display(),
+            invalidation_mode
         );
 
         let output = Command::new(&self.python_exe)
@@ -353,8 +1489,93 @@ impl BytecodeCompiler {
         Ok(())
     }
 
+    /// Compile `source` to `cache` without spawning a subprocess: acquire
+    /// the GIL, compile the source straight to a code object with
+    /// `Py_CompileStringObject`, marshal it with
+    /// `PyMarshal_WriteObjectToString`, and prepend a PEP 552
+    /// timestamp-based header before writing the result. Turns N subprocess
+    /// spawns into zero and is what lets `compile_batch`/`compile_batch_parallel`
+    /// scale on large trees.
+    fn compile_to_cache_in_process(&self, source: &Path, cache: &Path) -> Result<()> {
+        let magic = self
+            .expected_magic
+            .ok_or_else(|| anyhow!("no Python magic number available for in-process compilation"))?;
+        let source_bytes = fs::read(source)?;
+        let mtime_secs = fs::metadata(source)
+            .and_then(|m| m.modified())?
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as u32;
+        let size = source_bytes.len() as u32;
+
+        let marshalled = Python::with_gil(|py| -> Result<Vec<u8>> {
+            let source_cstr = std::ffi::CString::new(source_bytes)
+                .map_err(|_| anyhow!("source contains a NUL byte: {}", source.display()))?;
+            let filename_cstr = std::ffi::CString::new(source.to_string_lossy().as_bytes())
+                .map_err(|_| anyhow!("invalid source path: {}", source.display()))?;
+
+            unsafe {
+                let filename_obj = ffi::PyUnicode_FromString(filename_cstr.as_ptr());
+                if filename_obj.is_null() {
+                    return Err(PyErr::fetch(py).into());
+                }
+
+                let code_obj = ffi::Py_CompileStringObject(
+                    source_cstr.as_ptr(),
+                    filename_obj,
+                    ffi::Py_file_input,
+                    std::ptr::null_mut(),
+                    -1,
+                );
+                ffi::Py_DECREF(filename_obj);
+
+                if code_obj.is_null() {
+                    return Err(PyErr::fetch(py).into());
+                }
+
+                let marshalled_obj =
+                    ffi::PyMarshal_WriteObjectToString(code_obj, ffi::Py_MARSHAL_VERSION);
+                ffi::Py_DECREF(code_obj);
+
+                if marshalled_obj.is_null() {
+                    return Err(PyErr::fetch(py).into());
+                }
+
+                let mut buf_ptr: *mut std::os::raw::c_char = std::ptr::null_mut();
+                let mut buf_len: ffi::Py_ssize_t = 0;
+                let ok =
+                    ffi::PyBytes_AsStringAndSize(marshalled_obj, &mut buf_ptr, &mut buf_len) == 0;
+                let bytes = if ok {
+                    Some(
+                        std::slice::from_raw_parts(buf_ptr as *const u8, buf_len as usize)
+                            .to_vec(),
+                    )
+                } else {
+                    None
+                };
+                ffi::Py_DECREF(marshalled_obj);
+
+                match bytes {
+                    Some(bytes) => Ok(bytes),
+                    None => Err(PyErr::fetch(py).into()),
+                }
+            }
+        })?;
+
+        let mut buffer = Vec::with_capacity(PYC_HEADER_SIZE + marshalled.len());
+        buffer.extend_from_slice(&magic);
+        buffer.extend_from_slice(&0u32.to_le_bytes()); // timestamp-based (bit 0 unset)
+        buffer.extend_from_slice(&mtime_secs.to_le_bytes());
+        buffer.extend_from_slice(&size.to_le_bytes());
+        buffer.extend_from_slice(&marshalled);
+
+        fs::write(cache, &buffer)?;
+        Ok(())
+    }
+
     /// Read .pyc file and strip the 16-byte header
     fn read_and_strip_header(&self, pyc_path: &Path) -> Result<Vec<u8>> {
+        let _timer = PhaseTimer::start(pyc_path.to_string_lossy(), "read_and_strip_header");
         let data = fs::read(pyc_path)?;
 
         if data.len() < PYC_HEADER_SIZE {
@@ -368,19 +1589,50 @@ impl BytecodeCompiler {
         Ok(data[PYC_HEADER_SIZE..].to_vec())
     }
 
-    /// Batch compile all files, populating the registry
+    /// Batch compile all files, populating the registry.
+    ///
+    /// Fans the input files across `thread_count` worker threads via rayon
+    /// (bounded by `self.thread_count`, so this never oversubscribes beyond
+    /// what the caller configured via `with_thread_count`) instead of
+    /// compiling one file at a time - the original sequential design left
+    /// each file's subprocess/GIL round trip fully serialized, which
+    /// dominates discovery time on large trees. When no interpreter is
+    /// embedded, `compile()` shells out to `py_compile` as a subprocess per
+    /// file, so there is no live interpreter/GIL to serialize on and the
+    /// Rust-side I/O, header-stripping, and cache lookups run fully
+    /// concurrently. When an interpreter *is* embedded, `compile()` takes
+    /// the in-process path instead (see `compile_to_cache_in_process`),
+    /// which still parallelizes the non-compile work but serializes the
+    /// actual compile step behind the GIL like any other CPython C-API call
+    /// from multiple threads.
+    ///
+    /// Results are collected in input order before any registry insert or
+    /// warning is emitted, so the returned count and the registry's
+    /// contents are deterministic regardless of thread scheduling, and
+    /// warnings print in the same order regardless of which worker finished
+    /// first. A single file's compile failure is logged and skipped; it
+    /// does not abort the rest of the batch.
     ///
     /// Logs warnings for compilation failures but continues.
     pub fn compile_batch(&self, files: &[PathBuf], registry: &ModuleRegistry) -> usize {
-        let mut success_count = 0;
-
-        for file in files {
-            // Skip non-.py files
-            if file.extension().map_or(true, |e| e != "py") {
-                continue;
-            }
+        let _timer = PhaseTimer::start(format!("<batch:{}files>", files.len()), "compile_batch");
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.thread_count)
+            .build()
+            .expect("failed to build compilation thread pool");
+
+        let results: Vec<(&PathBuf, Result<Vec<u8>>)> = pool.install(|| {
+            files
+                .par_iter()
+                .filter(|file| file.extension().map_or(false, |e| e == "py"))
+                .map(|file| (file, self.compile(file)))
+                .collect()
+        });
 
-            match self.compile(file) {
+        let mut success_count = 0;
+        for (file, result) in results {
+            match result {
                 Ok(bytecode) => {
                     let name = self.path_to_module_name(file);
                     let is_package = file.file_name().map_or(false, |n| n == "__init__.py");
@@ -388,9 +1640,11 @@ impl BytecodeCompiler {
                     registry.insert(BytecodeEntry {
                         name: name.clone(),
                         source_path: file.clone(),
-                        bytecode,
+                        bytecode: Cow::Owned(bytecode),
                         is_package,
+                        is_namespace: false,
                     });
+                    self.register_namespace_ancestors(&name, registry);
 
                     success_count += 1;
                 }
@@ -402,12 +1656,153 @@ impl BytecodeCompiler {
         }
 
         eprintln!(
-            "[loader] Compiled {} of {} files",
+            "[loader] Compiled {} of {} files ({} threads)",
             success_count,
-            files.len()
+            files.len(),
+            self.thread_count
         );
         success_count
     }
+
+    /// Alias for [`Self::compile_batch`], kept for callers written against
+    /// the name from when the parallel path was a separate opt-in variant.
+    pub fn compile_batch_parallel(&self, files: &[PathBuf], registry: &ModuleRegistry) -> usize {
+        self.compile_batch(files, registry)
+    }
+
+    /// Compile every file resolved by a manifest (see [`crate::manifest`])
+    /// instead of requiring the caller to hand-list every path.
+    ///
+    /// Resolves `%include`/`%unset` directives and glob roots/excludes, then
+    /// feeds the resulting file list into `compile_batch`.
+    pub fn compile_from_manifest(
+        &self,
+        manifest: &Path,
+        registry: &ModuleRegistry,
+    ) -> Result<ManifestCompileResult> {
+        let resolved = crate::manifest::resolve_manifest(manifest)?;
+        let compiled = self.compile_batch(&resolved.files, registry);
+
+        Ok(ManifestCompileResult {
+            compiled,
+            total: resolved.files.len(),
+            unset: resolved.unset,
+        })
+    }
+
+    /// Pack every entry in `registry` into `.tach/cache/packed.cache` and
+    /// remap it read-only (see [`ModuleRegistry::freeze`]).
+    pub fn freeze_registry(&self, registry: &ModuleRegistry) -> Result<PathBuf> {
+        let packed_path = self.cache_dir.join("packed.cache");
+        registry.freeze(&packed_path)?;
+        Ok(packed_path)
+    }
+
+    /// Pack every entry in `registry` into `.tach/cache/registry.pack` for
+    /// a cold-start reload via `load_packed_registry` - see
+    /// [`ModuleRegistry::pack`]. Unlike `freeze_registry`, the result is
+    /// meant to outlive this process and be shared by other workers.
+    pub fn pack_registry(&self, registry: &ModuleRegistry) -> Result<PathBuf> {
+        let packed_path = self.cache_dir.join("registry.pack");
+        let magic = self
+            .expected_magic
+            .ok_or_else(|| anyhow!("no Python magic number available to pack the registry"))?;
+        registry.pack(&packed_path, magic)?;
+        Ok(packed_path)
+    }
+
+    /// Load a registry previously written by `pack_registry` without
+    /// recompiling anything - see [`ModuleRegistry::load_packed`].
+    pub fn load_packed_registry(&self) -> Result<ModuleRegistry> {
+        let packed_path = self.cache_dir.join("registry.pack");
+        let magic = self.expected_magic.ok_or_else(|| {
+            anyhow!("no Python magic number available to load the packed registry")
+        })?;
+        ModuleRegistry::load_packed(&packed_path, self.project_root.clone(), magic)
+    }
+}
+
+/// Outcome of [`BytecodeCompiler::compile_from_manifest`].
+pub struct ManifestCompileResult {
+    /// Number of files successfully compiled and inserted into the registry.
+    pub compiled: usize,
+    /// Number of files the manifest resolved to after includes/excludes/unsets.
+    pub total: usize,
+    /// `%unset` directives and what they overrode, for diagnostics.
+    pub unset: Vec<crate::manifest::UnsetDiagnostic>,
+}
+
+/// A single resource classified by [`BytecodeCompiler::discover_tree`].
+#[derive(Debug, Clone)]
+pub enum DiscoveredModule {
+    /// A plain `.py` module or package `__init__.py` - feed its `path` into
+    /// `compile_batch`/`compile_batch_parallel` to actually produce
+    /// bytecode for it.
+    Source {
+        name: String,
+        path: PathBuf,
+        is_package: bool,
+    },
+    /// A native extension (`.so`/`.pyd`/`.abi3.so`). Nothing to compile,
+    /// but it still occupies this dotted name, so `ingest` registers it
+    /// directly with empty bytecode instead of it silently vanishing from
+    /// the tree the way an un-filtered `compile_batch` call would drop it.
+    Extension { name: String, path: PathBuf },
+    /// A directory with submodules but no `__init__.py` of its own (PEP
+    /// 420). Same shape as `register_namespace_ancestors`' synthesized
+    /// entries, surfaced explicitly here so `discover_tree` reports the
+    /// whole tree rather than only the parts `compile_batch` touches.
+    Namespace { name: String, path: PathBuf },
+}
+
+/// Structured output of [`BytecodeCompiler::discover_tree`]: every resource
+/// found while walking a project, split so `compile_batch` only ever sees
+/// files it can actually compile and the registry can be seeded with
+/// everything else in one call.
+#[derive(Debug, Clone, Default)]
+pub struct DiscoveredTree {
+    pub modules: Vec<DiscoveredModule>,
+}
+
+impl DiscoveredTree {
+    /// `.py` source paths discovered - hand these to
+    /// `compile_batch`/`compile_batch_parallel`.
+    pub fn source_files(&self) -> Vec<PathBuf> {
+        self.modules
+            .iter()
+            .filter_map(|m| match m {
+                DiscoveredModule::Source { path, .. } => Some(path.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Register every non-compilable resource (extensions, namespace
+    /// packages) directly into `registry` with empty bytecode. Plain `.py`
+    /// sources are left for `compile_batch` to insert once it has actual
+    /// bytecode for them - calling this doesn't replace that step, it
+    /// complements it.
+    pub fn ingest(&self, registry: &ModuleRegistry) {
+        for module in &self.modules {
+            let (name, source_path, is_package, is_namespace) = match module {
+                DiscoveredModule::Source { .. } => continue,
+                DiscoveredModule::Extension { name, path } => (name, path, false, false),
+                DiscoveredModule::Namespace { name, path } => (name, path, true, true),
+            };
+
+            if registry.contains(name) {
+                continue;
+            }
+
+            registry.insert(BytecodeEntry {
+                name: name.clone(),
+                source_path: source_path.clone(),
+                bytecode: Cow::Borrowed(&[]),
+                is_package,
+                is_namespace,
+            });
+        }
+    }
 }
 
 // =============================================================================
@@ -434,7 +1829,40 @@ pub fn get_registry() -> Option<&'static ModuleRegistry> {
 /// Returns bytecode bytes if found, None otherwise.
 #[pyfunction]
 pub fn get_module(name: &str) -> Option<Vec<u8>> {
-    REGISTRY.get().and_then(|r| r.get_bytecode(name))
+    REGISTRY.get().and_then(|r| r.get_bytecode(name)).map(|b| b.to_vec())
+}
+
+/// Zero-copy variant of `get_module`: wraps the registry's own buffer in a
+/// Python `memoryview` instead of cloning it into a `Vec<u8>`, the same
+/// trick PyOxidizer's in-memory importer uses for its embedded modules.
+///
+/// # Safety invariant
+/// `ModuleRegistry` entries are populated once via the "Push" model during
+/// discovery/compilation and, for the lifetime of a test run, never
+/// mutated or removed after that - `retain_reachable` only prunes before
+/// execution starts, not during it. The returned `memoryview` borrows the
+/// entry's bytes directly with no lifetime tracked by the GIL or the Rust
+/// borrow checker, so it is sound only as long as that invariant holds. If
+/// a future registry mode allows mutating or removing entries while
+/// workers may be holding a memoryview into them, this function must stop
+/// being used for such entries (or be changed to copy instead of borrow).
+#[pyfunction]
+pub fn get_module_memoryview(py: Python<'_>, name: &str) -> Option<PyObject> {
+    let registry = REGISTRY.get()?;
+    let entry = registry.get_bytecode(name)?;
+    let bytes: &[u8] = &entry;
+
+    unsafe {
+        let view = ffi::PyMemoryView_FromMemory(
+            bytes.as_ptr() as *mut std::os::raw::c_char,
+            bytes.len() as ffi::Py_ssize_t,
+            ffi::PyBUF_READ,
+        );
+        if view.is_null() {
+            return None;
+        }
+        Some(PyObject::from_owned_ptr(py, view))
+    }
 }
 
 /// Get source path for a module from the registry
@@ -456,6 +1884,14 @@ pub fn is_module_package(name: &str) -> Option<bool> {
 
 /// Load bytecode into Python's sys.modules
 ///
+/// Before executing `name` itself, walks its dotted path and recursively
+/// injects any ancestor packages that aren't in `sys.modules` yet (pulling
+/// each one's bytecode/source path/`is_package` straight from the
+/// registry), then after executing binds the freshly-created module onto
+/// its immediate parent's attribute - the same package wiring `import
+/// foo.bar` gets for free from `PyImport_ExecCodeModuleObject` via the
+/// normal `__import__` path, which this Request-Model entry point bypasses.
+///
 /// # Safety
 /// This function uses raw C-API calls. The bytecode MUST:
 /// - Be at least 0 bytes (header already stripped)
@@ -477,6 +1913,27 @@ pub fn load_module(
         return Err(pyo3::exceptions::PyValueError::new_err("Bytecode is empty"));
     }
 
+    ensure_ancestors_loaded(py, name)?;
+    exec_and_register_module(py, name, source_path, bytecode)?;
+    bind_into_parent(py, name)?;
+
+    Ok(true)
+}
+
+/// Marshal, execute and register a single module's bytecode into
+/// `sys.modules`, then patch its `__file__`/`__package__`/`__path__`
+/// attributes. Shared by `load_module` for both the requested leaf module
+/// and any ancestor packages it has to backfill along the way.
+///
+/// # Safety
+/// Caller must ensure `bytecode` is a non-empty, valid marshalled code
+/// object; this mirrors the contract `load_module` itself documents.
+fn exec_and_register_module(
+    py: Python<'_>,
+    name: &str,
+    source_path: &str,
+    bytecode: &[u8],
+) -> PyResult<()> {
     unsafe {
         // 1. Deserialize bytecode to code object
         let code_obj = ffi::PyMarshal_ReadObjectFromString(
@@ -530,9 +1987,108 @@ pub fn load_module(
 
         // Module is now in sys.modules, we don't need to hold a reference
         ffi::Py_DECREF(module);
+    }
+
+    Ok(())
+}
+
+/// Walk `name`'s dotted path and make sure every ancestor package already
+/// sits in `sys.modules`, loading any that are missing straight from the
+/// registry (in left-to-right order, so `foo` exists before `foo.bar` is
+/// injected) and binding each onto its own parent as it goes.
+///
+/// Returns a clear `ImportError` - rather than leaving `sys.modules`
+/// partially wired - if an ancestor isn't registered at all.
+fn ensure_ancestors_loaded(py: Python<'_>, name: &str) -> PyResult<()> {
+    let sys_modules = py
+        .import("sys")?
+        .getattr("modules")?
+        .downcast_into::<PyDict>()
+        .map_err(|e| pyo3::exceptions::PyImportError::new_err(format!("sys.modules not a dict: {e}")))?;
+
+    let mut components: Vec<&str> = name.split('.').collect();
+    components.pop(); // the leaf itself is loaded by the caller, not here
+
+    let mut ancestor = String::new();
+    for part in components {
+        if !ancestor.is_empty() {
+            ancestor.push('.');
+        }
+        ancestor.push_str(part);
+
+        if sys_modules.contains(ancestor.as_str())? {
+            continue;
+        }
+
+        let registry = REGISTRY.get().ok_or_else(|| {
+            pyo3::exceptions::PyImportError::new_err(format!(
+                "module registry not initialized, can't load ancestor package {ancestor} of {name}"
+            ))
+        })?;
+        let ancestor_source_path = registry.get_source_path(&ancestor).ok_or_else(|| {
+            pyo3::exceptions::PyImportError::new_err(format!(
+                "ancestor package {ancestor} of {name} is not registered"
+            ))
+        })?;
+        let ancestor_bytecode = registry.get_bytecode(&ancestor).ok_or_else(|| {
+            pyo3::exceptions::PyImportError::new_err(format!(
+                "no bytecode registered for ancestor package {ancestor} of {name}"
+            ))
+        })?;
+        let ancestor_source_path = ancestor_source_path.to_string_lossy().into_owned();
+        if registry.is_namespace_package(&ancestor) == Some(true) {
+            // A synthesized namespace package has no bytecode to run: register
+            // an empty module whose `__path__` is the directory itself.
+            let module = pyo3::types::PyModule::new(py, &ancestor)?;
+            module.setattr("__path__", PyList::new(py, &[ancestor_source_path])?)?;
+            sys_modules.set_item(ancestor.as_str(), module)?;
+            bind_into_parent(py, &ancestor)?;
+            continue;
+        }
+        let ancestor_bytecode = ancestor_bytecode.to_vec();
+
+        exec_and_register_module(py, &ancestor, &ancestor_source_path, &ancestor_bytecode)?;
+        bind_into_parent(py, &ancestor)?;
+    }
+
+    Ok(())
+}
 
-        Ok(true)
+/// `PyObject_SetAttrString(parent, leaf, module)` - binds a just-loaded
+/// module onto its immediate parent package so `getattr(foo, 'bar')` works
+/// after loading `foo.bar`, not just `sys.modules["foo.bar"]` lookups.
+/// A no-op for top-level modules, which have no parent to bind onto.
+fn bind_into_parent(py: Python<'_>, name: &str) -> PyResult<()> {
+    let Some((parent, leaf)) = name.rsplit_once('.') else {
+        return Ok(());
+    };
+
+    let sys_modules = py
+        .import("sys")?
+        .getattr("modules")?
+        .downcast_into::<PyDict>()
+        .map_err(|e| pyo3::exceptions::PyImportError::new_err(format!("sys.modules not a dict: {e}")))?;
+
+    let parent_module = sys_modules.get_item(parent)?.ok_or_else(|| {
+        pyo3::exceptions::PyImportError::new_err(format!(
+            "parent package {parent} missing from sys.modules while wiring {name}"
+        ))
+    })?;
+    let child_module = sys_modules.get_item(name)?.ok_or_else(|| {
+        pyo3::exceptions::PyImportError::new_err(format!(
+            "{name} missing from sys.modules right after being executed"
+        ))
+    })?;
+
+    let leaf_cstr = std::ffi::CString::new(leaf)
+        .map_err(|_| pyo3::exceptions::PyValueError::new_err("Invalid module name"))?;
+    unsafe {
+        if ffi::PyObject_SetAttrString(parent_module.as_ptr(), leaf_cstr.as_ptr(), child_module.as_ptr()) != 0 {
+            return Err(PyErr::fetch(py));
+        }
     }
+
+    Ok(())
 }
 
 /// Patch module namespace with __file__, __package__, __path__
@@ -585,6 +2141,151 @@ unsafe fn patch_module_namespace(
     Ok(())
 }
 
+/// Marshal a registry entry's bytecode straight into a new code object,
+/// reading directly from the registry's stored buffer instead of cloning it
+/// into a `Vec<u8>` first (what `exec_module` used to do via `.to_vec()`).
+/// The `BytecodeRef` guard borrowed from `get_bytecode` keeps the buffer
+/// alive for the duration of this call.
+///
+/// # Safety
+/// Caller takes ownership of the returned code object reference and must
+/// `Py_DECREF` it exactly once.
+unsafe fn load_from_registry(py: Python<'_>, name: &str) -> PyResult<*mut ffi::PyObject> {
+    let registry = REGISTRY.get().ok_or_else(|| {
+        pyo3::exceptions::PyImportError::new_err(format!(
+            "module registry not initialized, can't exec {name}"
+        ))
+    })?;
+    let entry = registry.get_bytecode(name).ok_or_else(|| {
+        pyo3::exceptions::PyImportError::new_err(format!("no bytecode registered for {name}"))
+    })?;
+    let bytes: &[u8] = &entry;
+
+    let code_obj =
+        ffi::PyMarshal_ReadObjectFromString(bytes.as_ptr() as *const i8, bytes.len() as isize);
+    if code_obj.is_null() {
+        return Err(PyErr::fetch(py));
+    }
+    Ok(code_obj)
+}
+
+// =============================================================================
+// RegistryImporter: native sys.meta_path finder/loader
+// =============================================================================
+
+/// Native `importlib.abc.MetaPathFinder` + `Loader`, serving straight from
+/// `ModuleRegistry` (the "Push" model's front door).
+///
+/// Installed once at startup (see `zygote::install_meta_path_importer`) at
+/// the front of `sys.meta_path`, ahead of the stdlib path-based finders, so
+/// an ordinary `import foo.bar` anywhere in worker code resolves straight
+/// from the registry with a correctly populated `__spec__`/`__loader__` -
+/// no `get_module`/`load_module` calls from a Python-side harness required.
+/// Same idea as PyOxidizer's embedded meta-path importer.
+#[pyclass]
+pub struct RegistryImporter;
+
+#[pymethods]
+impl RegistryImporter {
+    #[new]
+    fn new() -> Self {
+        Self
+    }
+
+    /// `MetaPathFinder.find_spec(name, path, target=None)`
+    ///
+    /// Returns `None` for anything the registry doesn't know about, so the
+    /// stdlib's own finders further down `sys.meta_path` still get a chance
+    /// at it (the venv, site-packages, the standard library itself).
+    #[pyo3(signature = (name, path=None, target=None))]
+    fn find_spec<'py>(
+        &self,
+        py: Python<'py>,
+        name: &str,
+        path: Option<Bound<'py, PyAny>>,
+        target: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Option<Bound<'py, PyAny>>> {
+        let _ = (path, target);
+
+        let Some(registry) = REGISTRY.get() else {
+            return Ok(None);
+        };
+        let Some(source_path) = registry.get_source_path(name) else {
+            return Ok(None);
+        };
+        let is_package = registry.is_package(name).unwrap_or(false);
+        let origin = source_path.to_string_lossy().to_string();
+
+        let loader = Bound::new(py, RegistryImporter)?;
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("origin", &origin)?;
+        kwargs.set_item("is_package", is_package)?;
+        let spec = py
+            .import("importlib.util")?
+            .getattr("spec_from_loader")?
+            .call((name, &loader), Some(&kwargs))?;
+
+        if is_package {
+            let parent_dir = Path::new(&origin)
+                .parent()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+            spec.setattr("submodule_search_locations", PyList::new(py, &[parent_dir])?)?;
+        }
+
+        Ok(Some(spec))
+    }
+
+    /// `Loader.create_module(spec)` - `None` tells `importlib` to fall back
+    /// to its own default module creation (a plain new module object),
+    /// which is all `exec_module` below needs to run bytecode into.
+    fn create_module(&self, _spec: &Bound<'_, PyAny>) -> Option<PyObject> {
+        None
+    }
+
+    /// `Loader.exec_module(module)` - pulls this module's bytecode out of
+    /// the registry, marshals it, and execs it straight into the already-
+    /// created module's `__dict__`. Same marshal/exec steps `load_module`
+    /// does for the old explicit-call sites, just driven by `import` instead.
+    fn exec_module(&self, py: Python<'_>, module: &Bound<'_, PyAny>) -> PyResult<()> {
+        let name: String = module.getattr("__name__")?.extract()?;
+
+        // A synthesized namespace package carries empty bytecode; the module
+        // importlib already created from its spec is all there is to it.
+        if REGISTRY.get().and_then(|r| r.is_namespace_package(&name)) == Some(true) {
+            return Ok(());
+        }
+
+        unsafe {
+            let code_obj = load_from_registry(py, &name)?;
+
+            let module_dict = module.getattr("__dict__")?;
+            let result =
+                ffi::PyEval_EvalCode(code_obj, module_dict.as_ptr(), module_dict.as_ptr());
+            ffi::Py_DECREF(code_obj);
+
+            if result.is_null() {
+                return Err(PyErr::fetch(py));
+            }
+            ffi::Py_DECREF(result);
+        }
+
+        Ok(())
+    }
+}
+
+/// Install [`RegistryImporter`] at the front of `sys.meta_path`.
+///
+/// Called once at Zygote startup (see `zygote::inject_tach_rust_module`),
+/// before any worker-side code runs, so every `import` sees it first.
+pub fn install_meta_path_importer(py: Python<'_>) -> PyResult<()> {
+    let sys = py.import("sys")?;
+    let meta_path = sys.getattr("meta_path")?;
+    let importer = Bound::new(py, RegistryImporter)?;
+    meta_path.call_method1("insert", (0, importer))?;
+    Ok(())
+}
+
 // =============================================================================
 // Unit Tests
 // =============================================================================
@@ -624,9 +2325,37 @@ mod tests {
         let cache = compiler.cache_path(&source);
 
         assert!(cache.to_string_lossy().contains(".tach"));
+        assert!(cache.to_string_lossy().contains("__pycache__"));
+        assert!(cache.file_name().unwrap().to_string_lossy().starts_with("bar-"));
         assert!(cache.to_string_lossy().ends_with(".pyc"));
     }
 
+    /// Optimization levels must get distinct cache filenames - and level
+    /// zero must omit the `.opt-N` tag entirely, same as CPython.
+    #[test]
+    fn test_cache_path_optimization_levels() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("opt.py");
+
+        let zero = BytecodeCompiler::new(temp.path()).unwrap();
+        let one = BytecodeCompiler::new(temp.path())
+            .unwrap()
+            .with_optimization_level(BytecodeOptimizationLevel::One);
+        let two = BytecodeCompiler::new(temp.path())
+            .unwrap()
+            .with_optimization_level(BytecodeOptimizationLevel::Two);
+
+        let zero_path = zero.cache_path(&source);
+        let one_path = one.cache_path(&source);
+        let two_path = two.cache_path(&source);
+
+        assert!(!zero_path.to_string_lossy().contains("opt-"));
+        assert!(one_path.to_string_lossy().contains(".opt-1.pyc"));
+        assert!(two_path.to_string_lossy().contains(".opt-2.pyc"));
+        assert_ne!(zero_path, one_path);
+        assert_ne!(one_path, two_path);
+    }
+
     /// Test compilation of a simple module
     #[test]
     fn test_compile_simple_module() {
@@ -655,6 +2384,33 @@ mod tests {
         );
     }
 
+    /// Test the in-process compilation path (GIL + `Py_CompileStringObject`)
+    /// produces the same header-stripped, loadable bytecode as the
+    /// subprocess path
+    #[test]
+    fn test_compile_in_process() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("in_process.py");
+        fs::write(&source, "def hello(): return 'world'").unwrap();
+
+        let compiler = BytecodeCompiler::new(temp.path()).unwrap();
+        let cache = compiler.cache_path(&source);
+
+        Python::with_gil(|_py| {
+            compiler
+                .compile_to_cache_in_process(&source, &cache)
+                .unwrap();
+        });
+        assert!(BytecodeCompiler::is_interpreter_embedded());
+
+        let bytecode = compiler.read_and_strip_header(&cache).unwrap();
+        assert!(
+            bytecode[0] == 0x63 || bytecode[0] == 0xe3,
+            "First byte should be TYPE_CODE marker, got 0x{:02x}",
+            bytecode[0]
+        );
+    }
+
     /// Test cache staleness detection
     #[test]
     fn test_cache_staleness() {
@@ -688,8 +2444,9 @@ mod tests {
         registry.insert(BytecodeEntry {
             name: "foo.bar".to_string(),
             source_path: temp.path().join("foo/bar.py"),
-            bytecode: vec![1, 2, 3],
+            bytecode: vec![1, 2, 3].into(),
             is_package: false,
+            is_namespace: false,
         });
 
         // Retrieve
@@ -728,6 +2485,40 @@ mod tests {
     // Extended Coverage Tests
     // =========================================================================
 
+    /// Test that `try_reuse_pycache` finds and reuses a PEP 3147
+    /// `__pycache__/<stem>.<cache_tag>.pyc` artifact placed next to the
+    /// source file, independent of `.tach/cache`
+    #[test]
+    fn test_try_reuse_pycache_hit() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("reuse_me.py");
+        fs::write(&source, "x = 1").unwrap();
+
+        let compiler = BytecodeCompiler::new(temp.path()).unwrap();
+        let expected = compiler.compile(&source).unwrap();
+
+        let cache_tag = BytecodeCompiler::get_cache_tag_cached().unwrap();
+        let pycache_dir = temp.path().join("__pycache__");
+        fs::create_dir_all(&pycache_dir).unwrap();
+        let pycache_path = pycache_dir.join(format!("reuse_me.{}.pyc", cache_tag));
+        fs::copy(compiler.cache_path(&source), &pycache_path).unwrap();
+
+        let reused = compiler.try_reuse_pycache(&source);
+        assert_eq!(reused, Some(expected));
+    }
+
+    /// Test that `try_reuse_pycache` is a clean miss (not an error) when no
+    /// `__pycache__` artifact exists for the source file
+    #[test]
+    fn test_try_reuse_pycache_miss() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("no_pycache.py");
+        fs::write(&source, "x = 1").unwrap();
+
+        let compiler = BytecodeCompiler::new(temp.path()).unwrap();
+        assert!(compiler.try_reuse_pycache(&source).is_none());
+    }
+
     /// Test magic number validation with valid cache
     #[test]
     fn test_magic_validation_valid_cache() {
@@ -822,6 +2613,67 @@ mod tests {
         assert!(result.is_err(), "Compile should fail for missing source");
     }
 
+    /// `compile` must reject a source that resolves outside the compiler's
+    /// project root instead of silently compiling it into a misplaced cache
+    /// artifact - here via a literal `..` escaping the root.
+    #[test]
+    fn test_compile_rejects_source_outside_root() {
+        let temp = TempDir::new().unwrap();
+        let project_root = temp.path().join("project");
+        fs::create_dir_all(&project_root).unwrap();
+
+        let outside = temp.path().join("outside.py");
+        fs::write(&outside, "x = 1").unwrap();
+
+        let compiler = BytecodeCompiler::new(&project_root).unwrap();
+        let escaping = project_root.join("..").join("outside.py");
+        let result = compiler.compile(&escaping);
+
+        assert!(result.is_err(), "Compile should reject a path escaping project_root");
+    }
+
+    /// `compile` must land on the same cache artifact whether `source` is
+    /// spelled through a symlink or through the real path it resolves to -
+    /// `cache_path` fingerprints the path `audit_source_path` already
+    /// canonicalized, not whatever spelling `source` showed up as.
+    #[test]
+    fn test_compile_cache_path_is_stable_across_symlinked_source() {
+        let temp = TempDir::new().unwrap();
+        let project_root = temp.path().join("project");
+        let real_dir = project_root.join("real");
+        fs::create_dir_all(&real_dir).unwrap();
+        fs::write(real_dir.join("mod.py"), "x = 1").unwrap();
+
+        let link_dir = project_root.join("link");
+        std::os::unix::fs::symlink(&real_dir, &link_dir).unwrap();
+
+        let compiler = BytecodeCompiler::new(&project_root).unwrap();
+        let via_real = compiler.compile(&real_dir.join("mod.py"));
+        let via_link = compiler.compile(&link_dir.join("mod.py"));
+
+        assert!(via_real.is_ok());
+        assert!(via_link.is_ok());
+        // Both spellings canonicalize to the same file, so the second
+        // `compile` call must hit the cache the first one wrote rather than
+        // deriving a different fingerprint and writing a second artifact.
+        assert_eq!(via_real.unwrap(), via_link.unwrap());
+    }
+
+    /// Two out-of-root sources that share a file stem - and so would have
+    /// collapsed onto the same `strip_prefix` fallback path before the
+    /// fingerprint was added - must still get distinct cache paths.
+    #[test]
+    fn test_cache_path_fingerprint_avoids_stem_collision() {
+        let temp = TempDir::new().unwrap();
+        let compiler = BytecodeCompiler::new(temp.path()).unwrap();
+
+        let a = compiler.cache_path(Path::new("other_one/mod.py"));
+        let b = compiler.cache_path(Path::new("other_two/mod.py"));
+
+        assert_ne!(a, b);
+        assert_ne!(a.file_name(), b.file_name());
+    }
+
     /// Test cache_path for various path structures
     #[test]
     fn test_cache_path_various_structures() {
@@ -833,11 +2685,16 @@ mod tests {
         let cache1 = compiler.cache_path(&simple);
         assert!(cache1.to_string_lossy().ends_with(".pyc"));
 
-        // Nested file
+        // Nested file - directory structure is mirrored, not flattened, so
+        // each package dir gets its own __pycache__ alongside its modules
         let nested = temp.path().join("a").join("b").join("c.py");
         let cache2 = compiler.cache_path(&nested);
         assert!(cache2.to_string_lossy().ends_with(".pyc"));
-        assert!(cache2.to_string_lossy().contains("_")); // Separators replaced
+        let expected_suffix = Path::new("a")
+            .join("b")
+            .join("__pycache__")
+            .join(cache2.file_name().unwrap());
+        assert!(cache2.ends_with(&expected_suffix));
     }
 
     /// Test path_to_module_name for various paths
@@ -867,6 +2724,80 @@ mod tests {
         assert_eq!(name, "a.b.c");
     }
 
+    /// `discover_tree` should classify a plain module, a real package, a
+    /// namespace package (a directory with a submodule but no
+    /// `__init__.py`), and a native extension, then `ingest` should seed
+    /// the registry with everything except the plain module (left for
+    /// `compile_batch` to insert with real bytecode).
+    #[test]
+    fn test_discover_tree_classifies_resources() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+
+        fs::write(root.join("top.py"), "x = 1").unwrap();
+
+        fs::create_dir_all(root.join("pkg")).unwrap();
+        fs::write(root.join("pkg").join("__init__.py"), "").unwrap();
+        fs::write(root.join("pkg").join("mod.py"), "x = 1").unwrap();
+
+        fs::create_dir_all(root.join("ns").join("sub")).unwrap();
+        fs::write(root.join("ns").join("sub").join("__init__.py"), "").unwrap();
+
+        fs::create_dir_all(root.join("native")).unwrap();
+        fs::write(
+            root.join("native").join("speedups.cpython-311-x86_64-linux-gnu.so"),
+            "",
+        )
+        .unwrap();
+
+        let compiler = BytecodeCompiler::new(root).unwrap();
+        let tree = compiler.discover_tree(root).unwrap();
+
+        let names: Vec<&str> = tree
+            .modules
+            .iter()
+            .map(|m| match m {
+                DiscoveredModule::Source { name, .. } => name.as_str(),
+                DiscoveredModule::Extension { name, .. } => name.as_str(),
+                DiscoveredModule::Namespace { name, .. } => name.as_str(),
+            })
+            .collect();
+
+        assert!(names.contains(&"top"));
+        assert!(names.contains(&"pkg"));
+        assert!(names.contains(&"pkg.mod"));
+        assert!(names.contains(&"ns")); // namespace: no __init__.py of its own
+        assert!(names.contains(&"ns.sub"));
+        assert!(names.contains(&"native.speedups"));
+
+        // "ns.sub" has its own __init__.py, so it's a real package, not a
+        // namespace package - only its parent "ns" should be classified
+        // as one.
+        assert!(tree.modules.iter().any(
+            |m| matches!(m, DiscoveredModule::Source { name, is_package: true, .. } if name == "ns.sub")
+        ));
+        assert!(tree.modules.iter().any(
+            |m| matches!(m, DiscoveredModule::Namespace { name, .. } if name == "ns")
+        ));
+
+        let source_files = tree.source_files();
+        assert!(source_files.iter().any(|p| p.ends_with("top.py")));
+        assert!(source_files
+            .iter()
+            .any(|p| p.ends_with(Path::new("pkg").join("__init__.py"))));
+        assert!(!source_files
+            .iter()
+            .any(|p| p.to_string_lossy().contains("speedups")));
+
+        let registry = ModuleRegistry::new(root.to_path_buf());
+        tree.ingest(&registry);
+
+        assert!(registry.is_package("ns").unwrap());
+        assert!(registry.get_source_path("native.speedups").is_some());
+        // Plain modules are left for compile_batch, not `ingest`.
+        assert!(registry.get_bytecode("top").is_none());
+    }
+
     /// Test batch compilation with empty file list
     #[test]
     fn test_batch_compile_empty() {
@@ -903,4 +2834,324 @@ mod tests {
         // Cache should now be stale
         assert!(compiler.is_cache_stale(&source, &cache));
     }
+
+    /// Test freeze() preserves bytecode contents via the mmap-backed slice
+    #[test]
+    fn test_freeze_preserves_bytecode() {
+        let temp = TempDir::new().unwrap();
+        let registry = ModuleRegistry::new(temp.path().to_path_buf());
+
+        registry.insert(BytecodeEntry {
+            name: "foo".to_string(),
+            source_path: temp.path().join("foo.py"),
+            bytecode: vec![0xe3, 1, 2, 3].into(),
+            is_package: false,
+            is_namespace: false,
+        });
+        registry.insert(BytecodeEntry {
+            name: "bar".to_string(),
+            source_path: temp.path().join("bar.py"),
+            bytecode: vec![0xe3, 4, 5].into(),
+            is_package: false,
+            is_namespace: false,
+        });
+
+        let packed_path = temp.path().join(".tach").join("cache").join("packed.cache");
+        registry.freeze(&packed_path).expect("freeze should succeed");
+
+        assert!(packed_path.exists());
+        assert_eq!(&*registry.get_bytecode("foo").unwrap(), &[0xe3, 1, 2, 3]);
+        assert_eq!(&*registry.get_bytecode("bar").unwrap(), &[0xe3, 4, 5]);
+    }
+
+    /// Test freeze() on an empty registry doesn't error
+    #[test]
+    fn test_freeze_empty_registry() {
+        let temp = TempDir::new().unwrap();
+        let registry = ModuleRegistry::new(temp.path().to_path_buf());
+        let packed_path = temp.path().join("packed.cache");
+
+        assert!(registry.freeze(&packed_path).is_ok());
+        assert!(packed_path.exists());
+    }
+
+    /// Test parallel batch compilation compiles every file and preserves order
+    #[test]
+    fn test_compile_batch_parallel() {
+        let temp = TempDir::new().unwrap();
+
+        let files: Vec<PathBuf> = (0..6)
+            .map(|i| {
+                let path = temp.path().join(format!("par_mod{}.py", i));
+                fs::write(&path, format!("x = {}", i)).unwrap();
+                path
+            })
+            .collect();
+
+        let compiler = BytecodeCompiler::new(temp.path())
+            .unwrap()
+            .with_thread_count(3);
+        let registry = ModuleRegistry::new(temp.path().to_path_buf());
+
+        let count = compiler.compile_batch_parallel(&files, &registry);
+
+        assert_eq!(count, 6);
+        assert_eq!(registry.len(), 6);
+        for i in 0..6 {
+            assert!(registry.get_bytecode(&format!("par_mod{}", i)).is_some());
+        }
+    }
+
+    /// Test parallel batch compilation tolerates a thread count of zero
+    #[test]
+    fn test_compile_batch_parallel_zero_threads_clamped() {
+        let temp = TempDir::new().unwrap();
+        let compiler = BytecodeCompiler::new(temp.path())
+            .unwrap()
+            .with_thread_count(0);
+
+        let source = temp.path().join("clamped.py");
+        fs::write(&source, "x = 1").unwrap();
+        let registry = ModuleRegistry::new(temp.path().to_path_buf());
+
+        let count = compiler.compile_batch_parallel(&[source], &registry);
+        assert_eq!(count, 1);
+    }
+
+    /// Test BytecodeCompiler::freeze_registry writes into the compiler's cache dir
+    #[test]
+    fn test_compiler_freeze_registry() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("frozen_mod.py");
+        fs::write(&source, "x = 1").unwrap();
+
+        let compiler = BytecodeCompiler::new(temp.path()).unwrap();
+        let registry = ModuleRegistry::new(temp.path().to_path_buf());
+        compiler.compile_batch(&[source], &registry);
+
+        let packed_path = compiler.freeze_registry(&registry).unwrap();
+
+        assert!(packed_path.to_string_lossy().contains(".tach"));
+        assert!(registry.get_bytecode("frozen_mod").is_some());
+    }
+
+    /// Test that `pack`/`load_packed` round-trip every entry's bytecode,
+    /// name, and package/namespace flags through a fresh `mmap`
+    #[test]
+    fn test_pack_and_load_packed_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let registry = ModuleRegistry::new(temp.path().to_path_buf());
+        let magic = [1, 2, 3, 4];
+
+        registry.insert(BytecodeEntry {
+            name: "foo".to_string(),
+            source_path: temp.path().join("foo.py"),
+            bytecode: vec![0xe3, 1, 2, 3].into(),
+            is_package: false,
+            is_namespace: false,
+        });
+        registry.insert(BytecodeEntry {
+            name: "pkg".to_string(),
+            source_path: temp.path().join("pkg").join("__init__.py"),
+            bytecode: vec![0xe3, 4, 5].into(),
+            is_package: true,
+            is_namespace: false,
+        });
+
+        let packed_path = temp.path().join(".tach").join("cache").join("registry.pack");
+        registry.pack(&packed_path, magic).unwrap();
+
+        let loaded =
+            ModuleRegistry::load_packed(&packed_path, temp.path().to_path_buf(), magic).unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(&*loaded.get_bytecode("foo").unwrap(), &[0xe3, 1, 2, 3]);
+        assert_eq!(&*loaded.get_bytecode("pkg").unwrap(), &[0xe3, 4, 5]);
+        assert_eq!(loaded.is_package("pkg"), Some(true));
+        assert_eq!(loaded.is_package("foo"), Some(false));
+        assert_eq!(
+            loaded.get_source_path("foo"),
+            Some(temp.path().join("foo.py"))
+        );
+    }
+
+    /// Test that `load_packed` returns an `Err` instead of panicking on a
+    /// blob truncated mid-entry-table - e.g. `pack`'s non-atomic `fs::write`
+    /// cut short by a killed process, which this codebase does often
+    /// (PR_SET_PDEATHSIG, stale-worker timeouts, Ctrl+C).
+    #[test]
+    fn test_load_packed_rejects_truncated_entry_table() {
+        let temp = TempDir::new().unwrap();
+        let registry = ModuleRegistry::new(temp.path().to_path_buf());
+        let magic = [1, 2, 3, 4];
+        registry.insert(BytecodeEntry {
+            name: "foo".to_string(),
+            source_path: temp.path().join("foo.py"),
+            bytecode: vec![0xe3, 1, 2, 3].into(),
+            is_package: false,
+            is_namespace: false,
+        });
+
+        let packed_path = temp.path().join("registry.pack");
+        registry.pack(&packed_path, magic).unwrap();
+
+        let full = fs::read(&packed_path).unwrap();
+        // Cut the file off partway through the entry table (after the
+        // header, before the first entry's name bytes finish).
+        fs::write(&packed_path, &full[..20]).unwrap();
+
+        let result = ModuleRegistry::load_packed(&packed_path, temp.path().to_path_buf(), magic);
+        assert!(result.is_err());
+    }
+
+    /// Test that `load_packed` rejects a blob built for a different Python
+    /// bytecode magic instead of silently trusting stale bytecode
+    #[test]
+    fn test_load_packed_rejects_magic_mismatch() {
+        let temp = TempDir::new().unwrap();
+        let registry = ModuleRegistry::new(temp.path().to_path_buf());
+        registry.insert(BytecodeEntry {
+            name: "foo".to_string(),
+            source_path: temp.path().join("foo.py"),
+            bytecode: vec![0xe3, 1].into(),
+            is_package: false,
+            is_namespace: false,
+        });
+
+        let packed_path = temp.path().join("registry.pack");
+        registry.pack(&packed_path, [1, 2, 3, 4]).unwrap();
+
+        let result =
+            ModuleRegistry::load_packed(&packed_path, temp.path().to_path_buf(), [9, 9, 9, 9]);
+        assert!(result.is_err());
+    }
+
+    /// Test that `BytecodeCompiler::pack_registry`/`load_packed_registry`
+    /// round-trip through `.tach/cache` without recompiling
+    #[test]
+    fn test_compiler_pack_and_load_packed_registry() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("packed_mod.py");
+        fs::write(&source, "x = 1").unwrap();
+
+        let compiler = BytecodeCompiler::new(temp.path()).unwrap();
+        let registry = ModuleRegistry::new(temp.path().to_path_buf());
+        compiler.compile_batch(&[source], &registry);
+
+        let packed_path = compiler.pack_registry(&registry).unwrap();
+        assert!(packed_path.exists());
+
+        let loaded = compiler.load_packed_registry().unwrap();
+        assert!(loaded.get_bytecode("packed_mod").is_some());
+        assert_eq!(
+            &*loaded.get_bytecode("packed_mod").unwrap(),
+            &*registry.get_bytecode("packed_mod").unwrap()
+        );
+    }
+
+    /// Test that `source_hash` is deterministic and sensitive to its inputs
+    #[test]
+    fn test_source_hash_deterministic_and_sensitive() {
+        let magic = [0xaa, 0xbb, 0xcc, 0xdd];
+
+        assert_eq!(source_hash(magic, b"x = 1"), source_hash(magic, b"x = 1"));
+        assert_ne!(source_hash(magic, b"x = 1"), source_hash(magic, b"x = 2"));
+        assert_ne!(
+            source_hash(magic, b"x = 1"),
+            source_hash([0x11, 0x22, 0x33, 0x44], b"x = 1")
+        );
+    }
+
+    /// Test checked-hash invalidation: unchanged source reuses the cache,
+    /// changed source (even with an unchanged mtime-adjacent write) is stale
+    #[test]
+    fn test_cache_staleness_checked_hash() {
+        let temp = TempDir::new().unwrap();
+        let compiler = BytecodeCompiler::new(temp.path())
+            .unwrap()
+            .with_invalidation(CacheInvalidation::Hash { checked: true });
+
+        let source = temp.path().join("hashed.py");
+        fs::write(&source, "x = 1").unwrap();
+
+        let _ = compiler.compile(&source).unwrap();
+        let cache = compiler.cache_path(&source);
+
+        let header = read_pyc_header(&cache).unwrap();
+        assert!(header.is_hash_based());
+
+        // Same contents: cache stays fresh.
+        assert!(!compiler.is_cache_stale(&source, &cache));
+
+        // Changed contents: cache goes stale regardless of mtime ordering.
+        fs::write(&source, "x = 2").unwrap();
+        assert!(compiler.is_cache_stale(&source, &cache));
+
+        let _ = compiler.compile(&source).unwrap();
+        assert!(!compiler.is_cache_stale(&source, &cache));
+    }
+
+    /// Test unchecked-hash invalidation: cache is trusted unconditionally
+    /// once it exists and is hash-based, even after the source changes
+    #[test]
+    fn test_cache_staleness_unchecked_hash() {
+        let temp = TempDir::new().unwrap();
+        let compiler = BytecodeCompiler::new(temp.path())
+            .unwrap()
+            .with_invalidation(CacheInvalidation::Hash { checked: false });
+
+        let source = temp.path().join("unchecked.py");
+        fs::write(&source, "x = 1").unwrap();
+
+        let _ = compiler.compile(&source).unwrap();
+        let cache = compiler.cache_path(&source);
+
+        fs::write(&source, "x = 2").unwrap();
+        assert!(
+            !compiler.is_cache_stale(&source, &cache),
+            "unchecked mode must trust the cache without re-hashing"
+        );
+    }
+
+    /// Test that a submodule under a directory with no `__init__.py` gets a
+    /// synthesized namespace-package parent
+    #[test]
+    fn test_namespace_package_synthesized_for_missing_init() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("ns_pkg").join("leaf.py");
+        fs::create_dir_all(source.parent().unwrap()).unwrap();
+        fs::write(&source, "x = 1").unwrap();
+
+        let compiler = BytecodeCompiler::new(temp.path()).unwrap();
+        let registry = ModuleRegistry::new(temp.path().to_path_buf());
+        compiler.compile_batch(&[source], &registry);
+
+        assert!(registry.get_bytecode("ns_pkg.leaf").is_some());
+        assert_eq!(registry.is_package("ns_pkg"), Some(true));
+        assert_eq!(registry.is_namespace_package("ns_pkg"), Some(true));
+    }
+
+    /// Test that a real `__init__.py` discovered later overwrites a
+    /// previously-synthesized namespace entry under the same key
+    #[test]
+    fn test_real_init_upgrades_namespace_entry() {
+        let temp = TempDir::new().unwrap();
+        let leaf = temp.path().join("pkg").join("leaf.py");
+        fs::create_dir_all(leaf.parent().unwrap()).unwrap();
+        fs::write(&leaf, "x = 1").unwrap();
+
+        let compiler = BytecodeCompiler::new(temp.path()).unwrap();
+        let registry = ModuleRegistry::new(temp.path().to_path_buf());
+        compiler.compile_batch(&[leaf.clone()], &registry);
+        assert_eq!(registry.is_namespace_package("pkg"), Some(true));
+
+        // __init__.py shows up on a later compile pass.
+        let init = temp.path().join("pkg").join("__init__.py");
+        fs::write(&init, "").unwrap();
+        compiler.compile_batch(&[init, leaf], &registry);
+
+        assert_eq!(registry.len(), 2, "real __init__.py should replace, not duplicate, the 'pkg' key");
+        assert_eq!(registry.is_package("pkg"), Some(true));
+        assert_eq!(registry.is_namespace_package("pkg"), Some(false));
+    }
 }
@@ -6,21 +6,59 @@
 //! 3. Workers inherit when Zygote forks them
 //! 4. Worker calls dup2(memfd, STDOUT) to redirect
 //! 5. Supervisor reads from memfd after test completes
+//!
+//! ## Streaming mode
+//!
+//! `new_streaming` swaps the memfd per slot for a pipe, built the same way
+//! (created before the Zygote fork, no CLOEXEC, so it's inherited all the
+//! way down to the worker). The worker side is identical - `redirect_output`
+//! just dup2s whatever fd it's handed. The supervisor side differs: instead
+//! of `read_and_clear` pulling a static buffer after the fact, the pipe's
+//! read end is handed to a `logstream::LogMultiplexer`, which polls every
+//! slot's pipe concurrently and forwards output line-by-line as it's
+//! produced. See `logstream` for that half.
 
 use anyhow::{Context, Result};
 use std::collections::HashMap;
 use std::ffi::CString;
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
-use std::os::unix::io::{FromRawFd, RawFd};
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd, RawFd};
+
+use crate::logstream::LogMultiplexer;
 
 /// Size of each log buffer (1MB per worker slot)
 pub const LOG_BUFFER_SIZE: usize = 1024 * 1024;
 
-/// Manages memory-mapped log buffers for worker output capture
+/// A slot's worker-facing descriptor. `OwnedFd` closes itself on drop, so
+/// there's a single owner of each descriptor and no hand-written `Drop`
+/// close loop to get wrong.
+enum Slot {
+    /// Buffered mode: the single fd is read from directly after a test.
+    Memfd(OwnedFd),
+    /// Streaming mode: this is the pipe's write end; the read end lives in
+    /// a `LogMultiplexer` instead, so `LogCapture` can't read it back.
+    Pipe(OwnedFd),
+}
+
+impl Slot {
+    fn as_raw_fd(&self) -> RawFd {
+        match self {
+            Slot::Memfd(fd) | Slot::Pipe(fd) => fd.as_raw_fd(),
+        }
+    }
+
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        match self {
+            Slot::Memfd(fd) | Slot::Pipe(fd) => fd.as_fd(),
+        }
+    }
+}
+
+/// Manages per-worker-slot output capture, either buffered (memfd) or
+/// streamed (pipe, see `new_streaming`).
 pub struct LogCapture {
-    /// Map of slot_id -> raw fd
-    fds: HashMap<usize, RawFd>,
+    fds: HashMap<usize, Slot>,
     num_slots: usize,
 }
 
@@ -34,12 +72,12 @@ impl LogCapture {
 
             // Resize to buffer size
             unsafe {
-                if libc::ftruncate(fd, LOG_BUFFER_SIZE as i64) != 0 {
+                if libc::ftruncate(fd.as_raw_fd(), LOG_BUFFER_SIZE as i64) != 0 {
                     return Err(anyhow::anyhow!("ftruncate failed for slot {}", slot));
                 }
             }
 
-            fds.insert(slot, fd);
+            fds.insert(slot, Slot::Memfd(fd));
         }
 
         Ok(Self {
@@ -48,9 +86,40 @@ impl LogCapture {
         })
     }
 
-    /// Get the file descriptor for a slot
+    /// Create a log capture system backed by pipes instead of memfds, for
+    /// real-time output. Returns the `LogCapture` (still used for dispatch,
+    /// via `get_fd`/`fd`) alongside the `LogMultiplexer` that owns every
+    /// slot's read end.
+    pub fn new_streaming(max_slots: usize) -> Result<(Self, LogMultiplexer)> {
+        let mut fds = HashMap::new();
+        let mut read_ends = HashMap::new();
+
+        for slot in 0..max_slots {
+            let (read_fd, write_fd) = create_pipe()?;
+            read_ends.insert(slot, read_fd);
+            fds.insert(slot, Slot::Pipe(write_fd));
+        }
+
+        let mux = LogMultiplexer::new(read_ends)?;
+
+        Ok((
+            Self {
+                fds,
+                num_slots: max_slots,
+            },
+            mux,
+        ))
+    }
+
+    /// Get the raw file descriptor for a slot, e.g. to hand off across
+    /// `fork()` in a `TestPayload`. `LogCapture` remains the owner.
     pub fn get_fd(&self, slot: usize) -> Option<RawFd> {
-        self.fds.get(&slot).copied()
+        self.fds.get(&slot).map(|s| s.as_raw_fd())
+    }
+
+    /// Borrow a slot's descriptor without transferring ownership.
+    pub fn fd(&self, slot: usize) -> Option<BorrowedFd<'_>> {
+        self.fds.get(&slot).map(|s| s.as_fd())
     }
 
     /// Get number of slots
@@ -58,30 +127,55 @@ impl LogCapture {
         self.num_slots
     }
 
-    /// Read and clear logs from a slot
+    /// Close every slot's fd except `slot`'s, in the calling process.
+    ///
+    /// `create_memfd`/`create_pipe` deliberately skip `O_CLOEXEC` so a
+    /// slot's fd survives both the Zygote's fork and the worker's fork -
+    /// but that means a worker inherits every *other* slot's fd too, not
+    /// just its own. Call this worker-side right before `redirect_output`
+    /// to apply close-on-fork semantics in software: after it returns, the
+    /// only `LogCapture` fd left open in this process is `slot`'s.
+    ///
+    /// Safe to call with an already-closed or otherwise invalid fd for some
+    /// slot (e.g. a race with another worker closing its own copy) - `close`
+    /// failing there doesn't stop the rest from being sealed.
+    pub fn seal_to_slot(&self, slot: usize) {
+        for (&other_slot, fd) in self.fds.iter() {
+            if other_slot == slot {
+                continue;
+            }
+            unsafe {
+                libc::close(fd.as_raw_fd());
+            }
+        }
+    }
+
+    /// Read and clear logs from a slot. No-op for a streaming (pipe) slot:
+    /// its output already went out through the `LogMultiplexer`, and a pipe
+    /// can't be seeked/truncated the way a memfd can.
     pub fn read_and_clear(&self, slot: usize) -> Result<String> {
-        let fd = *self.fds.get(&slot).context("Invalid slot")?;
+        let fd = match self.fds.get(&slot).context("Invalid slot")? {
+            Slot::Memfd(fd) => fd,
+            Slot::Pipe(_) => return Ok(String::new()),
+        };
 
         // Seek to beginning
         unsafe {
-            libc::lseek(fd, 0, libc::SEEK_SET);
-        }
-
-        // Read content using dup'd fd (to not affect position)
-        let dup_fd = unsafe { libc::dup(fd) };
-        if dup_fd < 0 {
-            return Err(anyhow::anyhow!("dup failed"));
+            libc::lseek(fd.as_raw_fd(), 0, libc::SEEK_SET);
         }
 
-        let mut file = unsafe { File::from_raw_fd(dup_fd) };
+        // Read content using a cloned (dup'd) fd, so `file` closes only its
+        // own clone on drop. The clone shares the slot's file offset, which
+        // the seek above has just reset to the start.
+        let cloned = fd.try_clone().context("Failed to clone log slot fd")?;
+        let mut file = File::from(cloned);
         let mut content = String::new();
         let _ = file.read_to_string(&mut content);
-        // File will close dup_fd on drop, which is fine
 
         // Truncate to clear and reset for next use
         unsafe {
-            libc::ftruncate(fd, 0);
-            libc::ftruncate(fd, LOG_BUFFER_SIZE as i64);
+            libc::ftruncate(fd.as_raw_fd(), 0);
+            libc::ftruncate(fd.as_raw_fd(), LOG_BUFFER_SIZE as i64);
         }
 
         // Trim null bytes and trailing whitespace
@@ -90,18 +184,8 @@ impl LogCapture {
     }
 }
 
-impl Drop for LogCapture {
-    fn drop(&mut self) {
-        for (_, fd) in &self.fds {
-            unsafe {
-                libc::close(*fd);
-            }
-        }
-    }
-}
-
 /// Create an anonymous memory file WITHOUT MFD_CLOEXEC (so it survives fork)
-fn create_memfd(name: &str) -> Result<RawFd> {
+fn create_memfd(name: &str) -> Result<OwnedFd> {
     let c_name = CString::new(name)?;
 
     // NO MFD_CLOEXEC - fd must be inherited by forked children
@@ -113,8 +197,26 @@ fn create_memfd(name: &str) -> Result<RawFd> {
             std::io::Error::last_os_error()
         ))
     } else {
-        Ok(fd)
+        // SAFETY: memfd_create just returned this fd; we're its sole owner.
+        Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+    }
+}
+
+/// Create a pipe WITHOUT O_CLOEXEC on either end (so the write end survives
+/// fork all the way down to the worker, same as `create_memfd`). Returns
+/// `(read_end, write_end)`.
+fn create_pipe() -> Result<(OwnedFd, OwnedFd)> {
+    let mut raw_fds = [0 as RawFd; 2];
+    if unsafe { libc::pipe(raw_fds.as_mut_ptr()) } != 0 {
+        return Err(anyhow::anyhow!(
+            "pipe() failed: {}",
+            std::io::Error::last_os_error()
+        ));
     }
+    // SAFETY: pipe() just returned these fds; we're their sole owner.
+    let read_fd = unsafe { OwnedFd::from_raw_fd(raw_fds[0]) };
+    let write_fd = unsafe { OwnedFd::from_raw_fd(raw_fds[1]) };
+    Ok((read_fd, write_fd))
 }
 
 /// Redirect stdout/stderr to a file descriptor (called in worker after fork)
@@ -0,0 +1,265 @@
+//! Live multiplexed log streaming: a `poll()`-driven generalization of
+//! std's internal `read2` helper (the loop `Command::output` uses to drain
+//! a child's stdout and stderr pipes concurrently without deadlocking on
+//! whichever one fills up first) from two fds to N worker slots.
+//!
+//! `LogCapture`'s default memfd mode only lets the supervisor pull a slot's
+//! buffer after the test finishes. In streaming mode each slot's worker fd
+//! is the write end of a pipe instead, and a `LogMultiplexer` owns every
+//! read end, set non-blocking once in `new`: each `poll()` call waits on
+//! `libc::poll` for whichever are readable, drains available bytes into a
+//! per-slot line buffer, and hands back complete lines as soon as they're
+//! assembled, attributed to the slot that produced them. A line split
+//! across two reads is held in `pending` until the newline shows up; a
+//! slot's pipe EOFs once every process holding its write end has exited,
+//! at which point it's retired and no longer polled.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::os::fd::{AsRawFd, OwnedFd, RawFd};
+
+/// One worker slot's read end and line-buffering state.
+struct SlotState {
+    read_fd: OwnedFd,
+    /// Bytes read so far that don't yet form a complete line.
+    pending: Vec<u8>,
+    /// Every writer closed the pipe; stop polling this slot.
+    retired: bool,
+}
+
+/// Drains every worker slot's log pipe concurrently via `poll()`.
+pub struct LogMultiplexer {
+    slots: HashMap<usize, SlotState>,
+}
+
+impl LogMultiplexer {
+    /// Take ownership of each slot's read end, switching it non-blocking so
+    /// a slow/silent worker can never stall the others.
+    pub fn new(read_ends: HashMap<usize, OwnedFd>) -> Result<Self> {
+        for fd in read_ends.values() {
+            set_nonblocking(fd.as_raw_fd())?;
+        }
+
+        Ok(Self {
+            slots: read_ends
+                .into_iter()
+                .map(|(slot, read_fd)| {
+                    (
+                        slot,
+                        SlotState {
+                            read_fd,
+                            pending: Vec::new(),
+                            retired: false,
+                        },
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    /// True once every slot has retired (all pipes EOFed).
+    pub fn is_drained(&self) -> bool {
+        self.slots.values().all(|s| s.retired)
+    }
+
+    /// Wait up to `timeout_ms` for any live slot to become readable, drain
+    /// what's available, and invoke `on_line(slot, line)` for each complete
+    /// line assembled. Stdout and stderr share one fd per slot (the same
+    /// merge `redirect_output` does for memfd mode), so lines aren't
+    /// distinguished by stream here.
+    pub fn poll(&mut self, timeout_ms: i32, mut on_line: impl FnMut(usize, &str)) -> Result<()> {
+        let mut live: Vec<usize> = self
+            .slots
+            .iter()
+            .filter(|(_, s)| !s.retired)
+            .map(|(&slot, _)| slot)
+            .collect();
+        if live.is_empty() {
+            return Ok(());
+        }
+        live.sort_unstable();
+
+        let mut pollfds: Vec<libc::pollfd> = live
+            .iter()
+            .map(|slot| libc::pollfd {
+                fd: self.slots[slot].read_fd.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            })
+            .collect();
+
+        let ready =
+            unsafe { libc::poll(pollfds.as_mut_ptr(), pollfds.len() as libc::nfds_t, timeout_ms) };
+        if ready < 0 {
+            let err = std::io::Error::last_os_error();
+            if err.kind() == std::io::ErrorKind::Interrupted {
+                return Ok(());
+            }
+            return Err(err.into());
+        }
+
+        let mut buf = [0u8; 8192];
+        for (slot, pfd) in live.iter().zip(pollfds.iter()) {
+            if pfd.revents & (libc::POLLIN | libc::POLLHUP | libc::POLLERR) == 0 {
+                continue;
+            }
+            self.drain_slot(*slot, &mut buf, &mut on_line);
+        }
+
+        Ok(())
+    }
+
+    /// Read everything currently available from one slot without blocking,
+    /// forwarding complete lines and retiring the slot on EOF.
+    fn drain_slot(&mut self, slot: usize, buf: &mut [u8], on_line: &mut impl FnMut(usize, &str)) {
+        loop {
+            let fd = self.slots[&slot].read_fd.as_raw_fd();
+            let n = unsafe { libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+
+            if n > 0 {
+                let state = self.slots.get_mut(&slot).unwrap();
+                state.pending.extend_from_slice(&buf[..n as usize]);
+                flush_complete_lines(state, slot, on_line);
+            } else if n == 0 {
+                let state = self.slots.get_mut(&slot).unwrap();
+                if !state.pending.is_empty() {
+                    let line = String::from_utf8_lossy(&state.pending).into_owned();
+                    on_line(slot, line.trim_end_matches('\r'));
+                    state.pending.clear();
+                }
+                state.retired = true;
+                return;
+            } else {
+                let err = std::io::Error::last_os_error();
+                match err.kind() {
+                    std::io::ErrorKind::WouldBlock => return,
+                    std::io::ErrorKind::Interrupted => continue,
+                    // Treat anything else (e.g. ECONNRESET-equivalent on a
+                    // pipe) as terminal for this slot rather than spinning.
+                    _ => {
+                        self.slots.get_mut(&slot).unwrap().retired = true;
+                        return;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Split off and report every `\n`-terminated line currently buffered.
+fn flush_complete_lines(state: &mut SlotState, slot: usize, on_line: &mut impl FnMut(usize, &str)) {
+    while let Some(pos) = state.pending.iter().position(|&b| b == b'\n') {
+        let line_bytes: Vec<u8> = state.pending.drain(..=pos).collect();
+        let line = String::from_utf8_lossy(&line_bytes[..line_bytes.len() - 1]);
+        on_line(slot, line.trim_end_matches('\r'));
+    }
+}
+
+fn set_nonblocking(fd: RawFd) -> Result<()> {
+    unsafe {
+        let flags = libc::fcntl(fd, libc::F_GETFL, 0);
+        if flags < 0 {
+            return Err(anyhow::anyhow!(
+                "fcntl(F_GETFL) failed: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        if libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) < 0 {
+            return Err(anyhow::anyhow!(
+                "fcntl(F_SETFL) failed: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::fd::FromRawFd;
+
+    fn make_pipe() -> (OwnedFd, OwnedFd) {
+        let mut fds = [0i32; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) }
+    }
+
+    #[test]
+    fn test_forwards_complete_lines() {
+        let (read_fd, write_fd) = make_pipe();
+        let mut mux = LogMultiplexer::new(HashMap::from([(0, read_fd)])).unwrap();
+
+        unsafe {
+            libc::write(
+                write_fd.as_raw_fd(),
+                b"hello\nworld\n".as_ptr() as *const libc::c_void,
+                12,
+            );
+        }
+
+        let mut lines = Vec::new();
+        mux.poll(100, |slot, line| lines.push((slot, line.to_string())))
+            .unwrap();
+
+        assert_eq!(lines, vec![(0, "hello".to_string()), (0, "world".to_string())]);
+    }
+
+    #[test]
+    fn test_holds_partial_line_until_newline_arrives() {
+        let (read_fd, write_fd) = make_pipe();
+        let mut mux = LogMultiplexer::new(HashMap::from([(0, read_fd)])).unwrap();
+
+        unsafe {
+            libc::write(
+                write_fd.as_raw_fd(),
+                b"partial".as_ptr() as *const libc::c_void,
+                7,
+            );
+        }
+        let mut lines = Vec::new();
+        mux.poll(100, |slot, line| lines.push((slot, line.to_string())))
+            .unwrap();
+        assert!(lines.is_empty());
+
+        unsafe {
+            libc::write(
+                write_fd.as_raw_fd(),
+                b" done\n".as_ptr() as *const libc::c_void,
+                6,
+            );
+        }
+        mux.poll(100, |slot, line| lines.push((slot, line.to_string())))
+            .unwrap();
+        assert_eq!(lines, vec![(0, "partial done".to_string())]);
+    }
+
+    #[test]
+    fn test_eof_retires_slot_and_flushes_trailing_partial_line() {
+        let (read_fd, write_fd) = make_pipe();
+        let mut mux = LogMultiplexer::new(HashMap::from([(0, read_fd)])).unwrap();
+
+        unsafe {
+            libc::write(
+                write_fd.as_raw_fd(),
+                b"no newline".as_ptr() as *const libc::c_void,
+                10,
+            );
+        }
+        drop(write_fd);
+
+        let mut lines = Vec::new();
+        mux.poll(100, |slot, line| lines.push((slot, line.to_string())))
+            .unwrap();
+
+        assert_eq!(lines, vec![(0, "no newline".to_string())]);
+        assert!(mux.is_drained());
+    }
+
+    #[test]
+    fn test_is_drained_false_while_slots_live() {
+        let (read_fd, _write_fd) = make_pipe();
+        let mux = LogMultiplexer::new(HashMap::from([(0, read_fd)])).unwrap();
+        assert!(!mux.is_drained());
+    }
+}
@@ -1,12 +1,17 @@
-use tach_core::config::{self, Cli, Commands, OutputFormat};
+use tach_core::config::{self, Cli, Commands, OutputFormat, ReporterKind};
 use tach_core::debugger::{self, DebugServer};
 use tach_core::discovery;
+use tach_core::importgraph::ImportGraph;
+use tach_core::incremental::IncrementalState;
+use tach_core::jobserver::{JobserverClient, JobserverServer};
 use tach_core::junit::JunitReporter;
 use tach_core::lifecycle::CleanupGuard;
 use tach_core::logcapture::LogCapture;
-use tach_core::reporter::{HumanReporter, JsonReporter, MultiReporter, Reporter};
+use tach_core::provenance::ProvenanceCache;
+use tach_core::reporter::{self, HumanReporter, JsonReporter, MultiReporter, Reporter};
 use tach_core::resolver::{self, FixtureRegistry, Resolver};
 use tach_core::scheduler::Scheduler;
+use tach_core::selection;
 use tach_core::signals;
 use tach_core::watch;
 use tach_core::zygote;
@@ -95,14 +100,39 @@ fn main() -> Result<()> {
 
     // Parse CLI arguments FIRST
     let cli = Cli::parse();
-    let is_json = cli.format == OutputFormat::Json;
     let is_watch = cli.watch;
+    let cwd = std::env::current_dir()?;
+
+    // --- MERGE [tool.tach] DEFAULTS FROM pyproject.toml ---
+    // Precedence: explicit CLI flag > pyproject.toml > built-in default.
+    let tach_config = config::load_tach_config(&cwd);
+    let format = cli.format.clone().or_else(|| tach_config.format.clone()).unwrap_or_default();
+    let junit_xml = cli.junit_xml.clone().or_else(|| tach_config.junit_xml.clone());
+    let coverage_dir = cli.coverage.clone();
+    let no_isolation = cli.no_isolation || tach_config.no_isolation.unwrap_or(false);
+    let workers = cli.workers.or(tach_config.workers);
+    let includes = if cli.include.is_empty() {
+        tach_config.include.clone().unwrap_or_default()
+    } else {
+        cli.include.clone()
+    };
+    let ignores = if cli.ignore.is_empty() {
+        tach_config.exclude.clone().unwrap_or_default()
+    } else {
+        cli.ignore.clone()
+    };
+    let path_patterns = resolver::compile_path_patterns(&includes, &ignores)?;
+
+    let is_json = format == OutputFormat::Json;
 
-    // Set TACH_NO_ISOLATION env var from CLI flag (inherits to all children)
-    if cli.no_isolation {
+    // Set TACH_NO_ISOLATION env var from the merged flag (inherits to all children)
+    if no_isolation {
         std::env::set_var("TACH_NO_ISOLATION", "1");
     }
-    
+    if let Some(workers) = workers {
+        std::env::set_var("TACH_WORKERS", workers.to_string());
+    }
+
     // Set TACH_TARGET_PATH for Zygote to know which path to collect tests from
     std::env::set_var("TACH_TARGET_PATH", &cli.path);
 
@@ -118,11 +148,17 @@ fn main() -> Result<()> {
         }
     }
 
-    let cwd = std::env::current_dir()?;
-
     // Handle `list` subcommand (no watch mode)
     if let Some(Commands::List) = cli.command {
-        return handle_list_command(&cwd, is_json);
+        return handle_list_command(
+            &cwd,
+            is_json,
+            cli.doctest,
+            cli.filter.as_deref(),
+            cli.markers.as_deref(),
+            cli.shuffle,
+            cli.seed,
+        );
     }
 
     // --- WATCH MODE ---
@@ -132,38 +168,109 @@ fn main() -> Result<()> {
         }
 
         // Clone config values for the closure
-        let junit_path = cli.junit_xml.clone();
-        let format = cli.format.clone();
+        let junit_path = junit_xml.clone();
         let cwd_clone = cwd.clone();
         let path_clone = cli.path.clone();
 
-        return watch::start_watch_loop(&cwd, move || {
-            execute_session(&cwd_clone, &format, &junit_path, &path_clone)
+        let shuffle = cli.shuffle;
+        let seed = cli.seed;
+        let stream_logs = cli.stream_logs;
+        let fail_fast = cli.fail_fast;
+        let filter = resolver::compile_test_filter(cli.filter.as_deref(), cli.filter_regex.as_deref())?;
+        let path_patterns_clone = path_patterns.clone();
+        let reporter_kind = cli.reporter.clone();
+        let coverage_dir_clone = coverage_dir.clone();
+        // Seeded once up front and carried across every iteration of the
+        // watch loop (unlike the rest of the session, which `execute_session`
+        // rebuilds from scratch on each run - forking a fresh Zygote so
+        // workers never execute stale bytecode left over from before the
+        // edit). A change event's rescan only re-reads and re-parses the
+        // file(s) whose content hash actually changed, rather than
+        // re-walking and re-parsing the whole project tree every time.
+        let mut incremental_state = Some(IncrementalState::new(discovery::discover(&cwd)?));
+        return watch::start_watch_loop(&cwd, move |changed_paths| {
+            execute_session(
+                &cwd_clone,
+                &format,
+                &junit_path,
+                &path_clone,
+                shuffle,
+                seed,
+                stream_logs,
+                filter.clone(),
+                fail_fast,
+                &path_patterns_clone,
+                &reporter_kind,
+                changed_paths,
+                &mut incremental_state,
+                &coverage_dir_clone,
+            )
         });
     }
 
     // --- SINGLE RUN MODE ---
-    execute_session(&cwd, &cli.format, &cli.junit_xml, &cli.path)
+    let filter = resolver::compile_test_filter(cli.filter.as_deref(), cli.filter_regex.as_deref())?;
+    execute_session(
+        &cwd,
+        &format,
+        &junit_xml,
+        &cli.path,
+        cli.shuffle,
+        cli.seed,
+        cli.stream_logs,
+        filter,
+        cli.fail_fast,
+        &path_patterns,
+        &cli.reporter,
+        &[],
+        &mut None,
+        &coverage_dir,
+    )
 }
 
 /// Execute a complete test session (discovery → resolution → zygote → run)
 /// This is the reusable function that watch mode calls repeatedly.
+#[allow(clippy::too_many_arguments)]
 fn execute_session(
     cwd: &PathBuf,
     format: &OutputFormat,
     junit_path: &Option<PathBuf>,
     target_path: &str,
+    shuffle: bool,
+    seed: Option<u64>,
+    stream_logs: bool,
+    filter: Option<resolver::TestFilter>,
+    fail_fast: Option<usize>,
+    path_patterns: &resolver::PathPatternSet,
+    reporter_kind: &ReporterKind,
+    changed_paths: &[PathBuf],
+    incremental_state: &mut Option<IncrementalState>,
+    coverage_dir: &Option<PathBuf>,
 ) -> Result<()> {
     let is_json = *format == OutputFormat::Json;
 
-    // Create reporters
+    // Create reporters. --format picks the machine-readable side (always
+    // NDJSON for Json); --reporter picks the human-facing style underneath
+    // Human. The one --reporter value honoured under Json too is `junit`,
+    // which writes `junit.xml` when no --junit-xml path is given.
     let mut reporters: Vec<Box<dyn Reporter>> = Vec::new();
     match format {
         OutputFormat::Json => reporters.push(Box::new(JsonReporter)),
-        OutputFormat::Human => reporters.push(Box::new(HumanReporter)),
+        OutputFormat::Human => match reporter_kind {
+            ReporterKind::Pretty => reporters.push(Box::new(HumanReporter::new())),
+            ReporterKind::Dot => reporters.push(Box::new(reporter::DotReporter::new())),
+            ReporterKind::Tap => reporters.push(Box::new(reporter::TapReporter::new())),
+            ReporterKind::Ndjson => reporters.push(Box::new(JsonReporter)),
+            ReporterKind::Junit => {}
+        },
     }
     if let Some(path) = junit_path {
-        reporters.push(Box::new(JunitReporter::new(path.clone())));
+        reporters.push(Box::new(JunitReporter::new(path.clone(), cwd.clone())));
+    } else if *reporter_kind == ReporterKind::Junit {
+        reporters.push(Box::new(JunitReporter::new(
+            PathBuf::from("junit.xml"),
+            cwd.clone(),
+        )));
     }
     let mut reporter = MultiReporter::new(reporters);
 
@@ -175,7 +282,19 @@ fn execute_session(
     }
 
     let start = std::time::Instant::now();
-    let discovery_result = discovery::discover(cwd)?;
+    // In watch mode, `incremental_state` survives across iterations: a
+    // rescan only re-reads and re-parses files whose content hash actually
+    // changed since the last iteration (see `IncrementalState::rescan`)
+    // instead of walking and parsing the whole project tree again. The
+    // single-run path always passes `None` here, so it's unaffected.
+    let discovery_result = match incremental_state {
+        Some(state) => {
+            state.rescan(cwd)?;
+            state.current_result()
+        }
+        // Single-run mode: always `None`, so this is a plain one-off walk.
+        None => discovery::discover(cwd)?,
+    };
 
     if !is_json {
         eprintln!(
@@ -200,29 +319,53 @@ fn execute_session(
 
         for error in &errors {
             match error {
-                resolver::ResolutionError::MissingFixture { test, fixture } => {
-                    eprintln!("  ⚠ {} - missing: {}", test, fixture);
+                resolver::ResolutionError::MissingFixture { fixture, suggestions, path, .. } => {
+                    if suggestions.is_empty() {
+                        eprintln!("  ⚠ {} - missing: {}", path.join(" -> "), fixture);
+                    } else {
+                        eprintln!(
+                            "  ⚠ {} - missing: {} (did you mean: {}?)",
+                            path.join(" -> "), fixture, suggestions.join(", ")
+                        );
+                    }
                 }
-                resolver::ResolutionError::CyclicDependency { test, cycle } => {
-                    eprintln!("  ⚠ {} - cycle: {:?}", test, cycle);
+                resolver::ResolutionError::CyclicDependency { path, .. } => {
+                    eprintln!("  ⚠ cycle: {}", path.join(" -> "));
+                }
+            }
+        }
+
+        for warning in registry.warnings() {
+            match warning {
+                resolver::ResolutionWarning::ShadowedFixture { name, shadowing_file, shadowed_file } => {
+                    eprintln!(
+                        "  ⚠ {} in {} shadows conftest fixture from {} with a different scope/dependencies",
+                        name, shadowing_file.display(), shadowed_file.display()
+                    );
                 }
             }
         }
     }
 
     // --- PHASE 8.3: PATH FILTERING ---
-    // Filter tests to only include those matching the target path
-    let target = std::path::Path::new(target_path);
+    // Filter tests to only include those matching the target path.
+    // Supports Deno-style `path::pattern` syntax, e.g. `tests/foo.py::test_bar`,
+    // which narrows the path match and implies an extra --filter substring.
+    let (path_part, inline_filter) = match target_path.split_once("::") {
+        Some((path, pattern)) => (path, Some(resolver::TestFilter::Substring(pattern.to_string()))),
+        None => (target_path, None),
+    };
+    let target = std::path::Path::new(path_part);
     let target_canonical = target.canonicalize().unwrap_or_else(|_| target.to_path_buf());
-    
-    let filtered_tests: Vec<resolver::RunnableTest> = runnable_tests
+
+    let mut filtered_tests: Vec<resolver::RunnableTest> = runnable_tests
         .into_iter()
         .filter(|test| {
             let test_path = std::path::Path::new(&test.file_path);
             let test_canonical = test_path.canonicalize().unwrap_or_else(|_| test_path.to_path_buf());
-            
+
             // Match if test is under target directory OR matches exactly
-            test_canonical.starts_with(&target_canonical) || 
+            test_canonical.starts_with(&target_canonical) ||
             test_canonical == target_canonical ||
             // Handle relative path matching
             test_path.starts_with(target)
@@ -230,10 +373,110 @@ fn execute_session(
         .collect();
 
     if !is_json {
-        eprintln!("[supervisor] Selected {} tests to run (filtered by path: {})", 
+        eprintln!("[supervisor] Selected {} tests to run (filtered by path: {})",
             filtered_tests.len(), target_path);
     }
 
+    // --- GLOB FILTERING (--include / --ignore) ---
+    let deselected_by_glob = resolver::filter_tests_by_path(&mut filtered_tests, path_patterns);
+    if !is_json && deselected_by_glob > 0 {
+        eprintln!("[supervisor] Deselected {} tests not matching --include/--ignore", deselected_by_glob);
+    }
+
+    // --- NAME/PATTERN FILTERING (--filter / --filter-regex / inline path::pattern) ---
+    let mut deselected_by_name = resolver::filter_tests(&mut filtered_tests, &inline_filter);
+    deselected_by_name += resolver::filter_tests(&mut filtered_tests, &filter);
+    if !is_json && deselected_by_name > 0 {
+        eprintln!("[supervisor] Deselected {} tests not matching --filter", deselected_by_name);
+    }
+
+    // --- SHUFFLE PHASE ---
+    // Run last, on the final selection, so a replayed `--seed` reproduces the
+    // exact order of the tests that actually ran (not of tests later dropped
+    // by path/name filtering). `--seed` implies `--shuffle`.
+    let used_seed = if shuffle || seed.is_some() {
+        let used_seed = resolver::shuffle_tests(&mut filtered_tests, seed);
+        if !is_json {
+            eprintln!("[supervisor] Shuffled with seed: {}", used_seed);
+        }
+        Some(used_seed)
+    } else {
+        None
+    };
+
+    // --- WATCH MODE CHANGE IMPACT ---
+    // In watch mode, only re-run tests touched by the files that just
+    // changed: own file, a resolved fixture's source file, or a file
+    // transitively imported per a fresh `ImportGraph` built over the project
+    // tree (skipped - `ImportGraph::empty()` - on the no-op `changed_paths`
+    // case, i.e. every single-run invocation plus a watch-mode forced
+    // rerun). Falls back to the full `filtered_tests` selection when a
+    // changed file isn't traceable to any test, fixture, or import edge.
+    let import_graph = if changed_paths.is_empty() {
+        ImportGraph::empty()
+    } else {
+        ImportGraph::build(cwd)
+    };
+    let total_before_impact = filtered_tests.len();
+    let filtered_tests = match resolver::affected_by_changes(&filtered_tests, changed_paths, &import_graph) {
+        resolver::ChangeImpact::Affected(mut affected) => {
+            // Also catch dependencies neither a changed test file nor the
+            // static import graph can see - a data file loaded via plain
+            // `open()`, a config file, anything a previous run actually
+            // touched (see `provenance::ProvenanceCache`, fed from each
+            // worker's `FileOpenTracker` capture).
+            if !changed_paths.is_empty() {
+                let provenance = ProvenanceCache::load(cwd);
+                let changed_set: std::collections::HashSet<PathBuf> =
+                    changed_paths.iter().cloned().collect();
+                let mut sources: std::collections::HashMap<String, String> =
+                    std::collections::HashMap::new();
+                let mut file_cache: std::collections::HashMap<PathBuf, String> =
+                    std::collections::HashMap::new();
+                for test in &filtered_tests {
+                    let source = file_cache
+                        .entry(test.file_path.clone())
+                        .or_insert_with(|| std::fs::read_to_string(&test.file_path).unwrap_or_default())
+                        .clone();
+                    sources.insert(test.qualified_id(), source);
+                }
+                let dirty = provenance.dirty_tests(&sources, &changed_set);
+                if !dirty.is_empty() {
+                    let already: std::collections::HashSet<String> =
+                        affected.iter().map(|t| t.qualified_id()).collect();
+                    let extra: Vec<resolver::RunnableTest> = filtered_tests
+                        .iter()
+                        .filter(|t| dirty.contains(&t.qualified_id()) && !already.contains(&t.qualified_id()))
+                        .cloned()
+                        .collect();
+                    if !is_json && !extra.is_empty() {
+                        eprintln!(
+                            "[supervisor] +{} test(s) marked dirty by previously observed file reads",
+                            extra.len()
+                        );
+                    }
+                    affected.extend(extra);
+                }
+            }
+
+            if !is_json && !changed_paths.is_empty() {
+                eprintln!(
+                    "[supervisor] Re-running {} of {} test(s) affected by {} changed file(s)",
+                    affected.len(),
+                    total_before_impact,
+                    changed_paths.len()
+                );
+            }
+            affected
+        }
+        resolver::ChangeImpact::FullRun => {
+            if !is_json {
+                eprintln!("[supervisor] Change has unknown dependents, running full suite");
+            }
+            filtered_tests
+        }
+    };
+
     if filtered_tests.is_empty() {
         if !is_json {
             eprintln!("[supervisor] No tests found matching path: {}", target_path);
@@ -242,45 +485,135 @@ fn execute_session(
     }
 
     // --- RUN TESTS ---
-    run_tests(&cleanup, filtered_tests, &mut reporter, is_json)
+    run_tests(
+        &cleanup,
+        filtered_tests,
+        &mut reporter,
+        is_json,
+        used_seed,
+        stream_logs,
+        fail_fast,
+        coverage_dir,
+    )
 }
 
 /// Handle the `list` subcommand
-fn handle_list_command(cwd: &PathBuf, is_json: bool) -> Result<()> {
-    let discovery_result = discovery::discover(cwd)?;
+#[allow(clippy::too_many_arguments)]
+fn handle_list_command(
+    cwd: &PathBuf,
+    is_json: bool,
+    include_doctests: bool,
+    keyword_expr: Option<&str>,
+    marker_expr: Option<&str>,
+    shuffle: bool,
+    seed: Option<u64>,
+) -> Result<()> {
+    let mut discovery_result = discovery::discover_with_options(cwd, include_doctests)?;
+
+    if let Some(expr) = keyword_expr {
+        let parsed = selection::parse_selection(expr)?;
+        discovery_result = discovery_result.filter_by_keyword(&parsed);
+    }
+    if let Some(expr) = marker_expr {
+        let parsed = selection::parse_selection(expr)?;
+        discovery_result = discovery_result.filter_by_markers(&parsed);
+    }
 
     if is_json {
         discovery::dump_json(&discovery_result)?;
     } else {
-        for module in &discovery_result.modules {
-            for test in &module.tests {
-                eprintln!("{}::{}", module.path.display(), test.name);
-            }
+        let mut ids = discovery::flatten_node_ids(&discovery_result);
+        // Preview the order `tach test --shuffle` would run in, without
+        // actually executing anything. `--seed` implies `--shuffle`, same
+        // as the run path.
+        if shuffle || seed.is_some() {
+            let used_seed = resolver::shuffle_seeded(&mut ids, seed);
+            eprintln!("[tach] Shuffled with seed: {}", used_seed);
+        }
+        for id in &ids {
+            eprintln!("{}", id);
         }
     }
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run_tests(
     cleanup: &CleanupGuard,
     runnable_tests: Vec<resolver::RunnableTest>,
     reporter: &mut dyn Reporter,
     is_json: bool,
+    seed: Option<u64>,
+    stream_logs: bool,
+    fail_fast: Option<usize>,
+    coverage_dir: &Option<PathBuf>,
 ) -> Result<()> {
     let cwd = std::env::current_dir()?;
 
+    // Inherited by the Zygote (and thence every worker fork) so a real
+    // `tach_harness` can tell whether to install its `sys.settrace` hook
+    // without threading the flag through the protocol itself.
+    if coverage_dir.is_some() {
+        std::env::set_var("TACH_COVERAGE", "1");
+    }
+
     // --- CREATE DEBUG SERVER ---
     let debug_server = DebugServer::new()?;
     let debug_socket_path = debug_server.socket_path().to_path_buf();
     cleanup.track_socket(debug_socket_path.clone());
+    cleanup.track_socket(debug_server.ctl_socket_path().to_path_buf());
 
     // --- CREATE LOG CAPTURE ---
-    let max_workers = num_cpus::get().min(runnable_tests.len()).max(1);
-    let log_capture = LogCapture::new(max_workers)?;
-
-    if !is_json {
-        eprintln!("[supervisor] Created {} log buffers (memfd)", max_workers);
-    }
+    let worker_cap = std::env::var("TACH_WORKERS")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or_else(num_cpus::get);
+    let max_workers = worker_cap.min(runnable_tests.len()).max(1);
+    let (log_capture, log_mux) = if stream_logs {
+        let (log_capture, mux) = LogCapture::new_streaming(max_workers)?;
+        if !is_json {
+            eprintln!(
+                "[supervisor] Created {} log pipes (streaming)",
+                max_workers
+            );
+        }
+        (log_capture, Some(mux))
+    } else {
+        let log_capture = LogCapture::new(max_workers)?;
+        if !is_json {
+            eprintln!("[supervisor] Created {} log buffers (memfd)", max_workers);
+        }
+        (log_capture, None)
+    };
+
+    // --- JOBSERVER ---
+    // Coordinate parallelism with an enclosing `make -jN`: if one advertised
+    // a jobserver via MAKEFLAGS, throttle against it. Otherwise tach is the
+    // top of the tree, so start its own and export it for any jobserver-aware
+    // subprocesses it launches.
+    let jobserver_client = JobserverClient::from_env();
+    let _jobserver_server = if jobserver_client.is_none() {
+        match JobserverServer::start(max_workers.saturating_sub(1)) {
+            Ok(server) => {
+                std::env::set_var(
+                    "MAKEFLAGS",
+                    format!("--jobserver-auth={} -j{}", server.auth_string(), max_workers),
+                );
+                Some(server)
+            }
+            Err(e) => {
+                if !is_json {
+                    eprintln!("[supervisor] Failed to start jobserver: {}", e);
+                }
+                None
+            }
+        }
+    } else {
+        if !is_json {
+            eprintln!("[supervisor] Joining jobserver from MAKEFLAGS");
+        }
+        None
+    };
 
     // --- SOCKET PAIRS ---
     let (sup_cmd_sock, zyg_cmd_sock) = UnixStream::pair()?;
@@ -312,11 +645,15 @@ fn run_tests(
             drop(sup_cmd_sock);
             drop(sup_result_sock);
             std::mem::forget(debug_server);
-            std::mem::forget(log_capture);
+            // log_capture is moved into the Zygote below, not forgotten: it
+            // needs a live `LogCapture` to seal each worker down to its own
+            // slot (see `LogCapture::seal_to_slot`).
+            std::mem::forget(log_mux);
             std::mem::forget(run_context); // Don't cleanup in child
             std::mem::forget(unsafe { std::ptr::read(cleanup) });
+            std::mem::forget(_jobserver_server); // Parent owns the jobserver fds
 
-            if let Err(e) = zygote::entrypoint(zyg_cmd_sock, zyg_result_sock) {
+            if let Err(e) = zygote::entrypoint(zyg_cmd_sock, zyg_result_sock, log_capture) {
                 eprintln!("[zygote] Error: {:?}", e);
                 std::process::exit(1);
             }
@@ -342,14 +679,31 @@ fn run_tests(
             }
 
             // --- SCHEDULER PHASE ---
-            let mut scheduler = Scheduler::new(
+            let mut scheduler = Scheduler::with_log_mux(
                 sup_cmd_sock,
                 sup_result_sock,
                 log_capture,
+                log_mux,
                 debug_socket_path,
             )?;
+            if let Some(jobserver_client) = jobserver_client {
+                scheduler = scheduler.with_jobserver(jobserver_client);
+            }
+            if coverage_dir.is_some() {
+                scheduler = scheduler.with_coverage();
+            }
+            scheduler = scheduler.with_provenance(&cwd);
+            scheduler = scheduler.with_failure_snapshots(&cwd);
+
+            scheduler.run(runnable_tests, reporter, seed, fail_fast)?;
+            scheduler.write_provenance()?;
 
-            scheduler.run(runnable_tests, reporter)?;
+            if let Some(dir) = coverage_dir {
+                scheduler.write_coverage(dir)?;
+                if !is_json {
+                    eprintln!("[supervisor] Wrote coverage report to {}", dir.join("lcov.info").display());
+                }
+            }
 
             // Shutdown
             scheduler.shutdown()?;
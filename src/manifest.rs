@@ -0,0 +1,462 @@
+//! Manifest-Driven Batch Compilation
+//!
+//! Parses a line-oriented, INI-like manifest describing which source files
+//! `BytecodeCompiler::compile_from_manifest` should precompile, instead of
+//! requiring callers to hand-list every path to `compile_batch`.
+//!
+//! ## Format
+//!
+//! ```ini
+//! ; comments start with ';' or '#'
+//! [roots]
+//! src/**/*.py
+//! ; continuation: a leading-whitespace line adds another glob to `tests`
+//! tests = tests/**/*.py
+//!     more_tests/**/*.py
+//!
+//! [exclude]
+//! **/fixtures/**/*.py
+//!
+//! %include shared/base.manifest
+//! %unset src/legacy/**/*.py
+//! ```
+//!
+//! `%include <path>` splices another manifest file in place, resolved
+//! relative to the *including* file, with cycle detection. `%unset <glob>`
+//! drops previously-matched files so a later included fragment can veto an
+//! earlier root. `[roots]`/`[exclude]` globs are resolved relative to the
+//! top-level manifest's directory.
+
+use anyhow::{anyhow, Result};
+use glob::{glob_with, MatchOptions, Pattern};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An `%unset` directive's effect, for diagnostics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnsetDiagnostic {
+    pub pattern: String,
+    pub removed: Vec<PathBuf>,
+}
+
+/// The resolved output of a manifest: the final ordered file list plus a
+/// record of every override that happened along the way.
+#[derive(Debug, Clone, Default)]
+pub struct ManifestResult {
+    pub files: Vec<PathBuf>,
+    pub unset: Vec<UnsetDiagnostic>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Section {
+    None,
+    Roots,
+    Exclude,
+}
+
+struct ManifestParser {
+    /// Directory `[roots]`/`[exclude]` globs are resolved against.
+    base_dir: PathBuf,
+    files: Vec<PathBuf>,
+    seen: HashSet<PathBuf>,
+    exclude_patterns: Vec<Pattern>,
+    unset: Vec<UnsetDiagnostic>,
+    /// Canonicalized paths of manifests currently being included, for cycle detection.
+    include_stack: Vec<PathBuf>,
+}
+
+impl ManifestParser {
+    fn new(base_dir: PathBuf) -> Self {
+        Self {
+            base_dir,
+            files: Vec::new(),
+            seen: HashSet::new(),
+            exclude_patterns: Vec::new(),
+            unset: Vec::new(),
+            include_stack: Vec::new(),
+        }
+    }
+
+    fn process_file(&mut self, path: &Path) -> Result<()> {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if self.include_stack.contains(&canonical) {
+            return Err(anyhow!(
+                "manifest include cycle detected: {} includes itself transitively",
+                path.display()
+            ));
+        }
+        self.include_stack.push(canonical);
+
+        let source = fs::read_to_string(path)
+            .map_err(|e| anyhow!("failed to read manifest {}: {}", path.display(), e))?;
+        let file_dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+
+        let mut section = Section::None;
+        let mut pending: Option<String> = None;
+
+        for raw_line in source.lines() {
+            let trimmed = raw_line.trim();
+            if trimmed.is_empty() || trimmed.starts_with(';') || trimmed.starts_with('#') {
+                self.flush_line(&mut section, &file_dir, pending.take())?;
+                continue;
+            }
+
+            let is_continuation = raw_line.starts_with(' ') || raw_line.starts_with('\t');
+            if is_continuation && pending.is_some() {
+                let buf = pending.as_mut().unwrap();
+                buf.push('\n');
+                buf.push_str(trimmed);
+            } else {
+                self.flush_line(&mut section, &file_dir, pending.take())?;
+                pending = Some(trimmed.to_string());
+            }
+        }
+        self.flush_line(&mut section, &file_dir, pending.take())?;
+
+        self.include_stack.pop();
+        Ok(())
+    }
+
+    fn flush_line(
+        &mut self,
+        section: &mut Section,
+        file_dir: &Path,
+        line: Option<String>,
+    ) -> Result<()> {
+        let Some(line) = line else { return Ok(()) };
+        let line = line.trim();
+        if line.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(rest) = line.strip_prefix("%include") {
+            let target = rest.trim();
+            let include_path = resolve_path(file_dir, target);
+            return self.process_file(&include_path);
+        }
+
+        if let Some(rest) = line.strip_prefix("%unset") {
+            let pattern_str = rest.trim();
+            self.apply_unset(pattern_str);
+            return Ok(());
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            *section = match line[1..line.len() - 1].trim().to_lowercase().as_str() {
+                "roots" => Section::Roots,
+                "exclude" => Section::Exclude,
+                _ => Section::None,
+            };
+            return Ok(());
+        }
+
+        // `key = value` or a bare value line; each continuation line joined
+        // onto it is one more value.
+        let value = match line.split_once('=') {
+            Some((_, value)) => value.trim(),
+            None => line,
+        };
+
+        for value in value.lines().map(str::trim).filter(|v| !v.is_empty()) {
+            match section {
+                Section::Roots => self.add_root(value)?,
+                Section::Exclude => {
+                    let anchored = self.base_dir.join(value);
+                    self.exclude_patterns.push(compile_pattern(&anchored.to_string_lossy())?);
+                }
+                Section::None => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn add_root(&mut self, pattern: &str) -> Result<()> {
+        let anchored = self.base_dir.join(pattern);
+        let options = MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: false,
+            require_literal_leading_dot: false,
+        };
+        let matches = glob_with(&anchored.to_string_lossy(), options)
+            .map_err(|e| anyhow!("invalid glob '{}': {}", pattern, e))?;
+
+        for entry in matches {
+            let path = entry.map_err(|e| anyhow!("glob error for '{}': {}", pattern, e))?;
+            if path.extension().map_or(true, |e| e != "py") || !path.is_file() {
+                continue;
+            }
+            // Excludes aren't filtered here: a manifest may list `[exclude]`
+            // after `[roots]` (the order shown in this module's own doc
+            // example), and an earlier fragment's root shouldn't dodge a
+            // later fragment's exclude just because it was matched first.
+            // `apply_excludes` sweeps the complete file list once parsing
+            // has finished instead.
+            if self.seen.insert(path.clone()) {
+                self.files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop every file matching any `[exclude]` pattern seen anywhere in the
+    /// manifest (including `%include`d fragments). Run once, after parsing
+    /// has fully finished, so exclusion doesn't depend on whether `[roots]`
+    /// or `[exclude]` came first.
+    fn apply_excludes(&mut self) {
+        let patterns = &self.exclude_patterns;
+        self.files.retain(|f| !patterns.iter().any(|p| p.matches_path(f)));
+    }
+
+    fn apply_unset(&mut self, pattern_str: &str) {
+        let anchored = self.base_dir.join(pattern_str);
+        let Ok(pattern) = compile_pattern(&anchored.to_string_lossy()) else {
+            return;
+        };
+        let mut removed = Vec::new();
+        self.files.retain(|f| {
+            if pattern.matches_path(f) {
+                removed.push(f.clone());
+                self.seen.remove(f);
+                false
+            } else {
+                true
+            }
+        });
+        self.unset.push(UnsetDiagnostic {
+            pattern: pattern_str.to_string(),
+            removed,
+        });
+    }
+}
+
+fn compile_pattern(pattern: &str) -> Result<Pattern> {
+    Pattern::new(pattern).map_err(|e| anyhow!("invalid glob '{}': {}", pattern, e))
+}
+
+fn resolve_path(base: &Path, target: &str) -> PathBuf {
+    let target_path = Path::new(target);
+    if target_path.is_absolute() {
+        target_path.to_path_buf()
+    } else {
+        base.join(target_path)
+    }
+}
+
+/// Parse `manifest` (splicing in any `%include`d files) and return the
+/// resolved, deduplicated list of `.py` files plus `%unset` diagnostics.
+pub fn resolve_manifest(manifest: &Path) -> Result<ManifestResult> {
+    let base_dir = manifest
+        .parent()
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+    let mut parser = ManifestParser::new(base_dir);
+    parser.process_file(manifest)?;
+    parser.apply_excludes();
+
+    Ok(ManifestResult {
+        files: parser.files,
+        unset: parser.unset,
+    })
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, name: &str, contents: &str) -> PathBuf {
+        let path = dir.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_simple_roots_section() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "a.py", "x = 1");
+        write(temp.path(), "b.py", "x = 2");
+        let manifest = write(temp.path(), "manifest.ini", "[roots]\n*.py\n");
+
+        let result = resolve_manifest(&manifest).unwrap();
+        assert_eq!(result.files.len(), 2);
+    }
+
+    #[test]
+    fn test_key_equals_value_item() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "a.py", "x = 1");
+        let manifest = write(temp.path(), "manifest.ini", "[roots]\nunit = *.py\n");
+
+        let result = resolve_manifest(&manifest).unwrap();
+        assert_eq!(result.files.len(), 1);
+    }
+
+    #[test]
+    fn test_comments_are_ignored() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "a.py", "x = 1");
+        let manifest = write(
+            temp.path(),
+            "manifest.ini",
+            "; a comment\n# another comment\n[roots]\n*.py\n",
+        );
+
+        let result = resolve_manifest(&manifest).unwrap();
+        assert_eq!(result.files.len(), 1);
+    }
+
+    #[test]
+    fn test_continuation_line_appends_to_previous_value() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "a.py", "x = 1");
+        write(temp.path(), "sub/b.py", "x = 2");
+        let manifest = write(
+            temp.path(),
+            "manifest.ini",
+            "[roots]\nlist = a.py\n    sub/b.py\n",
+        );
+
+        let result = resolve_manifest(&manifest).unwrap();
+        // The continuation line is appended to `list` as a second glob, so
+        // both the first line's file and the continuation's file match.
+        assert_eq!(result.files.len(), 2);
+        assert!(result.files.iter().any(|f| f.ends_with("a.py")));
+        assert!(result.files.iter().any(|f| f.ends_with("sub/b.py")));
+    }
+
+    #[test]
+    fn test_exclude_filters_matching_roots() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "a.py", "x = 1");
+        write(temp.path(), "fixtures/b.py", "x = 2");
+        let manifest = write(
+            temp.path(),
+            "manifest.ini",
+            "[exclude]\nfixtures/*.py\n\n[roots]\n*.py\nfixtures/*.py\n",
+        );
+
+        let result = resolve_manifest(&manifest).unwrap();
+        assert_eq!(result.files.len(), 1);
+        assert!(result.files[0].ends_with("a.py"));
+    }
+
+    #[test]
+    fn test_exclude_filters_matching_roots_in_documented_order() {
+        // Same as `test_exclude_filters_matching_roots`, but with `[roots]`
+        // before `[exclude]` - the order shown in this module's own
+        // top-of-file doc example - to prove filtering doesn't depend on
+        // which section comes first.
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "a.py", "x = 1");
+        write(temp.path(), "fixtures/b.py", "x = 2");
+        let manifest = write(
+            temp.path(),
+            "manifest.ini",
+            "[roots]\n*.py\nfixtures/*.py\n\n[exclude]\nfixtures/*.py\n",
+        );
+
+        let result = resolve_manifest(&manifest).unwrap();
+        assert_eq!(result.files.len(), 1);
+        assert!(result.files[0].ends_with("a.py"));
+    }
+
+    #[test]
+    fn test_unset_removes_previously_matched_file() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "a.py", "x = 1");
+        write(temp.path(), "legacy.py", "x = 2");
+        let manifest = write(
+            temp.path(),
+            "manifest.ini",
+            "[roots]\n*.py\n\n%unset legacy.py\n",
+        );
+
+        let result = resolve_manifest(&manifest).unwrap();
+        assert_eq!(result.files.len(), 1);
+        assert!(result.files[0].ends_with("a.py"));
+        assert_eq!(result.unset.len(), 1);
+        assert_eq!(result.unset[0].removed.len(), 1);
+    }
+
+    #[test]
+    fn test_include_splices_fragment_in_place() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "a.py", "x = 1");
+        write(temp.path(), "b.py", "x = 2");
+        write(temp.path(), "fragment.ini", "[roots]\nb.py\n");
+        let manifest = write(
+            temp.path(),
+            "manifest.ini",
+            "[roots]\na.py\n\n%include fragment.ini\n",
+        );
+
+        let result = resolve_manifest(&manifest).unwrap();
+        assert_eq!(result.files.len(), 2);
+    }
+
+    #[test]
+    fn test_include_can_unset_earlier_root() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "a.py", "x = 1");
+        write(temp.path(), "b.py", "x = 2");
+        write(temp.path(), "veto.ini", "%unset a.py\n");
+        let manifest = write(
+            temp.path(),
+            "manifest.ini",
+            "[roots]\na.py\nb.py\n\n%include veto.ini\n",
+        );
+
+        let result = resolve_manifest(&manifest).unwrap();
+        assert_eq!(result.files.len(), 1);
+        assert!(result.files[0].ends_with("b.py"));
+    }
+
+    #[test]
+    fn test_include_cycle_is_detected() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "a.ini", "%include b.ini\n");
+        let manifest = write(temp.path(), "b.ini", "%include a.ini\n");
+
+        let result = resolve_manifest(&manifest);
+        assert!(result.is_err(), "cyclic %include should be rejected");
+    }
+
+    #[test]
+    fn test_include_self_is_detected() {
+        let temp = TempDir::new().unwrap();
+        let manifest = write(temp.path(), "self.ini", "%include self.ini\n");
+
+        let result = resolve_manifest(&manifest);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_non_py_files_are_excluded_from_glob_expansion() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "a.py", "x = 1");
+        write(temp.path(), "readme.txt", "not python");
+        let manifest = write(temp.path(), "manifest.ini", "[roots]\n*\n");
+
+        let result = resolve_manifest(&manifest).unwrap();
+        assert_eq!(result.files.len(), 1);
+        assert!(result.files[0].ends_with("a.py"));
+    }
+
+    #[test]
+    fn test_duplicate_roots_are_deduplicated() {
+        let temp = TempDir::new().unwrap();
+        write(temp.path(), "a.py", "x = 1");
+        let manifest = write(temp.path(), "manifest.ini", "[roots]\na.py\n*.py\n");
+
+        let result = resolve_manifest(&manifest).unwrap();
+        assert_eq!(result.files.len(), 1);
+    }
+}
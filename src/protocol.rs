@@ -27,8 +27,48 @@ pub struct TestPayload {
     pub fixtures: Vec<FixtureInfo>,
     /// File descriptor for log capture (memfd)
     pub log_fd: i32,
+    /// `LogCapture` slot this worker owns; every other slot's inherited fd
+    /// gets closed via `LogCapture::seal_to_slot` right before the worker
+    /// redirects its own stdout/stderr onto `log_fd`.
+    pub log_slot: usize,
     /// Path to supervisor's debug socket for breakpoint() support
     pub debug_socket_path: String,
+    /// Sandbox policy this test was resolved with - see
+    /// `resolver::permissions_from_markers` and `isolation::setup_filesystem`.
+    pub permissions: Permissions,
+}
+
+/// Per-test sandbox policy. `isolation::setup_filesystem` used to give every
+/// worker one fixed posture (read-only root, isolated network namespace,
+/// writable overlays on `/tmp` and the project); this makes that
+/// configurable per test, the way `@pytest.mark.tach_allow_net` and friends
+/// are meant to be consumed.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Permissions {
+    pub net: NetPolicy,
+    /// Extra paths, beyond the project root and `/tmp` (always writable),
+    /// given a writable overlay.
+    pub write_paths: Vec<String>,
+    /// Extra paths given a read-only bind mount from the host; this also
+    /// makes a path read-only where it lies inside the project root, `/tmp`
+    /// or a `write_paths` entry.
+    pub read_paths: Vec<String>,
+}
+
+/// Network access granted to a worker's isolated network namespace.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub enum NetPolicy {
+    /// No network at all, not even loopback.
+    None,
+    /// Isolated network namespace with its own loopback only - the default
+    /// posture for a worker with no network marker.
+    #[default]
+    Loopback,
+    /// Loopback, plus real egress to the listed hosts (empty means any
+    /// host). Real bridging requires host-side cooperation
+    /// `isolation::setup_filesystem` can't safely provide on its own today,
+    /// so this currently falls back to loopback-only with a warning.
+    AllowHosts(Vec<String>),
 }
 
 /// Fixture info for payload
@@ -60,6 +100,35 @@ pub struct TestResult {
     pub duration_ns: u64,
     /// Truncated to 4KB max
     pub message: String,
+    /// Per-test line coverage, present only when `--coverage` is enabled and
+    /// the worker's harness actually instrumented the test (see
+    /// `CoverageReport`). `None` for an ordinary run.
+    #[serde(default)]
+    pub coverage: Option<CoverageReport>,
+    /// Deduplicated absolute paths the worker actually opened while running
+    /// this test, captured by `provenance::FileOpenTracker` and resolved
+    /// through `/proc/self/fd` (a truncated capture sends a single sentinel
+    /// instead, see `provenance::Capture::into_wire`). Empty when capture
+    /// wasn't available (e.g. fanotify couldn't be set up) or isn't
+    /// meaningful for this result (`crash`); `ProvenanceCache` records an
+    /// empty list the same as a capture that saw no reads.
+    #[serde(default)]
+    pub read_files: Vec<String>,
+}
+
+/// Per-test line coverage, collected by a `sys.settrace` hook the worker's
+/// harness installs around the test body before handing control to it.
+/// Cheap and exact here because each test already runs in its own fork of
+/// the Zygote (see `zygote.rs`): traces from one test can't bleed into
+/// another, so a plain union/count of hit lines across all `CoverageReport`s
+/// is the real per-line execution count - no sampling, no subtraction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoverageReport {
+    pub test_id: u32,
+    /// (source file path, line numbers executed while this test ran) - one
+    /// entry per file touched, not just the test's own file, since a test
+    /// typically exercises code it imports too.
+    pub files: Vec<(String, Vec<u32>)>,
 }
 
 impl TestResult {
@@ -69,6 +138,8 @@ impl TestResult {
             status: STATUS_PASS,
             duration_ns,
             message: String::new(),
+            coverage: None,
+            read_files: Vec::new(),
         }
     }
 
@@ -78,6 +149,8 @@ impl TestResult {
             status: STATUS_FAIL,
             duration_ns,
             message: truncate_message(message),
+            coverage: None,
+            read_files: Vec::new(),
         }
     }
 
@@ -87,9 +160,26 @@ impl TestResult {
             status: STATUS_CRASH,
             duration_ns: 0,
             message: "Worker crashed (EOF on socket)".to_string(),
+            coverage: None,
+            read_files: Vec::new(),
         }
     }
 
+    /// Attach a coverage report collected while this test ran. Chained onto
+    /// `pass`/`fail` by the harness glue once `--coverage` is enabled.
+    pub fn with_coverage(mut self, coverage: CoverageReport) -> Self {
+        self.coverage = Some(coverage);
+        self
+    }
+
+    /// Attach the files a worker was observed opening while this test ran.
+    /// Chained onto `pass`/`fail` from `zygote::run_worker` once
+    /// `provenance::FileOpenTracker` has drained its capture.
+    pub fn with_read_files(mut self, read_files: Vec<String>) -> Self {
+        self.read_files = read_files;
+        self
+    }
+
     pub fn status_str(&self) -> &'static str {
         match self.status {
             STATUS_PASS => "PASS",
@@ -231,6 +321,46 @@ mod tests {
         assert_eq!(truncated.len(), 4096 + 15);
     }
 
+    #[test]
+    fn test_with_read_files_attaches_and_roundtrips() {
+        let result = TestResult::pass(42, 1_000_000)
+            .with_read_files(vec!["tests/fixtures/data.csv".to_string()]);
+        assert_eq!(result.read_files, vec!["tests/fixtures/data.csv".to_string()]);
+
+        let encoded = bincode::serialize(&result).unwrap();
+        let decoded: TestResult = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(decoded.read_files, result.read_files);
+    }
+
+    #[test]
+    fn test_read_files_empty_unless_attached() {
+        // A result built without `with_read_files` (here a crash, which
+        // never carries a capture) has no read files.
+        let result = TestResult::crash(7);
+        assert!(result.read_files.is_empty());
+    }
+
+    #[test]
+    fn test_permissions_default_is_loopback_only() {
+        let permissions = Permissions::default();
+        assert_eq!(permissions.net, NetPolicy::Loopback);
+        assert!(permissions.write_paths.is_empty());
+        assert!(permissions.read_paths.is_empty());
+    }
+
+    #[test]
+    fn test_permissions_roundtrip_through_bincode() {
+        let permissions = Permissions {
+            net: NetPolicy::AllowHosts(vec!["pypi.org".to_string()]),
+            write_paths: vec!["/data".to_string()],
+            read_paths: vec!["/etc/ca-certificates".to_string()],
+        };
+
+        let encoded = bincode::serialize(&permissions).unwrap();
+        let decoded: Permissions = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(decoded, permissions);
+    }
+
     #[test]
     fn test_encode_with_length_roundtrip() {
         let payload = TestPayload {
@@ -243,7 +373,9 @@ mod tests {
                 scope: "module".to_string(),
             }],
             log_fd: -1,
+            log_slot: 0,
             debug_socket_path: String::new(),
+            permissions: Permissions::default(),
         };
 
         let encoded = encode_with_length(&payload).unwrap();
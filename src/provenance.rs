@@ -0,0 +1,459 @@
+//! Syscall-provenance capture for auto-deriving the test dependency map
+//!
+//! Static import parsing (see `importgraph.rs`) only catches dependencies a
+//! test's own module graph can see - it misses data files, config, and
+//! anything loaded dynamically. This module captures the empirically real
+//! answer by watching which files a worker actually opens while a test runs.
+//!
+//! A full `ptrace(2)` syscall tracer was the obvious way to do this, but the
+//! supervisor would then have to become each worker's tracer, which fights
+//! with `snapshot.rs`'s existing SIGSTOP/SIGCONT golden-snapshot handshake
+//! (`zygote::post_fork_init`) - a tracer owns all of a tracee's stop/continue
+//! events, and two protocols racing over the same signals is how you get a
+//! wedged worker. `fanotify(7)` sidesteps that entirely: each worker watches
+//! its own activity from the inside, no tracer/tracee relationship needed.
+//!
+//! Workers already run in a private mount namespace
+//! (`isolation::setup_filesystem` calls `unshare(CLONE_NEWNS)`), so a
+//! `FAN_MARK_MOUNT` watch on "/" is naturally scoped to just that worker -
+//! no `FAN_REPORT_FID` mode and no `open_by_handle_at` dance required, since
+//! each event's `fd` is already a directly usable open descriptor,
+//! resolvable via `/proc/self/fd/<fd>` (the same trick `snapshot.rs` avoids
+//! needing at all by using `process_vm_readv` instead of ptrace).
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::ffi::CString;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::mem;
+use std::os::unix::io::RawFd;
+use std::path::{Path, PathBuf};
+
+/// Hard cap on how many distinct files we'll remember per test. Past this a
+/// test is almost certainly walking a large tree rather than reading a
+/// meaningful, stable dependency set, so the capture degrades to
+/// `Capture::Truncated` and the caller should fall back to always running
+/// the test rather than trusting a partial list.
+const MAX_TRACKED_FILES: usize = 2048;
+
+/// Sentinel written into `TestResult::read_files` in place of a file list
+/// when a capture was truncated. `protocol::TestResult` only has room for
+/// one `Vec<String>` field on the wire (as asked for - length-prefixed like
+/// every other encoded struct), so truncation rides along as a value no real
+/// path can ever equal, rather than widening the wire format for one bit of
+/// information.
+const TRUNCATED_SENTINEL: &str = "\0__tach_provenance_truncated__";
+
+/// What `FileOpenTracker::drain` managed to observe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Capture {
+    /// Deduplicated paths opened while the tracker was live.
+    Files(Vec<String>),
+    /// Hit `MAX_TRACKED_FILES` - the list can't be trusted as complete, so
+    /// the test should be treated as always-dirty instead of under-selected.
+    Truncated,
+}
+
+impl Capture {
+    /// Flatten into the shape that actually travels over the wire in
+    /// `TestResult::read_files`.
+    pub fn into_wire(self) -> Vec<String> {
+        match self {
+            Capture::Files(files) => files,
+            Capture::Truncated => vec![TRUNCATED_SENTINEL.to_string()],
+        }
+    }
+
+    /// Reconstruct from a `TestResult::read_files` value once it's back on
+    /// the supervisor side.
+    pub fn from_wire(read_files: &[String]) -> Capture {
+        if read_files.iter().any(|f| f == TRUNCATED_SENTINEL) {
+            Capture::Truncated
+        } else {
+            Capture::Files(read_files.to_vec())
+        }
+    }
+}
+
+/// Watches `FAN_OPEN` events across the calling process's mount namespace
+/// for the lifetime of one test.
+///
+/// `start()` should be called right after `isolation::setup_filesystem`
+/// succeeds, so the watch is scoped to the worker's own private namespace
+/// rather than the host's. `drain()` should be called right after the test
+/// finishes, before the worker reports its `TestResult`.
+pub struct FileOpenTracker {
+    fd: RawFd,
+}
+
+impl FileOpenTracker {
+    /// Start watching. Requires the same privilege `isolation::setup_filesystem`
+    /// already assumes (`CAP_SYS_ADMIN`) - a failure here is non-fatal to the
+    /// test itself, just means this run has no provenance capture.
+    pub fn start() -> io::Result<Self> {
+        let fd = unsafe {
+            libc::fanotify_init(
+                (libc::FAN_CLASS_NOTIF | libc::FAN_NONBLOCK | libc::FAN_CLOEXEC) as u32,
+                (libc::O_RDONLY | libc::O_LARGEFILE) as u32,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let root = CString::new("/").expect("no interior NUL");
+        let ret = unsafe {
+            libc::fanotify_mark(
+                fd,
+                libc::FAN_MARK_ADD | libc::FAN_MARK_MOUNT,
+                libc::FAN_OPEN as u64,
+                libc::AT_FDCWD,
+                root.as_ptr(),
+            )
+        };
+        if ret < 0 {
+            let err = io::Error::last_os_error();
+            unsafe {
+                libc::close(fd);
+            }
+            return Err(err);
+        }
+
+        Ok(Self { fd })
+    }
+
+    /// Drain every `FAN_OPEN` event queued so far and resolve each one's fd
+    /// to a path. Consumes `self` - the tracker's own fd is closed by `Drop`,
+    /// while each per-event fd fanotify hands back is closed right here as
+    /// soon as it's resolved.
+    pub fn drain(self) -> Capture {
+        let mut seen = HashSet::new();
+        let mut truncated = false;
+        let mut buf = [0u8; 4096];
+
+        'read: loop {
+            let n = unsafe { libc::read(self.fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+            if n <= 0 {
+                // EAGAIN (nothing queued right now) or any other read error -
+                // either way, there's nothing more to drain.
+                break;
+            }
+            let n = n as usize;
+            let mut offset = 0usize;
+            let meta_size = mem::size_of::<libc::fanotify_event_metadata>();
+
+            while offset + meta_size <= n {
+                let meta = unsafe {
+                    &*(buf.as_ptr().add(offset) as *const libc::fanotify_event_metadata)
+                };
+                let event_len = meta.event_len as usize;
+                if event_len == 0 || offset + event_len > n {
+                    break 'read;
+                }
+
+                if meta.fd >= 0 {
+                    if !truncated {
+                        if let Some(path) = resolve_fd_path(meta.fd) {
+                            seen.insert(path);
+                        }
+                        if seen.len() > MAX_TRACKED_FILES {
+                            truncated = true;
+                        }
+                    }
+                    unsafe {
+                        libc::close(meta.fd);
+                    }
+                }
+
+                offset += event_len;
+            }
+        }
+
+        if truncated {
+            Capture::Truncated
+        } else {
+            Capture::Files(seen.into_iter().collect())
+        }
+    }
+}
+
+impl Drop for FileOpenTracker {
+    fn drop(&mut self) {
+        unsafe {
+            libc::close(self.fd);
+        }
+    }
+}
+
+fn resolve_fd_path(fd: RawFd) -> Option<String> {
+    fs::read_link(format!("/proc/self/fd/{}", fd))
+        .ok()
+        .map(|p| p.to_string_lossy().into_owned())
+}
+
+/// One test's captured provenance, as persisted in the cache file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct CacheEntry {
+    /// Content hash of the test's own source file at capture time (see
+    /// `incremental::hash_source` for the same fingerprint-not-full-hash
+    /// rationale) - a stale hash means the captured file list might no
+    /// longer match what the test actually does, so it's dropped rather
+    /// than trusted.
+    source_hash: u64,
+    /// `true` if the capture was truncated (hit `MAX_TRACKED_FILES`) - such
+    /// a test is always reported dirty by `dirty_tests`, regardless of
+    /// which paths changed.
+    truncated: bool,
+    read_files: Vec<String>,
+}
+
+/// Persists `test_id -> observed read_files` across runs so watch mode can
+/// mark a test dirty when a file it previously read (not just imported)
+/// changes. Lives at `.tach/cache/provenance.json`, alongside
+/// `loader::BytecodeCompiler`'s bytecode cache.
+#[derive(Debug, Default)]
+pub struct ProvenanceCache {
+    path: PathBuf,
+    entries: HashMap<String, CacheEntry>,
+}
+
+impl ProvenanceCache {
+    /// Load the cache from `project_root/.tach/cache/provenance.json` (or
+    /// `TACH_CACHE_DIR` if set, matching `loader::BytecodeCompiler::new`). A
+    /// missing or unreadable file just starts empty - this cache is purely
+    /// an optimization, never a correctness requirement.
+    pub fn load(project_root: &Path) -> Self {
+        let cache_dir = match std::env::var_os("TACH_CACHE_DIR") {
+            Some(dir) => PathBuf::from(dir),
+            None => project_root.join(".tach").join("cache"),
+        };
+        let path = cache_dir.join("provenance.json");
+
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default();
+
+        Self { path, entries }
+    }
+
+    /// Record (or overwrite) one test's captured provenance.
+    pub fn record(&mut self, test_id: &str, test_source: &str, capture: &Capture) {
+        let (read_files, truncated) = match capture {
+            Capture::Files(files) => (files.clone(), false),
+            Capture::Truncated => (Vec::new(), true),
+        };
+        self.entries.insert(
+            test_id.to_string(),
+            CacheEntry {
+                source_hash: hash_source(test_source),
+                truncated,
+                read_files,
+            },
+        );
+    }
+
+    /// Persist the cache to disk as JSON. Best-effort: a write failure (e.g.
+    /// a read-only checkout) just means the next run recaptures from
+    /// scratch, same as a cold cache.
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("creating cache dir {}", parent.display()))?;
+        }
+        let json = serde_json::to_string(&self.entries)?;
+        fs::write(&self.path, json)
+            .with_context(|| format!("writing provenance cache to {}", self.path.display()))
+    }
+
+    /// Given the set of test ids that ran last time (with their current
+    /// source) and a set of changed file paths, return the subset that
+    /// should be considered dirty because a file they previously read
+    /// changed - including any test whose capture was truncated (an
+    /// incomplete list can't be trusted to say "unaffected"). A test whose
+    /// cached entry is stale (source changed since capture) or missing is
+    /// never reported here: its cached read_files can't be trusted either
+    /// way, and a changed test file is the caller's own check.
+    pub fn dirty_tests(
+        &self,
+        test_sources: &HashMap<String, String>,
+        changed_paths: &HashSet<PathBuf>,
+    ) -> HashSet<String> {
+        let mut dirty = HashSet::new();
+
+        for (test_id, source) in test_sources {
+            let Some(entry) = self.entries.get(test_id) else {
+                continue;
+            };
+
+            if entry.source_hash != hash_source(source) {
+                // Stale capture - the test itself changed since we last
+                // observed what it reads, so we can't trust read_files
+                // either way. Don't mark it dirty here: the caller's own
+                // "test file changed" check already covers this case.
+                continue;
+            }
+
+            if entry.truncated {
+                dirty.insert(test_id.clone());
+                continue;
+            }
+
+            if entry
+                .read_files
+                .iter()
+                .any(|f| changed_paths.contains(Path::new(f)))
+            {
+                dirty.insert(test_id.clone());
+            }
+        }
+
+        dirty
+    }
+}
+
+/// Cheap content fingerprint, same rationale as `incremental::hash_source`:
+/// collisions only cost a missed cache hit, never a false one.
+fn hash_source(source: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    hasher.finish()
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn tmp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tach_provenance_test_{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_cache_round_trips_through_disk() {
+        let dir = tmp_dir("roundtrip");
+        let mut cache = ProvenanceCache::load(&dir);
+        cache.record(
+            "tests/test_foo.py::test_bar",
+            "def test_bar(): pass",
+            &Capture::Files(vec!["tests/fixtures/data.csv".to_string()]),
+        );
+        cache.save().unwrap();
+
+        let reloaded = ProvenanceCache::load(&dir);
+        let mut sources = HashMap::new();
+        sources.insert(
+            "tests/test_foo.py::test_bar".to_string(),
+            "def test_bar(): pass".to_string(),
+        );
+        let mut changed = HashSet::new();
+        changed.insert(PathBuf::from("tests/fixtures/data.csv"));
+
+        let dirty = reloaded.dirty_tests(&sources, &changed);
+        assert!(dirty.contains("tests/test_foo.py::test_bar"));
+    }
+
+    #[test]
+    fn test_missing_cache_file_loads_empty() {
+        let dir = tmp_dir("missing");
+        let cache = ProvenanceCache::load(&dir);
+        assert!(cache.entries.is_empty());
+    }
+
+    #[test]
+    fn test_unchanged_read_file_does_not_mark_dirty() {
+        let dir = tmp_dir("unchanged");
+        let mut cache = ProvenanceCache::load(&dir);
+        cache.record(
+            "tests/test_foo.py::test_bar",
+            "def test_bar(): pass",
+            &Capture::Files(vec!["tests/fixtures/data.csv".to_string()]),
+        );
+
+        let mut sources = HashMap::new();
+        sources.insert(
+            "tests/test_foo.py::test_bar".to_string(),
+            "def test_bar(): pass".to_string(),
+        );
+        let changed = HashSet::new();
+
+        assert!(cache.dirty_tests(&sources, &changed).is_empty());
+    }
+
+    #[test]
+    fn test_stale_source_hash_is_not_trusted() {
+        let dir = tmp_dir("stale");
+        let mut cache = ProvenanceCache::load(&dir);
+        cache.record(
+            "tests/test_foo.py::test_bar",
+            "def test_bar(): pass",
+            &Capture::Files(vec!["tests/fixtures/data.csv".to_string()]),
+        );
+
+        let mut sources = HashMap::new();
+        sources.insert(
+            "tests/test_foo.py::test_bar".to_string(),
+            "def test_bar(): assert 1 == 1".to_string(),
+        );
+        let mut changed = HashSet::new();
+        changed.insert(PathBuf::from("tests/fixtures/data.csv"));
+
+        // Source changed since capture - stale entry isn't trusted to
+        // explain why this test would be dirty.
+        assert!(cache.dirty_tests(&sources, &changed).is_empty());
+    }
+
+    #[test]
+    fn test_truncated_capture_is_always_dirty() {
+        let dir = tmp_dir("truncated");
+        let mut cache = ProvenanceCache::load(&dir);
+        cache.record(
+            "tests/test_foo.py::test_bar",
+            "def test_bar(): pass",
+            &Capture::Truncated,
+        );
+
+        let mut sources = HashMap::new();
+        sources.insert(
+            "tests/test_foo.py::test_bar".to_string(),
+            "def test_bar(): pass".to_string(),
+        );
+        let changed = HashSet::new();
+
+        assert!(cache
+            .dirty_tests(&sources, &changed)
+            .contains("tests/test_foo.py::test_bar"));
+    }
+
+    #[test]
+    fn test_unrelated_test_not_marked_dirty() {
+        let dir = tmp_dir("unrelated");
+        let mut cache = ProvenanceCache::load(&dir);
+        cache.record(
+            "tests/test_foo.py::test_bar",
+            "def test_bar(): pass",
+            &Capture::Files(vec!["tests/fixtures/data.csv".to_string()]),
+        );
+
+        let mut sources = HashMap::new();
+        sources.insert(
+            "tests/test_foo.py::test_bar".to_string(),
+            "def test_bar(): pass".to_string(),
+        );
+        let mut changed = HashSet::new();
+        changed.insert(PathBuf::from("tests/fixtures/unrelated.csv"));
+
+        assert!(cache.dirty_tests(&sources, &changed).is_empty());
+    }
+}
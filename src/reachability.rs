@@ -0,0 +1,366 @@
+//! Import-Graph Reachability Pruning
+//!
+//! Tree-shakes a [`ModuleRegistry`]: starting from one or more entry-point
+//! module names, walks the static `import`/`from ... import` statements in
+//! each reachable module's source to find the transitive closure of modules
+//! actually used, then drops everything else so embedding a large
+//! dependency tree doesn't pay to inject dead modules into `sys.modules`.
+
+use crate::loader::{package_ancestors, BytecodeEntry, ModuleRegistry};
+use rustpython_ast as ast;
+use rustpython_parser::parse_program;
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::path::Path;
+
+/// Outcome of [`prune_unreachable`].
+pub struct PruneResult {
+    /// Entries that remain in the registry after pruning.
+    pub reachable: Vec<BytecodeEntry>,
+    /// Number of entries dropped because nothing reachable imports them.
+    pub pruned: usize,
+}
+
+/// Compute the transitive closure of modules reachable from `entry_points`
+/// by static imports, drop everything else from `registry`, and report the
+/// outcome.
+///
+/// Reachability is a fixpoint walk: the entry points seed the worklist, and
+/// each module popped off it contributes the modules named by its `import`
+/// statements. A package's ancestors (`a`, `a.b` for `a.b.c`) are always
+/// marked reachable alongside it, since Python must execute their
+/// `__init__.py` before the submodule. `from ... import *` is handled by
+/// construction: we only ever track whole-module reachability, so a star
+/// import conservatively keeps the entire target module.
+pub fn prune_unreachable(registry: &ModuleRegistry, entry_points: &[String]) -> PruneResult {
+    let reachable = reachable_set(registry, entry_points);
+    let pruned = registry.retain_reachable(&reachable);
+
+    PruneResult {
+        reachable: registry.iter_entries().collect(),
+        pruned,
+    }
+}
+
+/// Fixpoint worklist walk: returns every module name reachable from
+/// `entry_points`, including package ancestors.
+fn reachable_set(registry: &ModuleRegistry, entry_points: &[String]) -> HashSet<String> {
+    let mut reachable = HashSet::new();
+    let mut worklist: VecDeque<String> = VecDeque::new();
+
+    for entry in entry_points {
+        enqueue(entry.clone(), &mut reachable, &mut worklist);
+    }
+
+    while let Some(name) = worklist.pop_front() {
+        let Some(source_path) = registry.get_source_path(&name) else {
+            continue;
+        };
+
+        for imported in imports_of(&name, &source_path, registry) {
+            enqueue(imported, &mut reachable, &mut worklist);
+        }
+    }
+
+    reachable
+}
+
+/// Mark `name` (and its package ancestors) reachable, queuing each one for
+/// its own imports to be walked if this is the first time we've seen it.
+fn enqueue(name: String, reachable: &mut HashSet<String>, worklist: &mut VecDeque<String>) {
+    for ancestor in package_ancestors(&name) {
+        if reachable.insert(ancestor.clone()) {
+            worklist.push_back(ancestor);
+        }
+    }
+    if reachable.insert(name.clone()) {
+        worklist.push_back(name);
+    }
+}
+
+/// Parse `source_path` and collect the dotted module names it statically
+/// imports, with relative imports resolved against `module_name`'s package.
+fn imports_of(module_name: &str, source_path: &Path, registry: &ModuleRegistry) -> Vec<String> {
+    let Ok(source) = fs::read_to_string(source_path) else {
+        return vec![];
+    };
+    let Ok(suite) = parse_program(&source, &source_path.to_string_lossy()) else {
+        return vec![];
+    };
+
+    let mut out = Vec::new();
+    collect_imports(&suite, module_name, registry, &mut out);
+    out
+}
+
+/// Recursively walk a statement list for `import`/`from ... import`,
+/// descending into the bodies of `if`/`for`/`while`/`with`/`try`/`def`/
+/// `class` blocks so lazily-imported (e.g. try/except fallback) modules
+/// are still found.
+fn collect_imports(
+    stmts: &[ast::Stmt],
+    module_name: &str,
+    registry: &ModuleRegistry,
+    out: &mut Vec<String>,
+) {
+    for stmt in stmts {
+        match stmt {
+            ast::Stmt::Import(import) => {
+                out.extend(import.names.iter().map(|alias| alias.name.to_string()));
+            }
+            ast::Stmt::ImportFrom(import_from) => {
+                collect_import_from(import_from, module_name, registry, out);
+            }
+            ast::Stmt::FunctionDef(f) => collect_imports(&f.body, module_name, registry, out),
+            ast::Stmt::AsyncFunctionDef(f) => collect_imports(&f.body, module_name, registry, out),
+            ast::Stmt::ClassDef(c) => collect_imports(&c.body, module_name, registry, out),
+            ast::Stmt::If(s) => {
+                collect_imports(&s.body, module_name, registry, out);
+                collect_imports(&s.orelse, module_name, registry, out);
+            }
+            ast::Stmt::For(s) => {
+                collect_imports(&s.body, module_name, registry, out);
+                collect_imports(&s.orelse, module_name, registry, out);
+            }
+            ast::Stmt::AsyncFor(s) => {
+                collect_imports(&s.body, module_name, registry, out);
+                collect_imports(&s.orelse, module_name, registry, out);
+            }
+            ast::Stmt::While(s) => {
+                collect_imports(&s.body, module_name, registry, out);
+                collect_imports(&s.orelse, module_name, registry, out);
+            }
+            ast::Stmt::With(s) => collect_imports(&s.body, module_name, registry, out),
+            ast::Stmt::AsyncWith(s) => collect_imports(&s.body, module_name, registry, out),
+            ast::Stmt::Try(s) => {
+                collect_imports(&s.body, module_name, registry, out);
+                for handler in &s.handlers {
+                    let ast::ExceptHandler::ExceptHandler(h) = handler;
+                    collect_imports(&h.body, module_name, registry, out);
+                }
+                collect_imports(&s.orelse, module_name, registry, out);
+                collect_imports(&s.finalbody, module_name, registry, out);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Resolve a single `from ... import ...` statement to the module name(s)
+/// it pulls in: the target module itself, plus any imported name that is
+/// also a registry submodule (`from pkg import sub` where `pkg.sub` is a
+/// module, not just an attribute).
+fn collect_import_from(
+    import_from: &ast::StmtImportFrom,
+    module_name: &str,
+    registry: &ModuleRegistry,
+    out: &mut Vec<String>,
+) {
+    let level = import_from
+        .level
+        .as_ref()
+        .map(|l| l.to_string().parse::<usize>().unwrap_or(0))
+        .unwrap_or(0);
+
+    let target = if level > 0 {
+        let base = relative_base(module_name, level, registry);
+        match (&base, &import_from.module) {
+            (Some(base), Some(m)) => Some(format!("{base}.{m}")),
+            (Some(base), None) => Some(base.clone()),
+            (None, Some(m)) => Some(m.to_string()),
+            (None, None) => None,
+        }
+    } else {
+        import_from.module.as_ref().map(|m| m.to_string())
+    };
+
+    let Some(target) = target else {
+        return;
+    };
+
+    for alias in &import_from.names {
+        if alias.name.as_str() == "*" {
+            continue; // whole module is already kept below
+        }
+        let candidate = format!("{target}.{}", alias.name.as_str());
+        if registry.get_source_path(&candidate).is_some() {
+            out.push(candidate);
+        }
+    }
+
+    out.push(target);
+}
+
+/// The package a relative import in `module_name` is anchored to: one dot
+/// (`level == 1`) means "this module's own package"; each extra dot climbs
+/// one more package level.
+fn relative_base(module_name: &str, level: usize, registry: &ModuleRegistry) -> Option<String> {
+    let is_package = registry.is_package(module_name).unwrap_or(false);
+    let mut segments: Vec<&str> = module_name.split('.').collect();
+
+    if !is_package {
+        segments.pop();
+    }
+    for _ in 0..level.saturating_sub(1) {
+        segments.pop();
+    }
+
+    if segments.is_empty() {
+        None
+    } else {
+        Some(segments.join("."))
+    }
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn register(registry: &ModuleRegistry, dir: &Path, name: &str, source: &str) {
+        let rel = name.replace('.', "/") + ".py";
+        let path = dir.join(&rel);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, source).unwrap();
+
+        registry.insert(BytecodeEntry {
+            name: name.to_string(),
+            source_path: path,
+            bytecode: vec![0xe3].into(),
+            is_package: false,
+            is_namespace: false,
+        });
+    }
+
+    fn register_package(registry: &ModuleRegistry, dir: &Path, name: &str, source: &str) {
+        let rel = name.replace('.', "/") + "/__init__.py";
+        let path = dir.join(&rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, source).unwrap();
+
+        registry.insert(BytecodeEntry {
+            name: name.to_string(),
+            source_path: path,
+            bytecode: vec![0xe3].into(),
+            is_package: true,
+            is_namespace: false,
+        });
+    }
+
+    #[test]
+    fn test_package_ancestors() {
+        assert_eq!(package_ancestors("a"), Vec::<String>::new());
+        assert_eq!(package_ancestors("a.b.c"), vec!["a", "a.b"]);
+    }
+
+    #[test]
+    fn test_prune_drops_unreferenced_modules() {
+        let temp = TempDir::new().unwrap();
+        let registry = ModuleRegistry::new(temp.path().to_path_buf());
+
+        register(&registry, temp.path(), "main", "import used\n");
+        register(&registry, temp.path(), "used", "x = 1\n");
+        register(&registry, temp.path(), "dead", "x = 2\n");
+
+        let result = prune_unreachable(&registry, &["main".to_string()]);
+
+        assert_eq!(result.pruned, 1);
+        assert!(registry.get_bytecode("main").is_some());
+        assert!(registry.get_bytecode("used").is_some());
+        assert!(registry.get_bytecode("dead").is_none());
+    }
+
+    #[test]
+    fn test_prune_keeps_package_ancestors() {
+        let temp = TempDir::new().unwrap();
+        let registry = ModuleRegistry::new(temp.path().to_path_buf());
+
+        register(&registry, temp.path(), "main", "import pkg.sub.leaf\n");
+        register_package(&registry, temp.path(), "pkg", "");
+        register_package(&registry, temp.path(), "pkg.sub", "");
+        register(&registry, temp.path(), "pkg.sub.leaf", "x = 1\n");
+        register(&registry, temp.path(), "pkg.other", "x = 2\n");
+
+        let result = prune_unreachable(&registry, &["main".to_string()]);
+
+        assert_eq!(result.pruned, 1);
+        assert!(registry.get_bytecode("pkg").is_some());
+        assert!(registry.get_bytecode("pkg.sub").is_some());
+        assert!(registry.get_bytecode("pkg.sub.leaf").is_some());
+        assert!(registry.get_bytecode("pkg.other").is_none());
+    }
+
+    #[test]
+    fn test_prune_resolves_relative_import_from() {
+        let temp = TempDir::new().unwrap();
+        let registry = ModuleRegistry::new(temp.path().to_path_buf());
+
+        register(&registry, temp.path(), "main", "import pkg.a\n");
+        register_package(&registry, temp.path(), "pkg", "");
+        register(&registry, temp.path(), "pkg.a", "from . import b\n");
+        register(&registry, temp.path(), "pkg.b", "x = 1\n");
+        register(&registry, temp.path(), "pkg.c", "x = 2\n");
+
+        let result = prune_unreachable(&registry, &["main".to_string()]);
+
+        assert_eq!(result.pruned, 1);
+        assert!(registry.get_bytecode("pkg.b").is_some());
+        assert!(registry.get_bytecode("pkg.c").is_none());
+    }
+
+    #[test]
+    fn test_prune_finds_imports_inside_try_except() {
+        let temp = TempDir::new().unwrap();
+        let registry = ModuleRegistry::new(temp.path().to_path_buf());
+
+        register(
+            &registry,
+            temp.path(),
+            "main",
+            "try:\n    import fast_impl\nexcept ImportError:\n    import slow_impl\n",
+        );
+        register(&registry, temp.path(), "fast_impl", "x = 1\n");
+        register(&registry, temp.path(), "slow_impl", "x = 2\n");
+        register(&registry, temp.path(), "unrelated", "x = 3\n");
+
+        let result = prune_unreachable(&registry, &["main".to_string()]);
+
+        assert_eq!(result.pruned, 1);
+        assert!(registry.get_bytecode("fast_impl").is_some());
+        assert!(registry.get_bytecode("slow_impl").is_some());
+        assert!(registry.get_bytecode("unrelated").is_none());
+    }
+
+    #[test]
+    fn test_prune_star_import_keeps_whole_module() {
+        let temp = TempDir::new().unwrap();
+        let registry = ModuleRegistry::new(temp.path().to_path_buf());
+
+        register(&registry, temp.path(), "main", "from helpers import *\n");
+        register(&registry, temp.path(), "helpers", "x = 1\n");
+
+        let result = prune_unreachable(&registry, &["main".to_string()]);
+
+        assert_eq!(result.pruned, 0);
+        assert!(registry.get_bytecode("helpers").is_some());
+    }
+
+    #[test]
+    fn test_reachable_entries_reported() {
+        let temp = TempDir::new().unwrap();
+        let registry = ModuleRegistry::new(temp.path().to_path_buf());
+
+        register(&registry, temp.path(), "main", "x = 1\n");
+
+        let result = prune_unreachable(&registry, &["main".to_string()]);
+        let names: Vec<String> = result.reachable.iter().map(|e| e.name.clone()).collect();
+
+        assert_eq!(names, vec!["main".to_string()]);
+    }
+}
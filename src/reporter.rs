@@ -14,13 +14,20 @@
 //! All other output (logs, errors, debug) must go to stderr.
 
 use serde::Serialize;
+use std::io::Write;
 
 /// Machine-readable events for JSON output
 #[derive(Serialize)]
 #[serde(tag = "event", rename_all = "snake_case")]
 pub enum MachineEvent<'a> {
     /// Emitted at start of test run
-    RunStart { count: usize },
+    RunStart {
+        count: usize,
+        /// Shuffle seed, present only when `--shuffle` randomized test order.
+        /// Re-passing this seed reproduces the exact same run order.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        seed: Option<u64>,
+    },
     /// Emitted when a test begins execution
     TestStart { id: &'a str, file: &'a str },
     /// Emitted when a test completes
@@ -31,11 +38,26 @@ pub enum MachineEvent<'a> {
         #[serde(skip_serializing_if = "Option::is_none")]
         message: Option<&'a str>,
     },
+    /// Emitted for each captured line of a test's stdout/stderr, attributed
+    /// to the test id that produced it. Streamed as it's captured rather
+    /// than interleaved with other tests' raw output.
+    TestOutput {
+        id: &'a str,
+        stream: &'a str, // "stdout" | "stderr"
+        line: &'a str,
+    },
     /// Emitted at end of test run
     RunFinished {
         passed: usize,
         failed: usize,
         skipped: usize,
+        /// Expected failures (`@pytest.mark.xfail`) that failed as expected
+        xfailed: usize,
+        /// Expected failures that unexpectedly passed
+        xpassed: usize,
+        /// Worker crashes (panic, segfault, or no response), distinct from
+        /// ordinary assertion failures.
+        crashed: usize,
         duration_ms: u64,
     },
     /// Emitted on fatal error (Boss Refinement #2)
@@ -44,8 +66,10 @@ pub enum MachineEvent<'a> {
 
 /// Reporter trait for output abstraction
 pub trait Reporter {
-    /// Called at start of test run
-    fn on_run_start(&mut self, count: usize);
+    /// Called at start of test run.
+    /// `seed` is `Some` when `--shuffle` randomized the test order, so the
+    /// run can be reproduced exactly by re-passing the same seed.
+    fn on_run_start(&mut self, count: usize, seed: Option<u64>);
 
     /// Called when a test begins execution
     fn on_test_start(&mut self, id: &str, file: &str);
@@ -53,26 +77,57 @@ pub trait Reporter {
     /// Called when a test completes
     fn on_test_finished(&mut self, id: &str, status: &str, duration_ms: u64, message: Option<&str>);
 
-    /// Called at end of test run
-    fn on_run_finished(&mut self, passed: usize, failed: usize, skipped: usize, duration_ms: u64);
+    /// Called for each captured line of a test's stdout/stderr as it's
+    /// produced. Default no-op: reporters that don't care about captured
+    /// output (e.g. `JunitReporter`) don't need to implement this.
+    fn on_test_output(&mut self, _id: &str, _stream: &str, _line: &str) {}
+
+    /// Called once per test, alongside `on_test_start`, with its source
+    /// location. Default no-op: only reporters that surface per-test
+    /// file/line metadata (e.g. `JunitReporter`'s `@line` attribute) need it.
+    fn on_test_location(&mut self, _id: &str, _line: usize) {}
+
+    /// Called at end of test run.
+    /// `xfailed`/`xpassed` break out expected-failure reconciliation so CI
+    /// can distinguish a genuine regression from a known-broken test.
+    /// `crashed` counts workers that never reported a pass/fail/skip verdict
+    /// (panic, segfault, or stale-worker timeout), kept separate from
+    /// `failed` so CI can tell a crash from an assertion failure.
+    #[allow(clippy::too_many_arguments)]
+    fn on_run_finished(
+        &mut self,
+        passed: usize,
+        failed: usize,
+        skipped: usize,
+        xfailed: usize,
+        xpassed: usize,
+        crashed: usize,
+        duration_ms: u64,
+    );
 
     /// Called on fatal error (Boss Refinement #2)
     fn on_error(&mut self, message: &str);
 }
 
+/// Serializes one NDJSON record to stdout and flushes immediately, so a
+/// script or dashboard tailing the stream sees each event as it happens
+/// rather than whenever stdout's internal buffer happens to fill.
+fn emit_machine_event(event: &MachineEvent) {
+    println!("{}", serde_json::to_string(event).unwrap());
+    let _ = std::io::stdout().flush();
+}
+
 /// JSON Reporter - outputs NDJSON to stdout
 pub struct JsonReporter;
 
 impl Reporter for JsonReporter {
-    fn on_run_start(&mut self, count: usize) {
-        let event = MachineEvent::RunStart { count };
+    fn on_run_start(&mut self, count: usize, seed: Option<u64>) {
         // ONLY JsonReporter touches stdout
-        println!("{}", serde_json::to_string(&event).unwrap());
+        emit_machine_event(&MachineEvent::RunStart { count, seed });
     }
 
     fn on_test_start(&mut self, id: &str, file: &str) {
-        let event = MachineEvent::TestStart { id, file };
-        println!("{}", serde_json::to_string(&event).unwrap());
+        emit_machine_event(&MachineEvent::TestStart { id, file });
     }
 
     fn on_test_finished(
@@ -82,37 +137,75 @@ impl Reporter for JsonReporter {
         duration_ms: u64,
         message: Option<&str>,
     ) {
-        let event = MachineEvent::TestFinished {
+        emit_machine_event(&MachineEvent::TestFinished {
             id,
             status,
             duration_ms,
             message,
-        };
-        println!("{}", serde_json::to_string(&event).unwrap());
+        });
     }
 
-    fn on_run_finished(&mut self, passed: usize, failed: usize, skipped: usize, duration_ms: u64) {
-        let event = MachineEvent::RunFinished {
+    fn on_test_output(&mut self, id: &str, stream: &str, line: &str) {
+        // Each line is its own NDJSON record so ordering/association survives interleaving.
+        emit_machine_event(&MachineEvent::TestOutput { id, stream, line });
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn on_run_finished(
+        &mut self,
+        passed: usize,
+        failed: usize,
+        skipped: usize,
+        xfailed: usize,
+        xpassed: usize,
+        crashed: usize,
+        duration_ms: u64,
+    ) {
+        emit_machine_event(&MachineEvent::RunFinished {
             passed,
             failed,
             skipped,
+            xfailed,
+            xpassed,
+            crashed,
             duration_ms,
-        };
-        println!("{}", serde_json::to_string(&event).unwrap());
+        });
     }
 
     fn on_error(&mut self, message: &str) {
-        let event = MachineEvent::Error { message };
-        println!("{}", serde_json::to_string(&event).unwrap());
+        emit_machine_event(&MachineEvent::Error { message });
     }
 }
 
 /// Human Reporter - outputs readable text to stderr
-pub struct HumanReporter;
+pub struct HumanReporter {
+    /// Captured output lines per in-flight test id, keyed so output from
+    /// concurrently-running tests doesn't interleave. Flushed (and
+    /// discarded) in `on_test_finished`.
+    captured: std::collections::HashMap<String, Vec<(String, String)>>,
+}
+
+impl HumanReporter {
+    pub fn new() -> Self {
+        Self {
+            captured: std::collections::HashMap::new(),
+        }
+    }
+}
+
+impl Default for HumanReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Reporter for HumanReporter {
-    fn on_run_start(&mut self, count: usize) {
-        eprintln!("[tach] Running {} tests...\n", count);
+    fn on_run_start(&mut self, count: usize, seed: Option<u64>) {
+        eprintln!("[tach] Running {} tests...", count);
+        if let Some(seed) = seed {
+            eprintln!("[tach] Shuffled with seed: {} (re-run with --seed {} to reproduce)", seed, seed);
+        }
+        eprintln!();
     }
 
     fn on_test_start(&mut self, id: &str, _file: &str) {
@@ -121,11 +214,14 @@ impl Reporter for HumanReporter {
 
     fn on_test_finished(
         &mut self,
-        _id: &str,
+        id: &str,
         status: &str,
         duration_ms: u64,
         message: Option<&str>,
     ) {
+        // Always drop the buffer for this test - passing runs stay quiet.
+        let captured = self.captured.remove(id);
+
         match status {
             "pass" => eprintln!("✓ ({}ms)", duration_ms),
             "fail" => {
@@ -136,17 +232,39 @@ impl Reporter for HumanReporter {
                         eprintln!("    {}", line);
                     }
                 }
+                for (stream, line) in captured.into_iter().flatten() {
+                    eprintln!("    [{}] {}", stream, line);
+                }
             }
             "skip" => eprintln!("⊘ skipped"),
+            "xfail" => eprintln!("✗ ({}ms) (xfail - expected)", duration_ms),
+            "xpass" => eprintln!("‼ ({}ms) (xpass - unexpectedly passed)", duration_ms),
             _ => eprintln!("{}", status),
         }
     }
 
-    fn on_run_finished(&mut self, passed: usize, failed: usize, skipped: usize, duration_ms: u64) {
+    fn on_test_output(&mut self, id: &str, stream: &str, line: &str) {
+        self.captured
+            .entry(id.to_string())
+            .or_default()
+            .push((stream.to_string(), line.to_string()));
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn on_run_finished(
+        &mut self,
+        passed: usize,
+        failed: usize,
+        skipped: usize,
+        xfailed: usize,
+        xpassed: usize,
+        crashed: usize,
+        duration_ms: u64,
+    ) {
         eprintln!();
         eprintln!(
-            "[tach] {} passed, {} failed, {} skipped in {}ms",
-            passed, failed, skipped, duration_ms
+            "[tach] {} passed, {} failed, {} skipped, {} xfailed, {} xpassed, {} crashed in {}ms",
+            passed, failed, skipped, xfailed, xpassed, crashed, duration_ms
         );
     }
 
@@ -155,6 +273,150 @@ impl Reporter for HumanReporter {
     }
 }
 
+/// Dot Reporter - one character per test result, wrapped at 80 columns.
+/// Compact alternative to `HumanReporter` for large suites (`--reporter dot`).
+pub struct DotReporter {
+    printed: usize,
+}
+
+impl DotReporter {
+    pub fn new() -> Self {
+        Self { printed: 0 }
+    }
+
+    fn print_char(&mut self, c: char) {
+        eprint!("{}", c);
+        self.printed += 1;
+        if self.printed % 80 == 0 {
+            eprintln!();
+        }
+    }
+}
+
+impl Default for DotReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reporter for DotReporter {
+    fn on_run_start(&mut self, count: usize, seed: Option<u64>) {
+        eprintln!("[tach] Running {} tests...", count);
+        if let Some(seed) = seed {
+            eprintln!("[tach] Shuffled with seed: {}", seed);
+        }
+    }
+
+    fn on_test_start(&mut self, _id: &str, _file: &str) {}
+
+    fn on_test_finished(&mut self, _id: &str, status: &str, _duration_ms: u64, _message: Option<&str>) {
+        let c = match status {
+            "pass" => '.',
+            "skip" => 'S',
+            "xfail" => 'x',
+            "xpass" => 'X',
+            "crash" => '!',
+            _ => 'F',
+        };
+        self.print_char(c);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn on_run_finished(
+        &mut self,
+        passed: usize,
+        failed: usize,
+        skipped: usize,
+        xfailed: usize,
+        xpassed: usize,
+        crashed: usize,
+        duration_ms: u64,
+    ) {
+        eprintln!();
+        eprintln!();
+        eprintln!(
+            "[tach] {} passed, {} failed, {} skipped, {} xfailed, {} xpassed, {} crashed in {}ms",
+            passed, failed, skipped, xfailed, xpassed, crashed, duration_ms
+        );
+    }
+
+    fn on_error(&mut self, message: &str) {
+        eprintln!();
+        eprintln!("[tach] FATAL ERROR: {}", message);
+    }
+}
+
+/// TAP v13 Reporter (`--reporter tap`) - emits `ok`/`not ok` lines to stdout
+/// so CI harnesses that consume the Test Anything Protocol can ingest
+/// results directly, without going through JUnit XML.
+pub struct TapReporter {
+    count: usize,
+}
+
+impl TapReporter {
+    pub fn new() -> Self {
+        Self { count: 0 }
+    }
+}
+
+impl Default for TapReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reporter for TapReporter {
+    fn on_run_start(&mut self, count: usize, _seed: Option<u64>) {
+        println!("TAP version 13");
+        println!("1..{}", count);
+    }
+
+    fn on_test_start(&mut self, _id: &str, _file: &str) {}
+
+    fn on_test_finished(&mut self, id: &str, status: &str, _duration_ms: u64, message: Option<&str>) {
+        self.count += 1;
+        match status {
+            "pass" => println!("ok {} - {}", self.count, id),
+            "skip" => println!("ok {} - {} # SKIP", self.count, id),
+            "xfail" => println!("ok {} - {} # TODO expected failure", self.count, id),
+            "xpass" => println!("not ok {} - {} # TODO unexpectedly passed", self.count, id),
+            "crash" => {
+                println!("not ok {} - {} # crashed", self.count, id);
+                if let Some(msg) = message {
+                    for line in msg.lines() {
+                        println!("  # {}", line);
+                    }
+                }
+            }
+            _ => {
+                println!("not ok {} - {}", self.count, id);
+                if let Some(msg) = message {
+                    for line in msg.lines() {
+                        println!("  # {}", line);
+                    }
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn on_run_finished(
+        &mut self,
+        _passed: usize,
+        _failed: usize,
+        _skipped: usize,
+        _xfailed: usize,
+        _xpassed: usize,
+        _crashed: usize,
+        _duration_ms: u64,
+    ) {
+    }
+
+    fn on_error(&mut self, message: &str) {
+        println!("Bail out! {}", message);
+    }
+}
+
 // =============================================================================
 // MultiReporter (Phase 5.2)
 // =============================================================================
@@ -171,9 +433,9 @@ impl MultiReporter {
 }
 
 impl Reporter for MultiReporter {
-    fn on_run_start(&mut self, count: usize) {
+    fn on_run_start(&mut self, count: usize, seed: Option<u64>) {
         for r in &mut self.reporters {
-            r.on_run_start(count);
+            r.on_run_start(count, seed);
         }
     }
 
@@ -195,9 +457,31 @@ impl Reporter for MultiReporter {
         }
     }
 
-    fn on_run_finished(&mut self, passed: usize, failed: usize, skipped: usize, duration_ms: u64) {
+    fn on_test_output(&mut self, id: &str, stream: &str, line: &str) {
+        for r in &mut self.reporters {
+            r.on_test_output(id, stream, line);
+        }
+    }
+
+    fn on_test_location(&mut self, id: &str, line: usize) {
+        for r in &mut self.reporters {
+            r.on_test_location(id, line);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn on_run_finished(
+        &mut self,
+        passed: usize,
+        failed: usize,
+        skipped: usize,
+        xfailed: usize,
+        xpassed: usize,
+        crashed: usize,
+        duration_ms: u64,
+    ) {
         for r in &mut self.reporters {
-            r.on_run_finished(passed, failed, skipped, duration_ms);
+            r.on_run_finished(passed, failed, skipped, xfailed, xpassed, crashed, duration_ms);
         }
     }
 
@@ -251,4 +535,140 @@ mod tests {
         let json = serde_json::to_string(&event).unwrap();
         assert!(json.contains("\"event\":\"error\""));
     }
+
+    #[test]
+    fn test_test_output_event_serialization() {
+        let event = MachineEvent::TestOutput {
+            id: "test_foo",
+            stream: "stdout",
+            line: "hello world",
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains("\"event\":\"test_output\""));
+        assert!(json.contains("\"stream\":\"stdout\""));
+        assert!(json.contains("\"line\":\"hello world\""));
+    }
+
+    #[test]
+    fn test_reporter_default_on_test_output_is_noop() {
+        struct Silent;
+        impl Reporter for Silent {
+            fn on_run_start(&mut self, _count: usize, _seed: Option<u64>) {}
+            fn on_test_start(&mut self, _id: &str, _file: &str) {}
+            fn on_test_finished(&mut self, _id: &str, _status: &str, _duration_ms: u64, _message: Option<&str>) {}
+            fn on_run_finished(&mut self, _p: usize, _f: usize, _s: usize, _xf: usize, _xp: usize, _c: usize, _d: u64) {}
+            fn on_error(&mut self, _message: &str) {}
+        }
+        // Should compile and run without overriding on_test_output.
+        Silent.on_test_output("test_foo", "stdout", "line");
+    }
+
+    #[test]
+    fn test_human_reporter_buffers_output_until_failure() {
+        let mut reporter = HumanReporter::new();
+        reporter.on_test_output("test_foo", "stdout", "captured line");
+        assert_eq!(reporter.captured.get("test_foo").unwrap().len(), 1);
+
+        // Finishing drops the buffer regardless of outcome.
+        reporter.on_test_finished("test_foo", "pass", 10, None);
+        assert!(!reporter.captured.contains_key("test_foo"));
+    }
+
+    #[test]
+    fn test_human_reporter_keeps_output_separate_per_test() {
+        let mut reporter = HumanReporter::new();
+        reporter.on_test_output("test_a", "stdout", "from a");
+        reporter.on_test_output("test_b", "stderr", "from b");
+        assert_eq!(reporter.captured.len(), 2);
+        assert_eq!(reporter.captured["test_a"][0].0, "stdout");
+        assert_eq!(reporter.captured["test_b"][0].0, "stderr");
+    }
+
+    #[test]
+    fn test_dot_reporter_wraps_at_80_columns() {
+        let mut reporter = DotReporter::new();
+        for _ in 0..80 {
+            reporter.on_test_finished("test_x", "pass", 1, None);
+        }
+        assert_eq!(reporter.printed, 80);
+    }
+
+    #[test]
+    fn test_tap_reporter_numbers_tests_in_order() {
+        let mut reporter = TapReporter::new();
+        reporter.on_run_start(2, None);
+        assert_eq!(reporter.count, 0);
+        reporter.on_test_finished("test_a", "pass", 1, None);
+        reporter.on_test_finished("test_b", "fail", 1, Some("boom"));
+        assert_eq!(reporter.count, 2);
+    }
+
+    #[test]
+    fn test_dot_reporter_prints_bang_for_crash() {
+        let mut reporter = DotReporter::new();
+        reporter.on_test_finished("test_x", "crash", 1, Some("segfault"));
+        assert_eq!(reporter.printed, 1);
+    }
+
+    #[test]
+    fn test_tap_reporter_marks_crash_as_not_ok() {
+        let mut reporter = TapReporter::new();
+        reporter.on_run_start(1, None);
+        reporter.on_test_finished("test_a", "crash", 1, Some("segfault"));
+        assert_eq!(reporter.count, 1);
+    }
+
+    /// Records which events it saw, in order, so `MultiReporter` tests can
+    /// assert every child actually received every event. Shares its log via
+    /// `Rc<RefCell<_>>` so a test can keep a handle after handing the
+    /// reporter itself (boxed, by value) to a `MultiReporter`.
+    struct RecordingReporter {
+        events: std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+    }
+
+    impl Reporter for RecordingReporter {
+        fn on_run_start(&mut self, count: usize, _seed: Option<u64>) {
+            self.events.borrow_mut().push(format!("run_start({})", count));
+        }
+        fn on_test_start(&mut self, id: &str, _file: &str) {
+            self.events.borrow_mut().push(format!("test_start({})", id));
+        }
+        fn on_test_finished(&mut self, id: &str, status: &str, _duration_ms: u64, _message: Option<&str>) {
+            self.events.borrow_mut().push(format!("test_finished({}, {})", id, status));
+        }
+        fn on_run_finished(&mut self, p: usize, f: usize, s: usize, xf: usize, xp: usize, c: usize, _d: u64) {
+            self.events
+                .borrow_mut()
+                .push(format!("run_finished({}, {}, {}, {}, {}, {})", p, f, s, xf, xp, c));
+        }
+        fn on_error(&mut self, message: &str) {
+            self.events.borrow_mut().push(format!("error({})", message));
+        }
+    }
+
+    #[test]
+    fn test_multi_reporter_forwards_every_event_to_every_child() {
+        let a_log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let b_log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let a = RecordingReporter { events: a_log.clone() };
+        let b = RecordingReporter { events: b_log.clone() };
+
+        let mut multi = MultiReporter::new(vec![Box::new(a), Box::new(b)]);
+        multi.on_run_start(3, Some(42));
+        multi.on_test_start("tests/foo.py::test_bar", "tests/foo.py");
+        multi.on_test_finished("tests/foo.py::test_bar", "pass", 5, None);
+        multi.on_error("boom");
+        multi.on_run_finished(1, 0, 0, 0, 0, 0, 5);
+
+        let expected = vec![
+            "run_start(3)".to_string(),
+            "test_start(tests/foo.py::test_bar)".to_string(),
+            "test_finished(tests/foo.py::test_bar, pass)".to_string(),
+            "error(boom)".to_string(),
+            "run_finished(1, 0, 0, 0, 0, 0)".to_string(),
+        ];
+
+        assert_eq!(*a_log.borrow(), expected);
+        assert_eq!(*b_log.borrow(), expected);
+    }
 }
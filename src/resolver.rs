@@ -1,18 +1,62 @@
 //! Dependency Resolution & Graph Construction
 //! Resolves fixture dependencies and builds execution order.
 
-use crate::discovery::{DiscoveryResult, FixtureDefinition, FixtureScope, TestCase, TestModule};
+use crate::discovery::{
+    DiscoveryResult, FixtureDefinition, FixtureScope, Marker, ParametrizeArg, TestCase, TestModule,
+};
+use crate::importgraph::ImportGraph;
+use crate::protocol::{NetPolicy, Permissions};
+use anyhow::{anyhow, Result};
+use glob::Pattern;
+use regex::Regex;
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// A fully resolved test ready for execution
 #[derive(Debug, Clone)]
 pub struct RunnableTest {
     pub file_path: PathBuf,
+    /// The test's source name, with a pytest-style `[param-id]` suffix
+    /// appended if it (or a fixture it depends on) is parametrized - see
+    /// `Resolver::expand_parametrized`.
     pub test_name: String,
     pub is_async: bool,
     /// Fixtures in topological order (dependencies first)
     pub fixtures: Vec<ResolvedFixture>,
+    /// `Some(strict)` if annotated `@pytest.mark.xfail(...)`; see `XfailMarker`.
+    pub xfail_strict: Option<bool>,
+    /// 1-based source line of the `def test_...`, for JUnit `@line` reporting.
+    pub line_number: usize,
+    /// Sandbox policy derived from this test's marks - see
+    /// `permissions_from_markers` and `protocol::Permissions`.
+    pub permissions: Permissions,
+}
+
+impl RunnableTest {
+    /// Fully-qualified id (`path::test_name`), e.g. `tests/foo.py::test_bar`.
+    /// Used for `--filter`/`--filter-regex` matching and exact-path selection.
+    pub fn qualified_id(&self) -> String {
+        format!("{}::{}", self.file_path.display(), self.test_name)
+    }
+}
+
+/// Build a test's sandbox policy from its pytest marks - see
+/// `protocol::Permissions`. Only `@pytest.mark.tach_allow_net(...)` is
+/// recognized today: its (static-literal) positional args become the
+/// allowed hostlist, or an empty list (any host) if given bare. Anything
+/// else keeps the default posture - isolated network namespace, loopback
+/// only, no extra mounts.
+fn permissions_from_markers(markers: &[Marker]) -> Permissions {
+    let net = markers
+        .iter()
+        .find(|m| m.name == "tach_allow_net")
+        .map(|m| NetPolicy::AllowHosts(m.args.clone()))
+        .unwrap_or_default();
+
+    Permissions {
+        net,
+        ..Default::default()
+    }
 }
 
 /// A resolved fixture with full context
@@ -21,13 +65,32 @@ pub struct ResolvedFixture {
     pub name: String,
     pub source_file: PathBuf,
     pub scope: FixtureScope,
+    /// The concrete value bound to this instance, for a parametrized fixture
+    /// (`@pytest.fixture(params=[...])`). `None` for an unparametrized
+    /// fixture, or a parametrized one whose params didn't expand (e.g. an
+    /// empty list).
+    pub param_value: Option<String>,
 }
 
 /// Error types for resolution failures
 #[derive(Debug)]
 pub enum ResolutionError {
-    MissingFixture { test: String, fixture: String },
-    CyclicDependency { test: String, cycle: Vec<String> },
+    MissingFixture {
+        test: String,
+        fixture: String,
+        /// Visible fixtures close to `fixture` by edit distance, nearest
+        /// first, capped at three - e.g. `tmp_pat` suggests `tmp_path`.
+        suggestions: Vec<String>,
+        /// The chain that led here: `test -> direct dep -> ... -> fixture`.
+        path: Vec<String>,
+    },
+    CyclicDependency {
+        test: String,
+        cycle: Vec<String>,
+        /// `cycle` prefixed with the acyclic chain from the test entry
+        /// point, e.g. `test_foo -> db -> connection -> base -> db`.
+        path: Vec<String>,
+    },
 }
 
 /// pytest builtin fixtures that are provided at runtime, not discovered statically.
@@ -67,20 +130,70 @@ fn is_builtin_fixture(name: &str) -> bool {
     PYTEST_BUILTINS.contains(&name)
 }
 
+/// A non-fatal fixture-resolution observation: doesn't block a test from
+/// running, but is worth surfacing to the user.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolutionWarning {
+    /// A `local` or `class_scoped` fixture has the same name as a `global`
+    /// (conftest) fixture and a different `dependencies` list or `scope`,
+    /// so the override silently changes behavior rather than just
+    /// customizing it. Same-shape overrides (idiomatic pytest) don't warn.
+    ShadowedFixture {
+        name: String,
+        shadowing_file: PathBuf,
+        shadowed_file: PathBuf,
+    },
+}
+
+/// True if overriding `global_fixture` with `local_fixture` changes what a
+/// test actually gets, not just which file defines it.
+fn shadows_with_different_shape(local_fixture: &FixtureDefinition, global_fixture: &FixtureDefinition) -> bool {
+    local_fixture.scope != global_fixture.scope
+        || local_fixture.dependencies != global_fixture.dependencies
+}
+
+/// Directories to search for a `conftest.py`, nearest first: the test
+/// module's own directory, then each ancestor up to the project root -
+/// pytest's conftest lookup order.
+fn conftest_search_dirs(module_path: &Path) -> Vec<PathBuf> {
+    let dir = module_path.parent().unwrap_or_else(|| Path::new(""));
+    dir.ancestors().map(|p| p.to_path_buf()).collect()
+}
+
+/// Find `name` in the nearest `conftest.py` reachable from `module_path`,
+/// walking its directory chain toward the root and stopping at the first
+/// match (a closer conftest shadows one further up the tree).
+fn lookup_conftest<'a>(
+    global: &'a HashMap<PathBuf, HashMap<String, (FixtureDefinition, PathBuf)>>,
+    module_path: &Path,
+    name: &str,
+) -> Option<&'a (FixtureDefinition, PathBuf)> {
+    conftest_search_dirs(module_path)
+        .iter()
+        .find_map(|dir| global.get(dir).and_then(|fixtures| fixtures.get(name)))
+}
+
 /// Registry holding all discovered fixtures
 pub struct FixtureRegistry {
-    /// Global fixtures from conftest.py files
-    global: HashMap<String, (FixtureDefinition, PathBuf)>,
+    /// Fixtures from `conftest.py` files, keyed by the directory the
+    /// conftest lives in. Looked up by walking from a test module's own
+    /// directory up toward the root (see `conftest_search_dirs`), so a
+    /// conftest closer to the test shadows one further up the tree.
+    global: HashMap<PathBuf, HashMap<String, (FixtureDefinition, PathBuf)>>,
     /// Local fixtures per module (non-class-scoped only)
     local: HashMap<PathBuf, HashMap<String, FixtureDefinition>>,
     /// Class-scoped fixtures: (module_path, class_name) -> fixture_name -> fixture
     class_scoped: HashMap<(PathBuf, String), HashMap<String, FixtureDefinition>>,
+    /// Non-fatal observations gathered while building the registry, e.g. a
+    /// local fixture shadowing a same-named global one with a different shape.
+    warnings: Vec<ResolutionWarning>,
 }
 
 impl FixtureRegistry {
     /// Build registry from discovery results
     pub fn from_discovery(result: &DiscoveryResult) -> Self {
-        let mut global = HashMap::new();
+        let mut global: HashMap<PathBuf, HashMap<String, (FixtureDefinition, PathBuf)>> =
+            HashMap::new();
         let mut local = HashMap::new();
         let mut class_scoped: HashMap<(PathBuf, String), HashMap<String, FixtureDefinition>> =
             HashMap::new();
@@ -90,6 +203,7 @@ impl FixtureRegistry {
                 .path
                 .file_name()
                 .map_or(false, |n| n == "conftest.py");
+            let conftest_dir = module.path.parent().unwrap_or_else(|| Path::new(""));
 
             let mut module_fixtures = HashMap::new();
             for fixture in &module.fixtures {
@@ -101,7 +215,10 @@ impl FixtureRegistry {
                         .or_default()
                         .insert(fixture.name.clone(), fixture.clone());
                 } else if is_conftest {
-                    global.insert(fixture.name.clone(), (fixture.clone(), module.path.clone()));
+                    global
+                        .entry(conftest_dir.to_path_buf())
+                        .or_default()
+                        .insert(fixture.name.clone(), (fixture.clone(), module.path.clone()));
                 } else {
                     module_fixtures.insert(fixture.name.clone(), fixture.clone());
                 }
@@ -112,14 +229,53 @@ impl FixtureRegistry {
             }
         }
 
+        let mut warnings = Vec::new();
+        for (module_path, fixtures) in &local {
+            for (name, fixture) in fixtures {
+                if let Some((global_fixture, shadowed_file)) =
+                    lookup_conftest(&global, module_path, name)
+                {
+                    if shadows_with_different_shape(fixture, global_fixture) {
+                        warnings.push(ResolutionWarning::ShadowedFixture {
+                            name: name.clone(),
+                            shadowing_file: module_path.clone(),
+                            shadowed_file: shadowed_file.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        for ((module_path, _class_name), fixtures) in &class_scoped {
+            for (name, fixture) in fixtures {
+                if let Some((global_fixture, shadowed_file)) =
+                    lookup_conftest(&global, module_path, name)
+                {
+                    if shadows_with_different_shape(fixture, global_fixture) {
+                        warnings.push(ResolutionWarning::ShadowedFixture {
+                            name: name.clone(),
+                            shadowing_file: module_path.clone(),
+                            shadowed_file: shadowed_file.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
         Self {
             global,
             local,
             class_scoped,
+            warnings,
         }
     }
 
-    /// Look up a fixture: class scope -> local scope -> global scope
+    /// Non-fatal observations gathered while building the registry.
+    pub fn warnings(&self) -> &[ResolutionWarning] {
+        &self.warnings
+    }
+
+    /// Look up a fixture: class scope -> local scope -> nearest-conftest-first
+    /// global scope, walking the module's directory chain toward the root.
     fn lookup(
         &self,
         name: &str,
@@ -145,9 +301,106 @@ impl FixtureRegistry {
                 return Some((fixture.clone(), module_path.clone()));
             }
         }
-        // Fall back to global scope
-        self.global.get(name).cloned()
+        // Fall back to the nearest enclosing conftest.py
+        lookup_conftest(&self.global, module_path, name).cloned()
+    }
+
+    /// All fixture names visible to `test_name` in `module_path`: class scope
+    /// (if the test belongs to a `Test*` class) -> local module scope ->
+    /// every conftest.py on the directory chain up to the root -> pytest
+    /// builtins. Used as the candidate set for "did you mean?" suggestions
+    /// on a `MissingFixture` error.
+    fn visible_fixture_names(&self, module_path: &PathBuf, test_name: &str) -> Vec<String> {
+        let mut names = Vec::new();
+
+        if let Some(class_name) = test_name.split("::").next() {
+            if class_name.starts_with("Test") && test_name.contains("::") {
+                let key = (module_path.clone(), class_name.to_string());
+                if let Some(class_fixtures) = self.class_scoped.get(&key) {
+                    names.extend(class_fixtures.keys().cloned());
+                }
+            }
+        }
+
+        if let Some(local_fixtures) = self.local.get(module_path) {
+            names.extend(local_fixtures.keys().cloned());
+        }
+
+        for dir in conftest_search_dirs(module_path) {
+            if let Some(fixtures) = self.global.get(&dir) {
+                names.extend(fixtures.keys().cloned());
+            }
+        }
+        names.extend(PYTEST_BUILTINS.iter().map(|s| s.to_string()));
+
+        names
+    }
+}
+
+/// Classic Levenshtein edit distance (insert/delete/substitute, all cost 1).
+fn levenshtein_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (len_a, len_b) = (a.len(), b.len());
+
+    let mut matrix = vec![vec![0usize; len_b + 1]; len_a + 1];
+    for (i, row) in matrix.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for j in 0..=len_b {
+        matrix[0][j] = j;
+    }
+
+    for i in 1..=len_a {
+        for j in 1..=len_b {
+            let sub_cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            matrix[i][j] = (matrix[i - 1][j] + 1)
+                .min(matrix[i][j - 1] + 1)
+                .min(matrix[i - 1][j - 1] + sub_cost);
+        }
+    }
+
+    matrix[len_a][len_b]
+}
+
+/// Find up to three `candidates` close to `name` by edit distance (nearest
+/// first), within `max(1, name.len() / 3)` - loose enough to catch a typo
+/// like `tmp_pat` -> `tmp_path` without matching unrelated names.
+fn suggest_fixture_names(name: &str, candidates: &[String]) -> Vec<String> {
+    let threshold = std::cmp::max(1, name.len() / 3);
+    let mut best: HashMap<&str, usize> = HashMap::new();
+    for candidate in candidates {
+        if candidate == name {
+            continue;
+        }
+        let dist = levenshtein_distance(name, candidate);
+        if dist <= threshold {
+            best.entry(candidate.as_str())
+                .and_modify(|d| *d = (*d).min(dist))
+                .or_insert(dist);
+        }
     }
+
+    let mut scored: Vec<(usize, &str)> = best.into_iter().map(|(n, d)| (d, n)).collect();
+    scored.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(b.1)));
+    scored.into_iter().take(3).map(|(_, n)| n.to_string()).collect()
+}
+
+/// Cartesian product of several value axes, preserving axis order within
+/// each combination (e.g. `[["a","b"], ["x","y"]]` -> `[a,x] [a,y] [b,x]
+/// [b,y]`). Used to expand a parametrized test into its concrete instances.
+fn cartesian_product(axes: &[Vec<String>]) -> Vec<Vec<String>> {
+    axes.iter().fold(vec![Vec::new()], |acc, axis| {
+        acc.into_iter()
+            .flat_map(|combo| {
+                axis.iter().map(move |value| {
+                    let mut next = combo.clone();
+                    next.push(value.clone());
+                    next
+                })
+            })
+            .collect()
+    })
 }
 
 /// Resolver engine
@@ -160,7 +413,12 @@ impl<'a> Resolver<'a> {
         Self { registry }
     }
 
-    /// Resolve all tests from discovery results
+    /// Resolve all tests from discovery results. A parametrized test (see
+    /// [`Resolver::expand_parametrized`]) expands to several `RunnableTest`s.
+    ///
+    /// Resolution is collect-all, not fail-fast: a test with three unknown
+    /// fixtures reports all three `ResolutionError`s in one pass rather than
+    /// just the first.
     pub fn resolve_all(
         &self,
         result: &DiscoveryResult,
@@ -170,29 +428,39 @@ impl<'a> Resolver<'a> {
 
         for module in &result.modules {
             for test in &module.tests {
-                match self.resolve_test(test, &module.path) {
-                    Ok(resolved) => runnable.push(resolved),
-                    Err(e) => errors.push(e),
-                }
+                let (mut resolved, mut diagnostics) = self.resolve_test(test, &module.path);
+                runnable.append(&mut resolved);
+                errors.append(&mut diagnostics);
             }
         }
 
         (runnable, errors)
     }
 
-    /// Resolve a single test's fixture dependencies
+    /// Resolve a single test's fixture dependencies, then expand it into one
+    /// `RunnableTest` per parametrization combination. Every direct
+    /// dependency is attempted even if an earlier one failed, so the
+    /// returned diagnostics vector is the test's *complete* set of problems,
+    /// not just the first one hit; a non-empty diagnostics vector means the
+    /// test yields no `RunnableTest`s.
     fn resolve_test(
         &self,
         test: &TestCase,
         module_path: &PathBuf,
-    ) -> Result<RunnableTest, ResolutionError> {
+    ) -> (Vec<RunnableTest>, Vec<ResolutionError>) {
         let mut resolved_fixtures = Vec::new();
         let mut visited = HashSet::new();
+        let mut failed = HashSet::new();
         let mut stack = Vec::new();
+        let mut diagnostics = Vec::new();
 
         // Phase 7b: Filter out parametrized args - they're NOT fixtures
         // @pytest.mark.parametrize("arg") injects arg from the decorator, not fixture system
-        let parametrized_set: HashSet<_> = test.parametrized_args.iter().collect();
+        let parametrized_set: HashSet<_> = test
+            .parametrized_args
+            .iter()
+            .flat_map(|arg| arg.names.iter())
+            .collect();
 
         // Resolve each direct dependency (excluding parametrized args)
         for dep_name in &test.dependencies {
@@ -207,19 +475,93 @@ impl<'a> Resolver<'a> {
                 &test.name,
                 &mut resolved_fixtures,
                 &mut visited,
+                &mut failed,
                 &mut stack,
-            )?;
+                &mut diagnostics,
+            );
         }
 
-        Ok(RunnableTest {
+        if !diagnostics.is_empty() {
+            return (Vec::new(), diagnostics);
+        }
+
+        let base = RunnableTest {
             file_path: module_path.clone(),
             test_name: test.name.clone(),
             is_async: test.is_async,
             fixtures: resolved_fixtures,
-        })
+            xfail_strict: test.xfail.as_ref().map(|marker| marker.strict),
+            line_number: test.line_number,
+            permissions: permissions_from_markers(&test.markers),
+        };
+
+        (self.expand_parametrized(base, test, module_path), Vec::new())
+    }
+
+    /// Expand `base` into one `RunnableTest` per combination of parametrized
+    /// fixture values (`@pytest.fixture(params=[...])`, anywhere in its
+    /// resolved dependency set) and its own `@pytest.mark.parametrize` value
+    /// lists, taking the Cartesian product across all of them - mirroring
+    /// pytest turning `test_foo` into `test_foo[sqlite-utc]`,
+    /// `test_foo[sqlite-local]`, etc. Returns `vec![base]` unchanged if
+    /// nothing in play is parametrized.
+    fn expand_parametrized(
+        &self,
+        base: RunnableTest,
+        test: &TestCase,
+        module_path: &PathBuf,
+    ) -> Vec<RunnableTest> {
+        // One axis per parametrized fixture: its index into `base.fixtures`
+        // paired with its static param values.
+        let fixture_axes: Vec<(usize, Vec<String>)> = base
+            .fixtures
+            .iter()
+            .enumerate()
+            .filter_map(|(i, fixture)| {
+                let (def, _) = self.registry.lookup(&fixture.name, module_path, &test.name)?;
+                let values = def.params.filter(|v| !v.is_empty())?;
+                Some((i, values))
+            })
+            .collect();
+
+        // One axis per test-level `parametrize` decorator with static values.
+        let mark_axes: Vec<Vec<String>> = test
+            .parametrized_args
+            .iter()
+            .filter_map(|arg| arg.rows.clone())
+            .filter(|values| !values.is_empty())
+            .collect();
+
+        if fixture_axes.is_empty() && mark_axes.is_empty() {
+            return vec![base];
+        }
+
+        let axes: Vec<Vec<String>> = fixture_axes
+            .iter()
+            .map(|(_, values)| values.clone())
+            .chain(mark_axes)
+            .collect();
+
+        cartesian_product(&axes)
+            .into_iter()
+            .map(|combo| {
+                let mut runnable = base.clone();
+                runnable.test_name = format!("{}[{}]", test.name, combo.join("-"));
+                for (axis_idx, (fixture_idx, _)) in fixture_axes.iter().enumerate() {
+                    runnable.fixtures[*fixture_idx].param_value = Some(combo[axis_idx].clone());
+                }
+                runnable
+            })
+            .collect()
     }
 
-    /// Recursively resolve a fixture and its dependencies (DFS with cycle detection)
+    /// Recursively resolve a fixture and its dependencies (DFS with cycle
+    /// detection). Collect-all: a `MissingFixture`/`CyclicDependency` on one
+    /// branch is recorded into `diagnostics` and `name` is marked `failed`
+    /// (so a later reference to it is skipped rather than re-walked and
+    /// re-reported) instead of aborting the whole test. A fixture that
+    /// depends - even transitively - on a failed one is itself marked
+    /// `failed` and never added to `resolved`.
     fn resolve_fixture(
         &self,
         name: &str,
@@ -227,20 +569,29 @@ impl<'a> Resolver<'a> {
         test_name: &str,
         resolved: &mut Vec<ResolvedFixture>,
         visited: &mut HashSet<String>,
+        failed: &mut HashSet<String>,
         stack: &mut Vec<String>,
-    ) -> Result<(), ResolutionError> {
-        // Already fully resolved
-        if visited.contains(name) {
-            return Ok(());
+        diagnostics: &mut Vec<ResolutionError>,
+    ) {
+        // Already fully resolved, or already known to be broken
+        if visited.contains(name) || failed.contains(name) {
+            return;
         }
 
         // Cycle detection
         if stack.contains(&name.to_string()) {
             stack.push(name.to_string());
-            return Err(ResolutionError::CyclicDependency {
+            let path = std::iter::once(test_name.to_string())
+                .chain(stack.iter().cloned())
+                .collect();
+            diagnostics.push(ResolutionError::CyclicDependency {
                 test: test_name.to_string(),
                 cycle: stack.clone(),
+                path,
             });
+            stack.pop();
+            failed.insert(name.to_string());
+            return;
         }
 
         // PHASE 6: Skip resolution for pytest builtin fixtures
@@ -248,39 +599,522 @@ impl<'a> Resolver<'a> {
         // We mark them as visited and continue - pytest will inject them.
         if is_builtin_fixture(name) {
             visited.insert(name.to_string());
-            return Ok(());
+            return;
         }
 
         // Look up fixture (pass test_name for class-scoped lookup)
-        let (fixture, source_file) = self
-            .registry
-            .lookup(name, module_path, test_name)
-            .ok_or_else(|| ResolutionError::MissingFixture {
+        let Some((fixture, source_file)) = self.registry.lookup(name, module_path, test_name) else {
+            let candidates = self.registry.visible_fixture_names(module_path, test_name);
+            let path = std::iter::once(test_name.to_string())
+                .chain(stack.iter().cloned())
+                .chain(std::iter::once(name.to_string()))
+                .collect();
+            diagnostics.push(ResolutionError::MissingFixture {
                 test: test_name.to_string(),
                 fixture: name.to_string(),
-            })?;
+                suggestions: suggest_fixture_names(name, &candidates),
+                path,
+            });
+            failed.insert(name.to_string());
+            return;
+        };
 
         // Push onto recursion stack
         stack.push(name.to_string());
 
-        // Resolve transitive dependencies first
+        // Resolve transitive dependencies first, continuing past a failed
+        // one so every problem in this fixture's subtree gets reported.
+        let mut any_dep_failed = false;
         for dep in &fixture.dependencies {
-            self.resolve_fixture(dep, module_path, test_name, resolved, visited, stack)?;
+            self.resolve_fixture(dep, module_path, test_name, resolved, visited, failed, stack, diagnostics);
+            any_dep_failed |= failed.contains(dep);
         }
 
         // Pop from stack
         stack.pop();
 
+        if any_dep_failed {
+            failed.insert(name.to_string());
+            return;
+        }
+
         // Mark as visited and add to resolved list
         visited.insert(name.to_string());
         resolved.push(ResolvedFixture {
             name: name.to_string(),
             source_file,
             scope: fixture.scope,
+            param_value: None,
         });
+    }
+}
+
+/// Identifies a single fixture *instance*'s lifetime: the span of the
+/// ordered test list over which it stays alive. Two fixtures with the same
+/// key are the same instance and must be set up once, shared, and torn
+/// down once - this is the crux of scope-aware scheduling.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum ScopeKey {
+    Function(usize, String),
+    Class(PathBuf, String, String),
+    Module(PathBuf, String),
+    Package(PathBuf, String),
+    Session(String),
+}
+
+/// Derive the lifetime key for a fixture as used by a given test.
+///
+/// `test_index` is the test's position in the (already scope-sorted) plan
+/// and only matters for `Function` scope, where every test gets its own
+/// instance.
+fn scope_key(test: &RunnableTest, fixture: &ResolvedFixture, test_index: usize) -> ScopeKey {
+    match fixture.scope {
+        FixtureScope::Function => ScopeKey::Function(test_index, fixture.name.clone()),
+        FixtureScope::Class => {
+            let class_name = test
+                .test_name
+                .split("::")
+                .next()
+                .filter(|_| test.test_name.contains("::"))
+                .unwrap_or("");
+            ScopeKey::Class(
+                test.file_path.clone(),
+                class_name.to_string(),
+                fixture.name.clone(),
+            )
+        }
+        FixtureScope::Module => ScopeKey::Module(test.file_path.clone(), fixture.name.clone()),
+        FixtureScope::Package => {
+            let package_dir = test
+                .file_path
+                .parent()
+                .map(PathBuf::from)
+                .unwrap_or_default();
+            ScopeKey::Package(package_dir, fixture.name.clone())
+        }
+        FixtureScope::Session => ScopeKey::Session(fixture.name.clone()),
+    }
+}
+
+/// A fixture coming into or going out of scope at a plan step.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FixtureBoundary {
+    pub fixture_name: String,
+    pub scope: FixtureScope,
+}
+
+/// One step of an `ExecutionPlan`: a test to run, plus the fixtures that
+/// must be set up immediately before it and torn down immediately after.
+#[derive(Debug, Clone)]
+pub struct PlanStep {
+    pub test: RunnableTest,
+    /// Fixtures entering scope at this step, widest scope first (mirrors
+    /// pytest's setup order: `session` -> `package` -> `module` -> `class`
+    /// -> `function`).
+    pub setup: Vec<FixtureBoundary>,
+    /// Fixtures leaving scope after this step, narrowest scope first (the
+    /// exact reverse of `setup` order).
+    pub teardown: Vec<FixtureBoundary>,
+}
+
+/// An ordered plan of tests, reordered so that tests sharing a wider-scoped
+/// fixture run contiguously, with explicit setup/teardown boundary markers
+/// so a runner instantiates each fixture exactly once per scope lifetime.
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionPlan {
+    pub steps: Vec<PlanStep>,
+}
+
+/// Build a scope-aware execution plan from resolved tests.
+///
+/// Tests are reordered (grouped by package directory, then module, then
+/// class) so that `Package`/`Module`/`Class`-scoped fixtures form
+/// contiguous runs; `Function`-scoped fixtures are rebuilt for every test
+/// regardless of position. Within a tie, the incoming order is preserved.
+pub fn build_execution_plan(mut tests: Vec<RunnableTest>) -> ExecutionPlan {
+    let group_key = |t: &RunnableTest| {
+        let package_dir = t.file_path.parent().map(PathBuf::from).unwrap_or_default();
+        let class_name = t
+            .test_name
+            .split("::")
+            .next()
+            .filter(|_| t.test_name.contains("::"))
+            .unwrap_or("")
+            .to_string();
+        (package_dir, t.file_path.clone(), class_name)
+    };
+    tests.sort_by(|a, b| group_key(a).cmp(&group_key(b)));
+
+    // First pass: find each fixture instance's first and last step index.
+    let mut lifetimes: HashMap<ScopeKey, (usize, usize, FixtureBoundary)> = HashMap::new();
+    for (i, test) in tests.iter().enumerate() {
+        for fixture in &test.fixtures {
+            let key = scope_key(test, fixture, i);
+            lifetimes
+                .entry(key)
+                .and_modify(|(_, last, _)| *last = i)
+                .or_insert((
+                    i,
+                    i,
+                    FixtureBoundary {
+                        fixture_name: fixture.name.clone(),
+                        scope: fixture.scope.clone(),
+                    },
+                ));
+        }
+    }
+
+    // Second pass: bucket boundaries by the step they attach to.
+    let mut setups_at: HashMap<usize, Vec<FixtureBoundary>> = HashMap::new();
+    let mut teardowns_at: HashMap<usize, Vec<FixtureBoundary>> = HashMap::new();
+    for (first, last, boundary) in lifetimes.into_values() {
+        setups_at.entry(first).or_default().push(boundary.clone());
+        teardowns_at.entry(last).or_default().push(boundary);
+    }
+
+    let steps = tests
+        .into_iter()
+        .enumerate()
+        .map(|(i, test)| {
+            let mut setup = setups_at.remove(&i).unwrap_or_default();
+            let mut teardown = teardowns_at.remove(&i).unwrap_or_default();
+            // Widest scope first on the way in, narrowest first on the way out.
+            setup.sort_by(|a, b| b.scope.rank().cmp(&a.scope.rank()));
+            teardown.sort_by(|a, b| a.scope.rank().cmp(&b.scope.rank()));
+            PlanStep {
+                test,
+                setup,
+                teardown,
+            }
+        })
+        .collect();
+
+    ExecutionPlan { steps }
+}
+
+/// A single event in a flattened execution schedule.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleEvent {
+    Setup(FixtureBoundary),
+    RunTest(RunnableTest),
+    Teardown(FixtureBoundary),
+}
+
+/// Flatten an `ExecutionPlan` into the linear sequence of setup/run/teardown
+/// events a runner executes in order.
+///
+/// This is a straight re-expression of each `PlanStep`'s `setup`/`test`/
+/// `teardown` fields as one ordered stream; the plan already guarantees a
+/// scoped fixture's teardown event only appears after the last step that
+/// needs it, so no fixture is torn down early here.
+pub fn flatten_schedule(plan: ExecutionPlan) -> Vec<ScheduleEvent> {
+    let mut events = Vec::new();
+    for step in plan.steps {
+        events.extend(step.setup.into_iter().map(ScheduleEvent::Setup));
+        events.push(ScheduleEvent::RunTest(step.test));
+        events.extend(step.teardown.into_iter().map(ScheduleEvent::Teardown));
+    }
+    events
+}
+
+/// Minimal union-find over plan-step indices, used to group tests that
+/// must not run concurrently because they share a wider-scoped fixture.
+struct DisjointSet {
+    parent: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+        }
+    }
+
+    fn find(&mut self, i: usize) -> usize {
+        if self.parent[i] != i {
+            self.parent[i] = self.find(self.parent[i]);
+        }
+        self.parent[i]
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra != rb {
+            self.parent[ra] = rb;
+        }
+    }
+}
+
+/// A group of tests that must run serially because they (transitively)
+/// share a `Class`/`Module`/`Package`/`Session`-scoped fixture instance.
+/// Steps stay in execution-plan order so setup/teardown boundaries remain
+/// valid within the lane.
+#[derive(Debug, Clone)]
+pub struct Lane {
+    pub steps: Vec<PlanStep>,
+}
+
+/// A concurrency-aware schedule derived from an `ExecutionPlan`: lanes that
+/// are safe to run in parallel with each other, plus the degree of
+/// parallelism a runner should use.
+///
+/// Within a lane, tests share a non-`Function`-scoped fixture and must run
+/// one at a time (and in order, so the shared fixture's setup/teardown
+/// boundaries are respected). Across lanes, nothing is shared, so a runner
+/// may freely run up to `max_parallel` lanes at once.
+#[derive(Debug, Clone)]
+pub struct ConcurrencySchedule {
+    pub lanes: Vec<Lane>,
+    pub max_parallel: usize,
+}
+
+/// Partition an `ExecutionPlan` into parallel-safe lanes.
+///
+/// Two tests land in the same lane iff they share at least one
+/// `Class`/`Module`/`Package`/`Session`-scoped fixture instance (computed
+/// transitively: if A shares with B and B shares with C, all three end up
+/// in one lane even if A and C share nothing directly). Tests with only
+/// `Function`-scoped fixtures (or none at all) form singleton lanes and
+/// are free to run alongside anything else.
+pub fn build_concurrency_schedule(plan: ExecutionPlan, max_parallel: usize) -> ConcurrencySchedule {
+    let n = plan.steps.len();
+    let mut sets = DisjointSet::new(n);
+    let mut first_owner: HashMap<ScopeKey, usize> = HashMap::new();
+
+    for (i, step) in plan.steps.iter().enumerate() {
+        for fixture in &step.test.fixtures {
+            if fixture.scope == FixtureScope::Function {
+                continue; // function-scoped fixtures never force serialization
+            }
+            let key = scope_key(&step.test, fixture, i);
+            match first_owner.get(&key) {
+                Some(&owner) => sets.union(owner, i),
+                None => {
+                    first_owner.insert(key, i);
+                }
+            }
+        }
+    }
+
+    let mut lanes_by_root: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..n {
+        let root = sets.find(i);
+        lanes_by_root.entry(root).or_default().push(i);
+    }
+
+    // Preserve original plan order both within and across lanes.
+    let mut lane_indices: Vec<Vec<usize>> = lanes_by_root.into_values().collect();
+    lane_indices.sort_by_key(|indices| indices[0]);
+
+    let mut steps: Vec<Option<PlanStep>> = plan.steps.into_iter().map(Some).collect();
+    let lanes = lane_indices
+        .into_iter()
+        .map(|indices| Lane {
+            steps: indices
+                .into_iter()
+                .map(|i| steps[i].take().expect("each step belongs to exactly one lane"))
+                .collect(),
+        })
+        .collect();
+
+    ConcurrencySchedule { lanes, max_parallel }
+}
+
+/// A SplitMix64 PRNG, used only to drive `--shuffle`. Deliberately hand-rolled
+/// instead of pulling in the `rand` crate (not otherwise a dependency of this
+/// tree) for what's ultimately a few lines of Fisher-Yates: see
+/// <https://prng.di.unimi.it/splitmix64.c> for the reference algorithm. Not
+/// cryptographically secure, and not meant to be - only deterministic given a
+/// seed, which is all `--shuffle`/`--seed` reproduction needs.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// A value in `0..bound`, via Lemire's rejection-free-in-practice bias
+    /// reduction (good enough here: `bound` is always a small test count, so
+    /// the modulo bias `% bound` alone would introduce is negligible, but the
+    /// wide multiply costs nothing and removes the question entirely).
+    fn below(&mut self, bound: u64) -> u64 {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+}
+
+/// Generate a seed from the current time when the caller didn't pin one with
+/// `--seed`, so a fresh `--shuffle` run still picks a different order each
+/// time. Not meant to be unpredictable, just arbitrary.
+fn random_seed() -> u64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    SplitMix64(nanos ^ (std::process::id() as u64)).next_u64()
+}
+
+/// Shuffle resolved tests in place using a seeded PRNG.
+///
+/// Must be called AFTER `resolve_all` so that per-test fixture ordering
+/// (topological, within each `RunnableTest`) is untouched - only the
+/// order of tests relative to each other changes.
+///
+/// If `seed` is `None`, a random seed is generated so the caller can
+/// report it (e.g. via `HumanReporter`) for exact reproduction of a run.
+pub fn shuffle_tests(tests: &mut [RunnableTest], seed: Option<u64>) -> u64 {
+    shuffle_seeded(tests, seed)
+}
 
-        Ok(())
+/// Shuffle any slice in place with a seeded PRNG, generating a seed when
+/// none is given. Shared by `shuffle_tests` (for `tach test --shuffle`) and
+/// `tach list --shuffle` (which previews run order over node ids instead of
+/// `RunnableTest`s) so both reproduce a run with the exact same seed.
+///
+/// In-place Fisher-Yates: for `i` from `len - 1` down to `1`, draw `j` in
+/// `[0, i]` from the PRNG and swap `items[i]` with `items[j]`.
+pub fn shuffle_seeded<T>(items: &mut [T], seed: Option<u64>) -> u64 {
+    let seed = seed.unwrap_or_else(random_seed);
+    let mut rng = SplitMix64(seed);
+    for i in (1..items.len()).rev() {
+        let j = rng.below(i as u64 + 1) as usize;
+        items.swap(i, j);
+    }
+    seed
+}
+
+/// A compiled `--filter`/`--filter-regex` matcher against a test's
+/// [`RunnableTest::qualified_id`].
+#[derive(Clone)]
+pub enum TestFilter {
+    Substring(String),
+    Regex(Regex),
+}
+
+impl TestFilter {
+    pub fn matches(&self, test: &RunnableTest) -> bool {
+        let id = test.qualified_id();
+        match self {
+            TestFilter::Substring(needle) => id.contains(needle.as_str()),
+            TestFilter::Regex(re) => re.is_match(&id),
+        }
+    }
+}
+
+/// Compile a `--filter` substring or `--filter-regex` pattern into a
+/// [`TestFilter`]. At most one of `filter`/`filter_regex` should be set;
+/// if both are, the regex takes precedence.
+pub fn compile_test_filter(filter: Option<&str>, filter_regex: Option<&str>) -> Result<Option<TestFilter>> {
+    if let Some(pattern) = filter_regex {
+        let re = Regex::new(pattern).map_err(|e| anyhow!("invalid --filter-regex '{}': {}", pattern, e))?;
+        return Ok(Some(TestFilter::Regex(re)));
+    }
+    Ok(filter.map(|s| TestFilter::Substring(s.to_string())))
+}
+
+/// Filter `tests` in place, retaining only those matching `filter` (or all,
+/// if `filter` is `None`). Returns the number of tests deselected.
+pub fn filter_tests(tests: &mut Vec<RunnableTest>, filter: &Option<TestFilter>) -> usize {
+    let Some(filter) = filter else { return 0 };
+    let before = tests.len();
+    tests.retain(|test| filter.matches(test));
+    before - tests.len()
+}
+
+/// A compiled `--include`/`--ignore` glob set for selecting tests by
+/// `file_path`, following Deno's `FilePatterns` include/exclude model. A
+/// path is selected if it matches any include pattern (or none are given)
+/// and no ignore pattern.
+#[derive(Clone)]
+pub struct PathPatternSet {
+    includes: Vec<Pattern>,
+    ignores: Vec<Pattern>,
+}
+
+impl PathPatternSet {
+    pub fn matches(&self, path: &std::path::Path) -> bool {
+        let included = self.includes.is_empty() || self.includes.iter().any(|p| p.matches_path(path));
+        let ignored = self.ignores.iter().any(|p| p.matches_path(path));
+        included && !ignored
+    }
+}
+
+/// Compile `--include`/`--ignore` glob strings into a [`PathPatternSet`].
+pub fn compile_path_patterns(includes: &[String], ignores: &[String]) -> Result<PathPatternSet> {
+    let compile = |globs: &[String], flag: &str| -> Result<Vec<Pattern>> {
+        globs
+            .iter()
+            .map(|g| Pattern::new(g).map_err(|e| anyhow!("invalid {} glob '{}': {}", flag, g, e)))
+            .collect()
+    };
+    Ok(PathPatternSet {
+        includes: compile(includes, "--include")?,
+        ignores: compile(ignores, "--ignore")?,
+    })
+}
+
+/// Filter `tests` in place by `patterns` (matched against `file_path`).
+/// Returns the number of tests deselected.
+pub fn filter_tests_by_path(tests: &mut Vec<RunnableTest>, patterns: &PathPatternSet) -> usize {
+    let before = tests.len();
+    tests.retain(|test| patterns.matches(&test.file_path));
+    before - tests.len()
+}
+
+// =============================================================================
+// Watch Mode: Change Impact (Phase 9.7)
+// =============================================================================
+
+/// Outcome of intersecting a set of changed files against a resolved test
+/// suite, for watch mode's "only re-run what changed" mode.
+pub enum ChangeImpact {
+    /// Only these tests are affected; everything else can be skipped.
+    Affected(Vec<RunnableTest>),
+    /// A changed file isn't traceable to any test file or fixture source, so
+    /// we can't prove nothing depends on it transitively - run everything.
+    FullRun,
+}
+
+/// Narrow `tests` down to those affected by `changed_paths`, mirroring Deno's
+/// `has_graph_root_local_dependent_changed`: a test is affected if a change
+/// touched its own file, one of its resolved fixtures' source files, or a
+/// file it transitively imports (per `import_graph`). An empty
+/// `changed_paths` (the initial run) means "run everything". Any changed
+/// path `import_graph` can't trace to a test file at all - because nothing
+/// resolves its imports back that far, e.g. a dynamic import or a file
+/// outside the project tree - falls back to a full run rather than risk
+/// silently skipping an affected test.
+pub fn affected_by_changes(
+    tests: &[RunnableTest],
+    changed_paths: &[PathBuf],
+    import_graph: &ImportGraph,
+) -> ChangeImpact {
+    if changed_paths.is_empty() {
+        return ChangeImpact::Affected(tests.to_vec());
+    }
+
+    let depends_on = |test: &RunnableTest, changed: &PathBuf| {
+        test.file_path == *changed
+            || test.fixtures.iter().any(|f| f.source_file == *changed)
+            || import_graph.transitive_dependents(changed).contains(&test.file_path)
+    };
+
+    for changed in changed_paths {
+        if !tests.iter().any(|t| depends_on(t, changed)) {
+            return ChangeImpact::FullRun;
+        }
     }
+
+    let affected = tests
+        .iter()
+        .filter(|t| changed_paths.iter().any(|changed| depends_on(t, changed)))
+        .cloned()
+        .collect();
+
+    ChangeImpact::Affected(affected)
 }
 
 // =============================================================================
@@ -310,6 +1144,10 @@ mod tests {
             is_async: false,
             line_number: 1,
             parametrized_args: vec![],
+            param_sets: vec![],
+            markers: vec![],
+            is_doctest: false,
+            xfail: None,
         }
     }
 
@@ -354,99 +1192,359 @@ mod tests {
     }
 
     #[test]
-    fn test_cycle_detection() {
-        // Create a cyclic dependency: a -> b -> a
+    fn test_nearest_conftest_wins_over_ancestor_conftest() {
+        // root conftest.py and a nested tests/integration/conftest.py both
+        // define "db" - a test in that subdirectory should get the nearer one.
         let discovery = DiscoveryResult {
             modules: vec![
                 TestModule {
                     path: PathBuf::from("conftest.py"),
                     tests: vec![],
-                    fixtures: vec![
-                        make_fixture("a", vec!["b"]),
-                        make_fixture("b", vec!["a"]), // Cycle!
-                    ],
+                    fixtures: vec![make_fixture("db", vec![])],
                 },
                 TestModule {
-                    path: PathBuf::from("test_cycle.py"),
-                    tests: vec![make_test("test_foo", vec!["a"])],
-                    fixtures: vec![],
+                    path: PathBuf::from("tests/integration/conftest.py"),
+                    tests: vec![],
+                    fixtures: vec![make_fixture("db", vec!["connection"])],
                 },
             ],
         };
 
         let registry = FixtureRegistry::from_discovery(&discovery);
-        let resolver = Resolver::new(&registry);
-        let (runnable, errors) = resolver.resolve_all(&discovery);
 
-        // Should have no runnable tests and one error
-        assert!(
-            runnable.is_empty(),
-            "Cyclic dependency should fail resolution"
-        );
-        assert!(!errors.is_empty(), "Should have resolution error");
+        let nested_path = PathBuf::from("tests/integration/test_api.py");
+        let (fixture, source) = registry.lookup("db", &nested_path, "test_simple").unwrap();
+        assert!(!fixture.dependencies.is_empty(), "should prefer the nearer conftest");
+        assert_eq!(source, PathBuf::from("tests/integration/conftest.py"));
 
-        // Verify it's a CyclicDependency error
-        match &errors[0] {
-            ResolutionError::CyclicDependency { cycle, .. } => {
-                assert!(cycle.contains(&"a".to_string()), "Cycle should contain 'a'");
-                assert!(cycle.contains(&"b".to_string()), "Cycle should contain 'b'");
-            }
-            _ => panic!("Expected CyclicDependency error"),
-        }
+        // A sibling directory without its own conftest falls back to the root.
+        let sibling_path = PathBuf::from("tests/unit/test_models.py");
+        let (fixture, source) = registry.lookup("db", &sibling_path, "test_simple").unwrap();
+        assert!(fixture.dependencies.is_empty(), "should fall back to the root conftest");
+        assert_eq!(source, PathBuf::from("conftest.py"));
     }
 
     #[test]
-    fn test_missing_fixture_error() {
-        // Create a test that depends on a non-existent fixture
+    fn test_conftest_only_visible_at_or_below_its_directory() {
+        // A conftest.py under tests/integration/ must not leak into tests/unit/.
         let discovery = DiscoveryResult {
             modules: vec![TestModule {
-                path: PathBuf::from("test_missing.py"),
-                tests: vec![make_test("test_foo", vec!["nonexistent"])],
-                fixtures: vec![],
+                path: PathBuf::from("tests/integration/conftest.py"),
+                tests: vec![],
+                fixtures: vec![make_fixture("api_client", vec![])],
             }],
         };
 
         let registry = FixtureRegistry::from_discovery(&discovery);
-        let resolver = Resolver::new(&registry);
-        let (runnable, errors) = resolver.resolve_all(&discovery);
-
-        // Should have no runnable tests and one error
-        assert!(
-            runnable.is_empty(),
-            "Missing fixture should fail resolution"
-        );
-        assert!(!errors.is_empty(), "Should have resolution error");
-
-        // Verify it's a MissingFixture error
-        match &errors[0] {
-            ResolutionError::MissingFixture { fixture, test } => {
-                assert_eq!(fixture, "nonexistent");
-                assert_eq!(test, "test_foo");
-            }
-            _ => panic!("Expected MissingFixture error"),
-        }
+        let unrelated_path = PathBuf::from("tests/unit/test_models.py");
+        assert!(registry.lookup("api_client", &unrelated_path, "test_simple").is_none());
     }
 
     #[test]
-    fn test_transitive_dependency_resolution() {
-        // Create a chain: test_foo -> db -> connection -> base
+    fn test_shadowed_fixture_warning_when_shape_differs() {
         let discovery = DiscoveryResult {
             modules: vec![
                 TestModule {
                     path: PathBuf::from("conftest.py"),
                     tests: vec![],
-                    fixtures: vec![
-                        make_fixture("base", vec![]),
-                        make_fixture("connection", vec!["base"]),
-                        make_fixture("db", vec!["connection"]),
-                    ],
+                    fixtures: vec![make_fixture("db", vec![])],
                 },
                 TestModule {
-                    path: PathBuf::from("test_chain.py"),
-                    tests: vec![make_test("test_foo", vec!["db"])],
-                    fixtures: vec![],
-                },
-            ],
+                    path: PathBuf::from("test_local.py"),
+                    tests: vec![],
+                    fixtures: vec![make_fixture("db", vec!["connection"])],
+                },
+            ],
+        };
+
+        let registry = FixtureRegistry::from_discovery(&discovery);
+
+        assert_eq!(registry.warnings().len(), 1);
+        match &registry.warnings()[0] {
+            ResolutionWarning::ShadowedFixture { name, shadowing_file, shadowed_file } => {
+                assert_eq!(name, "db");
+                assert_eq!(shadowing_file, &PathBuf::from("test_local.py"));
+                assert_eq!(shadowed_file, &PathBuf::from("conftest.py"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_no_shadow_warning_when_override_has_same_shape() {
+        let discovery = DiscoveryResult {
+            modules: vec![
+                TestModule {
+                    path: PathBuf::from("conftest.py"),
+                    tests: vec![],
+                    fixtures: vec![make_fixture("db", vec!["connection"])],
+                },
+                TestModule {
+                    path: PathBuf::from("test_local.py"),
+                    tests: vec![],
+                    fixtures: vec![make_fixture("db", vec!["connection"])],
+                },
+            ],
+        };
+
+        let registry = FixtureRegistry::from_discovery(&discovery);
+
+        assert!(registry.warnings().is_empty(), "same dependencies and scope is idiomatic, not a shadow warning");
+    }
+
+    #[test]
+    fn test_cycle_detection() {
+        // Create a cyclic dependency: a -> b -> a
+        let discovery = DiscoveryResult {
+            modules: vec![
+                TestModule {
+                    path: PathBuf::from("conftest.py"),
+                    tests: vec![],
+                    fixtures: vec![
+                        make_fixture("a", vec!["b"]),
+                        make_fixture("b", vec!["a"]), // Cycle!
+                    ],
+                },
+                TestModule {
+                    path: PathBuf::from("test_cycle.py"),
+                    tests: vec![make_test("test_foo", vec!["a"])],
+                    fixtures: vec![],
+                },
+            ],
+        };
+
+        let registry = FixtureRegistry::from_discovery(&discovery);
+        let resolver = Resolver::new(&registry);
+        let (runnable, errors) = resolver.resolve_all(&discovery);
+
+        // Should have no runnable tests and one error
+        assert!(
+            runnable.is_empty(),
+            "Cyclic dependency should fail resolution"
+        );
+        assert!(!errors.is_empty(), "Should have resolution error");
+
+        // Verify it's a CyclicDependency error
+        match &errors[0] {
+            ResolutionError::CyclicDependency { cycle, path, .. } => {
+                assert!(cycle.contains(&"a".to_string()), "Cycle should contain 'a'");
+                assert!(cycle.contains(&"b".to_string()), "Cycle should contain 'b'");
+                // path is prefixed with the test entry point, then the acyclic
+                // chain down to the repeated node: test_foo -> a -> b -> a
+                assert_eq!(path, &vec!["test_foo".to_string(), "a".to_string(), "b".to_string(), "a".to_string()]);
+            }
+            _ => panic!("Expected CyclicDependency error"),
+        }
+    }
+
+    #[test]
+    fn test_missing_fixture_error() {
+        // Create a test that depends on a non-existent fixture
+        let discovery = DiscoveryResult {
+            modules: vec![TestModule {
+                path: PathBuf::from("test_missing.py"),
+                tests: vec![make_test("test_foo", vec!["nonexistent"])],
+                fixtures: vec![],
+            }],
+        };
+
+        let registry = FixtureRegistry::from_discovery(&discovery);
+        let resolver = Resolver::new(&registry);
+        let (runnable, errors) = resolver.resolve_all(&discovery);
+
+        // Should have no runnable tests and one error
+        assert!(
+            runnable.is_empty(),
+            "Missing fixture should fail resolution"
+        );
+        assert!(!errors.is_empty(), "Should have resolution error");
+
+        // Verify it's a MissingFixture error
+        match &errors[0] {
+            ResolutionError::MissingFixture { fixture, test, .. } => {
+                assert_eq!(fixture, "nonexistent");
+                assert_eq!(test, "test_foo");
+            }
+            _ => panic!("Expected MissingFixture error"),
+        }
+    }
+
+    #[test]
+    fn test_levenshtein_distance_basic_cases() {
+        assert_eq!(levenshtein_distance("tmp_path", "tmp_path"), 0);
+        assert_eq!(levenshtein_distance("tmp_pat", "tmp_path"), 1);
+        assert_eq!(levenshtein_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_suggest_fixture_names_catches_typo() {
+        let candidates = vec!["tmp_path".to_string(), "db".to_string(), "monkeypatch".to_string()];
+        let suggestions = suggest_fixture_names("tmp_pat", &candidates);
+        assert_eq!(suggestions, vec!["tmp_path".to_string()]);
+    }
+
+    #[test]
+    fn test_suggest_fixture_names_caps_at_three_nearest() {
+        let candidates = vec![
+            "dba".to_string(),
+            "dbb".to_string(),
+            "dbc".to_string(),
+            "dbd".to_string(),
+        ];
+        let suggestions = suggest_fixture_names("db", &candidates);
+        assert_eq!(suggestions.len(), 3);
+    }
+
+    #[test]
+    fn test_missing_fixture_suggests_close_name() {
+        // "db" is a typo for the registered "dbx" fixture.
+        let discovery = DiscoveryResult {
+            modules: vec![
+                TestModule {
+                    path: PathBuf::from("conftest.py"),
+                    tests: vec![],
+                    fixtures: vec![make_fixture("dbx", vec![])],
+                },
+                TestModule {
+                    path: PathBuf::from("test_typo.py"),
+                    tests: vec![make_test("test_foo", vec!["db"])],
+                    fixtures: vec![],
+                },
+            ],
+        };
+
+        let registry = FixtureRegistry::from_discovery(&discovery);
+        let resolver = Resolver::new(&registry);
+        let (_, errors) = resolver.resolve_all(&discovery);
+
+        match &errors[0] {
+            ResolutionError::MissingFixture { suggestions, .. } => {
+                assert_eq!(suggestions, &vec!["dbx".to_string()]);
+            }
+            _ => panic!("Expected MissingFixture error"),
+        }
+    }
+
+    #[test]
+    fn test_missing_fixture_records_full_resolution_path() {
+        // test_foo -> db -> connection -> (missing) base
+        let discovery = DiscoveryResult {
+            modules: vec![
+                TestModule {
+                    path: PathBuf::from("conftest.py"),
+                    tests: vec![],
+                    fixtures: vec![
+                        make_fixture("connection", vec!["base"]),
+                        make_fixture("db", vec!["connection"]),
+                    ],
+                },
+                TestModule {
+                    path: PathBuf::from("test_chain.py"),
+                    tests: vec![make_test("test_foo", vec!["db"])],
+                    fixtures: vec![],
+                },
+            ],
+        };
+
+        let registry = FixtureRegistry::from_discovery(&discovery);
+        let resolver = Resolver::new(&registry);
+        let (_, errors) = resolver.resolve_all(&discovery);
+
+        match &errors[0] {
+            ResolutionError::MissingFixture { path, fixture, .. } => {
+                assert_eq!(fixture, "base");
+                assert_eq!(
+                    path,
+                    &vec![
+                        "test_foo".to_string(),
+                        "db".to_string(),
+                        "connection".to_string(),
+                        "base".to_string(),
+                    ]
+                );
+            }
+            _ => panic!("Expected MissingFixture error"),
+        }
+    }
+
+    #[test]
+    fn test_missing_fixtures_are_all_collected_not_just_the_first() {
+        // Three independent direct deps, all unknown - every one should be
+        // reported, not just the first hit.
+        let discovery = DiscoveryResult {
+            modules: vec![TestModule {
+                path: PathBuf::from("test_many_missing.py"),
+                tests: vec![make_test("test_foo", vec!["alpha", "beta", "gamma"])],
+                fixtures: vec![],
+            }],
+        };
+
+        let registry = FixtureRegistry::from_discovery(&discovery);
+        let resolver = Resolver::new(&registry);
+        let (runnable, errors) = resolver.resolve_all(&discovery);
+
+        assert!(runnable.is_empty());
+        assert_eq!(errors.len(), 3, "all three missing fixtures should be reported");
+        let missing: Vec<_> = errors
+            .iter()
+            .map(|e| match e {
+                ResolutionError::MissingFixture { fixture, .. } => fixture.as_str(),
+                _ => panic!("Expected MissingFixture error"),
+            })
+            .collect();
+        assert_eq!(missing, vec!["alpha", "beta", "gamma"]);
+    }
+
+    #[test]
+    fn test_failed_branch_is_not_rewalked_for_a_shared_fixture() {
+        // "shared" is missing and depended on by both "a" and "b", which
+        // test_foo both depend on directly - the missing-fixture diagnostic
+        // must only be reported once.
+        let discovery = DiscoveryResult {
+            modules: vec![
+                TestModule {
+                    path: PathBuf::from("conftest.py"),
+                    tests: vec![],
+                    fixtures: vec![
+                        make_fixture("a", vec!["shared"]),
+                        make_fixture("b", vec!["shared"]),
+                    ],
+                },
+                TestModule {
+                    path: PathBuf::from("test_shared.py"),
+                    tests: vec![make_test("test_foo", vec!["a", "b"])],
+                    fixtures: vec![],
+                },
+            ],
+        };
+
+        let registry = FixtureRegistry::from_discovery(&discovery);
+        let resolver = Resolver::new(&registry);
+        let (runnable, errors) = resolver.resolve_all(&discovery);
+
+        assert!(runnable.is_empty());
+        assert_eq!(errors.len(), 1, "shared missing fixture reported once, not per-branch");
+    }
+
+    #[test]
+    fn test_transitive_dependency_resolution() {
+        // Create a chain: test_foo -> db -> connection -> base
+        let discovery = DiscoveryResult {
+            modules: vec![
+                TestModule {
+                    path: PathBuf::from("conftest.py"),
+                    tests: vec![],
+                    fixtures: vec![
+                        make_fixture("base", vec![]),
+                        make_fixture("connection", vec!["base"]),
+                        make_fixture("db", vec!["connection"]),
+                    ],
+                },
+                TestModule {
+                    path: PathBuf::from("test_chain.py"),
+                    tests: vec![make_test("test_foo", vec!["db"])],
+                    fixtures: vec![],
+                },
+            ],
         };
 
         let registry = FixtureRegistry::from_discovery(&discovery);
@@ -464,6 +1562,141 @@ mod tests {
         assert_eq!(test.fixtures[2].name, "db");
     }
 
+    // =========================================================================
+    // Parametrized Fixture Expansion Tests
+    // =========================================================================
+
+    fn make_parametrized_fixture(name: &str, params: Vec<&str>) -> FixtureDefinition {
+        FixtureDefinition {
+            params: Some(params.into_iter().map(|s| s.to_string()).collect()),
+            ..make_fixture(name, vec![])
+        }
+    }
+
+    #[test]
+    fn test_parametrized_fixture_expands_into_one_test_per_value() {
+        let discovery = DiscoveryResult {
+            modules: vec![
+                TestModule {
+                    path: PathBuf::from("conftest.py"),
+                    tests: vec![],
+                    fixtures: vec![make_parametrized_fixture("backend", vec!["sqlite", "postgres"])],
+                },
+                TestModule {
+                    path: PathBuf::from("test_db.py"),
+                    tests: vec![make_test("test_query", vec!["backend"])],
+                    fixtures: vec![],
+                },
+            ],
+        };
+
+        let registry = FixtureRegistry::from_discovery(&discovery);
+        let resolver = Resolver::new(&registry);
+        let (runnable, errors) = resolver.resolve_all(&discovery);
+
+        assert!(errors.is_empty());
+        let names: Vec<_> = runnable.iter().map(|t| t.test_name.as_str()).collect();
+        assert_eq!(names, vec!["test_query[sqlite]", "test_query[postgres]"]);
+
+        // Each instance binds its own concrete value on the resolved fixture.
+        let values: Vec<_> = runnable[0]
+            .fixtures
+            .iter()
+            .map(|f| f.param_value.clone())
+            .collect();
+        assert_eq!(values, vec![Some("sqlite".to_string())]);
+    }
+
+    #[test]
+    fn test_parametrized_fixtures_take_cartesian_product() {
+        let discovery = DiscoveryResult {
+            modules: vec![
+                TestModule {
+                    path: PathBuf::from("conftest.py"),
+                    tests: vec![],
+                    fixtures: vec![
+                        make_parametrized_fixture("backend", vec!["sqlite", "postgres"]),
+                        make_parametrized_fixture("tz", vec!["utc", "local"]),
+                    ],
+                },
+                TestModule {
+                    path: PathBuf::from("test_db.py"),
+                    tests: vec![make_test("test_query", vec!["backend", "tz"])],
+                    fixtures: vec![],
+                },
+            ],
+        };
+
+        let registry = FixtureRegistry::from_discovery(&discovery);
+        let resolver = Resolver::new(&registry);
+        let (runnable, errors) = resolver.resolve_all(&discovery);
+
+        assert!(errors.is_empty());
+        let names: Vec<_> = runnable.iter().map(|t| t.test_name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "test_query[sqlite-utc]",
+                "test_query[sqlite-local]",
+                "test_query[postgres-utc]",
+                "test_query[postgres-local]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_test_level_parametrize_expands_without_a_fixture() {
+        let discovery = DiscoveryResult {
+            modules: vec![TestModule {
+                path: PathBuf::from("test_math.py"),
+                tests: vec![TestCase {
+                    name: "test_square".to_string(),
+                    dependencies: vec!["n".to_string()],
+                    is_async: false,
+                    line_number: 1,
+                    xfail: None,
+                    parametrized_args: vec![ParametrizeArg {
+                        names: vec!["n".to_string()],
+                        rows: Some(vec!["2".to_string(), "3".to_string()]),
+                    }],
+                    param_sets: vec![],
+                    markers: vec![],
+                    is_doctest: false,
+                }],
+                fixtures: vec![],
+            }],
+        };
+
+        let registry = FixtureRegistry::from_discovery(&discovery);
+        let resolver = Resolver::new(&registry);
+        let (runnable, errors) = resolver.resolve_all(&discovery);
+
+        assert!(errors.is_empty());
+        let names: Vec<_> = runnable.iter().map(|t| t.test_name.as_str()).collect();
+        assert_eq!(names, vec!["test_square[2]", "test_square[3]"]);
+        // "n" is the parametrize arg, not a fixture - nothing to resolve.
+        assert!(runnable[0].fixtures.is_empty());
+    }
+
+    #[test]
+    fn test_unparametrized_test_is_not_expanded() {
+        let discovery = DiscoveryResult {
+            modules: vec![TestModule {
+                path: PathBuf::from("test_plain.py"),
+                tests: vec![make_test("test_plain", vec![])],
+                fixtures: vec![],
+            }],
+        };
+
+        let registry = FixtureRegistry::from_discovery(&discovery);
+        let resolver = Resolver::new(&registry);
+        let (runnable, errors) = resolver.resolve_all(&discovery);
+
+        assert!(errors.is_empty());
+        assert_eq!(runnable.len(), 1);
+        assert_eq!(runnable[0].test_name, "test_plain");
+    }
+
     // =========================================================================
     // Phase 6: Builtin Fixture Tests
     // =========================================================================
@@ -521,6 +1754,199 @@ mod tests {
         assert_eq!(runnable.len(), 4);
     }
 
+    #[test]
+    fn test_shuffle_tests_is_deterministic_for_seed() {
+        let make = |n: usize| {
+            (0..n)
+                .map(|i| RunnableTest {
+                    file_path: PathBuf::from("test_a.py"),
+                    test_name: format!("test_{}", i),
+                    is_async: false,
+                    fixtures: vec![],
+                    xfail_strict: None,
+                    line_number: 1,
+                    permissions: Permissions::default(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut a = make(20);
+        let mut b = make(20);
+
+        let seed_a = shuffle_tests(&mut a, Some(42));
+        let seed_b = shuffle_tests(&mut b, Some(42));
+
+        assert_eq!(seed_a, seed_b);
+        let names_a: Vec<_> = a.iter().map(|t| t.test_name.clone()).collect();
+        let names_b: Vec<_> = b.iter().map(|t| t.test_name.clone()).collect();
+        assert_eq!(names_a, names_b);
+    }
+
+    #[test]
+    fn test_shuffle_tests_generates_seed_when_absent() {
+        let original: Vec<RunnableTest> = (0..20)
+            .map(|i| RunnableTest {
+                file_path: PathBuf::from("test_a.py"),
+                test_name: format!("test_{}", i),
+                is_async: false,
+                fixtures: vec![],
+                xfail_strict: None,
+                line_number: 1,
+                permissions: Permissions::default(),
+            })
+            .collect();
+
+        let mut tests = original.clone();
+        let seed = shuffle_tests(&mut tests, None);
+        // Re-running with the reported seed must reproduce the same order.
+        let mut replay = original.clone();
+        let _ = shuffle_tests(&mut replay, Some(seed));
+        let names: Vec<_> = tests.iter().map(|t| t.test_name.clone()).collect();
+        let replay_names: Vec<_> = replay.iter().map(|t| t.test_name.clone()).collect();
+        assert_eq!(names, replay_names);
+    }
+
+    #[test]
+    fn test_shuffle_seeded_is_deterministic_over_arbitrary_items() {
+        let mut a: Vec<String> = (0..20).map(|i| format!("id_{}", i)).collect();
+        let mut b = a.clone();
+
+        let seed_a = shuffle_seeded(&mut a, Some(7));
+        let seed_b = shuffle_seeded(&mut b, Some(7));
+
+        assert_eq!(seed_a, seed_b);
+        assert_eq!(a, b);
+    }
+
+    fn make_runnable(file: &str, name: &str) -> RunnableTest {
+        RunnableTest {
+            file_path: PathBuf::from(file),
+            test_name: name.to_string(),
+            is_async: false,
+            fixtures: vec![],
+            xfail_strict: None,
+            line_number: 1,
+            permissions: Permissions::default(),
+        }
+    }
+
+    #[test]
+    fn test_qualified_id_format() {
+        let test = make_runnable("tests/foo.py", "test_bar");
+        assert_eq!(test.qualified_id(), "tests/foo.py::test_bar");
+    }
+
+    #[test]
+    fn test_affected_by_changes_empty_paths_runs_everything() {
+        let tests = vec![make_runnable("tests/foo.py", "test_bar")];
+        let impact = affected_by_changes(&tests, &[], &ImportGraph::empty());
+        assert!(matches!(impact, ChangeImpact::Affected(affected) if affected.len() == 1));
+    }
+
+    #[test]
+    fn test_affected_by_changes_matches_own_file() {
+        let tests = vec![
+            make_runnable("tests/foo.py", "test_bar"),
+            make_runnable("tests/baz.py", "test_qux"),
+        ];
+        let changed = vec![PathBuf::from("tests/foo.py")];
+        let impact = affected_by_changes(&tests, &changed, &ImportGraph::empty());
+        match impact {
+            ChangeImpact::Affected(affected) => {
+                assert_eq!(affected.len(), 1);
+                assert_eq!(affected[0].test_name, "test_bar");
+            }
+            ChangeImpact::FullRun => panic!("expected a narrowed run"),
+        }
+    }
+
+    #[test]
+    fn test_affected_by_changes_unknown_path_falls_back_to_full_run() {
+        let tests = vec![make_runnable("tests/foo.py", "test_bar")];
+        let changed = vec![PathBuf::from("unrelated.py")];
+        let impact = affected_by_changes(&tests, &changed, &ImportGraph::empty());
+        assert!(matches!(impact, ChangeImpact::FullRun));
+    }
+
+    #[test]
+    fn test_filter_tests_substring() {
+        let mut tests = vec![
+            make_runnable("tests/foo.py", "test_login"),
+            make_runnable("tests/foo.py", "test_logout"),
+            make_runnable("tests/bar.py", "test_unrelated"),
+        ];
+
+        let filter = compile_test_filter(Some("login"), None).unwrap();
+        let deselected = filter_tests(&mut tests, &filter);
+
+        assert_eq!(deselected, 2);
+        assert_eq!(tests.len(), 1);
+        assert_eq!(tests[0].test_name, "test_login");
+    }
+
+    #[test]
+    fn test_filter_tests_regex() {
+        let mut tests = vec![
+            make_runnable("tests/foo.py", "test_login"),
+            make_runnable("tests/foo.py", "test_logout"),
+            make_runnable("tests/bar.py", "test_unrelated"),
+        ];
+
+        let filter = compile_test_filter(None, Some("^tests/foo\\.py::test_log(in|out)$")).unwrap();
+        let deselected = filter_tests(&mut tests, &filter);
+
+        assert_eq!(deselected, 1);
+        assert_eq!(tests.len(), 2);
+    }
+
+    #[test]
+    fn test_compile_test_filter_invalid_regex() {
+        let result = compile_test_filter(None, Some("("));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_filter_tests_none_is_noop() {
+        let mut tests = vec![make_runnable("tests/foo.py", "test_login")];
+        let deselected = filter_tests(&mut tests, &None);
+        assert_eq!(deselected, 0);
+        assert_eq!(tests.len(), 1);
+    }
+
+    #[test]
+    fn test_path_patterns_include_and_ignore() {
+        let mut tests = vec![
+            make_runnable("tests/unit/test_a.py", "test_1"),
+            make_runnable("tests/slow/test_b.py", "test_2"),
+            make_runnable("other/test_c.py", "test_3"),
+        ];
+
+        let patterns = compile_path_patterns(
+            &["tests/**/*.py".to_string()],
+            &["tests/slow/**".to_string()],
+        )
+        .unwrap();
+        let deselected = filter_tests_by_path(&mut tests, &patterns);
+
+        assert_eq!(deselected, 2);
+        assert_eq!(tests.len(), 1);
+        assert_eq!(tests[0].file_path, PathBuf::from("tests/unit/test_a.py"));
+    }
+
+    #[test]
+    fn test_path_patterns_no_include_matches_everything() {
+        let mut tests = vec![make_runnable("tests/unit/test_a.py", "test_1")];
+        let patterns = compile_path_patterns(&[], &[]).unwrap();
+        let deselected = filter_tests_by_path(&mut tests, &patterns);
+        assert_eq!(deselected, 0);
+    }
+
+    #[test]
+    fn test_compile_path_patterns_invalid_glob() {
+        let result = compile_path_patterns(&["[".to_string()], &[]);
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_mixed_builtin_and_user_fixtures() {
         // Test depends on both builtin and user-defined fixture
@@ -549,4 +1975,264 @@ mod tests {
         assert_eq!(runnable[0].fixtures.len(), 1);
         assert_eq!(runnable[0].fixtures[0].name, "db");
     }
+
+    // =========================================================================
+    // Execution Plan Tests
+    // =========================================================================
+
+    fn make_runnable(
+        file: &str,
+        test_name: &str,
+        fixtures: Vec<(&str, FixtureScope)>,
+    ) -> RunnableTest {
+        RunnableTest {
+            file_path: PathBuf::from(file),
+            test_name: test_name.to_string(),
+            is_async: false,
+            fixtures: fixtures
+                .into_iter()
+                .map(|(name, scope)| ResolvedFixture {
+                    name: name.to_string(),
+                    source_file: PathBuf::from(file),
+                    scope,
+                    param_value: None,
+                })
+                .collect(),
+            xfail_strict: None,
+            line_number: 1,
+            permissions: Permissions::default(),
+        }
+    }
+
+    #[test]
+    fn test_plan_session_fixture_set_up_once_and_torn_down_last() {
+        let tests = vec![
+            make_runnable("test_a.py", "test_1", vec![("db", FixtureScope::Session)]),
+            make_runnable("test_b.py", "test_2", vec![("db", FixtureScope::Session)]),
+        ];
+        let plan = build_execution_plan(tests);
+
+        assert_eq!(plan.steps[0].setup[0].fixture_name, "db");
+        assert!(plan.steps[1].setup.is_empty(), "session fixture is shared, not rebuilt");
+        assert!(plan.steps[0].teardown.is_empty());
+        assert_eq!(plan.steps[1].teardown[0].fixture_name, "db");
+    }
+
+    #[test]
+    fn test_plan_function_fixture_rebuilt_per_test() {
+        let tests = vec![
+            make_runnable("test_a.py", "test_1", vec![("tmp", FixtureScope::Function)]),
+            make_runnable("test_a.py", "test_2", vec![("tmp", FixtureScope::Function)]),
+        ];
+        let plan = build_execution_plan(tests);
+
+        // Every test gets its own setup AND teardown of a function-scoped fixture.
+        for step in &plan.steps {
+            assert_eq!(step.setup.len(), 1);
+            assert_eq!(step.teardown.len(), 1);
+        }
+    }
+
+    #[test]
+    fn test_plan_groups_tests_by_module_even_if_interleaved() {
+        let tests = vec![
+            make_runnable("test_a.py", "test_1", vec![("db", FixtureScope::Module)]),
+            make_runnable("test_b.py", "test_2", vec![("db", FixtureScope::Module)]),
+            make_runnable("test_a.py", "test_3", vec![("db", FixtureScope::Module)]),
+        ];
+        let plan = build_execution_plan(tests);
+
+        // Reordered so both test_a.py tests are contiguous, sharing one `db` instance.
+        assert_eq!(plan.steps[0].test.file_path, PathBuf::from("test_a.py"));
+        assert_eq!(plan.steps[1].test.file_path, PathBuf::from("test_a.py"));
+        assert_eq!(plan.steps[2].test.file_path, PathBuf::from("test_b.py"));
+        assert_eq!(plan.steps[0].setup[0].fixture_name, "db");
+        assert!(plan.steps[1].setup.is_empty());
+        assert_eq!(plan.steps[1].teardown[0].fixture_name, "db");
+    }
+
+    #[test]
+    fn test_plan_setup_order_widest_scope_first() {
+        let tests = vec![make_runnable(
+            "test_a.py",
+            "test_1",
+            vec![
+                ("conn", FixtureScope::Function),
+                ("db", FixtureScope::Session),
+                ("cfg", FixtureScope::Module),
+            ],
+        )];
+        let plan = build_execution_plan(tests);
+
+        let setup_names: Vec<_> = plan.steps[0]
+            .setup
+            .iter()
+            .map(|b| b.fixture_name.as_str())
+            .collect();
+        assert_eq!(setup_names, vec!["db", "cfg", "conn"]);
+
+        // Teardown on the same (only) step must unwind in the opposite order.
+        let teardown_names: Vec<_> = plan.steps[0]
+            .teardown
+            .iter()
+            .map(|b| b.fixture_name.as_str())
+            .collect();
+        assert_eq!(teardown_names, vec!["conn", "cfg", "db"]);
+    }
+
+    #[test]
+    fn test_plan_class_scope_shared_within_class_only() {
+        let tests = vec![
+            make_runnable(
+                "test_a.py",
+                "TestFoo::test_1",
+                vec![("client", FixtureScope::Class)],
+            ),
+            make_runnable(
+                "test_a.py",
+                "TestFoo::test_2",
+                vec![("client", FixtureScope::Class)],
+            ),
+            make_runnable(
+                "test_a.py",
+                "TestBar::test_3",
+                vec![("client", FixtureScope::Class)],
+            ),
+        ];
+        let plan = build_execution_plan(tests);
+
+        // Grouping sorts by class name, so TestBar::test_3 runs first with
+        // its own instance; the two TestFoo tests follow and share one.
+        assert_eq!(plan.steps[1].setup[0].fixture_name, "client");
+        assert!(plan.steps[2].setup.is_empty(), "same class, fixture already live");
+        assert_eq!(plan.steps[2].teardown[0].fixture_name, "client");
+        // Different class gets its own instance.
+        assert_eq!(plan.steps[0].setup[0].fixture_name, "client");
+    }
+
+    #[test]
+    fn test_flatten_schedule_preserves_setup_run_teardown_order() {
+        let tests = vec![
+            make_runnable("test_a.py", "test_1", vec![("db", FixtureScope::Module)]),
+            make_runnable("test_a.py", "test_2", vec![("db", FixtureScope::Module)]),
+        ];
+        let plan = build_execution_plan(tests);
+        let events = flatten_schedule(plan);
+
+        assert_eq!(events.len(), 4, "one setup, two run events, one teardown");
+        assert!(matches!(events[0], ScheduleEvent::Setup(_)));
+        assert!(matches!(events[1], ScheduleEvent::RunTest(_)));
+        assert!(matches!(events[2], ScheduleEvent::RunTest(_)));
+        assert!(matches!(events[3], ScheduleEvent::Teardown(_)));
+    }
+
+    #[test]
+    fn test_flatten_schedule_teardown_waits_for_last_dependent_test() {
+        let tests = vec![
+            make_runnable("test_a.py", "test_1", vec![("db", FixtureScope::Session)]),
+            make_runnable("test_b.py", "test_2", vec![("cache", FixtureScope::Function)]),
+            make_runnable("test_c.py", "test_3", vec![("db", FixtureScope::Session)]),
+        ];
+        let plan = build_execution_plan(tests);
+        let events = flatten_schedule(plan);
+
+        let db_teardown_index = events
+            .iter()
+            .position(|e| matches!(e, ScheduleEvent::Teardown(b) if b.fixture_name == "db"))
+            .expect("db is torn down somewhere");
+        let last_db_test_index = events
+            .iter()
+            .rposition(|e| matches!(e, ScheduleEvent::RunTest(t) if t.fixtures.iter().any(|f| f.name == "db")))
+            .expect("a test using db runs somewhere");
+        assert!(db_teardown_index > last_db_test_index, "teardown must come after the last test needing it");
+    }
+
+    // =========================================================================
+    // Concurrency Schedule Tests
+    // =========================================================================
+
+    #[test]
+    fn test_schedule_function_scoped_tests_are_independent_lanes() {
+        let tests = vec![
+            make_runnable("test_a.py", "test_1", vec![("tmp", FixtureScope::Function)]),
+            make_runnable("test_a.py", "test_2", vec![("tmp", FixtureScope::Function)]),
+        ];
+        let plan = build_execution_plan(tests);
+        let schedule = build_concurrency_schedule(plan, 4);
+
+        assert_eq!(schedule.lanes.len(), 2, "no shared non-function fixture, should be parallel-safe");
+        assert_eq!(schedule.max_parallel, 4);
+    }
+
+    #[test]
+    fn test_schedule_module_scoped_tests_share_one_lane() {
+        let tests = vec![
+            make_runnable("test_a.py", "test_1", vec![("db", FixtureScope::Module)]),
+            make_runnable("test_a.py", "test_2", vec![("db", FixtureScope::Module)]),
+        ];
+        let plan = build_execution_plan(tests);
+        let schedule = build_concurrency_schedule(plan, 4);
+
+        assert_eq!(schedule.lanes.len(), 1, "shared module fixture must serialize both tests");
+        assert_eq!(schedule.lanes[0].steps.len(), 2);
+    }
+
+    #[test]
+    fn test_schedule_unrelated_modules_are_separate_lanes() {
+        let tests = vec![
+            make_runnable("test_a.py", "test_1", vec![("db", FixtureScope::Module)]),
+            make_runnable("test_b.py", "test_2", vec![("db", FixtureScope::Module)]),
+        ];
+        let plan = build_execution_plan(tests);
+        let schedule = build_concurrency_schedule(plan, 4);
+
+        // Same fixture NAME, but different modules -> different instances -> separate lanes.
+        assert_eq!(schedule.lanes.len(), 2);
+    }
+
+    #[test]
+    fn test_schedule_transitive_sharing_merges_lanes() {
+        // test_1 and test_2 share "db"; test_2 and test_3 share "cache".
+        // All three must end up serialized together even though test_1 and
+        // test_3 share nothing directly.
+        let tests = vec![
+            make_runnable("test_a.py", "test_1", vec![("db", FixtureScope::Class)]),
+            make_runnable(
+                "test_a.py",
+                "test_2",
+                vec![("db", FixtureScope::Class), ("cache", FixtureScope::Class)],
+            ),
+            make_runnable("test_a.py", "test_3", vec![("cache", FixtureScope::Class)]),
+        ];
+        // Give them all the same (absent) class so the Class scope key matches.
+        let plan = build_execution_plan(tests);
+        let schedule = build_concurrency_schedule(plan, 4);
+
+        assert_eq!(schedule.lanes.len(), 1);
+        assert_eq!(schedule.lanes[0].steps.len(), 3);
+    }
+
+    #[test]
+    fn test_schedule_session_fixture_merges_otherwise_unrelated_modules() {
+        let tests = vec![
+            make_runnable("test_a.py", "test_1", vec![("db", FixtureScope::Session)]),
+            make_runnable("test_b.py", "test_2", vec![("db", FixtureScope::Session)]),
+        ];
+        let plan = build_execution_plan(tests);
+        let schedule = build_concurrency_schedule(plan, 4);
+
+        assert_eq!(
+            schedule.lanes.len(),
+            1,
+            "session scope spans module boundaries by design"
+        );
+    }
+
+    #[test]
+    fn test_schedule_preserves_max_parallel_knob() {
+        let tests = vec![make_runnable("test_a.py", "test_1", vec![])];
+        let plan = build_execution_plan(tests);
+        let schedule = build_concurrency_schedule(plan, 8);
+        assert_eq!(schedule.max_parallel, 8);
+    }
 }
@@ -1,7 +1,15 @@
 //! Parallel Scheduler with crash timeout detection
 
+use crate::coverage::CoverageMerger;
+use crate::jobserver::JobserverClient;
 use crate::logcapture::LogCapture;
-use crate::protocol::{FixtureInfo, TestPayload, TestResult, CMD_EXIT, CMD_FORK, STATUS_PASS};
+use crate::logstream::LogMultiplexer;
+use crate::protocol::{
+    FixtureInfo, TestPayload, TestResult, CMD_EXIT, CMD_FORK, STATUS_CRASH, STATUS_ERROR,
+    STATUS_HARNESS_ERROR, STATUS_PASS, STATUS_SKIP,
+};
+use crate::failure_snapshot;
+use crate::provenance::{Capture, ProvenanceCache};
 use crate::reporter::Reporter;
 use crate::resolver::RunnableTest;
 use crate::signals;
@@ -10,14 +18,42 @@ use std::collections::HashMap;
 use std::io::{Read, Write};
 use std::os::unix::net::UnixStream;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 /// Active worker tracking
 struct ActiveWorker {
     test_name: String,
+    file_path: PathBuf,
     slot: usize,
     start_time: Instant,
+    /// `Some(strict)` if the test was marked `@pytest.mark.xfail(...)`, used
+    /// to reconcile its raw pass/fail result into `xfail`/`xpass`.
+    xfail_strict: Option<bool>,
+    /// Whether this worker is using tach's own implicit jobserver token
+    /// (never read from/written to the pipe) rather than one acquired via
+    /// `JobserverClient::acquire`.
+    used_free_slot: bool,
+    /// The token byte to hand back to the jobserver on completion, if this
+    /// worker acquired one (i.e. `!used_free_slot`).
+    jobserver_token: Option<u8>,
+}
+
+/// Reconcile a raw pass/fail/skip result against a test's xfail annotation.
+fn classify_status(raw_status: u8, xfail_strict: Option<bool>) -> &'static str {
+    let raw = match raw_status {
+        STATUS_PASS => "pass",
+        STATUS_SKIP => "skip",
+        STATUS_CRASH => "crash",
+        STATUS_ERROR | STATUS_HARNESS_ERROR => "error",
+        _ => "fail",
+    };
+    match (raw, xfail_strict) {
+        ("pass", Some(_)) => "xpass",
+        ("fail", Some(_)) => "xfail",
+        (other, _) => other,
+    }
 }
 
 /// Scheduler with crash detection
@@ -25,9 +61,32 @@ pub struct Scheduler {
     cmd_socket: UnixStream,
     result_socket: Arc<Mutex<UnixStream>>,
     log_capture: Arc<Mutex<LogCapture>>,
+    /// Only set when the run was started with live log streaming enabled.
+    log_mux: Option<LogMultiplexer>,
     active_workers: Arc<Mutex<HashMap<u32, ActiveWorker>>>,
     max_workers: usize,
     debug_socket_path: PathBuf,
+    /// When set, worker dispatch is additionally gated on holding a
+    /// jobserver token, so total concurrency across an enclosing `make -jN`
+    /// (or other jobserver-aware build) stays bounded.
+    jobserver: Option<JobserverClient>,
+    /// Whether tach's own implicit jobserver token (the one every
+    /// participant holds without reading/writing the pipe) is currently in
+    /// use by a worker. Only meaningful when `jobserver` is `Some`.
+    free_slot_used: AtomicBool,
+    /// Only constructed when `--coverage` is passed. Fed from each worker's
+    /// `TestResult::coverage` as results arrive; written out via
+    /// `write_coverage` once `run` returns.
+    coverage: Option<Mutex<CoverageMerger>>,
+    /// Fed from each worker's `TestResult::read_files` as results arrive;
+    /// written out via `write_provenance` once `run` returns. Set via
+    /// `with_provenance` - unlike coverage this is always wanted (watch mode
+    /// reads it back via `ProvenanceCache::dirty_tests`), so it's on by
+    /// default rather than gated behind a CLI flag.
+    provenance: Option<Mutex<ProvenanceCache>>,
+    /// Project root to snapshot-test xfail failure messages against, via
+    /// `failure_snapshot`. `None` disables the feature entirely.
+    failure_snapshot_root: Option<PathBuf>,
 }
 
 impl Scheduler {
@@ -36,6 +95,43 @@ impl Scheduler {
         result_socket: UnixStream,
         log_capture: LogCapture,
         debug_socket_path: PathBuf,
+    ) -> Result<Self> {
+        Self::with_log_mux(cmd_socket, result_socket, log_capture, None, debug_socket_path)
+    }
+
+    /// Gate worker dispatch on holding a token from `jobserver`, in addition
+    /// to the existing `max_workers` cap.
+    pub fn with_jobserver(mut self, jobserver: JobserverClient) -> Self {
+        self.jobserver = Some(jobserver);
+        self
+    }
+
+    /// Enable per-test line coverage collection: results carrying a
+    /// `CoverageReport` (see `protocol::TestResult::coverage`) get merged
+    /// into an LCOV report, written via `write_coverage` after `run`.
+    pub fn with_coverage(mut self) -> Self {
+        self.coverage = Some(Mutex::new(CoverageMerger::new()));
+        self
+    }
+
+    /// Write the merged coverage report to `dir` as `<dir>/lcov.info`.
+    /// No-op if `with_coverage` was never called.
+    pub fn write_coverage(&self, dir: &std::path::Path) -> Result<()> {
+        if let Some(coverage) = &self.coverage {
+            coverage.lock().unwrap().write_lcov(dir)?;
+        }
+        Ok(())
+    }
+
+    /// Like `new`, but also wires up a `LogMultiplexer` so worker output is
+    /// forwarded to the reporter as it's produced instead of only after a
+    /// test finishes.
+    pub fn with_log_mux(
+        cmd_socket: UnixStream,
+        result_socket: UnixStream,
+        log_capture: LogCapture,
+        log_mux: Option<LogMultiplexer>,
+        debug_socket_path: PathBuf,
     ) -> Result<Self> {
         let max_workers = log_capture.slot_count();
 
@@ -46,25 +142,87 @@ impl Scheduler {
             cmd_socket,
             result_socket: Arc::new(Mutex::new(result_socket)),
             log_capture: Arc::new(Mutex::new(log_capture)),
+            log_mux,
             active_workers: Arc::new(Mutex::new(HashMap::new())),
             max_workers,
             debug_socket_path,
+            jobserver: None,
+            free_slot_used: AtomicBool::new(false),
+            coverage: None,
+            provenance: None,
+            failure_snapshot_root: None,
         })
     }
 
+    /// Load (or start) a `ProvenanceCache` at `project_root` and record each
+    /// result's `TestResult::read_files` into it as results arrive, so watch
+    /// mode can mark a test dirty when a file it previously read changes -
+    /// see `provenance::ProvenanceCache::dirty_tests`.
+    pub fn with_provenance(mut self, project_root: &std::path::Path) -> Self {
+        self.provenance = Some(Mutex::new(ProvenanceCache::load(project_root)));
+        self
+    }
+
+    /// Write the provenance cache to `.tach/cache/provenance.json`. No-op if
+    /// `with_provenance` was never called.
+    pub fn write_provenance(&self) -> Result<()> {
+        if let Some(provenance) = &self.provenance {
+            provenance.lock().unwrap().save()?;
+        }
+        Ok(())
+    }
+
+    /// Snapshot-test every `@pytest.mark.xfail` test's failure message
+    /// against `project_root/.tach/snapshots/<id>.txt` (normalized per
+    /// `failure_snapshot::normalize`), appending a diff to the reported
+    /// message on mismatch. See `failure_snapshot` for the `TACH_BLESS=1`
+    /// rewrite-in-place escape hatch.
+    pub fn with_failure_snapshots(mut self, project_root: &std::path::Path) -> Self {
+        self.failure_snapshot_root = Some(project_root.to_path_buf());
+        self
+    }
+
+    /// Drain any worker output that's arrived since the last poll, tagging
+    /// each line with whichever test is currently occupying that slot.
+    /// Non-blocking: a slot with nothing ready is skipped, not waited on.
+    fn drain_log_stream(&mut self, reporter: &mut dyn Reporter) {
+        let Some(mux) = self.log_mux.as_mut() else {
+            return;
+        };
+        let active_workers = &self.active_workers;
+        let _ = mux.poll(0, |slot, line| {
+            let test_name = active_workers
+                .lock()
+                .unwrap()
+                .values()
+                .find(|w| w.slot == slot)
+                .map(|w| w.test_name.clone());
+            if let Some(test_name) = test_name {
+                reporter.on_test_output(&test_name, "stdout", line);
+            }
+        });
+    }
+
     pub fn run(
         &mut self,
         tests: Vec<RunnableTest>,
         reporter: &mut dyn Reporter,
+        seed: Option<u64>,
+        fail_fast: Option<usize>,
     ) -> Result<SchedulerStats> {
         let start = Instant::now();
         let total = tests.len();
         let mut passed = 0usize;
         let mut failed = 0usize;
+        let mut skipped = 0usize;
+        let mut crashed = 0usize;
+        let mut xfailed = 0usize;
+        let mut xpassed = 0usize;
         let mut collected = 0usize;
+        let mut dispatched = 0usize;
 
         // Emit run_start event
-        reporter.on_run_start(total);
+        reporter.on_run_start(total, seed);
 
         // Dispatch all tests
         let mut queue: Vec<(u32, RunnableTest)> = tests
@@ -80,19 +238,38 @@ impl Scheduler {
                 break;
             }
 
+            // Stop dispatching new tests once the failure threshold is hit;
+            // the Zygote is still shut down normally by the caller. A crash
+            // counts toward the threshold just like an assertion failure -
+            // it's tracked separately only so reporters can tell them apart.
+            if let Some(threshold) = fail_fast {
+                if failed + crashed >= threshold {
+                    break;
+                }
+            }
+
             let slot = test_id as usize % self.max_workers;
 
             // Wait if at max capacity
             while self.active_workers.lock().unwrap().len() >= self.max_workers {
+                self.drain_log_stream(reporter);
                 // Try to collect a result
-                if let Some((test_name, status, duration_ms, msg)) =
+                if let Some((test_name, status, duration_ms, msg, strict)) =
                     self.try_collect_result_for_reporter()
                 {
                     reporter.on_test_finished(&test_name, status, duration_ms, msg.as_deref());
-                    if status == "pass" {
-                        passed += 1;
-                    } else {
-                        failed += 1;
+                    match status {
+                        "pass" => passed += 1,
+                        "skip" => skipped += 1,
+                        "crash" => crashed += 1,
+                        "xfail" => xfailed += 1,
+                        "xpass" => {
+                            xpassed += 1;
+                            if strict == Some(true) {
+                                failed += 1;
+                            }
+                        }
+                        _ => failed += 1,
                     }
                     collected += 1;
                 }
@@ -101,59 +278,99 @@ impl Scheduler {
             // Emit test_start event
             let file = test.file_path.to_string_lossy().to_string();
             reporter.on_test_start(&test.test_name, &file);
+            reporter.on_test_location(&test.test_name, test.line_number);
 
-            if let Err(e) = self.dispatch_test(&test, test_id, slot) {
-                reporter.on_test_finished(&test.test_name, "fail", 0, Some(&e.to_string()));
-                failed += 1;
+            let (used_free_slot, jobserver_token) = self.acquire_jobserver_slot()?;
+            if let Err(e) = self.dispatch_test(&test, test_id, slot, used_free_slot, jobserver_token) {
+                self.release_jobserver_slot(used_free_slot, jobserver_token);
+                reporter.on_test_finished(&test.test_name, "crash", 0, Some(&e.to_string()));
+                crashed += 1;
                 collected += 1;
             }
+            dispatched += 1;
+        }
+
+        let undispatched = total - dispatched;
+        if undispatched > 0 && fail_fast.is_some() {
+            reporter.on_error(&format!(
+                "Stopping after {} failure(s) (--fail-fast): {} test(s) skipped",
+                failed + crashed, undispatched
+            ));
         }
 
-        // Collect remaining results with timeout for crash detection
+        // Collect remaining results with timeout for crash detection.
+        // Bounded by `dispatched`, not `total`: tests skipped by --fail-fast
+        // were never sent to the Zygote and will never report a result.
         let deadline = Instant::now() + Duration::from_secs(10);
-        while collected < total && Instant::now() < deadline {
-            if let Some((test_name, status, duration_ms, msg)) =
+        while collected < dispatched && Instant::now() < deadline {
+            self.drain_log_stream(reporter);
+            if let Some((test_name, status, duration_ms, msg, strict)) =
                 self.try_collect_result_for_reporter()
             {
                 reporter.on_test_finished(&test_name, status, duration_ms, msg.as_deref());
-                if status == "pass" {
-                    passed += 1;
-                } else {
-                    failed += 1;
+                match status {
+                    "pass" => passed += 1,
+                    "skip" => skipped += 1,
+                    "crash" => crashed += 1,
+                    "xfail" => xfailed += 1,
+                    "xpass" => {
+                        xpassed += 1;
+                        if strict == Some(true) {
+                            failed += 1;
+                        }
+                    }
+                    _ => failed += 1,
                 }
                 collected += 1;
             } else {
                 // Check for stale workers (possible crashes)
                 let stale = self.get_stale_workers(Duration::from_secs(3));
                 for (test_id, test_name, slot) in stale {
-                    reporter.on_test_finished(&test_name, "fail", 0, Some("CRASHED - no response"));
+                    reporter.on_test_finished(&test_name, "crash", 0, Some("CRASHED - no response"));
                     let _ = self.log_capture.lock().unwrap().read_and_clear(slot);
-                    self.active_workers.lock().unwrap().remove(&test_id);
-                    failed += 1;
+                    if let Some(w) = self.active_workers.lock().unwrap().remove(&test_id) {
+                        self.release_jobserver_slot(w.used_free_slot, w.jobserver_token);
+                    }
+                    crashed += 1;
                     collected += 1;
                 }
             }
         }
 
+        // One last non-blocking drain so output from the final test(s)
+        // isn't left sitting in the pipe past `run_finished`. Full drain to
+        // EOF only happens once the Zygote is reaped after `shutdown()`.
+        self.drain_log_stream(reporter);
+
         let elapsed = start.elapsed();
         let duration_ms = elapsed.as_millis() as u64;
 
+        // Tests skipped by a runtime `skipif`/`skip` marker plus those never
+        // dispatched because `--fail-fast` tripped or a shutdown was
+        // requested first - none ran to a pass/fail verdict, so they're
+        // reported as one "skipped" count.
+        let skipped = skipped + undispatched;
+
         // Emit run_finished event
-        reporter.on_run_finished(passed, failed, 0, duration_ms);
+        reporter.on_run_finished(passed, failed, skipped, xfailed, xpassed, crashed, duration_ms);
 
         Ok(SchedulerStats {
             total,
             passed,
             failed,
+            xfailed,
+            xpassed,
+            skipped,
+            crashed,
             duration_ms,
         })
     }
 
     /// Collect result and return formatted data for reporter
-    /// Returns: (test_name, status, duration_ms, message)
+    /// Returns: (test_name, status, duration_ms, message, xfail_strict)
     fn try_collect_result_for_reporter(
         &self,
-    ) -> Option<(String, &'static str, u64, Option<String>)> {
+    ) -> Option<(String, &'static str, u64, Option<String>, Option<bool>)> {
         let mut socket = self.result_socket.lock().unwrap();
 
         let mut len_buf = [0u8; 4];
@@ -165,32 +382,58 @@ impl Scheduler {
                 match socket.read_exact(&mut result_buf) {
                     Ok(_) => {
                         if let Ok(result) = bincode::deserialize::<TestResult>(&result_buf) {
+                            if let (Some(coverage), Some(report)) = (&self.coverage, &result.coverage) {
+                                coverage.lock().unwrap().merge(report);
+                            }
+
                             // Get and remove worker
-                            let (test_name, slot) = {
+                            let (test_name, file_path, slot, xfail_strict) = {
                                 let mut workers = self.active_workers.lock().unwrap();
                                 match workers.remove(&result.test_id) {
-                                    Some(w) => (w.test_name, w.slot),
-                                    None => (format!("test_{}", result.test_id), 0),
+                                    Some(w) => {
+                                        self.release_jobserver_slot(w.used_free_slot, w.jobserver_token);
+                                        (w.test_name, w.file_path, w.slot, w.xfail_strict)
+                                    }
+                                    None => (format!("test_{}", result.test_id), PathBuf::new(), 0, None),
                                 }
                             };
 
+                            if let Some(provenance) = &self.provenance {
+                                if let Ok(source) = std::fs::read_to_string(&file_path) {
+                                    let qualified_id =
+                                        format!("{}::{}", file_path.display(), test_name);
+                                    let capture = Capture::from_wire(&result.read_files);
+                                    provenance.lock().unwrap().record(&qualified_id, &source, &capture);
+                                }
+                            }
+
                             // Read and discard logs (they went to memfd)
                             let _ = self.log_capture.lock().unwrap().read_and_clear(slot);
 
-                            // Format for reporter
-                            let status = if result.status == STATUS_PASS {
-                                "pass"
-                            } else {
-                                "fail"
-                            };
+                            // Format for reporter, reconciled against xfail annotation
+                            let status = classify_status(result.status, xfail_strict);
                             let duration_ms = result.duration_ns / 1_000_000;
-                            let msg = if result.message.is_empty() {
-                                None
-                            } else {
-                                Some(result.message)
-                            };
+                            let mut message = result.message;
+
+                            // An xfail test's message is its assertion, not an
+                            // incidental failure - snapshot-test it so the
+                            // assertion means "still fails the same way", not
+                            // "fails with byte-identical, machine-specific text".
+                            if status == "xfail" {
+                                if let Some(root) = &self.failure_snapshot_root {
+                                    let qualified_id = format!("{}::{}", file_path.display(), test_name);
+                                    let dir = failure_snapshot::snapshot_dir(root);
+                                    if let Ok(failure_snapshot::Outcome::Mismatch(diff)) =
+                                        failure_snapshot::check_or_bless(&dir, &qualified_id, &message, root)
+                                    {
+                                        message = format!("{}\n\nsnapshot mismatch:\n{}", message, diff);
+                                    }
+                                }
+                            }
+
+                            let msg = if message.is_empty() { None } else { Some(message) };
 
-                            return Some((test_name, status, duration_ms, msg));
+                            return Some((test_name, status, duration_ms, msg, xfail_strict));
                         }
                     }
                     Err(_) => {}
@@ -201,7 +444,44 @@ impl Scheduler {
         None
     }
 
-    fn dispatch_test(&mut self, test: &RunnableTest, test_id: u32, slot: usize) -> Result<()> {
+    /// Claim a slot against `self.jobserver`, if one is configured: the
+    /// first caller gets tach's own implicit token for free, everyone after
+    /// that blocks on `JobserverClient::acquire` until the enclosing build
+    /// hands one back. Returns `(used_free_slot, token)` to pass through to
+    /// `dispatch_test` and later back to `release_jobserver_slot`.
+    fn acquire_jobserver_slot(&self) -> Result<(bool, Option<u8>)> {
+        let Some(jobserver) = &self.jobserver else {
+            return Ok((false, None));
+        };
+
+        if !self.free_slot_used.swap(true, Ordering::SeqCst) {
+            Ok((true, None))
+        } else {
+            Ok((false, Some(jobserver.acquire()?)))
+        }
+    }
+
+    /// Hand a slot claimed by `acquire_jobserver_slot` back, so the token
+    /// (or the implicit free slot) is available for the next dispatch.
+    fn release_jobserver_slot(&self, used_free_slot: bool, token: Option<u8>) {
+        let Some(jobserver) = &self.jobserver else {
+            return;
+        };
+        if used_free_slot {
+            self.free_slot_used.store(false, Ordering::SeqCst);
+        } else if let Some(token) = token {
+            let _ = jobserver.release(token);
+        }
+    }
+
+    fn dispatch_test(
+        &mut self,
+        test: &RunnableTest,
+        test_id: u32,
+        slot: usize,
+        used_free_slot: bool,
+        jobserver_token: Option<u8>,
+    ) -> Result<()> {
         let log_fd = self.log_capture.lock().unwrap().get_fd(slot).unwrap_or(-1);
 
         let payload = TestPayload {
@@ -215,7 +495,9 @@ impl Scheduler {
                 .map(|f| FixtureInfo::from_scope(f.name.clone(), &f.scope))
                 .collect(),
             log_fd,
+            log_slot: slot,
             debug_socket_path: self.debug_socket_path.to_string_lossy().to_string(),
+            permissions: test.permissions.clone(),
         };
 
         let payload_bytes = bincode::serialize(&payload)?;
@@ -232,8 +514,12 @@ impl Scheduler {
             test_id,
             ActiveWorker {
                 test_name: test.test_name.clone(),
+                file_path: test.file_path.clone(),
                 slot,
                 start_time: Instant::now(),
+                xfail_strict: test.xfail_strict,
+                used_free_slot,
+                jobserver_token,
             },
         );
 
@@ -320,5 +606,14 @@ pub struct SchedulerStats {
     pub total: usize,
     pub passed: usize,
     pub failed: usize,
+    pub xfailed: usize,
+    pub xpassed: usize,
+    /// Tests that never reached a pass/fail verdict: skipped by a runtime
+    /// marker, or never dispatched because `--fail-fast`'s threshold was
+    /// reached or a shutdown (Ctrl+C) was requested.
+    pub skipped: usize,
+    /// Tests whose worker crashed (panic, segfault, or no response within
+    /// the stale-worker timeout) rather than reporting a pass/fail/skip.
+    pub crashed: usize,
     pub duration_ms: u64,
 }
@@ -0,0 +1,210 @@
+//! `-k`/`-m` selection expression evaluation, mirroring pytest's keyword and
+//! marker selection grammar: a small boolean expression of `and`/`or`/`not`
+//! and parentheses over bare identifiers. Used by `DiscoveryResult::filter_by_keyword`
+//! and `DiscoveryResult::filter_by_markers` so IDE/CLI consumers can narrow
+//! the test list without shelling out to pytest.
+
+use anyhow::{bail, Result};
+
+/// A parsed `-k`/`-m` selection expression. What an `Ident` matches against
+/// depends on the caller: a case-insensitive node-id substring for `-k`, an
+/// exact marker name for `-m`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectionExpr {
+    Ident(String),
+    Not(Box<SelectionExpr>),
+    And(Box<SelectionExpr>, Box<SelectionExpr>),
+    Or(Box<SelectionExpr>, Box<SelectionExpr>),
+}
+
+impl SelectionExpr {
+    /// Evaluate the expression, calling `matches_ident` once per identifier
+    /// it contains.
+    pub fn eval(&self, matches_ident: &dyn Fn(&str) -> bool) -> bool {
+        match self {
+            SelectionExpr::Ident(name) => matches_ident(name),
+            SelectionExpr::Not(inner) => !inner.eval(matches_ident),
+            SelectionExpr::And(lhs, rhs) => lhs.eval(matches_ident) && rhs.eval(matches_ident),
+            SelectionExpr::Or(lhs, rhs) => lhs.eval(matches_ident) || rhs.eval(matches_ident),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    Ident(String),
+}
+
+fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        if c == '(' {
+            chars.next();
+            tokens.push(Token::LParen);
+            continue;
+        }
+        if c == ')' {
+            chars.next();
+            tokens.push(Token::RParen);
+            continue;
+        }
+
+        let mut word = String::new();
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() || c == '(' || c == ')' {
+                break;
+            }
+            word.push(c);
+            chars.next();
+        }
+        match word.as_str() {
+            "and" => tokens.push(Token::And),
+            "or" => tokens.push(Token::Or),
+            "not" => tokens.push(Token::Not),
+            _ => tokens.push(Token::Ident(word)),
+        }
+    }
+
+    tokens
+}
+
+/// Recursive-descent parser over the token stream. Precedence, loosest to
+/// tightest: `or`, `and`, `not`, atom (identifier or `(...)`).
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<SelectionExpr> {
+        let mut lhs = self.parse_and()?;
+        while matches!(self.peek(), Some(Token::Or)) {
+            self.advance();
+            let rhs = self.parse_and()?;
+            lhs = SelectionExpr::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<SelectionExpr> {
+        let mut lhs = self.parse_not()?;
+        while matches!(self.peek(), Some(Token::And)) {
+            self.advance();
+            let rhs = self.parse_not()?;
+            lhs = SelectionExpr::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_not(&mut self) -> Result<SelectionExpr> {
+        if matches!(self.peek(), Some(Token::Not)) {
+            self.advance();
+            return Ok(SelectionExpr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_atom()
+    }
+
+    fn parse_atom(&mut self) -> Result<SelectionExpr> {
+        match self.advance() {
+            Some(Token::LParen) => {
+                let inner = self.parse_or()?;
+                match self.advance() {
+                    Some(Token::RParen) => Ok(inner),
+                    other => bail!("expected ')', found {:?}", other),
+                }
+            }
+            Some(Token::Ident(name)) => Ok(SelectionExpr::Ident(name)),
+            other => bail!("expected an identifier or '(', found {:?}", other),
+        }
+    }
+}
+
+/// Parse a `-k`/`-m` selection expression, e.g. `"slow and not network"`.
+pub fn parse_selection(expr: &str) -> Result<SelectionExpr> {
+    let tokens = tokenize(expr);
+    if tokens.is_empty() {
+        bail!("empty selection expression");
+    }
+    let mut parser = Parser { tokens, pos: 0 };
+    let parsed = parser.parse_or()?;
+    if parser.pos != parser.tokens.len() {
+        bail!("unexpected trailing input in selection expression '{}'", expr);
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(expr: &str, truthy: &[&str]) -> bool {
+        let parsed = parse_selection(expr).unwrap();
+        parsed.eval(&|ident| truthy.contains(&ident))
+    }
+
+    #[test]
+    fn test_parse_single_identifier() {
+        assert_eq!(parse_selection("slow").unwrap(), SelectionExpr::Ident("slow".to_string()));
+    }
+
+    #[test]
+    fn test_and_or_not_precedence() {
+        // "not" binds tighter than "and", which binds tighter than "or":
+        // `a or b and not c` == `a or (b and (not c))`.
+        assert!(eval("a or b and not c", &["a"]));
+        assert!(!eval("a or b and not c", &[]));
+        assert!(eval("a or b and not c", &["b"]));
+        assert!(!eval("a or b and not c", &["b", "c"]));
+    }
+
+    #[test]
+    fn test_parentheses_override_precedence() {
+        assert!(!eval("(a or b) and c", &["a"]));
+        assert!(eval("(a or b) and c", &["a", "c"]));
+    }
+
+    #[test]
+    fn test_not_applies_to_parenthesized_group() {
+        assert!(eval("not (a and b)", &["a"]));
+        assert!(!eval("not (a and b)", &["a", "b"]));
+    }
+
+    #[test]
+    fn test_unbalanced_parens_is_an_error() {
+        assert!(parse_selection("(a and b").is_err());
+        assert!(parse_selection("a and b)").is_err());
+    }
+
+    #[test]
+    fn test_empty_expression_is_an_error() {
+        assert!(parse_selection("").is_err());
+        assert!(parse_selection("   ").is_err());
+    }
+
+    #[test]
+    fn test_dangling_operator_is_an_error() {
+        assert!(parse_selection("a and").is_err());
+        assert!(parse_selection("and a").is_err());
+    }
+}
@@ -8,35 +8,301 @@
 //! This eliminates fork() overhead in the hot loop (target: <50μs reset vs ~1ms fork)
 
 use anyhow::{anyhow, Context, Result};
-use nix::sys::socket::{sendmsg, ControlMessage, MsgFlags};
+use nix::errno::Errno;
+use nix::sys::epoll::{
+    epoll_create1, epoll_ctl, epoll_wait, EpollCreateFlags, EpollEvent, EpollFlags, EpollOp,
+};
+use nix::sys::signal::{sigprocmask, SigSet, SigmaskHow, Signal};
+use nix::sys::signalfd::{SfdFlags, SignalFd};
+use nix::sys::socket::{
+    sendmsg, setsockopt, sockopt, ControlMessage, MsgFlags, UnixCredentials,
+};
 use nix::sys::uio::{process_vm_readv, RemoteIoVec};
-use nix::unistd::Pid;
-use std::collections::HashMap;
-use std::fs;
-use std::io::{IoSlice, IoSliceMut};
-use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use nix::sys::wait::{waitpid, WaitPidFlag, WaitStatus};
+use nix::unistd::{getgid, getpid, getuid, Pid};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{IoSlice, IoSliceMut, Write as _};
+use std::os::fd::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+use std::os::unix::fs::FileExt;
 use std::os::unix::net::UnixStream;
-use userfaultfd::{Uffd, UffdBuilder};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use userfaultfd::{FeatureFlags, RegisterMode, Uffd, UffdBuilder, WriteProtectMode};
 
 /// Page size (4KB on x86_64/aarch64)
 const PAGE_SIZE: usize = 4096;
 
+// =============================================================================
+// Error Handling
+// =============================================================================
+
+/// Error type for the public snapshot API (`SnapshotManager::new`,
+/// `register_worker_with_uffd`, `handle_pending_faults`, `send_fd`,
+/// `recv_fd`).
+///
+/// The rest of this module still uses `anyhow` internally for quick
+/// `.with_context()` plumbing, but these entry points return `SnapshotError`
+/// so callers get a lossless conversion into `std::io::Error` (see the
+/// `From` impl below) and can recover the raw OS error via
+/// [`SnapshotError::raw_os_error`] - e.g. to distinguish "uffd unprivileged"
+/// (`EPERM`) from "out of memory during UFFDIO_COPY" (`ENOMEM`) - instead of
+/// only getting a formatted `Display` string. This mirrors nix's own
+/// error-type overhaul, where `Errno` converts cleanly into `io::Error`.
+#[derive(Debug)]
+pub struct SnapshotError(anyhow::Error);
+
+impl SnapshotError {
+    fn from_io(context: impl Into<String>, source: std::io::Error) -> Self {
+        SnapshotError(anyhow::Error::new(source).context(context.into()))
+    }
+
+    /// The raw OS error (`errno`), if this failure originated in a syscall -
+    /// uffd register/copy, `madvise`, or SCM_RIGHTS `sendmsg`/`recvmsg` -
+    /// rather than e.g. a malformed `/proc` entry.
+    pub fn raw_os_error(&self) -> Option<i32> {
+        errno_from_chain(&self.0).map(|errno| errno as i32)
+    }
+}
+
+/// Best-effort extraction of a raw `errno` from an `anyhow::Error`'s cause
+/// chain. Shared by `SnapshotError::raw_os_error` and the self-pipe's
+/// `report_spawn_error`, which only has an `anyhow::Error` in hand (e.g.
+/// from `isolation::setup_filesystem`) and needs some concrete `Errno` to
+/// put in the payload.
+fn errno_from_chain(err: &anyhow::Error) -> Option<Errno> {
+    err.chain().find_map(|cause| {
+        if let Some(errno) = cause.downcast_ref::<Errno>() {
+            Some(*errno)
+        } else {
+            cause
+                .downcast_ref::<std::io::Error>()
+                .and_then(|io_err| io_err.raw_os_error())
+                .map(Errno::from_raw)
+        }
+    })
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+impl From<anyhow::Error> for SnapshotError {
+    fn from(err: anyhow::Error) -> Self {
+        SnapshotError(err)
+    }
+}
+
+impl From<userfaultfd::Error> for SnapshotError {
+    fn from(err: userfaultfd::Error) -> Self {
+        SnapshotError(anyhow::Error::from(err))
+    }
+}
+
+impl From<SnapshotError> for std::io::Error {
+    fn from(err: SnapshotError) -> Self {
+        match err.raw_os_error() {
+            Some(errno) => std::io::Error::from_raw_os_error(errno),
+            None => std::io::Error::new(std::io::ErrorKind::Other, err.0),
+        }
+    }
+}
+
+// =============================================================================
+// Self-Pipe: Detecting Fork+Setup Failures
+// =============================================================================
+
+/// Fixed footer written after the errno in a self-pipe failure payload, so
+/// `wait_for_spawn` can tell a genuine `report_spawn_failure` write from
+/// four stray bytes landing on the wrong fd.
+const SELFPIPE_FOOTER: &[u8; 4] = b"NOEX";
+
+/// A forked worker reported (via the self-pipe `create_selfpipe` sets up)
+/// that it failed before becoming fully live, with the raw `errno` it hit.
+#[derive(Debug)]
+pub struct SpawnError(pub Errno);
+
+impl fmt::Display for SpawnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "worker failed before becoming live: {}", self.0)
+    }
+}
+
+impl std::error::Error for SpawnError {}
+
+/// Create a `CLOEXEC` self-pipe for detecting whether a soon-to-be-forked
+/// worker makes it through post-fork setup alive.
+///
+/// Both ends are `O_CLOEXEC` so neither leaks into any subprocess the
+/// worker's own test code spawns (the same concern `CMD_FORK`'s handler
+/// already calls out for `SIGCHLD` disposition). Pass the pair to
+/// `wait_for_spawn` in the parent and `report_spawn_failure`/drop the write
+/// end in the child.
+pub fn create_selfpipe() -> Result<(OwnedFd, OwnedFd), SnapshotError> {
+    let mut fds = [0i32; 2];
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } < 0 {
+        return Err(SnapshotError::from_io(
+            "pipe2 failed for spawn self-pipe",
+            std::io::Error::last_os_error(),
+        ));
+    }
+    // SAFETY: pipe2 just handed us two freshly-opened, uniquely-owned fds.
+    let (read_fd, write_fd) =
+        unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+    Ok((read_fd, write_fd))
+}
+
+/// Report from the child (post-`fork()`) that setup failed with `errno`,
+/// then `_exit` without returning.
+///
+/// Writes the raw errno as 4 little-endian bytes - the same byte-roundtrip
+/// `send_fd`/`recv_fd` use for a worker PID - followed by `SELFPIPE_FOOTER`,
+/// using only the raw `write(2)` syscall: the child may be calling this from
+/// a state (half-initialized isolation, a Python interpreter mid-setup)
+/// where anything heavier isn't safe to run.
+pub fn report_spawn_failure(write_fd: RawFd, errno: Errno) -> ! {
+    let mut payload = [0u8; 8];
+    payload[..4].copy_from_slice(&(errno as i32).to_le_bytes());
+    payload[4..].copy_from_slice(SELFPIPE_FOOTER);
+    unsafe {
+        libc::write(write_fd, payload.as_ptr() as *const libc::c_void, payload.len());
+    }
+    std::process::exit(1);
+}
+
+/// Convenience for `report_spawn_failure` when the caller only has an
+/// `anyhow::Error` (e.g. from `isolation::setup_filesystem`) rather than a
+/// raw `Errno` - falls back to `EIO` if the chain carries no OS error.
+pub fn report_spawn_error(write_fd: RawFd, err: &anyhow::Error) -> ! {
+    report_spawn_failure(write_fd, errno_from_chain(err).unwrap_or(Errno::EIO))
+}
+
+/// Called from the parent after `fork()`: block until the child either
+/// closes its copy of `write_fd` (setup succeeded) or writes a
+/// `report_spawn_failure` payload to it.
+///
+/// Takes ownership of both pipe ends and drops the parent's own copy of
+/// `write_fd` first - otherwise `read_fd` could never see EOF, since a pipe
+/// only reports EOF once every writer has closed its end.
+pub fn wait_for_spawn(read_fd: OwnedFd, write_fd: OwnedFd) -> Result<(), SpawnError> {
+    drop(write_fd);
+
+    let mut buf = [0u8; 8];
+    let mut filled = 0usize;
+    while filled < buf.len() {
+        let n = unsafe {
+            libc::read(
+                read_fd.as_raw_fd(),
+                buf[filled..].as_mut_ptr() as *mut libc::c_void,
+                buf.len() - filled,
+            )
+        };
+        if n < 0 {
+            if Errno::last() == Errno::EINTR {
+                continue;
+            }
+            break; // pipe gone outright (e.g. the child was killed) - not this function's call to report
+        }
+        if n == 0 {
+            break; // EOF
+        }
+        filled += n as usize;
+    }
+
+    if filled == 0 {
+        return Ok(());
+    }
+    if filled != buf.len() || &buf[4..] != SELFPIPE_FOOTER {
+        return Err(SpawnError(Errno::EIO));
+    }
+
+    let raw = i32::from_le_bytes(buf[..4].try_into().unwrap());
+    Err(SpawnError(Errno::from_raw(raw)))
+}
+
 // =============================================================================
 // SCM_RIGHTS: File Descriptor Passing over Unix Sockets
 // =============================================================================
 
+/// Why a UFFD hand-off was rejected after its `SCM_CREDENTIALS` came back
+/// from the kernel, before any fd the peer sent is trusted.
+///
+/// Distinct from `SnapshotError` (which is mostly "a syscall failed") since
+/// these are rejections of a message that was received just fine - the
+/// kernel-validated sender identity just didn't check out.
+#[derive(Debug)]
+pub enum CredentialError {
+    /// The kernel-validated sender PID didn't match the PID the message
+    /// body claimed - the confused-deputy case this check exists to catch.
+    PidMismatch { claimed: i32, actual: i32 },
+    /// The sender's uid isn't one permitted to register a UFFD (by default,
+    /// not the uid this Supervisor itself runs as).
+    UidNotPermitted { uid: u32 },
+}
+
+impl fmt::Display for CredentialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CredentialError::PidMismatch { claimed, actual } => write!(
+                f,
+                "SCM_CREDENTIALS pid {} does not match claimed pid {} in UFFD hand-off - rejecting",
+                actual, claimed
+            ),
+            CredentialError::UidNotPermitted { uid } => write!(
+                f,
+                "SCM_CREDENTIALS uid {} is not permitted to register a UFFD - rejecting",
+                uid
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CredentialError {}
+
 /// Send a file descriptor over a Unix socket using SCM_RIGHTS
 ///
 /// This is used by the Worker to send its UFFD to the Supervisor.
 /// The message contains the worker's PID (4 bytes) with the FD attached.
-pub fn send_fd(sock: &UnixStream, pid: i32, fd: RawFd) -> Result<()> {
+pub fn send_fd(sock: &UnixStream, pid: i32, fd: RawFd) -> Result<(), SnapshotError> {
+    send_fds(sock, pid, &[fd])
+}
+
+/// Send several file descriptors over a Unix socket in one SCM_RIGHTS
+/// message, alongside `SCM_CREDENTIALS` identifying this process to the
+/// receiver.
+///
+/// Used for the shared-golden-memfd handoff, where the Worker sends its
+/// UFFD *and* the memfd it has already `mmap(MAP_SHARED)`-ed over its
+/// writable regions together (see `recv_two_fds`), so the Supervisor never
+/// observes one without the other. The credentials let `recv_fds` verify
+/// `pid` is actually who sent this message rather than trusting it at face
+/// value - the kernel overwrites a forged `ucred` with the real sender's
+/// identity, so we can't spoof our way past that check even if we wanted to.
+pub fn send_fds(sock: &UnixStream, pid: i32, fds: &[RawFd]) -> Result<(), SnapshotError> {
     let pid_bytes = pid.to_le_bytes();
     let iov = [IoSlice::new(&pid_bytes)];
-    let fds = [fd];
-    let cmsg = [ControlMessage::ScmRights(&fds)];
+    let creds = UnixCredentials::from(libc::ucred {
+        pid: getpid().as_raw(),
+        uid: getuid().as_raw(),
+        gid: getgid().as_raw(),
+    });
+    let cmsg = [
+        ControlMessage::ScmRights(fds),
+        ControlMessage::ScmCredentials(&creds),
+    ];
 
     sendmsg::<()>(sock.as_raw_fd(), &iov, &cmsg, MsgFlags::empty(), None)
-        .context("Failed to send FD via SCM_RIGHTS")?;
+        .context("Failed to send FDs via SCM_RIGHTS")?;
 
     Ok(())
 }
@@ -45,19 +311,58 @@ pub fn send_fd(sock: &UnixStream, pid: i32, fd: RawFd) -> Result<()> {
 ///
 /// This is used by the Supervisor to receive the Worker's UFFD.
 /// Returns (worker_pid, uffd_fd).
-pub fn recv_fd(sock: &UnixStream) -> Result<(i32, OwnedFd)> {
+pub fn recv_fd(sock: &UnixStream) -> Result<(i32, OwnedFd), SnapshotError> {
+    let (pid, mut fds) = recv_fds(sock, 1)?;
+    Ok((pid, fds.remove(0)))
+}
+
+/// Receive exactly two file descriptors from one SCM_RIGHTS message.
+///
+/// Used for the shared-golden-memfd handoff: the Worker sends its UFFD and
+/// its already-`MAP_SHARED`-mapped golden memfd together, so the Supervisor
+/// can register both for the same worker in `register_worker_with_shared_memfd`
+/// without a second message ever being able to arrive out of order.
+/// Returns (worker_pid, uffd_fd, memfd).
+pub fn recv_two_fds(sock: &UnixStream) -> Result<(i32, OwnedFd, OwnedFd), SnapshotError> {
+    let (pid, mut fds) = recv_fds(sock, 2)?;
+    let memfd = fds.remove(1);
+    let uffd = fds.remove(0);
+    Ok((pid, uffd, memfd))
+}
+
+/// Receive up to `max_fds` file descriptors from one SCM_RIGHTS message,
+/// authenticated against kernel-supplied `SCM_CREDENTIALS`.
+///
+/// `recv_fd`/`recv_two_fds` are thin wrappers around this for the common
+/// one- and two-fd cases; returns every fd actually present in the message,
+/// which may be fewer than `max_fds`.
+///
+/// Enables `SO_PASSCRED` so the kernel attaches the sender's real
+/// (`ucred`-validated) pid/uid/gid, then rejects the message with a
+/// `CredentialError` if that pid doesn't match the one carried in the
+/// message body or the uid isn't this Supervisor's own - closing the
+/// confused-deputy hole where a message body's claimed pid was trusted on
+/// its own.
+fn recv_fds(sock: &UnixStream, max_fds: usize) -> Result<(i32, Vec<OwnedFd>), SnapshotError> {
     use std::mem::MaybeUninit;
 
+    setsockopt(sock, sockopt::PassCred, &true)
+        .context("Failed to set SO_PASSCRED on UFFD hand-off socket")?;
+
     let mut pid_buf = [0u8; 4];
     let mut iov = libc::iovec {
         iov_base: pid_buf.as_mut_ptr() as *mut libc::c_void,
         iov_len: pid_buf.len(),
     };
 
-    // Control message buffer sized for one file descriptor
+    // Control message buffer sized for `max_fds` file descriptors plus one
+    // SCM_CREDENTIALS (ucred).
     // SAFETY: CMSG_SPACE is a const-like macro that computes buffer size
-    let mut cmsg_buf =
-        [0u8; unsafe { libc::CMSG_SPACE(std::mem::size_of::<RawFd>() as u32) } as usize];
+    let cmsg_cap = unsafe {
+        libc::CMSG_SPACE((std::mem::size_of::<RawFd>() * max_fds) as u32)
+            + libc::CMSG_SPACE(std::mem::size_of::<libc::ucred>() as u32)
+    } as usize;
+    let mut cmsg_buf = vec![0u8; cmsg_cap];
 
     let mut msg: libc::msghdr = unsafe { MaybeUninit::zeroed().assume_init() };
     msg.msg_iov = &mut iov;
@@ -68,37 +373,267 @@ pub fn recv_fd(sock: &UnixStream) -> Result<(i32, OwnedFd)> {
     // SAFETY: recvmsg is a safe syscall with properly initialized buffers
     let bytes_received = unsafe { libc::recvmsg(sock.as_raw_fd(), &mut msg, 0) };
     if bytes_received < 0 {
-        return Err(anyhow!(
-            "recvmsg failed: {}",
-            std::io::Error::last_os_error()
+        return Err(SnapshotError::from_io(
+            "recvmsg failed",
+            std::io::Error::last_os_error(),
         ));
     }
+    if msg.msg_flags & libc::MSG_CTRUNC != 0 {
+        return Err(SnapshotError::from(anyhow!(
+            "ancillary data truncated (MSG_CTRUNC) receiving SCM_RIGHTS message - \
+             descriptors may have been silently dropped, refusing to proceed"
+        )));
+    }
 
     // Extract PID from message body
     let pid = i32::from_le_bytes(pid_buf);
 
-    // Extract file descriptor from control message
-    let mut received_fd: Option<RawFd> = None;
+    // SAFETY: msg was just populated by the recvmsg call above
+    let (received_fds, received_cred) = unsafe { extract_fds_and_cred(&msg) };
 
-    // SAFETY: Iterating over control messages in properly received buffer
-    unsafe {
-        let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
-        while !cmsg.is_null() {
-            if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_RIGHTS {
-                let fd_ptr = libc::CMSG_DATA(cmsg) as *const RawFd;
-                received_fd = Some(*fd_ptr);
-                break;
+    if received_fds.is_empty() {
+        return Err(SnapshotError::from(anyhow!(
+            "No file descriptors in SCM_RIGHTS message"
+        )));
+    }
+
+    validate_sender_cred(pid, received_cred)?;
+
+    // SAFETY: We just received these FDs via recvmsg, we own them now
+    let owned_fds = received_fds
+        .into_iter()
+        .map(|fd| unsafe { OwnedFd::from_raw_fd(fd) })
+        .collect();
+
+    Ok((pid, owned_fds))
+}
+
+/// Walk the control messages of an already-populated `recvmsg` `msghdr`,
+/// pulling out every `SCM_RIGHTS` fd and the `SCM_CREDENTIALS` ucred (if
+/// present). Shared by `recv_fds` and `recv_fd_batch` so both parse the
+/// same two control messages the same way.
+///
+/// # Safety
+/// `msg` must have been populated by a successful `recvmsg` call whose
+/// control buffer is still alive.
+unsafe fn extract_fds_and_cred(msg: &libc::msghdr) -> (Vec<RawFd>, Option<libc::ucred>) {
+    let mut received_fds: Vec<RawFd> = Vec::new();
+    let mut received_cred: Option<libc::ucred> = None;
+
+    let mut cmsg = libc::CMSG_FIRSTHDR(msg);
+    while !cmsg.is_null() {
+        if (*cmsg).cmsg_level == libc::SOL_SOCKET {
+            match (*cmsg).cmsg_type {
+                libc::SCM_RIGHTS => {
+                    let data_len = (*cmsg).cmsg_len as usize - libc::CMSG_LEN(0) as usize;
+                    let count = data_len / std::mem::size_of::<RawFd>();
+                    let fd_ptr = libc::CMSG_DATA(cmsg) as *const RawFd;
+                    for i in 0..count {
+                        received_fds.push(*fd_ptr.add(i));
+                    }
+                }
+                libc::SCM_CREDENTIALS => {
+                    received_cred = Some(*(libc::CMSG_DATA(cmsg) as *const libc::ucred));
+                }
+                _ => {}
             }
-            cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
         }
+        cmsg = libc::CMSG_NXTHDR(msg, cmsg);
+    }
+
+    (received_fds, received_cred)
+}
+
+/// Reject a received `SCM_CREDENTIALS` ucred that doesn't vouch for the
+/// `claimed_pid` carried in the message body, or that isn't this process's
+/// own uid. Shared by `recv_fds` and `recv_fd_batch`.
+fn validate_sender_cred(claimed_pid: i32, cred: Option<libc::ucred>) -> Result<(), SnapshotError> {
+    let cred = cred.ok_or_else(|| {
+        SnapshotError::from(anyhow!(
+            "No SCM_CREDENTIALS in UFFD hand-off message - refusing to trust the claimed PID"
+        ))
+    })?;
+    if cred.pid != claimed_pid {
+        return Err(SnapshotError::from(anyhow::Error::new(
+            CredentialError::PidMismatch { claimed: claimed_pid, actual: cred.pid },
+        )));
+    }
+    if cred.uid != getuid().as_raw() {
+        return Err(SnapshotError::from(anyhow::Error::new(
+            CredentialError::UidNotPermitted { uid: cred.uid },
+        )));
+    }
+    Ok(())
+}
+
+/// Maximum fds `send_fd_batch`/`recv_fd_batch` will move in one message -
+/// generous enough for a UFFD plus a handful of shared memfds/eventfds
+/// without letting a malformed header claim an unbounded count.
+const MAX_BATCH_FDS: usize = 8;
+
+/// The role a single fd in a batched hand-off plays, so the receiver can
+/// slot each one into the right place on `WorkerSnapshot` instead of relying
+/// on fds simply arriving in a fixed order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FdRole {
+    /// The worker's userfaultfd.
+    Uffd,
+    /// The `MAP_SHARED` golden-image memfd (see `recv_two_fds`).
+    GoldenMemfd,
+    /// An eventfd the worker signals once it's ready to be faulted against.
+    ReadyEventFd,
+}
+
+impl FdRole {
+    fn to_u8(self) -> u8 {
+        match self {
+            FdRole::Uffd => 0,
+            FdRole::GoldenMemfd => 1,
+            FdRole::ReadyEventFd => 2,
+        }
+    }
+
+    fn from_u8(byte: u8) -> Result<Self, SnapshotError> {
+        match byte {
+            0 => Ok(FdRole::Uffd),
+            1 => Ok(FdRole::GoldenMemfd),
+            2 => Ok(FdRole::ReadyEventFd),
+            other => Err(SnapshotError::from(anyhow!(
+                "unknown FdRole byte {} in fd batch header",
+                other
+            ))),
+        }
+    }
+}
+
+/// Send an arbitrary set of role-tagged file descriptors - e.g. a worker's
+/// UFFD, its shared golden memfd, and a readiness eventfd - to the
+/// Supervisor in a single `sendmsg`/SCM_RIGHTS message, alongside
+/// `SCM_CREDENTIALS` as `send_fds` does.
+///
+/// The message body is `pid(4) || count(1) || role(1) * count`, so
+/// `recv_fd_batch` can associate each fd positionally with its role without
+/// a second round trip.
+pub fn send_fd_batch(sock: &UnixStream, pid: i32, items: &[(FdRole, RawFd)]) -> Result<(), SnapshotError> {
+    if items.len() > MAX_BATCH_FDS {
+        return Err(SnapshotError::from(anyhow!(
+            "fd batch of {} descriptors exceeds MAX_BATCH_FDS ({})",
+            items.len(),
+            MAX_BATCH_FDS
+        )));
+    }
+
+    let mut header = Vec::with_capacity(5 + items.len());
+    header.extend_from_slice(&pid.to_le_bytes());
+    header.push(items.len() as u8);
+    header.extend(items.iter().map(|(role, _)| role.to_u8()));
+
+    let iov = [IoSlice::new(&header)];
+    let fds: Vec<RawFd> = items.iter().map(|(_, fd)| *fd).collect();
+    let creds = UnixCredentials::from(libc::ucred {
+        pid: getpid().as_raw(),
+        uid: getuid().as_raw(),
+        gid: getgid().as_raw(),
+    });
+    let cmsg = [
+        ControlMessage::ScmRights(&fds),
+        ControlMessage::ScmCredentials(&creds),
+    ];
+
+    sendmsg::<()>(sock.as_raw_fd(), &iov, &cmsg, MsgFlags::empty(), None)
+        .context("Failed to send fd batch via SCM_RIGHTS")?;
+
+    Ok(())
+}
+
+/// Receive a role-tagged batch of file descriptors sent by `send_fd_batch`.
+///
+/// Sizes the ancillary buffer for up to `MAX_BATCH_FDS` descriptors plus one
+/// `SCM_CREDENTIALS`, rejects the message outright if the kernel reports
+/// `MSG_CTRUNC` (rather than silently handing back whichever descriptors
+/// happened to fit), and authenticates the sender the same way `recv_fds`
+/// does. Returns the fds in header order, each paired with the `FdRole` the
+/// sender tagged it with.
+pub fn recv_fd_batch(sock: &UnixStream) -> Result<(i32, Vec<(FdRole, OwnedFd)>), SnapshotError> {
+    use std::mem::MaybeUninit;
+
+    setsockopt(sock, sockopt::PassCred, &true)
+        .context("Failed to set SO_PASSCRED on UFFD hand-off socket")?;
+
+    let mut header_buf = [0u8; 5 + MAX_BATCH_FDS];
+    let mut iov = libc::iovec {
+        iov_base: header_buf.as_mut_ptr() as *mut libc::c_void,
+        iov_len: header_buf.len(),
+    };
+
+    // SAFETY: CMSG_SPACE is a const-like macro that computes buffer size
+    let cmsg_cap = unsafe {
+        libc::CMSG_SPACE((std::mem::size_of::<RawFd>() * MAX_BATCH_FDS) as u32)
+            + libc::CMSG_SPACE(std::mem::size_of::<libc::ucred>() as u32)
+    } as usize;
+    let mut cmsg_buf = vec![0u8; cmsg_cap];
+
+    let mut msg: libc::msghdr = unsafe { MaybeUninit::zeroed().assume_init() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = cmsg_buf.as_mut_ptr() as *mut libc::c_void;
+    msg.msg_controllen = cmsg_buf.len();
+
+    // SAFETY: recvmsg is a safe syscall with properly initialized buffers
+    let bytes_received = unsafe { libc::recvmsg(sock.as_raw_fd(), &mut msg, 0) };
+    if bytes_received < 0 {
+        return Err(SnapshotError::from_io(
+            "recvmsg failed",
+            std::io::Error::last_os_error(),
+        ));
+    }
+    if msg.msg_flags & libc::MSG_CTRUNC != 0 {
+        return Err(SnapshotError::from(anyhow!(
+            "ancillary data truncated (MSG_CTRUNC) receiving fd batch - \
+             descriptors may have been silently dropped, refusing to proceed"
+        )));
+    }
+    if bytes_received < 5 {
+        return Err(SnapshotError::from(anyhow!(
+            "fd batch header truncated: got {} bytes, need at least 5",
+            bytes_received
+        )));
+    }
+
+    let pid = i32::from_le_bytes(header_buf[0..4].try_into().unwrap());
+    let count = header_buf[4] as usize;
+    if count > MAX_BATCH_FDS || 5 + count > bytes_received as usize {
+        return Err(SnapshotError::from(anyhow!(
+            "fd batch header claims {} descriptors but only {} body bytes arrived",
+            count,
+            bytes_received
+        )));
+    }
+    let roles = header_buf[5..5 + count]
+        .iter()
+        .map(|&b| FdRole::from_u8(b))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // SAFETY: msg was just populated by the recvmsg call above
+    let (received_fds, received_cred) = unsafe { extract_fds_and_cred(&msg) };
+
+    if received_fds.len() != count {
+        return Err(SnapshotError::from(anyhow!(
+            "fd batch header promised {} descriptors but SCM_RIGHTS carried {}",
+            count,
+            received_fds.len()
+        )));
     }
 
-    let fd = received_fd.ok_or_else(|| anyhow!("No file descriptor in SCM_RIGHTS message"))?;
+    validate_sender_cred(pid, received_cred)?;
 
-    // SAFETY: We just received this FD via recvmsg, we own it now
-    let owned_fd = unsafe { OwnedFd::from_raw_fd(fd) };
+    // SAFETY: We just received these FDs via recvmsg, we own them now
+    let owned = roles
+        .into_iter()
+        .zip(received_fds.into_iter().map(|fd| unsafe { OwnedFd::from_raw_fd(fd) }))
+        .collect();
 
-    Ok((pid, owned_fd))
+    Ok((pid, owned))
 }
 
 // =============================================================================
@@ -211,6 +746,72 @@ fn align_to_page(addr: usize) -> usize {
     addr & !(PAGE_SIZE - 1)
 }
 
+// =============================================================================
+// Thread Freezing (pre-capture quiescence)
+// =============================================================================
+
+/// How long to keep polling a thread's `/proc` state before giving up on it
+/// having stopped.
+const FREEZE_POLL_ATTEMPTS: u32 = 200;
+const FREEZE_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// List every thread ID of `pid` by reading `/proc/<pid>/task`.
+fn list_threads(pid: Pid) -> Result<Vec<i32>> {
+    let task_dir = format!("/proc/{}/task", pid);
+    let mut tids = Vec::new();
+    for entry in fs::read_dir(&task_dir).with_context(|| format!("Failed to read {}", task_dir))? {
+        let entry = entry?;
+        if let Some(tid) = entry.file_name().to_str().and_then(|n| n.parse::<i32>().ok()) {
+            tids.push(tid);
+        }
+    }
+    Ok(tids)
+}
+
+/// Send `signal` to a single thread (not the whole thread group) via `tgkill`.
+fn tgkill(pid: Pid, tid: i32, signal: Signal) -> Result<()> {
+    let ret = unsafe { libc::syscall(libc::SYS_tgkill, pid.as_raw(), tid, signal as libc::c_int) };
+    if ret < 0 {
+        return Err(anyhow!(
+            "tgkill(pid={}, tid={}, {:?}) failed: {}",
+            pid,
+            tid,
+            signal,
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+/// Parse the state character (`R`, `S`, `T`, ...) out of `/proc/<pid>/task/<tid>/stat`.
+///
+/// The second field (`comm`) is parenthesized and may itself contain spaces
+/// or parens, so we split on the *last* `)` rather than on whitespace.
+fn thread_state(stat: &str) -> Option<char> {
+    stat.rsplit_once(')')?.1.trim_start().chars().next()
+}
+
+/// Block until thread `tid` of `pid` is observed stopped (state `T`).
+///
+/// A non-leader thread isn't `waitpid`-able without a ptrace attach, so we
+/// poll the same state the kernel already exposes in `/proc` instead.
+fn wait_until_stopped(pid: Pid, tid: i32) -> Result<()> {
+    let stat_path = format!("/proc/{}/task/{}/stat", pid, tid);
+    for _ in 0..FREEZE_POLL_ATTEMPTS {
+        let stat = fs::read_to_string(&stat_path)
+            .with_context(|| format!("Failed to read {}", stat_path))?;
+        if thread_state(&stat) == Some('T') {
+            return Ok(());
+        }
+        std::thread::sleep(FREEZE_POLL_INTERVAL);
+    }
+    Err(anyhow!(
+        "Thread {} of PID {} did not reach stopped state within timeout",
+        tid,
+        pid
+    ))
+}
+
 // =============================================================================
 // Per-Worker Snapshot State
 // =============================================================================
@@ -219,105 +820,1323 @@ fn align_to_page(addr: usize) -> usize {
 pub struct WorkerSnapshot {
     /// The worker's userfaultfd
     pub uffd: Uffd,
-    /// Golden pages: page_addr -> page_data
-    pub golden_pages: HashMap<usize, Vec<u8>>,
+    /// Where this worker's golden pages live
+    golden_store: GoldenStore,
     /// Registered memory regions
     pub regions: Vec<MemoryRegion>,
+    /// Which reset strategy this worker's UFFD was registered for
+    pub mode: ResetMode,
+    /// Golden pages written to since the last reset. Only populated (and
+    /// only meaningful) when `mode == ResetMode::WriteProtect`.
+    dirty_pages: HashSet<usize>,
+    /// Scratch page-sized buffer reused across `GoldenStore::OnDisk` reads
+    /// so a fault doesn't allocate on the hot path.
+    bounce: RefCell<[u8; PAGE_SIZE]>,
+    /// Scratch buffer for a coalesced multi-page `UFFDIO_COPY`, pre-sized to
+    /// `SnapshotManager::prefetch_window_pages` pages at registration so
+    /// `handle_fault`/`handle_pending_faults` don't allocate on the hot path.
+    batch_bounce: RefCell<Vec<u8>>,
+    /// Golden pages not yet copied into this worker's live mapping. Checked
+    /// when extending a faulted page into a run of prefetched neighbors, so
+    /// a page already resident is never re-offered to `UFFDIO_COPY` (which
+    /// the kernel would reject wholesale with `EEXIST`). Repopulated by
+    /// `reset_worker` for whichever pages it just dropped. Unused by
+    /// `GoldenStore::Shared`, which is restored via `UFFDIO_CONTINUE`.
+    pending_pages: RefCell<HashSet<usize>>,
+    /// Cumulative fault accounting for this worker.
+    stats: RefCell<FaultStats>,
+    /// Opt-in ring buffer of recent faults, enabled via `enable_event_log`.
+    event_log: RefCell<Option<FaultEventLog>>,
+    /// Readiness eventfd handed off alongside the UFFD (and, optionally, a
+    /// shared golden memfd) via `register_worker_with_fd_batch`. Only
+    /// present when the worker sent one under `FdRole::ReadyEventFd`; kept
+    /// open for the worker's lifetime since closing it would arm `POLLHUP`
+    /// on the other end.
+    ready_eventfd: Option<OwnedFd>,
 }
 
-// =============================================================================
-// Snapshot Manager
-// =============================================================================
-
-/// Central manager for capturing and restoring worker memory
-pub struct SnapshotManager {
-    /// Whether userfaultfd is available
-    pub available: bool,
-    /// Per-worker snapshots
-    workers: HashMap<i32, WorkerSnapshot>,
+impl WorkerSnapshot {
+    /// Record that a fault was satisfied, updating cumulative stats and (if
+    /// enabled) appending to the event log.
+    fn record_fault(&self, page_addr: usize, kind: FaultEventKind, bytes: usize, latency: Duration) {
+        self.stats.borrow_mut().record(page_addr, bytes, latency);
+
+        if let Some(log) = self.event_log.borrow_mut().as_mut() {
+            log.push(FaultEvent {
+                timestamp_ns: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_nanos(),
+                page_addr,
+                kind,
+                latency_ns: latency.as_nanos().min(u64::MAX as u128) as u64,
+            });
+        }
+    }
 }
 
-impl SnapshotManager {
-    /// Create a new SnapshotManager, testing for userfaultfd availability
-    pub fn new() -> Result<Self> {
-        // Test if userfaultfd is available
-        let available = match UffdBuilder::new()
-            .close_on_exec(true)
-            .non_blocking(false)
-            .create()
-        {
-            Ok(_) => {
-                eprintln!("[snapshot] userfaultfd available - Fast-Reset mode enabled");
-                true
-            }
-            Err(e) => {
-                eprintln!(
-                    "[snapshot] userfaultfd unavailable ({}). Falling back to fork-server.",
-                    e
-                );
-                false
-            }
-        };
+/// Try to restore a run of contiguous still-missing golden pages starting
+/// at `page_start` via a single `UFFDIO_COPY`, coalescing up to
+/// `window_pages` of them (the faulting page plus however many of its
+/// not-yet-resident neighbors are also backed by golden data) to amortize
+/// the ioctl cost across the whole run instead of paying it once per page.
+///
+/// Returns the number of bytes copied, or `None` if `page_start` itself
+/// isn't a golden page (the caller should zero-fill it instead). A free
+/// function (not a `SnapshotManager` method) so it can be called from
+/// `handle_pending_faults` while a worker is already borrowed mutably out
+/// of `self.workers`.
+fn restore_golden_run(
+    worker: &WorkerSnapshot,
+    pid: Pid,
+    page_start: usize,
+    window_pages: usize,
+) -> Result<Option<usize>> {
+    let mut batch = worker.batch_bounce.borrow_mut();
+    let window = window_pages.min((batch.capacity() / PAGE_SIZE).max(1));
+    let pending = worker.pending_pages.borrow();
+    let n_pages = worker.golden_store.read_run(page_start, window, &pending, &mut batch)?;
+    drop(pending);
+    if n_pages == 0 {
+        return Ok(None);
+    }
 
-        Ok(Self {
-            available,
-            workers: HashMap::new(),
-        })
+    let len = n_pages * PAGE_SIZE;
+    eprintln!(
+        "[snapshot] Restoring {} page(s) at {:x} ({} bytes) for PID {}",
+        n_pages, page_start, len, pid
+    );
+    // CRITICAL: Uffd::copy signature is (src, dst, len, wake)
+    unsafe {
+        worker.uffd.copy(
+            batch.as_ptr() as *const libc::c_void, // src data
+            page_start as *mut libc::c_void,        // dst addr
+            len,                                     // len
+            true,                                   // wake the faulting thread
+        )
     }
+    .with_context(|| format!("Failed to copy {}-page run at {:x}", n_pages, page_start))?;
 
-    /// Get the raw UFFD file descriptor for a worker (for polling)
-    pub fn get_worker_uffd(&self, pid: Pid) -> Option<RawFd> {
-        self.workers.get(&pid.as_raw()).map(|w| w.uffd.as_raw_fd())
+    let mut pending = worker.pending_pages.borrow_mut();
+    for i in 0..n_pages {
+        pending.remove(&(page_start + i * PAGE_SIZE));
     }
+    Ok(Some(len))
+}
 
-    /// Register a worker with its UFFD (received via SCM_RIGHTS)
-    ///
-    /// This is called when a worker sends its UFFD to the Supervisor.
-    /// The worker must be in SIGSTOP state before calling this.
-    pub fn register_worker_with_uffd(&mut self, pid: Pid, uffd: Uffd) -> Result<()> {
-        if !self.available {
-            return Ok(()); // No-op in fallback mode
+/// Where a worker's golden snapshot pages are stored.
+///
+/// `InMemory` is the original behavior. `OnDisk` trades a `pread` per fault
+/// for not pinning every captured page in Supervisor RAM, which matters when
+/// running many workers (or workers with large heaps) concurrently -
+/// analogous to crosvm vmm-swap's file-backed `page_handler`.
+enum GoldenStore {
+    InMemory(HashMap<usize, Vec<u8>>),
+    OnDisk {
+        file: File,
+        /// page_addr -> (byte offset into `file`, page length)
+        index: HashMap<usize, (u64, usize)>,
+    },
+    /// Golden pages live in a sealed `memfd` that every worker sharing this
+    /// golden image has `mmap(MAP_SHARED)`-ed. Faults are satisfied with
+    /// `UFFDIO_CONTINUE` (see `handle_pending_faults`), which never touches
+    /// `memfd`/`index` directly - they're kept here purely for accounting
+    /// (`status`) and so the worker's dup of the fd stays alive as long as
+    /// its `WorkerSnapshot` does.
+    Shared {
+        memfd: File,
+        /// page_addr -> (byte offset into `memfd`, page length)
+        index: HashMap<usize, (u64, usize)>,
+    },
+}
+
+impl GoldenStore {
+    /// Status this store reports to callers (e.g. for fleet-wide memory accounting).
+    fn status(&self) -> Status {
+        match self {
+            GoldenStore::InMemory(_) => Status::InMemory,
+            GoldenStore::OnDisk { .. } => Status::SpilledToDisk,
+            GoldenStore::Shared { .. } => Status::Shared,
         }
+    }
 
-        // Parse memory maps and filter for snapshotable regions
-        let regions = parse_memory_maps(pid)?;
-        let snapshot_regions: Vec<MemoryRegion> = regions
-            .into_iter()
-            .filter(|r| r.should_snapshot())
-            .collect();
+    /// Read a golden page into `bounce`, returning its length if present.
+    ///
+    /// Only meaningful for `InMemory`/`OnDisk`; `Shared` pages are never
+    /// copied through a bounce buffer (see `FaultKind::Minor` handling).
+    fn read_page(&self, page_addr: usize, bounce: &mut [u8; PAGE_SIZE]) -> Result<Option<usize>> {
+        match self {
+            GoldenStore::InMemory(pages) => Ok(pages.get(&page_addr).map(|data| {
+                bounce[..data.len()].copy_from_slice(data);
+                data.len()
+            })),
+            GoldenStore::OnDisk { file, index } => match index.get(&page_addr) {
+                Some(&(offset, len)) => {
+                    file.read_exact_at(&mut bounce[..len], offset).with_context(|| {
+                        format!("Failed to pread golden page at offset {}", offset)
+                    })?;
+                    Ok(Some(len))
+                }
+                None => Ok(None),
+            },
+            GoldenStore::Shared { index, .. } => {
+                Ok(index.get(&page_addr).map(|&(_, len)| len))
+            }
+        }
+    }
 
-        eprintln!(
-            "[snapshot] Registering worker PID {}: {} regions to capture",
-            pid,
-            snapshot_regions.len()
-        );
+    /// Offset of `page_addr` within the shared `memfd`, if this is a
+    /// `Shared` store and the page is present.
+    fn shared_offset(&self, page_addr: usize) -> Option<u64> {
+        match self {
+            GoldenStore::Shared { index, .. } => index.get(&page_addr).map(|&(offset, _)| offset),
+            _ => None,
+        }
+    }
 
-        // Capture golden copy for each region
-        let mut golden_pages = HashMap::new();
-        for region in &snapshot_regions {
-            let pages = self.capture_region_pages(pid, region)?;
-            golden_pages.extend(pages);
+    /// Every golden page address this store holds, for seeding a fresh
+    /// worker's `pending_pages` (and re-seeding it after a full reset).
+    fn page_addrs(&self) -> HashSet<usize> {
+        match self {
+            GoldenStore::InMemory(pages) => pages.keys().copied().collect(),
+            GoldenStore::OnDisk { index, .. } => index.keys().copied().collect(),
+            GoldenStore::Shared { index, .. } => index.keys().copied().collect(),
         }
+    }
 
-        // Register regions with the worker's UFFD
-        for region in &snapshot_regions {
-            uffd.register(region.start as *mut libc::c_void, region.len)
-                .with_context(|| format!("Failed to register region {}", region.name))?;
+    /// Read up to `max_pages` *full* (exactly `PAGE_SIZE`-byte) golden pages
+    /// starting at `start_page` into `out`, stopping at the first page that
+    /// isn't full-length, isn't in this store, or - for every page after the
+    /// first - isn't in `pending` (i.e. already resident; re-copying it
+    /// would make the kernel reject the whole `UFFDIO_COPY` range with
+    /// `EEXIST`). The first page is never checked against `pending` since
+    /// the caller only gets here because UFFD just reported it missing.
+    ///
+    /// Returns the number of pages actually read (0 if `start_page` itself
+    /// isn't a golden page). Used to coalesce a run of still-missing pages
+    /// into a single `UFFDIO_COPY`; `Shared` stores never coalesce this way
+    /// since they're restored via `UFFDIO_CONTINUE`, not a byte copy.
+    fn read_run(
+        &self,
+        start_page: usize,
+        max_pages: usize,
+        pending: &HashSet<usize>,
+        out: &mut Vec<u8>,
+    ) -> Result<usize> {
+        out.clear();
+        let mut n = 0;
+        while n < max_pages {
+            let page_addr = start_page + n * PAGE_SIZE;
+            if n > 0 && !pending.contains(&page_addr) {
+                break;
+            }
+            match self {
+                GoldenStore::InMemory(pages) => match pages.get(&page_addr) {
+                    Some(data) if data.len() == PAGE_SIZE => out.extend_from_slice(data),
+                    _ => break,
+                },
+                GoldenStore::OnDisk { file, index } => match index.get(&page_addr) {
+                    Some(&(offset, len)) if len == PAGE_SIZE => {
+                        let cur = out.len();
+                        out.resize(cur + PAGE_SIZE, 0);
+                        file.read_exact_at(&mut out[cur..], offset).with_context(|| {
+                            format!("Failed to pread golden page at offset {}", offset)
+                        })?;
+                    }
+                    _ => break,
+                },
+                GoldenStore::Shared { .. } => break,
+            }
+            n += 1;
         }
+        Ok(n)
+    }
+}
+
+/// Where a worker's golden pages currently live, for reporting/accounting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// Golden pages are held in Supervisor RAM (`GoldenStore::InMemory`).
+    InMemory,
+    /// Golden pages were written once to a backing file and dropped from
+    /// RAM; faults are satisfied via `pread` (`GoldenStore::OnDisk`).
+    SpilledToDisk,
+    /// Golden pages live in one sealed `memfd` shared `MAP_SHARED` across
+    /// every worker of this golden image; faults are satisfied via
+    /// `UFFDIO_CONTINUE` (`GoldenStore::Shared`).
+    Shared,
+}
+
+// =============================================================================
+// Golden Snapshot Persistence
+// =============================================================================
+
+/// Magic bytes at the start of a persisted golden snapshot file.
+const GOLDEN_FILE_MAGIC: &[u8; 8] = b"TACHGLDN";
+/// On-disk format version; bumped whenever the layout below changes.
+const GOLDEN_FILE_VERSION: u32 = 1;
+
+/// Identifies the donor process a persisted golden snapshot was captured
+/// from, so a loaded snapshot can be told apart from one captured against a
+/// since-upgraded interpreter before it's ever handed to a worker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenFingerprint {
+    /// Path (from `/proc/pid/maps`) of the libpython the donor had mapped.
+    pub libpython_path: String,
+    /// That libpython's `NT_GNU_BUILD_ID` note, if it has one.
+    pub build_id: Vec<u8>,
+}
+
+impl GoldenFingerprint {
+    /// Derive a fingerprint from a set of already-filtered snapshot
+    /// `regions`, or `None` if none of them is libpython (e.g. a non-Python
+    /// donor process) - persistence is simply skipped in that case.
+    fn capture(regions: &[MemoryRegion]) -> Option<Self> {
+        let libpython_path = regions.iter().find(|r| r.name.contains("libpython"))?.name.clone();
+        let build_id = read_build_id(Path::new(&libpython_path)).unwrap_or_default();
+        Some(GoldenFingerprint { libpython_path, build_id })
+    }
+}
+
+/// A golden snapshot reconstructed from disk by `SnapshotManager::load_golden`,
+/// without needing a live donor process.
+pub struct LoadedGolden {
+    pub regions: Vec<MemoryRegion>,
+    pub golden_pages: HashMap<usize, Vec<u8>>,
+    pub fingerprint: GoldenFingerprint,
+}
+
+/// Extract the `NT_GNU_BUILD_ID` note from an ELF file's `PT_NOTE` segment,
+/// if it has one. Used to fingerprint a donor's libpython so a persisted
+/// golden snapshot can tell a library upgrade from a stale cache hit.
+///
+/// Only handles little-endian 32/64-bit ELF (the only byte order Tach's
+/// supported targets - x86_64/aarch64 - use); anything else, or any
+/// malformed input, is treated as "no build-id" rather than an error, since
+/// the fingerprint can always fall back to the libpython path alone.
+fn read_build_id(path: &Path) -> Option<Vec<u8>> {
+    let data = fs::read(path).ok()?;
+    if data.len() < 64 || &data[0..4] != b"\x7fELF" || data[5] != 1 {
+        return None;
+    }
+    let is_64 = data[4] == 2;
+
+    let (phoff, phentsize, phnum) = if is_64 {
+        (
+            u64::from_le_bytes(data.get(32..40)?.try_into().ok()?) as usize,
+            u16::from_le_bytes(data.get(54..56)?.try_into().ok()?) as usize,
+            u16::from_le_bytes(data.get(56..58)?.try_into().ok()?) as usize,
+        )
+    } else {
+        (
+            u32::from_le_bytes(data.get(28..32)?.try_into().ok()?) as usize,
+            u16::from_le_bytes(data.get(42..44)?.try_into().ok()?) as usize,
+            u16::from_le_bytes(data.get(44..46)?.try_into().ok()?) as usize,
+        )
+    };
+
+    const PT_NOTE: u32 = 4;
+    for i in 0..phnum {
+        let ph = data.get(phoff + i * phentsize..phoff + (i + 1) * phentsize)?;
+        let p_type = u32::from_le_bytes(ph.get(0..4)?.try_into().ok()?);
+        if p_type != PT_NOTE {
+            continue;
+        }
+        let (p_offset, p_filesz) = if is_64 {
+            (
+                u64::from_le_bytes(ph.get(8..16)?.try_into().ok()?) as usize,
+                u64::from_le_bytes(ph.get(32..40)?.try_into().ok()?) as usize,
+            )
+        } else {
+            (
+                u32::from_le_bytes(ph.get(4..8)?.try_into().ok()?) as usize,
+                u32::from_le_bytes(ph.get(16..20)?.try_into().ok()?) as usize,
+            )
+        };
+        if let Some(id) = data
+            .get(p_offset..p_offset.checked_add(p_filesz)?)
+            .and_then(parse_build_id_note)
+        {
+            return Some(id);
+        }
+    }
+    None
+}
+
+/// Parse a `PT_NOTE` segment's contents looking for `NT_GNU_BUILD_ID`
+/// (name `"GNU\0"`, type 3), per the ELF note format: `namesz`, `descsz`,
+/// `type` (each a `u32`), then the name and descriptor, each padded up to
+/// 4-byte alignment.
+fn parse_build_id_note(mut notes: &[u8]) -> Option<Vec<u8>> {
+    const NT_GNU_BUILD_ID: u32 = 3;
+    let align4 = |n: usize| (n + 3) & !3;
+
+    while notes.len() >= 12 {
+        let namesz = u32::from_le_bytes(notes[0..4].try_into().ok()?) as usize;
+        let descsz = u32::from_le_bytes(notes[4..8].try_into().ok()?) as usize;
+        let note_type = u32::from_le_bytes(notes[8..12].try_into().ok()?);
+        let name_end = 12usize.checked_add(align4(namesz))?;
+        let desc_end = name_end.checked_add(align4(descsz))?;
+        if notes.len() < desc_end {
+            return None;
+        }
+
+        if note_type == NT_GNU_BUILD_ID && notes.get(12..16) == Some(&b"GNU\0"[..]) {
+            return Some(notes[name_end..name_end + descsz].to_vec());
+        }
+        notes = &notes[desc_end..];
+    }
+    None
+}
+
+/// Write a `u32`-length-prefixed byte string to `file`.
+fn write_len_prefixed(file: &mut File, bytes: &[u8]) -> Result<()> {
+    file.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    file.write_all(bytes)?;
+    Ok(())
+}
+
+/// Read back a `u32`-length-prefixed byte string written by `write_len_prefixed`.
+fn read_len_prefixed(cursor: &mut &[u8]) -> Result<Vec<u8>> {
+    let len = u32::from_le_bytes(take(cursor, 4)?.try_into().unwrap()) as usize;
+    Ok(take(cursor, len)?.to_vec())
+}
+
+/// Write a `u16`-length-prefixed byte string to `file` - used for the small
+/// per-region `perms`/`name` fields, where a full `u32` prefix would be
+/// wasteful.
+fn write_len_prefixed_u16(file: &mut File, bytes: &[u8]) -> Result<()> {
+    file.write_all(&(bytes.len() as u16).to_le_bytes())?;
+    file.write_all(bytes)?;
+    Ok(())
+}
+
+/// Read back a `u16`-length-prefixed byte string written by `write_len_prefixed_u16`.
+fn read_len_prefixed_u16(cursor: &mut &[u8]) -> Result<Vec<u8>> {
+    let len = u16::from_le_bytes(take(cursor, 2)?.try_into().unwrap()) as usize;
+    Ok(take(cursor, len)?.to_vec())
+}
+
+/// Split `n` bytes off the front of `cursor`, advancing it, or error on EOF.
+fn take<'a>(cursor: &mut &'a [u8], n: usize) -> Result<&'a [u8]> {
+    if cursor.len() < n {
+        return Err(anyhow!("Unexpected EOF while reading golden snapshot file"));
+    }
+    let (head, rest) = cursor.split_at(n);
+    *cursor = rest;
+    Ok(head)
+}
+
+// =============================================================================
+// Fault Accounting
+// =============================================================================
+
+/// Kind of fault a `FaultEvent` or `FaultStats` entry came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultEventKind {
+    /// A missing-page fault, satisfied via `UFFDIO_COPY` or `UFFDIO_ZEROPAGE`.
+    Missing,
+    /// A write-protect fault, satisfied by lifting protection on the page.
+    WriteProtect,
+    /// A minor fault against a `GoldenStore::Shared` region, satisfied via
+    /// `UFFDIO_CONTINUE` instead of copying any bytes.
+    Minor,
+}
+
+/// Outcome of reaping one worker's child process off the `SIGCHLD`
+/// signalfd (see `SnapshotManager::take_reaped_workers`), distinguishing a
+/// clean exit from a crash so callers don't have to re-derive that from a
+/// raw `WaitStatus`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkerExit {
+    /// The worker exited normally with this status code.
+    Exited(i32),
+    /// The worker was killed by this signal.
+    Signaled(Signal),
+}
+
+/// Outcome of a single `SnapshotManager::run_fault_loop` call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultLoopResult {
+    /// One of the polled workers' UFFDs hung up, meaning its process exited
+    /// (or its descriptor was otherwise closed). The caller should reap it
+    /// (e.g. via `waitpid`) and drop it from the next call's `pids`.
+    WorkerExited(Pid),
+    /// `epoll_wait` returned with nothing ready before `deadline` elapsed.
+    TimedOut,
+    /// `pids` was empty - nothing to wait on.
+    Idle,
+}
+
+/// Cumulative page-fault accounting for a single worker.
+///
+/// Cheap enough to sample on every `handle_fault`/`handle_pending_faults`
+/// call; lets callers tell which workloads dirty the most pages and whether
+/// the snapshot engine itself is the reset-latency bottleneck, per
+/// crosvm swap's `PageFaultEventLogger`.
+#[derive(Debug, Clone, Default)]
+pub struct FaultStats {
+    /// Total faults handled across the worker's lifetime.
+    pub total_faults: u64,
+    /// Total bytes materialized to satisfy faults (copied or zero-filled).
+    pub total_bytes_copied: u64,
+    /// Histogram of read_event -> fault-satisfied latency.
+    pub latency_histogram: LatencyHistogram,
+    touched_pages: HashSet<usize>,
+}
+
+impl FaultStats {
+    /// Number of distinct pages touched across the worker's lifetime.
+    pub fn unique_pages(&self) -> usize {
+        self.touched_pages.len()
+    }
+
+    fn record(&mut self, page_addr: usize, bytes: usize, latency: Duration) {
+        self.total_faults += 1;
+        self.total_bytes_copied += bytes as u64;
+        self.touched_pages.insert(page_addr);
+        self.latency_histogram.record(latency);
+    }
+}
+
+/// Fixed-bucket histogram of fault-handling latency.
+///
+/// Buckets are `< 1us`, `< 10us`, `< 100us`, `< 1ms`, `< 10ms`, `>= 10ms`
+/// - coarse enough to answer "is the snapshot engine the bottleneck?"
+/// without pulling in a stats crate.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyHistogram {
+    buckets: [u64; 6],
+}
+
+impl LatencyHistogram {
+    const BOUNDARIES_NS: [u64; 5] = [1_000, 10_000, 100_000, 1_000_000, 10_000_000];
+
+    fn record(&mut self, latency: Duration) {
+        let latency_ns = latency.as_nanos().min(u64::MAX as u128) as u64;
+        let bucket = Self::BOUNDARIES_NS
+            .iter()
+            .position(|&boundary| latency_ns < boundary)
+            .unwrap_or(Self::BOUNDARIES_NS.len());
+        self.buckets[bucket] += 1;
+    }
+
+    /// Per-bucket counts, in the same order as the documented boundaries.
+    pub fn counts(&self) -> [u64; 6] {
+        self.buckets
+    }
+}
+
+/// A single recorded fault, kept in a worker's opt-in ring-buffer event log.
+#[derive(Debug, Clone)]
+pub struct FaultEvent {
+    pub timestamp_ns: u128,
+    pub page_addr: usize,
+    pub kind: FaultEventKind,
+    pub latency_ns: u64,
+}
+
+/// Bounded ring buffer of `FaultEvent`s for a worker, only allocated once a
+/// caller opts in via `SnapshotManager::enable_event_log`.
+struct FaultEventLog {
+    capacity: usize,
+    events: VecDeque<FaultEvent>,
+}
+
+impl FaultEventLog {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            events: VecDeque::with_capacity(capacity.min(1024)),
+        }
+    }
+
+    fn push(&mut self, event: FaultEvent) {
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+}
+
+/// Per-worker memory reset strategy, decided once at registration time by
+/// whether the worker's UFFD negotiated `UFFD_FEATURE_PAGEFAULT_FLAG_WP`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetMode {
+    /// Full `MADV_DONTNEED` across every registered region on every reset,
+    /// relying on plain copy-on-fault (`UFFDIO_COPY`) to refill each page
+    /// as it's touched again - the original behavior, used as a fallback
+    /// when the kernel's UFFD doesn't support write-protect faults.
+    CopyOnFault,
+    /// Userfaultfd write-protection (`UFFDIO_REGISTER_MODE_WP` +
+    /// `UFFDIO_WRITEPROTECT`) tracks exactly which golden pages were
+    /// written since the last reset, so `reset_worker` only needs to drop
+    /// and re-protect those - turning an O(total pages) reset into
+    /// O(dirtied pages). Modeled on crosvm vmm-swap's dual-mode page
+    /// handler.
+    WriteProtect,
+    /// `UFFDIO_REGISTER_MODE_MINOR` over a `GoldenStore::Shared` memfd: the
+    /// golden pages live in one shared, sealed page cache that every worker
+    /// maps `MAP_SHARED`, so a fault is satisfied with `UFFDIO_CONTINUE`
+    /// (no byte copy) instead of `UFFDIO_COPY` of a private buffer. Collapses
+    /// golden RSS from per-worker to a single shared copy.
+    SharedMinor,
+}
+
+// =============================================================================
+// Snapshot Manager
+// =============================================================================
+
+/// Central manager for capturing and restoring worker memory
+pub struct SnapshotManager {
+    /// Whether userfaultfd is available
+    pub available: bool,
+    /// Per-worker snapshots
+    workers: HashMap<i32, WorkerSnapshot>,
+    /// When set, newly registered workers spill their golden pages into a
+    /// backing file under this directory instead of keeping them in RAM.
+    backing_dir: Option<PathBuf>,
+    /// Where freshly-captured golden snapshots are written for warm start
+    /// across supervisor restarts (see `persist_golden`/`load_golden`).
+    golden_persist_path: Option<PathBuf>,
+    /// A golden snapshot loaded from `golden_persist_path` at construction
+    /// time, if one existed and parsed cleanly. Consulted by every
+    /// `register_worker_with_uffd_frozen` call; never invalidated, since the
+    /// same warm image is reused for every worker that matches its
+    /// fingerprint.
+    loaded_golden: Option<LoadedGolden>,
+    /// Persistent epoll instance backing `run_reactor`. Every worker's UFFD
+    /// is added here as it's registered and removed as it's dropped, so the
+    /// reactor never has to rebuild its watch set from scratch the way
+    /// `run_fault_loop` does for an ad hoc `&[Pid]`.
+    reactor_epoll_fd: RawFd,
+    /// UFFD fd -> worker PID, kept in lockstep with `reactor_epoll_fd`'s
+    /// interest list so `run_reactor` can map a ready fd back to a worker
+    /// without scanning `workers`.
+    reactor_fd_to_pid: HashMap<RawFd, i32>,
+    /// How many contiguous still-missing golden pages `handle_fault`/
+    /// `handle_pending_faults` will coalesce into a single `UFFDIO_COPY`
+    /// when a fault's golden page is found. Applied to workers at
+    /// registration time; see `set_prefetch_window`.
+    prefetch_window_pages: usize,
+    /// `SIGCHLD` delivered as file-descriptor events instead of an async
+    /// signal handler, registered in `reactor_epoll_fd` alongside every
+    /// worker's UFFD so `run_reactor` notices a worker's death the same way
+    /// it notices a page fault - no race with a handler interrupting
+    /// arbitrary code.
+    child_signalfd: SignalFd,
+    /// Workers reaped off `child_signalfd` since the last
+    /// `take_reaped_workers` call.
+    reaped_workers: Vec<(Pid, WorkerExit)>,
+}
+
+/// Default for `SnapshotManager::prefetch_window_pages`: 16 pages (64 KiB)
+/// ahead of the faulting page, chosen to amortize `UFFDIO_COPY`'s per-call
+/// overhead without over-copying heap-heavy workers that touch memory
+/// sparsely.
+const DEFAULT_PREFETCH_WINDOW_PAGES: usize = 16;
+
+impl Drop for SnapshotManager {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.reactor_epoll_fd) };
+    }
+}
+
+impl SnapshotManager {
+    /// Create a new SnapshotManager, testing for userfaultfd availability
+    pub fn new() -> Result<Self, SnapshotError> {
+        // Test if userfaultfd is available
+        let available = match UffdBuilder::new()
+            .close_on_exec(true)
+            .non_blocking(false)
+            .create()
+        {
+            Ok(_) => {
+                eprintln!("[snapshot] userfaultfd available - Fast-Reset mode enabled");
+                true
+            }
+            Err(e) => {
+                eprintln!(
+                    "[snapshot] userfaultfd unavailable ({}). Falling back to fork-server.",
+                    e
+                );
+                false
+            }
+        };
+
+        let reactor_epoll_fd = epoll_create1(EpollCreateFlags::EPOLL_CLOEXEC)
+            .context("Failed to create reactor epoll instance")?;
+
+        // Block SIGCHLD process-wide and read it back as signalfd_siginfo
+        // events instead of an async handler, so a worker's death is just
+        // another epoll wakeup on `reactor_epoll_fd` - reaped on our own
+        // stack, never racing a handler interrupting arbitrary code.
+        let mut sigchld_mask = SigSet::empty();
+        sigchld_mask.add(Signal::SIGCHLD);
+        sigprocmask(SigmaskHow::SIG_BLOCK, Some(&sigchld_mask), None)
+            .context("Failed to block SIGCHLD ahead of signalfd")?;
+        let child_signalfd = SignalFd::with_flags(
+            &sigchld_mask,
+            SfdFlags::SFD_CLOEXEC | SfdFlags::SFD_NONBLOCK,
+        )
+        .context("Failed to create SIGCHLD signalfd")?;
+
+        let mut signalfd_event = EpollEvent::new(
+            EpollFlags::EPOLLIN | EpollFlags::EPOLLET,
+            child_signalfd.as_raw_fd() as u64,
+        );
+        epoll_ctl(
+            reactor_epoll_fd,
+            EpollOp::EpollCtlAdd,
+            child_signalfd.as_raw_fd(),
+            &mut signalfd_event,
+        )
+        .context("Failed to add SIGCHLD signalfd to reactor epoll")?;
+
+        Ok(Self {
+            available,
+            workers: HashMap::new(),
+            backing_dir: None,
+            golden_persist_path: None,
+            loaded_golden: None,
+            reactor_epoll_fd,
+            reactor_fd_to_pid: HashMap::new(),
+            prefetch_window_pages: DEFAULT_PREFETCH_WINDOW_PAGES,
+            child_signalfd,
+            reaped_workers: Vec::new(),
+        })
+    }
+
+    /// Override how many contiguous still-missing golden pages a single
+    /// fault will pull in via one `UFFDIO_COPY`. Takes effect for workers
+    /// registered after this call; already-registered workers keep the
+    /// window (and pre-sized scratch buffer) they started with. Pass `1` to
+    /// disable prefetch and copy exactly one page per fault.
+    pub fn set_prefetch_window(&mut self, pages: usize) {
+        self.prefetch_window_pages = pages.max(1);
+    }
+
+    /// Create a SnapshotManager that spills golden pages to disk instead of
+    /// pinning them in Supervisor RAM.
+    ///
+    /// Trades a `pread` of bounce-buffer latency per fault for being able to
+    /// run many workers (or workers with large heaps) concurrently without
+    /// the golden set itself becoming the memory bottleneck.
+    pub fn new_with_backing(dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create backing directory {:?}", dir))?;
+        let mut manager = Self::new()?;
+        manager.backing_dir = Some(dir);
+        Ok(manager)
+    }
+
+    /// Create a SnapshotManager that persists freshly-captured golden
+    /// snapshots to `path` and, if `path` already holds one from a previous
+    /// run, warm-starts from it instead of paying a live capture.
+    ///
+    /// A stale or unreadable file at `path` is logged and treated the same
+    /// as a missing one - this only ever trades away the warm-start fast
+    /// path, never registration itself.
+    pub fn new_with_golden_persist(path: PathBuf) -> Result<Self> {
+        let mut manager = Self::new()?;
+        manager.loaded_golden = if path.exists() {
+            match Self::load_golden_inner(&path) {
+                Ok(loaded) => {
+                    eprintln!(
+                        "[snapshot] Loaded persisted golden snapshot from {:?} ({} pages, libpython {})",
+                        path,
+                        loaded.golden_pages.len(),
+                        loaded.fingerprint.libpython_path
+                    );
+                    Some(loaded)
+                }
+                Err(e) => {
+                    eprintln!(
+                        "[snapshot] Failed to load persisted golden snapshot from {:?} ({:#}), cold-starting",
+                        path, e
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        manager.golden_persist_path = Some(path);
+        Ok(manager)
+    }
+
+    /// Get the raw UFFD file descriptor for a worker (for polling)
+    pub fn get_worker_uffd(&self, pid: Pid) -> Option<RawFd> {
+        self.workers.get(&pid.as_raw()).map(|w| w.uffd.as_raw_fd())
+    }
+
+    /// Stop every thread of `pid`, not just the one that raised SIGSTOP.
+    ///
+    /// A single SIGSTOP only guarantees *that* thread is quiescent; in a
+    /// real (multi-threaded) test process, another thread can still be
+    /// mutating memory while we capture the golden snapshot, corrupting it.
+    /// This enumerates `/proc/<pid>/task`, `tgkill`s every thread with
+    /// SIGSTOP, and only returns once each is observed stopped - the same
+    /// "freeze everything before touching memory" invariant crosvm enforces
+    /// before a swap-out. Pair with `thaw_worker` once capture is done.
+    pub fn freeze_worker(&self, pid: Pid) -> Result<()> {
+        for tid in list_threads(pid)? {
+            tgkill(pid, tid, Signal::SIGSTOP)?;
+            wait_until_stopped(pid, tid)?;
+        }
+        Ok(())
+    }
+
+    /// Resume every thread of `pid` previously stopped by `freeze_worker`.
+    pub fn thaw_worker(&self, pid: Pid) -> Result<()> {
+        for tid in list_threads(pid)? {
+            tgkill(pid, tid, Signal::SIGCONT)?;
+        }
+        Ok(())
+    }
+
+    /// Register a worker with its UFFD (received via SCM_RIGHTS)
+    ///
+    /// This is called when a worker sends its UFFD to the Supervisor. Every
+    /// thread of the worker is frozen internally (see `freeze_worker`) for
+    /// the duration of golden-page capture, so callers no longer need to
+    /// SIGSTOP the worker themselves beforehand.
+    pub fn register_worker_with_uffd(&mut self, pid: Pid, uffd: Uffd) -> Result<(), SnapshotError> {
+        if !self.available {
+            return Ok(()); // No-op in fallback mode
+        }
+
+        self.freeze_worker(pid)?;
+        let result = self.register_worker_with_uffd_frozen(pid, uffd);
+        self.thaw_worker(pid)?;
+        result.map_err(SnapshotError::from)
+    }
+
+    /// The body of `register_worker_with_uffd`, run while every thread of
+    /// `pid` is stopped so capture can't race with a concurrent write.
+    fn register_worker_with_uffd_frozen(&mut self, pid: Pid, uffd: Uffd) -> Result<()> {
+        // Parse memory maps and filter for snapshotable regions
+        let regions = parse_memory_maps(pid)?;
+        let live_regions: Vec<MemoryRegion> = regions
+            .into_iter()
+            .filter(|r| r.should_snapshot())
+            .collect();
+
+        eprintln!(
+            "[snapshot] Registering worker PID {}: {} regions to capture",
+            pid,
+            live_regions.len()
+        );
+
+        // Warm start: if a persisted golden snapshot was loaded at startup
+        // and its fingerprint (libpython path + build-id) still matches this
+        // worker, skip `process_vm_readv` entirely and reuse the pages and
+        // region layout read from disk. Workers with no libpython mapping
+        // (e.g. the non-Python processes the test suite drives) have no
+        // fingerprint at all and always take the live-capture path below,
+        // same as before this feature existed.
+        let fingerprint = GoldenFingerprint::capture(&live_regions);
+        let (snapshot_regions, golden_pages, freshly_captured) =
+            match (&self.loaded_golden, &fingerprint) {
+                (Some(loaded), Some(fingerprint)) if &loaded.fingerprint == fingerprint => {
+                    eprintln!(
+                        "[snapshot] PID {}: warm-starting from persisted golden snapshot ({} pages)",
+                        pid,
+                        loaded.golden_pages.len()
+                    );
+                    (loaded.regions.clone(), loaded.golden_pages.clone(), false)
+                }
+                (Some(_), Some(_)) => {
+                    eprintln!(
+                        "[snapshot] PID {}: persisted golden snapshot is stale (libpython changed), recapturing",
+                        pid
+                    );
+                    let golden_pages = self.capture_all_pages(pid, &live_regions)?;
+                    (live_regions, golden_pages, true)
+                }
+                _ => {
+                    let golden_pages = self.capture_all_pages(pid, &live_regions)?;
+                    (live_regions, golden_pages, true)
+                }
+            };
+
+        if freshly_captured {
+            if let Some(fingerprint) = &fingerprint {
+                if let Err(e) =
+                    self.persist_golden(pid, &snapshot_regions, &golden_pages, fingerprint)
+                {
+                    eprintln!("[snapshot] PID {}: failed to persist golden snapshot: {:#}", pid, e);
+                }
+            }
+        }
+
+        // Register regions with the worker's UFFD. If the kernel negotiated
+        // write-protect fault support, register in WP mode and immediately
+        // write-protect the whole golden range so every write after this
+        // point raises a WriteProtect fault we can track in `dirty_pages`;
+        // otherwise fall back to the original plain (missing-fault-only)
+        // registration.
+        let supports_wp = uffd.context_features().contains(FeatureFlags::PAGEFAULT_FLAG_WP);
+        let mode = if supports_wp {
+            for region in &snapshot_regions {
+                uffd.register_with_mode(
+                    region.start as *mut libc::c_void,
+                    region.len,
+                    RegisterMode::REGISTER_MODE_WP,
+                )
+                .with_context(|| format!("Failed to register (WP) region {}", region.name))?;
+
+                uffd.write_protect(
+                    region.start as *mut libc::c_void,
+                    region.len,
+                    WriteProtectMode::WRITE_PROTECT,
+                )
+                .with_context(|| format!("Failed to write-protect region {}", region.name))?;
+            }
+            eprintln!(
+                "[snapshot] PID {}: write-protect incremental reset enabled",
+                pid
+            );
+            ResetMode::WriteProtect
+        } else {
+            for region in &snapshot_regions {
+                uffd.register(region.start as *mut libc::c_void, region.len)
+                    .with_context(|| format!("Failed to register region {}", region.name))?;
+            }
+            eprintln!(
+                "[snapshot] PID {}: UFFD_FEATURE_PAGEFAULT_FLAG_WP unavailable, using full copy-on-fault reset",
+                pid
+            );
+            ResetMode::CopyOnFault
+        };
+
+        let golden_store = self.spill_or_keep(pid, golden_pages)?;
+        let pending_pages = golden_store.page_addrs();
 
         // Store worker snapshot
         self.workers.insert(
             pid.as_raw(),
             WorkerSnapshot {
                 uffd,
-                golden_pages,
+                golden_store,
                 regions: snapshot_regions,
+                mode,
+                dirty_pages: HashSet::new(),
+                bounce: RefCell::new([0u8; PAGE_SIZE]),
+                batch_bounce: RefCell::new(Vec::with_capacity(
+                    self.prefetch_window_pages * PAGE_SIZE,
+                )),
+                pending_pages: RefCell::new(pending_pages),
+                stats: RefCell::new(FaultStats::default()),
+                event_log: RefCell::new(None),
+                ready_eventfd: None,
             },
         );
 
+        self.add_to_reactor(pid)
+    }
+
+    /// Like `register_worker_with_uffd`, but shares one golden image across
+    /// every worker instead of giving this worker its own private copy.
+    ///
+    /// `memfd` must already be `mmap(MAP_SHARED)`-ed by the worker over its
+    /// writable regions (so its current contents match the worker's live
+    /// memory) and handed to us over the same SCM_RIGHTS channel as `uffd`
+    /// (see `recv_two_fds`). We capture golden bytes into `memfd` itself -
+    /// not a separate buffer - so the page cache the worker already has
+    /// mapped becomes the golden image, then register the worker's regions
+    /// in `UFFDIO_REGISTER_MODE_MINOR` so a fault is satisfied with
+    /// `UFFDIO_CONTINUE` against that same shared page cache.
+    ///
+    /// Falls back to `register_worker_with_uffd` (private `UFFDIO_COPY`)
+    /// when the running kernel doesn't support `UFFD_FEATURE_MINOR_SHMEM`.
+    pub fn register_worker_with_shared_memfd(
+        &mut self,
+        pid: Pid,
+        uffd: Uffd,
+        memfd: OwnedFd,
+    ) -> Result<(), SnapshotError> {
+        if !self.available {
+            return Ok(()); // No-op in fallback mode
+        }
+
+        self.freeze_worker(pid)?;
+        let result = self.register_worker_with_shared_memfd_frozen(pid, uffd, memfd);
+        self.thaw_worker(pid)?;
+        result.map_err(SnapshotError::from)
+    }
+
+    /// The body of `register_worker_with_shared_memfd`, run while every
+    /// thread of `pid` is stopped.
+    fn register_worker_with_shared_memfd_frozen(
+        &mut self,
+        pid: Pid,
+        uffd: Uffd,
+        memfd: OwnedFd,
+    ) -> Result<()> {
+        if !uffd.context_features().contains(FeatureFlags::MINOR_SHMEM) {
+            eprintln!(
+                "[snapshot] PID {}: UFFD_FEATURE_MINOR_SHMEM unavailable, falling back to private golden copies",
+                pid
+            );
+            return self.register_worker_with_uffd_frozen(pid, uffd);
+        }
+
+        let regions = parse_memory_maps(pid)?;
+        let snapshot_regions: Vec<MemoryRegion> = regions
+            .into_iter()
+            .filter(|r| r.should_snapshot())
+            .collect();
+
+        eprintln!(
+            "[snapshot] Registering worker PID {} (shared golden): {} regions to capture",
+            pid,
+            snapshot_regions.len()
+        );
+
+        let mut memfd = File::from(memfd);
+        let index = self.capture_region_to_memfd(pid, &snapshot_regions, &mut memfd)?;
+
+        for region in &snapshot_regions {
+            uffd.register_with_mode(
+                region.start as *mut libc::c_void,
+                region.len,
+                RegisterMode::REGISTER_MODE_MINOR,
+            )
+            .with_context(|| format!("Failed to register (MINOR) region {}", region.name))?;
+        }
+        eprintln!(
+            "[snapshot] PID {}: shared golden, minor-fault incremental reset enabled",
+            pid
+        );
+
+        self.workers.insert(
+            pid.as_raw(),
+            WorkerSnapshot {
+                uffd,
+                golden_store: GoldenStore::Shared { memfd, index },
+                regions: snapshot_regions,
+                mode: ResetMode::SharedMinor,
+                dirty_pages: HashSet::new(),
+                bounce: RefCell::new([0u8; PAGE_SIZE]),
+                batch_bounce: RefCell::new(Vec::new()),
+                pending_pages: RefCell::new(HashSet::new()),
+                stats: RefCell::new(FaultStats::default()),
+                event_log: RefCell::new(None),
+                ready_eventfd: None,
+            },
+        );
+
+        self.add_to_reactor(pid)
+    }
+
+    /// Register a worker whose fds arrived as one `FdRole`-tagged batch (see
+    /// `send_fd_batch`/`recv_fd_batch`) rather than through `recv_fd`/
+    /// `recv_two_fds`'s fixed positional shapes.
+    ///
+    /// Requires exactly one `FdRole::Uffd`. If a `FdRole::GoldenMemfd` is
+    /// also present, registers via `register_worker_with_shared_memfd`;
+    /// otherwise via `register_worker_with_uffd`. A `FdRole::ReadyEventFd`,
+    /// if present, is stashed on the resulting `WorkerSnapshot` for the
+    /// caller to retrieve later rather than interpreted here - the manager
+    /// doesn't yet act on worker readiness itself.
+    pub fn register_worker_with_fd_batch(
+        &mut self,
+        pid: Pid,
+        fds: Vec<(FdRole, OwnedFd)>,
+    ) -> Result<(), SnapshotError> {
+        let mut uffd_fd = None;
+        let mut memfd = None;
+        let mut ready_eventfd = None;
+        for (role, fd) in fds {
+            match role {
+                FdRole::Uffd if uffd_fd.is_none() => uffd_fd = Some(fd),
+                FdRole::GoldenMemfd if memfd.is_none() => memfd = Some(fd),
+                FdRole::ReadyEventFd if ready_eventfd.is_none() => ready_eventfd = Some(fd),
+                other => {
+                    return Err(SnapshotError::from(anyhow!(
+                        "fd batch for PID {} carried a duplicate {:?}",
+                        pid,
+                        other
+                    )))
+                }
+            }
+        }
+
+        let uffd_fd = uffd_fd.ok_or_else(|| {
+            SnapshotError::from(anyhow!("fd batch for PID {} carried no FdRole::Uffd", pid))
+        })?;
+        // SAFETY: uffd_fd was just received via recv_fd_batch's SCM_RIGHTS
+        // message and handed to us as the worker's sole reference to it.
+        let uffd = unsafe { Uffd::from_raw_fd(uffd_fd.into_raw_fd()) };
+
+        match memfd {
+            Some(memfd) => self.register_worker_with_shared_memfd(pid, uffd, memfd)?,
+            None => self.register_worker_with_uffd(pid, uffd)?,
+        }
+
+        if let Some(eventfd) = ready_eventfd {
+            if let Some(worker) = self.workers.get_mut(&pid.as_raw()) {
+                worker.ready_eventfd = Some(eventfd);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add `pid`'s UFFD to the persistent reactor epoll set, recording the
+    /// fd -> pid mapping `run_reactor` uses to route wakeups.
+    ///
+    /// Registered edge-triggered (`EPOLLET`): `handle_pending_faults`
+    /// already drains every pending `UFFD_EVENT_PAGEFAULT` in a loop before
+    /// returning, so a level-triggered re-wakeup for the same fd would just
+    /// be wasted work once hundreds of workers share this one epoll set.
+    ///
+    /// Shared by every `register_worker_with_*` path once the worker has
+    /// been inserted into `self.workers`.
+    fn add_to_reactor(&mut self, pid: Pid) -> Result<()> {
+        let uffd_fd = self
+            .get_worker_uffd(pid)
+            .expect("worker was just inserted above");
+        let mut event = EpollEvent::new(EpollFlags::EPOLLIN | EpollFlags::EPOLLET, uffd_fd as u64);
+        epoll_ctl(self.reactor_epoll_fd, EpollOp::EpollCtlAdd, uffd_fd, &mut event)
+            .with_context(|| format!("Failed to add UFFD fd {} (PID {}) to reactor epoll", uffd_fd, pid))?;
+        self.reactor_fd_to_pid.insert(uffd_fd, pid.as_raw());
+
         Ok(())
     }
 
+    /// Build the `GoldenStore` for a freshly captured worker: either keep the
+    /// pages in RAM, or - if this manager was created with a backing
+    /// directory - write each page once to a per-worker file and drop the
+    /// in-RAM copy.
+    fn spill_or_keep(
+        &self,
+        pid: Pid,
+        golden_pages: HashMap<usize, Vec<u8>>,
+    ) -> Result<GoldenStore> {
+        let Some(dir) = &self.backing_dir else {
+            return Ok(GoldenStore::InMemory(golden_pages));
+        };
+
+        let path = dir.join(format!("{}.golden", pid));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .with_context(|| format!("Failed to create backing file {:?}", path))?;
+
+        let mut index = HashMap::with_capacity(golden_pages.len());
+        let mut offset: u64 = 0;
+        for (page_addr, data) in &golden_pages {
+            file.write_at(data, offset)
+                .with_context(|| format!("Failed to write golden page to {:?}", path))?;
+            index.insert(*page_addr, (offset, data.len()));
+            offset += data.len() as u64;
+        }
+
+        eprintln!(
+            "[snapshot] PID {}: spilled {} golden pages ({} bytes) to {:?}",
+            pid,
+            index.len(),
+            offset,
+            path
+        );
+
+        Ok(GoldenStore::OnDisk { file, index })
+    }
+
+    /// Write `regions` and `golden_pages` to `self.golden_persist_path`
+    /// (region list, then each page's bytes, prefixed with `fingerprint`),
+    /// for warm start on the next supervisor launch. No-op if no persist
+    /// path was configured.
+    ///
+    /// Writes to a `.tmp` sibling and renames into place, so a supervisor
+    /// that crashes mid-write never leaves a half-written file for the next
+    /// launch's `load_golden` to trip over.
+    fn persist_golden(
+        &self,
+        pid: Pid,
+        regions: &[MemoryRegion],
+        golden_pages: &HashMap<usize, Vec<u8>>,
+        fingerprint: &GoldenFingerprint,
+    ) -> Result<()> {
+        let Some(path) = &self.golden_persist_path else {
+            return Ok(());
+        };
+
+        let tmp_path = path.with_extension("tmp");
+        let mut file = File::create(&tmp_path)
+            .with_context(|| format!("Failed to create golden snapshot file {:?}", tmp_path))?;
+
+        file.write_all(GOLDEN_FILE_MAGIC)?;
+        file.write_all(&GOLDEN_FILE_VERSION.to_le_bytes())?;
+        write_len_prefixed(&mut file, fingerprint.libpython_path.as_bytes())?;
+        write_len_prefixed(&mut file, &fingerprint.build_id)?;
+
+        file.write_all(&(regions.len() as u32).to_le_bytes())?;
+        for region in regions {
+            file.write_all(&(region.start as u64).to_le_bytes())?;
+            file.write_all(&(region.end as u64).to_le_bytes())?;
+            write_len_prefixed_u16(&mut file, region.perms.as_bytes())?;
+            write_len_prefixed_u16(&mut file, region.name.as_bytes())?;
+        }
+
+        let mut addrs: Vec<usize> = golden_pages.keys().copied().collect();
+        addrs.sort_unstable();
+        file.write_all(&(addrs.len() as u64).to_le_bytes())?;
+        for addr in addrs {
+            let data = &golden_pages[&addr];
+            file.write_all(&(addr as u64).to_le_bytes())?;
+            file.write_all(&(data.len() as u32).to_le_bytes())?;
+            file.write_all(data)?;
+        }
+
+        file.sync_all()
+            .with_context(|| format!("Failed to sync golden snapshot file {:?}", tmp_path))?;
+        drop(file);
+        fs::rename(&tmp_path, path)
+            .with_context(|| format!("Failed to finalize golden snapshot file {:?}", path))?;
+
+        eprintln!(
+            "[snapshot] PID {}: persisted golden snapshot ({} pages) to {:?}",
+            pid,
+            golden_pages.len(),
+            path
+        );
+        Ok(())
+    }
+
+    /// Reconstruct a previously `persist_golden`-ed snapshot from `path`,
+    /// without needing a live donor process.
+    ///
+    /// Callers that want warm start wired into registration should use
+    /// `SnapshotManager::new_with_golden_persist` instead; this is exposed
+    /// directly for tooling that just wants to inspect or validate a
+    /// snapshot file.
+    pub fn load_golden(path: &Path) -> Result<LoadedGolden, SnapshotError> {
+        Self::load_golden_inner(path).map_err(SnapshotError::from)
+    }
+
+    fn load_golden_inner(path: &Path) -> Result<LoadedGolden> {
+        let data = fs::read(path)
+            .with_context(|| format!("Failed to read golden snapshot file {:?}", path))?;
+        let mut cursor = &data[..];
+
+        if take(&mut cursor, 8)? != GOLDEN_FILE_MAGIC {
+            return Err(anyhow!("{:?} is not a tach golden snapshot file", path));
+        }
+        let version = u32::from_le_bytes(take(&mut cursor, 4)?.try_into().unwrap());
+        if version != GOLDEN_FILE_VERSION {
+            return Err(anyhow!(
+                "{:?} has golden snapshot version {}, expected {}",
+                path,
+                version,
+                GOLDEN_FILE_VERSION
+            ));
+        }
+
+        let libpython_path = String::from_utf8(read_len_prefixed(&mut cursor)?)
+            .with_context(|| format!("{:?}: invalid libpython path", path))?;
+        let build_id = read_len_prefixed(&mut cursor)?;
+        let fingerprint = GoldenFingerprint { libpython_path, build_id };
+
+        let region_count = u32::from_le_bytes(take(&mut cursor, 4)?.try_into().unwrap());
+        let mut regions = Vec::with_capacity(region_count as usize);
+        for _ in 0..region_count {
+            let start = u64::from_le_bytes(take(&mut cursor, 8)?.try_into().unwrap()) as usize;
+            let end = u64::from_le_bytes(take(&mut cursor, 8)?.try_into().unwrap()) as usize;
+            let perms = String::from_utf8(read_len_prefixed_u16(&mut cursor)?)
+                .with_context(|| format!("{:?}: invalid region perms", path))?;
+            let name = String::from_utf8(read_len_prefixed_u16(&mut cursor)?)
+                .with_context(|| format!("{:?}: invalid region name", path))?;
+            regions.push(MemoryRegion { start, end, len: end - start, perms, name });
+        }
+
+        let page_count = u64::from_le_bytes(take(&mut cursor, 8)?.try_into().unwrap());
+        let mut golden_pages = HashMap::with_capacity(page_count as usize);
+        for _ in 0..page_count {
+            let addr = u64::from_le_bytes(take(&mut cursor, 8)?.try_into().unwrap()) as usize;
+            let len = u32::from_le_bytes(take(&mut cursor, 4)?.try_into().unwrap()) as usize;
+            let bytes = take(&mut cursor, len)?.to_vec();
+            golden_pages.insert(addr, bytes);
+        }
+
+        Ok(LoadedGolden { regions, golden_pages, fingerprint })
+    }
+
+    /// Report whether a worker's golden pages are in RAM or spilled to disk.
+    pub fn worker_status(&self, pid: Pid) -> Option<Status> {
+        self.workers.get(&pid.as_raw()).map(|w| w.golden_store.status())
+    }
+
+    /// Snapshot of a worker's cumulative fault accounting.
+    pub fn fault_stats(&self, pid: Pid) -> Option<FaultStats> {
+        self.workers
+            .get(&pid.as_raw())
+            .map(|w| w.stats.borrow().clone())
+    }
+
+    /// Start recording a bounded ring buffer of this worker's faults.
+    ///
+    /// Opt-in: the buffer isn't allocated until this is called, so profiling
+    /// a handful of workers doesn't cost the rest anything.
+    pub fn enable_event_log(&self, pid: Pid, capacity: usize) -> Result<()> {
+        let worker = self
+            .workers
+            .get(&pid.as_raw())
+            .ok_or_else(|| anyhow!("Worker {} not registered with SnapshotManager", pid))?;
+        *worker.event_log.borrow_mut() = Some(FaultEventLog::new(capacity));
+        Ok(())
+    }
+
+    /// Flush (and clear) a worker's event log to `path`, one event per line.
+    ///
+    /// Returns the number of events written. Does nothing (returns `Ok(0)`)
+    /// if the log was never enabled for this worker.
+    pub fn flush_event_log(&self, pid: Pid, path: &Path) -> Result<usize> {
+        let worker = self
+            .workers
+            .get(&pid.as_raw())
+            .ok_or_else(|| anyhow!("Worker {} not registered with SnapshotManager", pid))?;
+
+        let mut log = worker.event_log.borrow_mut();
+        let Some(log) = log.as_mut() else {
+            return Ok(0);
+        };
+
+        let mut file = File::create(path)
+            .with_context(|| format!("Failed to create event log file {:?}", path))?;
+        for event in &log.events {
+            writeln!(
+                file,
+                "{}\t{:x}\t{:?}\t{}",
+                event.timestamp_ns, event.page_addr, event.kind, event.latency_ns
+            )?;
+        }
+
+        let flushed = log.events.len();
+        log.events.clear();
+        Ok(flushed)
+    }
+
+    /// Capture every one of `regions` from `pid`'s live memory via
+    /// `capture_region_pages`, merged into a single golden page map.
+    fn capture_all_pages(
+        &self,
+        pid: Pid,
+        regions: &[MemoryRegion],
+    ) -> Result<HashMap<usize, Vec<u8>>> {
+        let mut golden_pages = HashMap::new();
+        for region in regions {
+            let pages = self.capture_region_pages(pid, region)?;
+            golden_pages.extend(pages);
+        }
+        Ok(golden_pages)
+    }
+
     /// Capture a single memory region using process_vm_readv
     /// Returns a HashMap of page_addr -> page_data
     fn capture_region_pages(
@@ -370,12 +2189,78 @@ impl SnapshotManager {
         Ok(pages)
     }
 
+    /// Capture `region`'s golden bytes into `memfd` at `base_offset`,
+    /// appending to the running page index.
+    ///
+    /// Shared by `register_worker_with_shared_memfd_frozen` to build one
+    /// combined golden image per worker across all of its snapshot regions,
+    /// rather than one memfd per region.
+    fn capture_region_into_memfd(
+        &self,
+        pid: Pid,
+        region: &MemoryRegion,
+        memfd: &mut File,
+        base_offset: u64,
+        index: &mut HashMap<usize, (u64, usize)>,
+    ) -> Result<u64> {
+        let pages = self.capture_region_pages(pid, region)?;
+
+        let mut addrs: Vec<usize> = pages.keys().copied().collect();
+        addrs.sort_unstable();
+
+        let mut offset = base_offset;
+        for page_addr in addrs {
+            let data = &pages[&page_addr];
+            memfd.write_all(data).with_context(|| {
+                format!("Failed to write golden page {:x} to shared memfd", page_addr)
+            })?;
+            index.insert(page_addr, (offset, data.len()));
+            offset += data.len() as u64;
+        }
+
+        Ok(offset)
+    }
+
+    /// Capture every one of `pid`'s snapshot `regions` into the worker's
+    /// already-`MAP_SHARED`-mapped `memfd`, then seal it, for
+    /// `GoldenStore::Shared` / `ResetMode::SharedMinor`.
+    ///
+    /// The worker created and mapped `memfd` itself (see
+    /// `register_worker_with_shared_memfd_frozen`); we only own the golden
+    /// bytes and the seal. Sealing (`F_SEAL_WRITE`/`SHRINK`/`GROW`) happens
+    /// only after every region is captured, so no worker mapping this memfd
+    /// can ever observe a partially-written golden image or mutate the
+    /// pages every other worker is sharing.
+    fn capture_region_to_memfd(
+        &self,
+        pid: Pid,
+        regions: &[MemoryRegion],
+        memfd: &mut File,
+    ) -> Result<HashMap<usize, (u64, usize)>> {
+        let mut index = HashMap::new();
+        let mut offset = 0u64;
+        for region in regions {
+            offset = self.capture_region_into_memfd(pid, region, memfd, offset, &mut index)?;
+        }
+
+        let seals = libc::F_SEAL_SEAL | libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_WRITE;
+        if unsafe { libc::fcntl(memfd.as_raw_fd(), libc::F_ADD_SEALS, seals) } < 0 {
+            return Err(anyhow!(
+                "fcntl(F_ADD_SEALS) failed for PID {}'s golden memfd: {}",
+                pid,
+                std::io::Error::last_os_error()
+            ));
+        }
+
+        Ok(index)
+    }
+
     /// Reset a worker's memory by invalidating pages (remote)
     ///
     /// Uses process_madvise (Linux 5.10+) to operate on REMOTE process memory.
     /// NOTE: MADV_DONTNEED via process_madvise requires Linux 5.12+.
     /// If this fails, use Worker Self-Reset (Seppuku) pattern instead.
-    pub fn reset_worker(&self, pid: Pid) -> Result<()> {
+    pub fn reset_worker(&mut self, pid: Pid) -> Result<()> {
         if !self.available {
             return Ok(()); // No-op in fallback mode
         }
@@ -395,27 +2280,44 @@ impl SnapshotManager {
             ));
         }
 
-        // Construct iovec array for all regions
-        let iovecs: Vec<libc::iovec> = worker
-            .regions
-            .iter()
-            .map(|r| libc::iovec {
-                iov_base: r.start as *mut libc::c_void,
-                iov_len: r.len,
-            })
-            .collect();
+        // Construct iovec array covering only the pages that actually need
+        // invalidating. In WriteProtect mode that's just the pages touched
+        // since the last reset (tracked via `dirty_pages`); in CopyOnFault
+        // mode the whole region set is unconditionally dirty.
+        let iovecs: Vec<libc::iovec> = match worker.mode {
+            ResetMode::WriteProtect => worker
+                .dirty_pages
+                .iter()
+                .map(|&page| libc::iovec {
+                    iov_base: page as *mut libc::c_void,
+                    iov_len: PAGE_SIZE,
+                })
+                .collect(),
+            ResetMode::CopyOnFault => worker
+                .regions
+                .iter()
+                .map(|r| libc::iovec {
+                    iov_base: r.start as *mut libc::c_void,
+                    iov_len: r.len,
+                })
+                .collect(),
+        };
 
-        // Call process_madvise - REMOTE MADV_DONTNEED
-        const SYS_PROCESS_MADVISE: libc::c_long = 440;
-        let ret = unsafe {
-            libc::syscall(
-                SYS_PROCESS_MADVISE,
-                pidfd,
-                iovecs.as_ptr(),
-                iovecs.len(),
-                libc::MADV_DONTNEED,
-                0u32,
-            )
+        let ret = if iovecs.is_empty() {
+            0
+        } else {
+            // Call process_madvise - REMOTE MADV_DONTNEED
+            const SYS_PROCESS_MADVISE: libc::c_long = 440;
+            unsafe {
+                libc::syscall(
+                    SYS_PROCESS_MADVISE,
+                    pidfd,
+                    iovecs.as_ptr(),
+                    iovecs.len(),
+                    libc::MADV_DONTNEED,
+                    0u32,
+                )
+            }
         };
 
         unsafe { libc::close(pidfd) };
@@ -428,10 +2330,41 @@ impl SnapshotManager {
             ));
         }
 
+        let region_count = iovecs.len();
+
+        // Whichever pages `MADV_DONTNEED` just dropped are missing again;
+        // mark them pending so the next fault's `restore_golden_run` knows
+        // it's safe to fold them into a coalesced `UFFDIO_COPY` run instead
+        // of treating them as already resident.
+        if worker.mode == ResetMode::WriteProtect {
+            worker.pending_pages.borrow_mut().extend(worker.dirty_pages.iter().copied());
+        } else if worker.mode == ResetMode::CopyOnFault {
+            *worker.pending_pages.borrow_mut() = worker.golden_store.page_addrs();
+        }
+
+        // In WriteProtect mode, re-arm protection on the pages we just
+        // dropped so the next write raises a fresh fault, then clear the
+        // dirty set for the next generation.
+        if worker.mode == ResetMode::WriteProtect {
+            for &page in &worker.dirty_pages {
+                unsafe {
+                    worker
+                        .uffd
+                        .write_protect(
+                            page as *mut libc::c_void,
+                            PAGE_SIZE,
+                            WriteProtectMode::WRITE_PROTECT,
+                        )
+                        .with_context(|| format!("Failed to re-protect page at {:x}", page))?;
+                }
+            }
+            let worker = self.workers.get_mut(&pid.as_raw()).expect("worker present");
+            worker.dirty_pages.clear();
+        }
+
         eprintln!(
-            "[snapshot] Reset worker {}: invalidated {} regions",
-            pid,
-            iovecs.len()
+            "[snapshot] Reset worker {}: invalidated {} region(s) ({:?})",
+            pid, region_count, self.workers[&pid.as_raw()].mode
         );
 
         Ok(())
@@ -446,26 +2379,11 @@ impl SnapshotManager {
             .get(&pid.as_raw())
             .ok_or_else(|| anyhow!("Worker {} not registered with SnapshotManager", pid))?;
 
+        let fault_start = Instant::now();
         let page_start = align_to_page(fault_addr);
 
-        if let Some(data) = worker.golden_pages.get(&page_start) {
-            // Restore the page from golden snapshot
-            eprintln!(
-                "[snapshot] Restoring page at {:x} ({} bytes) for PID {}",
-                page_start,
-                data.len(),
-                pid
-            );
-            // CRITICAL: Uffd::copy signature is (src, dst, len, wake)
-            unsafe {
-                worker.uffd.copy(
-                    data.as_ptr() as *const libc::c_void, // src data
-                    page_start as *mut libc::c_void,      // dst addr
-                    data.len(),                           // len
-                    true,                                 // wake the faulting thread
-                )
-            }
-            .with_context(|| format!("Failed to copy page at {:x}", page_start))?;
+        if let Some(len) = restore_golden_run(worker, pid, page_start, self.prefetch_window_pages)? {
+            worker.record_fault(page_start, FaultEventKind::Missing, len, fault_start.elapsed());
         } else {
             // Page not in snapshot - zero it
             eprintln!(
@@ -478,6 +2396,12 @@ impl SnapshotManager {
                     .zeropage(page_start as *mut libc::c_void, PAGE_SIZE, true)
             }
             .with_context(|| format!("Failed to zero page at {:x}", page_start))?;
+            worker.record_fault(
+                page_start,
+                FaultEventKind::Missing,
+                PAGE_SIZE,
+                fault_start.elapsed(),
+            );
         }
 
         Ok(())
@@ -487,78 +2411,339 @@ impl SnapshotManager {
     ///
     /// This reads from the UFFD file descriptor and handles
     /// any pending page faults by restoring from golden snapshot.
-    pub fn handle_pending_faults(&mut self, pid: Pid) -> Result<usize> {
-        use userfaultfd::Event;
+    pub fn handle_pending_faults(&mut self, pid: Pid) -> Result<usize, SnapshotError> {
+        use userfaultfd::{Event, FaultKind};
 
+        let window_pages = self.prefetch_window_pages;
         let worker = self
             .workers
-            .get(&pid.as_raw())
+            .get_mut(&pid.as_raw())
             .ok_or_else(|| anyhow!("Worker {} not registered with SnapshotManager", pid))?;
 
         let mut handled = 0;
 
         // Read events from UFFD
         loop {
-            match worker.uffd.read_event() {
-                Ok(Some(Event::Pagefault { addr, .. })) => {
+            let event = worker.uffd.read_event();
+            let fault_start = Instant::now();
+            match event {
+                Ok(Some(Event::Pagefault {
+                    addr,
+                    kind: FaultKind::WriteProtect,
+                    ..
+                })) => {
+                    // A write landed on a page we protected at registration
+                    // (or re-armed after the last reset). Record it as dirty
+                    // so the next reset_worker() only drops this page, then
+                    // lift protection so the write retires normally.
                     let fault_addr = addr.addr();
+                    let page_start = align_to_page(fault_addr);
+                    worker.dirty_pages.insert(page_start);
                     eprintln!(
-                        "[snapshot] UFFD_EVENT_PAGEFAULT at {:x} for PID {}",
-                        fault_addr, pid
+                        "[snapshot] UFFD_EVENT_PAGEFAULT (write-protect) at {:x} for PID {}",
+                        page_start, pid
                     );
-
-                    // Get data and restore
+                    unsafe {
+                        worker.uffd.write_protect(
+                            page_start as *mut libc::c_void,
+                            PAGE_SIZE,
+                            WriteProtectMode::NONE,
+                        )?;
+                    }
+                    worker.record_fault(
+                        page_start,
+                        FaultEventKind::WriteProtect,
+                        0,
+                        fault_start.elapsed(),
+                    );
+                    handled += 1;
+                }
+                Ok(Some(Event::Pagefault {
+                    addr,
+                    kind: FaultKind::Minor,
+                    ..
+                })) => {
+                    // The page is already present in the shared memfd's page
+                    // cache (another worker - or this one, before a reset -
+                    // already faulted it in); just point this worker's PTE
+                    // at it instead of copying any bytes.
+                    let fault_addr = addr.addr();
                     let page_start = align_to_page(fault_addr);
-                    if let Some(data) = worker.golden_pages.get(&page_start) {
-                        eprintln!(
-                            "[snapshot] Restoring page {:x} ({} bytes)",
-                            page_start,
-                            data.len()
-                        );
-                        // CRITICAL: Uffd::copy signature is (src, dst, len, wake)
-                        unsafe {
-                            worker.uffd.copy(
-                                data.as_ptr() as *const libc::c_void, // src data
-                                page_start as *mut libc::c_void,      // dst addr
-                                data.len(),                           // len
-                                true,                                 // wake
-                            )?;
-                        }
-                    } else {
-                        eprintln!(
-                            "[snapshot] Zero-filling page {:x} (not in snapshot)",
-                            page_start
-                        );
+                    eprintln!(
+                        "[snapshot] UFFD_EVENT_PAGEFAULT (minor) at {:x} for PID {}",
+                        page_start, pid
+                    );
+
+                    if worker.golden_store.shared_offset(page_start).is_some() {
                         unsafe {
-                            worker.uffd.zeropage(
+                            worker.uffd.continue_(
                                 page_start as *mut libc::c_void,
                                 PAGE_SIZE,
                                 true,
                             )?;
                         }
+                    } else {
+                        eprintln!(
+                            "[snapshot] No shared golden page at {:x} for PID {}, leaving fault unresolved",
+                            page_start, pid
+                        );
                     }
+
+                    worker.record_fault(
+                        page_start,
+                        FaultEventKind::Minor,
+                        0,
+                        fault_start.elapsed(),
+                    );
+                    handled += 1;
+                }
+                Ok(Some(Event::Pagefault { addr, .. })) => {
+                    let fault_addr = addr.addr();
+                    eprintln!(
+                        "[snapshot] UFFD_EVENT_PAGEFAULT at {:x} for PID {}",
+                        fault_addr, pid
+                    );
+
+                    // Get data and restore
+                    let page_start = align_to_page(fault_addr);
+                    let copied_len =
+                        if let Some(len) = restore_golden_run(worker, pid, page_start, window_pages)? {
+                            len
+                        } else {
+                            eprintln!(
+                                "[snapshot] Zero-filling page {:x} (not in snapshot)",
+                                page_start
+                            );
+                            unsafe {
+                                worker.uffd.zeropage(
+                                    page_start as *mut libc::c_void,
+                                    PAGE_SIZE,
+                                    true,
+                                )?;
+                            }
+                            PAGE_SIZE
+                        };
+                    worker.record_fault(
+                        page_start,
+                        FaultEventKind::Missing,
+                        copied_len,
+                        fault_start.elapsed(),
+                    );
                     handled += 1;
                 }
                 Ok(Some(event)) => {
                     eprintln!("[snapshot] UFFD event: {:?} for PID {}", event, pid);
                 }
-                Ok(None) => {
-                    // No more events
-                    break;
+                Ok(None) => {
+                    // No more events
+                    break;
+                }
+                Err(e) => {
+                    // Any error means no events ready or UFFD closed
+                    eprintln!("[snapshot] UFFD read_event: {} (breaking poll loop)", e);
+                    break;
+                }
+            }
+        }
+
+        Ok(handled)
+    }
+
+    /// Block on `epoll_wait` across every UFFD in `pids`, dispatching faults
+    /// via `handle_pending_faults` as fds become readable, instead of the
+    /// old sleep-and-poll busy loop. Returns as soon as any worker's UFFD
+    /// hangs up (its process exited), `deadline` elapses, or `pids` is
+    /// empty - callers loop on this the same way they used to loop on the
+    /// sleep-and-poll body, but without burning CPU or the 1ms latency
+    /// floor between faults.
+    pub fn run_fault_loop(
+        &mut self,
+        pids: &[Pid],
+        deadline: Option<Duration>,
+    ) -> Result<FaultLoopResult> {
+        if pids.is_empty() {
+            return Ok(FaultLoopResult::Idle);
+        }
+
+        let epoll_fd =
+            epoll_create1(EpollCreateFlags::EPOLL_CLOEXEC).context("Failed to create epoll instance")?;
+
+        let mut fd_to_pid = HashMap::new();
+        for &pid in pids {
+            let fd = self
+                .get_worker_uffd(pid)
+                .ok_or_else(|| anyhow!("Worker {} not registered with SnapshotManager", pid))?;
+            let mut event = EpollEvent::new(EpollFlags::EPOLLIN | EpollFlags::EPOLLET, fd as u64);
+            epoll_ctl(epoll_fd, EpollOp::EpollCtlAdd, fd, &mut event)
+                .with_context(|| format!("Failed to register UFFD fd {} (PID {}) with epoll", fd, pid))?;
+            fd_to_pid.insert(fd, pid);
+        }
+
+        let deadline_instant = deadline.map(|d| Instant::now() + d);
+        let mut events = vec![EpollEvent::empty(); fd_to_pid.len()];
+
+        let result = loop {
+            let timeout_ms: isize = match deadline_instant {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        break Ok(FaultLoopResult::TimedOut);
+                    }
+                    remaining.as_millis().min(isize::MAX as u128) as isize
+                }
+                None => -1,
+            };
+
+            let ready = epoll_wait(epoll_fd, &mut events, timeout_ms).context("epoll_wait failed")?;
+            if ready == 0 {
+                break Ok(FaultLoopResult::TimedOut);
+            }
+
+            let mut exited = None;
+            for event in &events[..ready] {
+                let fd = event.data() as RawFd;
+                let Some(&pid) = fd_to_pid.get(&fd) else {
+                    continue;
+                };
+
+                if event.events().contains(EpollFlags::EPOLLHUP) {
+                    exited = Some(pid);
+                    continue;
+                }
+
+                self.handle_pending_faults(pid)?;
+            }
+
+            if let Some(pid) = exited {
+                break Ok(FaultLoopResult::WorkerExited(pid));
+            }
+        };
+
+        unsafe { libc::close(epoll_fd) };
+
+        result
+    }
+
+    /// Block on the manager's own persistent epoll reactor, servicing every
+    /// worker whose UFFD became readable, and return how many faults each
+    /// one had handled by the time this call returns.
+    ///
+    /// Unlike `run_fault_loop`, which builds an ad hoc epoll set from a
+    /// caller-supplied `&[Pid]` and stops at the first fault-bearing wakeup,
+    /// `run_reactor` watches every worker this manager currently has
+    /// registered (the set `register_worker_with_uffd`/`remove_worker`
+    /// maintain) and drains all of them before returning - so one thread can
+    /// sit in this loop and service an arbitrarily large recycled-worker
+    /// pool with no polling and no per-worker thread.
+    pub fn run_reactor(
+        &mut self,
+        deadline: Option<Duration>,
+    ) -> Result<HashMap<Pid, usize>, SnapshotError> {
+        if self.workers.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let timeout_ms: isize = match deadline {
+            Some(d) => d.as_millis().min(isize::MAX as u128) as isize,
+            None => -1,
+        };
+
+        // +1 for `child_signalfd`, always in the interest set alongside every
+        // worker's UFFD.
+        let mut events = vec![EpollEvent::empty(); self.workers.len() + 1];
+        let ready = epoll_wait(self.reactor_epoll_fd, &mut events, timeout_ms)
+            .context("epoll_wait failed")?;
+
+        let mut handled = HashMap::new();
+        for event in &events[..ready] {
+            let fd = event.data() as RawFd;
+
+            if fd == self.child_signalfd.as_raw_fd() {
+                self.reap_exited_workers()?;
+                continue;
+            }
+
+            let Some(&raw_pid) = self.reactor_fd_to_pid.get(&fd) else {
+                continue;
+            };
+            let pid = Pid::from_raw(raw_pid);
+            let count = self.handle_pending_faults(pid)?;
+            if count > 0 {
+                handled.insert(pid, count);
+            }
+        }
+
+        Ok(handled)
+    }
+
+    /// Drain every pending `signalfd_siginfo` off `child_signalfd`, then
+    /// `waitpid(.., WNOHANG)` in a loop to reap every child that has since
+    /// exited - not just ones this manager has a `WorkerSnapshot` for, since
+    /// `SIGCHLD` coalesces and a single wakeup may cover several deaths.
+    /// Each reaped worker still registered here has its UFFD dropped from
+    /// the reactor via `remove_worker` and is appended to `reaped_workers`
+    /// for `take_reaped_workers` to hand back to the caller.
+    fn reap_exited_workers(&mut self) -> Result<(), SnapshotError> {
+        loop {
+            match self.child_signalfd.read_signal() {
+                Ok(Some(_)) => continue,
+                Ok(None) => break,
+                Err(e) => {
+                    return Err(SnapshotError::from(
+                        anyhow::Error::from(e).context("Failed to read signalfd_siginfo"),
+                    ))
+                }
+            }
+        }
+
+        loop {
+            match waitpid(None, Some(WaitPidFlag::WNOHANG)) {
+                Ok(WaitStatus::StillAlive) | Err(Errno::ECHILD) => break,
+                Ok(status) => {
+                    let Some(pid) = status.pid() else {
+                        continue;
+                    };
+                    let exit = match status {
+                        WaitStatus::Exited(_, code) => WorkerExit::Exited(code),
+                        WaitStatus::Signaled(_, signal, _) => WorkerExit::Signaled(signal),
+                        // Stopped/Continued/PtraceEvent etc. - not a death, nothing to reap.
+                        _ => continue,
+                    };
+                    eprintln!("[snapshot] PID {} reaped: {:?}", pid, exit);
+                    self.remove_worker(pid);
+                    self.reaped_workers.push((pid, exit));
                 }
                 Err(e) => {
-                    // Any error means no events ready or UFFD closed
-                    eprintln!("[snapshot] UFFD read_event: {} (breaking poll loop)", e);
-                    break;
+                    return Err(SnapshotError::from(anyhow!("waitpid failed: {}", e)));
                 }
             }
         }
 
-        Ok(handled)
+        Ok(())
+    }
+
+    /// Drain and return every worker this manager has reaped off
+    /// `child_signalfd` since the last call, with each one's exit outcome.
+    ///
+    /// `run_reactor` reaps dead workers as a side effect of servicing the
+    /// signalfd; this is how a caller finds out which PIDs that happened to
+    /// and whether it was a clean exit or a crash, deterministically and
+    /// without installing its own `SIGCHLD` handler.
+    pub fn take_reaped_workers(&mut self) -> Vec<(Pid, WorkerExit)> {
+        std::mem::take(&mut self.reaped_workers)
     }
 
     /// Remove a worker from the manager (when killed after 1000 tests)
     pub fn remove_worker(&mut self, pid: Pid) {
+        if let Some(fd) = self.get_worker_uffd(pid) {
+            // Best-effort: the fd is about to close anyway when the
+            // `WorkerSnapshot` below is dropped, which also drops it from
+            // epoll's interest list implicitly, but removing it explicitly
+            // avoids a stale entry if something still holds the fd open.
+            let mut ev = EpollEvent::empty();
+            let _ = epoll_ctl(self.reactor_epoll_fd, EpollOp::EpollCtlDel, fd, &mut ev);
+            self.reactor_fd_to_pid.remove(&fd);
+        }
         self.workers.remove(&pid.as_raw());
     }
 
@@ -566,12 +2751,61 @@ impl SnapshotManager {
     pub fn worker_pids(&self) -> Vec<Pid> {
         self.workers.keys().map(|&p| Pid::from_raw(p)).collect()
     }
+
+    /// Number of pages written since the worker's last reset.
+    ///
+    /// Always 0 for workers in `ResetMode::CopyOnFault`, since that mode
+    /// doesn't track individual dirty pages. Returns `None` if the worker
+    /// isn't registered.
+    pub fn dirty_page_count(&self, pid: Pid) -> Option<usize> {
+        self.workers.get(&pid.as_raw()).map(|w| w.dirty_pages.len())
+    }
+
+    /// Page-aligned addresses written since the worker's last reset.
+    ///
+    /// Same dirty-page tracking as `dirty_page_count`, but returning the
+    /// addresses themselves rather than just the count - useful for
+    /// inspecting exactly what a workload touched (e.g. live-migration
+    /// style dirty-set introspection) without waiting for a reset to
+    /// consume the set. Returns `None` if the worker isn't registered.
+    pub fn dirty_pages(&self, pid: Pid) -> Option<Vec<usize>> {
+        self.workers
+            .get(&pid.as_raw())
+            .map(|w| w.dirty_pages.iter().copied().collect())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // =========================================================================
+    // SCM_RIGHTS Tests
+    // =========================================================================
+
+    #[test]
+    fn test_send_recv_fd_round_trip() {
+        // SCM_CREDENTIALS is kernel-populated from the real sending process,
+        // so the claimed pid must match this test process's own pid or
+        // recv_fd's PidMismatch check rejects the message.
+        let self_pid = std::process::id() as i32;
+        let (a, b) = UnixStream::pair().unwrap();
+        send_fd(&a, self_pid, libc::STDIN_FILENO).unwrap();
+        let (pid, fd) = recv_fd(&b).unwrap();
+        assert_eq!(pid, self_pid);
+        assert!(fd.as_raw_fd() >= 0);
+    }
+
+    #[test]
+    fn test_send_recv_two_fds_round_trip() {
+        let self_pid = std::process::id() as i32;
+        let (a, b) = UnixStream::pair().unwrap();
+        send_fds(&a, self_pid, &[libc::STDIN_FILENO, libc::STDOUT_FILENO]).unwrap();
+        let (pid, uffd_fd, memfd) = recv_two_fds(&b).unwrap();
+        assert_eq!(pid, self_pid);
+        assert_ne!(uffd_fd.as_raw_fd(), memfd.as_raw_fd());
+    }
+
     // =========================================================================
     // Memory Region Parsing Tests
     // =========================================================================
@@ -603,6 +2837,31 @@ mod tests {
         assert!(readable_count > 0, "Should have readable regions");
     }
 
+    // =========================================================================
+    // Error Handling Tests
+    // =========================================================================
+
+    #[test]
+    fn test_snapshot_error_raw_os_error_from_errno() {
+        let err = SnapshotError::from(
+            anyhow::Error::new(Errno::ENOMEM).context("Failed to copy page at 0"),
+        );
+        assert_eq!(err.raw_os_error(), Some(Errno::ENOMEM as i32));
+    }
+
+    #[test]
+    fn test_snapshot_error_raw_os_error_none_without_errno() {
+        let err = SnapshotError::from(anyhow!("Worker {} not registered with SnapshotManager", 1));
+        assert_eq!(err.raw_os_error(), None);
+    }
+
+    #[test]
+    fn test_snapshot_error_into_io_error_preserves_errno() {
+        let err = SnapshotError::from(anyhow::Error::new(Errno::EPERM).context("uffd register"));
+        let io_err: std::io::Error = err.into();
+        assert_eq!(io_err.raw_os_error(), Some(Errno::EPERM as i32));
+    }
+
     // =========================================================================
     // Memory Region Filtering Tests
     // =========================================================================
@@ -725,6 +2984,74 @@ mod tests {
         assert_eq!(align_to_page(0x7f1234560fff), 0x7f1234560000);
     }
 
+    // =========================================================================
+    // Thread Freezing Tests
+    // =========================================================================
+
+    #[test]
+    fn test_list_threads_finds_self() {
+        let pid = Pid::from_raw(std::process::id() as i32);
+        let tids = list_threads(pid).expect("Failed to list threads");
+        assert!(!tids.is_empty(), "A running process has at least one thread");
+    }
+
+    #[test]
+    fn test_thread_state_parses_simple_comm() {
+        let stat = "1234 (python3) S 1 1234 1234 0 -1 4194560";
+        assert_eq!(thread_state(stat), Some('S'));
+    }
+
+    #[test]
+    fn test_thread_state_parses_comm_with_parens_and_spaces() {
+        let stat = "1234 (my (weird) proc) T 1 1234 1234 0 -1 4194560";
+        assert_eq!(thread_state(stat), Some('T'));
+    }
+
+    #[test]
+    fn test_freeze_and_thaw_nonexistent_pid_errors() {
+        let mgr = SnapshotManager::new().unwrap();
+        let fake_pid = Pid::from_raw(999_999);
+        assert!(mgr.freeze_worker(fake_pid).is_err());
+    }
+
+    // =========================================================================
+    // Fault Loop Tests
+    // =========================================================================
+
+    #[test]
+    fn test_run_fault_loop_empty_pids_is_idle() {
+        let mut mgr = SnapshotManager::new().unwrap();
+        let result = mgr.run_fault_loop(&[], None).expect("run_fault_loop failed");
+        assert_eq!(result, FaultLoopResult::Idle);
+    }
+
+    #[test]
+    fn test_run_fault_loop_unregistered_worker_errors() {
+        let mut mgr = SnapshotManager::new().unwrap();
+        let fake_pid = Pid::from_raw(999_999);
+        assert!(mgr.run_fault_loop(&[fake_pid], None).is_err());
+    }
+
+    // =========================================================================
+    // Reactor Tests
+    // =========================================================================
+
+    #[test]
+    fn test_run_reactor_no_workers_returns_empty() {
+        let mut mgr = SnapshotManager::new().unwrap();
+        let handled = mgr.run_reactor(Some(Duration::from_millis(10))).unwrap();
+        assert!(handled.is_empty());
+    }
+
+    #[test]
+    fn test_remove_worker_drops_reactor_registration() {
+        let mut mgr = SnapshotManager::new().unwrap();
+        let fake_pid = Pid::from_raw(999_999);
+        // No-op on an unregistered worker - shouldn't panic or leave state behind.
+        mgr.remove_worker(fake_pid);
+        assert!(mgr.reactor_fd_to_pid.is_empty());
+    }
+
     // =========================================================================
     // SnapshotManager Tests
     // =========================================================================
@@ -759,6 +3086,274 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_dirty_page_count_nonexistent_worker() {
+        let mgr = SnapshotManager::new().unwrap();
+        let fake_pid = Pid::from_raw(99999);
+        assert!(
+            mgr.dirty_page_count(fake_pid).is_none(),
+            "Nonexistent worker should report no dirty-page count"
+        );
+    }
+
+    #[test]
+    fn test_dirty_pages_nonexistent_worker() {
+        let mgr = SnapshotManager::new().unwrap();
+        let fake_pid = Pid::from_raw(99999);
+        assert!(
+            mgr.dirty_pages(fake_pid).is_none(),
+            "Nonexistent worker should report no dirty-page addresses"
+        );
+    }
+
+    #[test]
+    fn test_worker_status_nonexistent_worker() {
+        let mgr = SnapshotManager::new().unwrap();
+        let fake_pid = Pid::from_raw(99999);
+        assert!(
+            mgr.worker_status(fake_pid).is_none(),
+            "Nonexistent worker should report no status"
+        );
+    }
+
+    #[test]
+    fn test_new_with_backing_creates_directory() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let backing_dir = temp.path().join("golden-store");
+        assert!(!backing_dir.exists());
+
+        let mgr = SnapshotManager::new_with_backing(backing_dir.clone()).unwrap();
+
+        assert!(backing_dir.is_dir(), "Backing directory should be created");
+        eprintln!("SnapshotManager available: {}", mgr.available);
+    }
+
+    #[test]
+    fn test_set_prefetch_window_clamps_to_at_least_one() {
+        let mut mgr = SnapshotManager::new().unwrap();
+        mgr.set_prefetch_window(0);
+        assert_eq!(mgr.prefetch_window_pages, 1);
+        mgr.set_prefetch_window(32);
+        assert_eq!(mgr.prefetch_window_pages, 32);
+    }
+
+    // =========================================================================
+    // GoldenStore Tests
+    // =========================================================================
+
+    #[test]
+    fn test_golden_store_in_memory_round_trip() {
+        let mut pages = HashMap::new();
+        pages.insert(0x1000, vec![0xAB; PAGE_SIZE]);
+        let store = GoldenStore::InMemory(pages);
+
+        assert_eq!(store.status(), Status::InMemory);
+
+        let mut bounce = [0u8; PAGE_SIZE];
+        let len = store.read_page(0x1000, &mut bounce).unwrap().unwrap();
+        assert_eq!(len, PAGE_SIZE);
+        assert!(bounce.iter().all(|&b| b == 0xAB));
+
+        assert!(store.read_page(0x2000, &mut bounce).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_golden_store_on_disk_round_trip() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("worker.golden");
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .unwrap();
+
+        let page_data = vec![0xCD; PAGE_SIZE];
+        file.write_at(&page_data, 0).unwrap();
+
+        let mut index = HashMap::new();
+        index.insert(0x4000, (0u64, PAGE_SIZE));
+        let store = GoldenStore::OnDisk { file, index };
+
+        assert_eq!(store.status(), Status::SpilledToDisk);
+
+        let mut bounce = [0u8; PAGE_SIZE];
+        let len = store.read_page(0x4000, &mut bounce).unwrap().unwrap();
+        assert_eq!(len, PAGE_SIZE);
+        assert!(bounce.iter().all(|&b| b == 0xCD));
+
+        assert!(store.read_page(0x5000, &mut bounce).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_golden_store_shared_status_and_offset() {
+        let name = std::ffi::CString::new("test-shared-golden").unwrap();
+        let fd = unsafe { libc::memfd_create(name.as_ptr(), libc::MFD_CLOEXEC) };
+        assert!(fd >= 0);
+        let memfd = unsafe { File::from_raw_fd(fd) };
+
+        let mut index = HashMap::new();
+        index.insert(0x6000, (0u64, PAGE_SIZE));
+        let store = GoldenStore::Shared { memfd, index };
+
+        assert_eq!(store.status(), Status::Shared);
+        assert_eq!(store.shared_offset(0x6000), Some(0));
+        assert_eq!(store.shared_offset(0x7000), None);
+
+        // read_page reports presence/length but never copies shared bytes.
+        let mut bounce = [0u8; PAGE_SIZE];
+        assert_eq!(store.read_page(0x6000, &mut bounce).unwrap(), Some(PAGE_SIZE));
+    }
+
+    #[test]
+    fn test_read_run_coalesces_contiguous_pending_pages() {
+        let mut pages = HashMap::new();
+        pages.insert(0x1000, vec![0xAA; PAGE_SIZE]);
+        pages.insert(0x2000, vec![0xBB; PAGE_SIZE]);
+        pages.insert(0x3000, vec![0xCC; PAGE_SIZE]);
+        let store = GoldenStore::InMemory(pages);
+        let pending: HashSet<usize> = [0x1000, 0x2000, 0x3000].into_iter().collect();
+
+        let mut out = Vec::new();
+        let n = store.read_run(0x1000, 8, &pending, &mut out).unwrap();
+        assert_eq!(n, 3);
+        assert_eq!(out.len(), 3 * PAGE_SIZE);
+        assert!(out[..PAGE_SIZE].iter().all(|&b| b == 0xAA));
+        assert!(out[PAGE_SIZE..2 * PAGE_SIZE].iter().all(|&b| b == 0xBB));
+        assert!(out[2 * PAGE_SIZE..].iter().all(|&b| b == 0xCC));
+    }
+
+    #[test]
+    fn test_read_run_stops_at_page_not_pending() {
+        let mut pages = HashMap::new();
+        pages.insert(0x1000, vec![0xAA; PAGE_SIZE]);
+        pages.insert(0x2000, vec![0xBB; PAGE_SIZE]);
+        let store = GoldenStore::InMemory(pages);
+        // 0x2000 is golden-backed but already resident (not pending), so the
+        // run must stop after the faulting page instead of re-copying it.
+        let pending: HashSet<usize> = [0x1000].into_iter().collect();
+
+        let mut out = Vec::new();
+        let n = store.read_run(0x1000, 8, &pending, &mut out).unwrap();
+        assert_eq!(n, 1);
+        assert_eq!(out.len(), PAGE_SIZE);
+    }
+
+    #[test]
+    fn test_read_run_stops_at_gap_and_caps_at_window() {
+        let mut pages = HashMap::new();
+        pages.insert(0x1000, vec![0xAA; PAGE_SIZE]);
+        pages.insert(0x3000, vec![0xCC; PAGE_SIZE]); // not contiguous with 0x1000
+        let store = GoldenStore::InMemory(pages);
+        let pending: HashSet<usize> = [0x1000, 0x3000].into_iter().collect();
+
+        let mut out = Vec::new();
+        assert_eq!(store.read_run(0x1000, 8, &pending, &mut out).unwrap(), 1);
+
+        // Window of 1 caps the run even when more contiguous pages exist.
+        let mut pages = HashMap::new();
+        pages.insert(0x1000, vec![0xAA; PAGE_SIZE]);
+        pages.insert(0x2000, vec![0xBB; PAGE_SIZE]);
+        let store = GoldenStore::InMemory(pages);
+        let pending: HashSet<usize> = [0x1000, 0x2000].into_iter().collect();
+        let mut out = Vec::new();
+        assert_eq!(store.read_run(0x1000, 1, &pending, &mut out).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_read_run_returns_zero_for_non_golden_page() {
+        let store = GoldenStore::InMemory(HashMap::new());
+        let mut out = Vec::new();
+        assert_eq!(store.read_run(0x9000, 8, &HashSet::new(), &mut out).unwrap(), 0);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_page_addrs_lists_every_golden_page() {
+        let mut pages = HashMap::new();
+        pages.insert(0x1000, vec![0xAA; PAGE_SIZE]);
+        pages.insert(0x2000, vec![0xBB; PAGE_SIZE]);
+        let store = GoldenStore::InMemory(pages);
+        let addrs = store.page_addrs();
+        assert_eq!(addrs, [0x1000, 0x2000].into_iter().collect());
+    }
+
+    // =========================================================================
+    // Fault Accounting Tests
+    // =========================================================================
+
+    #[test]
+    fn test_fault_stats_nonexistent_worker() {
+        let mgr = SnapshotManager::new().unwrap();
+        let fake_pid = Pid::from_raw(99999);
+        assert!(
+            mgr.fault_stats(fake_pid).is_none(),
+            "Nonexistent worker should report no fault stats"
+        );
+    }
+
+    #[test]
+    fn test_fault_stats_accumulate() {
+        let mut stats = FaultStats::default();
+        stats.record(0x1000, PAGE_SIZE, Duration::from_micros(5));
+        stats.record(0x1000, PAGE_SIZE, Duration::from_micros(5));
+        stats.record(0x2000, PAGE_SIZE, Duration::from_millis(2));
+
+        assert_eq!(stats.total_faults, 3);
+        assert_eq!(stats.total_bytes_copied, (3 * PAGE_SIZE) as u64);
+        assert_eq!(stats.unique_pages(), 2, "0x1000 touched twice should count once");
+    }
+
+    #[test]
+    fn test_latency_histogram_buckets() {
+        let mut hist = LatencyHistogram::default();
+        hist.record(Duration::from_nanos(500)); // < 1us
+        hist.record(Duration::from_micros(5)); // < 10us
+        hist.record(Duration::from_millis(50)); // >= 10ms
+
+        let counts = hist.counts();
+        assert_eq!(counts[0], 1);
+        assert_eq!(counts[1], 1);
+        assert_eq!(counts[5], 1);
+        assert_eq!(counts.iter().sum::<u64>(), 3);
+    }
+
+    #[test]
+    fn test_enable_event_log_nonexistent_worker_errors() {
+        let mgr = SnapshotManager::new().unwrap();
+        let fake_pid = Pid::from_raw(99999);
+        assert!(mgr.enable_event_log(fake_pid, 16).is_err());
+    }
+
+    #[test]
+    fn test_flush_event_log_nonexistent_worker_errors() {
+        let mgr = SnapshotManager::new().unwrap();
+        let fake_pid = Pid::from_raw(99999);
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("events.log");
+        assert!(mgr.flush_event_log(fake_pid, &path).is_err());
+    }
+
+    #[test]
+    fn test_fault_event_log_ring_buffer_drops_oldest() {
+        let mut log = FaultEventLog::new(2);
+        for i in 0..3 {
+            log.push(FaultEvent {
+                timestamp_ns: i,
+                page_addr: i as usize * PAGE_SIZE,
+                kind: FaultEventKind::Missing,
+                latency_ns: 0,
+            });
+        }
+        assert_eq!(log.events.len(), 2, "Ring buffer should stay at capacity");
+        assert_eq!(
+            log.events.front().unwrap().timestamp_ns,
+            1,
+            "Oldest event should have been dropped"
+        );
+    }
+
     // =========================================================================
     // SCM_RIGHTS Tests (require actual socket, basic validation only)
     // =========================================================================
@@ -779,4 +3374,147 @@ mod tests {
         let recovered = i32::from_le_bytes(bytes);
         assert_eq!(pid, recovered);
     }
+
+    // =========================================================================
+    // Golden Snapshot Persistence Tests
+    // =========================================================================
+
+    #[test]
+    fn test_parse_build_id_note_round_trip() {
+        let build_id = vec![0xAA, 0xBB, 0xCC, 0xDD, 0x01];
+        let mut note = Vec::new();
+        note.extend_from_slice(&4u32.to_le_bytes()); // namesz ("GNU\0")
+        note.extend_from_slice(&(build_id.len() as u32).to_le_bytes()); // descsz
+        note.extend_from_slice(&3u32.to_le_bytes()); // NT_GNU_BUILD_ID
+        note.extend_from_slice(b"GNU\0");
+        note.extend_from_slice(&build_id);
+        note.resize(note.len() + 3, 0); // pad descsz to 4-byte alignment
+
+        assert_eq!(parse_build_id_note(&note), Some(build_id));
+    }
+
+    #[test]
+    fn test_parse_build_id_note_ignores_other_notes() {
+        let mut note = Vec::new();
+        note.extend_from_slice(&4u32.to_le_bytes());
+        note.extend_from_slice(&4u32.to_le_bytes());
+        note.extend_from_slice(&1u32.to_le_bytes()); // NT_GNU_ABI_TAG, not a build-id
+        note.extend_from_slice(b"GNU\0");
+        note.extend_from_slice(&[0u8; 4]);
+
+        assert_eq!(parse_build_id_note(&note), None);
+    }
+
+    #[test]
+    fn test_read_build_id_rejects_non_elf_file() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("not-an-elf");
+        fs::write(&path, b"not an ELF file at all").unwrap();
+        assert_eq!(read_build_id(&path), None);
+    }
+
+    #[test]
+    fn test_golden_fingerprint_none_without_libpython() {
+        let regions = vec![MemoryRegion {
+            start: 0x1000,
+            end: 0x2000,
+            len: 0x1000,
+            perms: "rw-p".to_string(),
+            name: "[heap]".to_string(),
+        }];
+        assert!(GoldenFingerprint::capture(&regions).is_none());
+    }
+
+    #[test]
+    fn test_golden_fingerprint_uses_libpython_path() {
+        let regions = vec![MemoryRegion {
+            start: 0x1000,
+            end: 0x2000,
+            len: 0x1000,
+            perms: "rw-p".to_string(),
+            name: "/usr/lib/libpython3.12.so.1.0".to_string(),
+        }];
+        let fingerprint = GoldenFingerprint::capture(&regions).unwrap();
+        assert_eq!(fingerprint.libpython_path, "/usr/lib/libpython3.12.so.1.0");
+        assert!(fingerprint.build_id.is_empty(), "no real file to read a build-id from");
+    }
+
+    #[test]
+    fn test_persist_and_load_golden_round_trip() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("golden.snapshot");
+
+        let regions = vec![MemoryRegion {
+            start: 0x1000,
+            end: 0x3000,
+            len: 0x2000,
+            perms: "rw-p".to_string(),
+            name: "[heap]".to_string(),
+        }];
+        let mut golden_pages = HashMap::new();
+        golden_pages.insert(0x1000, vec![0x11; PAGE_SIZE]);
+        golden_pages.insert(0x2000, vec![0x22; PAGE_SIZE]);
+        let fingerprint = GoldenFingerprint {
+            libpython_path: "/usr/lib/libpython3.12.so.1.0".to_string(),
+            build_id: vec![0xDE, 0xAD, 0xBE, 0xEF],
+        };
+
+        let mgr = SnapshotManager::new_with_golden_persist(path.clone()).unwrap();
+        mgr.persist_golden(Pid::from_raw(1), &regions, &golden_pages, &fingerprint)
+            .unwrap();
+
+        let loaded = SnapshotManager::load_golden(&path).unwrap();
+        assert_eq!(loaded.fingerprint, fingerprint);
+        assert_eq!(loaded.regions.len(), 1);
+        assert_eq!(loaded.regions[0].start, 0x1000);
+        assert_eq!(loaded.golden_pages, golden_pages);
+    }
+
+    #[test]
+    fn test_load_golden_rejects_bad_magic() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("bogus.snapshot");
+        fs::write(&path, b"not a golden snapshot file").unwrap();
+
+        assert!(SnapshotManager::load_golden(&path).is_err());
+    }
+
+    #[test]
+    fn test_new_with_golden_persist_warm_starts_from_existing_file() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("golden.snapshot");
+
+        let regions = vec![MemoryRegion {
+            start: 0x5000,
+            end: 0x6000,
+            len: 0x1000,
+            perms: "rw-p".to_string(),
+            name: "[heap]".to_string(),
+        }];
+        let mut golden_pages = HashMap::new();
+        golden_pages.insert(0x5000, vec![0x33; PAGE_SIZE]);
+        let fingerprint = GoldenFingerprint {
+            libpython_path: "/usr/lib/libpython3.12.so.1.0".to_string(),
+            build_id: vec![],
+        };
+
+        let seeding_mgr = SnapshotManager::new_with_golden_persist(path.clone()).unwrap();
+        seeding_mgr
+            .persist_golden(Pid::from_raw(1), &regions, &golden_pages, &fingerprint)
+            .unwrap();
+
+        let warm_mgr = SnapshotManager::new_with_golden_persist(path).unwrap();
+        let loaded = warm_mgr.loaded_golden.as_ref().expect("should have loaded persisted snapshot");
+        assert_eq!(loaded.fingerprint, fingerprint);
+        assert_eq!(loaded.golden_pages, golden_pages);
+    }
+
+    #[test]
+    fn test_new_with_golden_persist_missing_file_is_not_an_error() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("does-not-exist.snapshot");
+
+        let mgr = SnapshotManager::new_with_golden_persist(path).unwrap();
+        assert!(mgr.loaded_golden.is_none());
+    }
 }
@@ -7,11 +7,28 @@
 //! Workers fork from Zygote which has old code in memory.
 //! Changed files on disk won't be seen unless we recycle the Zygote.
 //! This module respawns the entire test session on each change.
+//!
+//! The *discovery* side doesn't pay that same cost, though: the caller
+//! threads an `incremental::IncrementalState` through repeated
+//! `run_session` calls (see `main.rs`), so a change event only re-parses
+//! the file(s) whose content actually changed rather than re-walking and
+//! re-parsing the whole project tree - see `IncrementalState::rescan`.
+//!
+//! This loop also honors `signals::SHUTDOWN_REQUESTED` so Ctrl+C exits
+//! cleanly, and listens for a plain Enter keypress to force a full rerun
+//! on demand even when nothing's changed on disk.
+//!
+//! Changed paths are filtered through a real gitignore matcher (see
+//! `ignorefile::WatchIgnore`) built once at startup, rather than a
+//! hardcoded list of substrings to skip.
 
+use crate::ignorefile::WatchIgnore;
+use crate::signals;
 use anyhow::Result;
-use crossbeam_channel::{unbounded, Receiver};
+use crossbeam_channel::{select, unbounded, Receiver};
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
 use std::ffi::OsStr;
+use std::io::BufRead;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
@@ -30,7 +47,7 @@ pub fn clear_screen() {
 ///
 pub fn start_watch_loop<F>(project_root: &Path, mut run_session: F) -> Result<()>
 where
-    F: FnMut() -> Result<()>,
+    F: FnMut(&[PathBuf]) -> Result<()>,
 {
     let (tx, rx) = unbounded();
 
@@ -47,50 +64,91 @@ where
     // Watch the project directory recursively
     watcher.watch(project_root, RecursiveMode::Recursive)?;
 
+    let keypress_rx = spawn_keypress_listener();
+    let watch_ignore = WatchIgnore::load(project_root);
+
     eprintln!(
         "[tach] 👁  Watching for changes in {}",
         project_root.display()
     );
-    eprintln!("[tach] Press Ctrl+C to stop.\n");
+    eprintln!("[tach] Press Ctrl+C to stop, Enter to force a full rerun.\n");
 
-    // Initial run
-    if let Err(e) = run_session() {
+    // Initial run: no changed paths yet, so run everything
+    if let Err(e) = run_session(&[]) {
         eprintln!("[tach] Initial run failed: {}", e);
     }
 
     // Event loop
     loop {
-        // Wait for first event
-        match rx.recv() {
-            Ok(first_event) => {
-                // Collect affected paths
-                let mut changed_paths = collect_python_paths(&first_event);
-
-                // Debounce: accumulate events until 100ms of silence
-                while let Ok(event) = rx.recv_timeout(Duration::from_millis(100)) {
-                    changed_paths.extend(collect_python_paths(&event));
-                }
+        if signals::shutdown_requested() {
+            break;
+        }
 
-                // Filter: only .py file changes trigger re-run
-                if changed_paths.is_empty() {
-                    continue;
+        select! {
+            recv(rx) -> msg => match msg {
+                Ok(first_event) => {
+                    // Collect affected paths
+                    let mut changed_paths = collect_python_paths(&first_event, &watch_ignore);
+
+                    // Debounce: accumulate events until 100ms of silence
+                    while let Ok(event) = rx.recv_timeout(Duration::from_millis(100)) {
+                        changed_paths.extend(collect_python_paths(&event, &watch_ignore));
+                    }
+
+                    // Filter: only .py file changes trigger re-run
+                    if changed_paths.is_empty() {
+                        continue;
+                    }
+
+                    // === CRITICAL: Full Session Recycle ===
+                    // This respawns the Zygote to pick up new source code
+                    clear_screen();
+                    eprintln!(
+                        "[tach] 🔄 Change detected in {} file(s). Reloading...\n",
+                        changed_paths.len()
+                    );
+
+                    // Relativize to match `RunnableTest.file_path`, which is stored
+                    // relative to `project_root` (see `discovery::discover`).
+                    let relative_paths: Vec<PathBuf> = changed_paths
+                        .iter()
+                        .map(|p| {
+                            p.strip_prefix(project_root)
+                                .unwrap_or(p)
+                                .to_path_buf()
+                        })
+                        .collect();
+
+                    if let Err(e) = run_session(&relative_paths) {
+                        eprintln!("[tach] Run failed: {}", e);
+                    }
                 }
-
-                // === CRITICAL: Full Session Recycle ===
-                // This respawns the Zygote to pick up new source code
-                clear_screen();
-                eprintln!(
-                    "[tach] 🔄 Change detected in {} file(s). Reloading...\n",
-                    changed_paths.len()
-                );
-
-                if let Err(e) = run_session() {
-                    eprintln!("[tach] Run failed: {}", e);
+                Err(_) => {
+                    // Channel closed - watcher dropped
+                    break;
                 }
-            }
-            Err(_) => {
-                // Channel closed - watcher dropped
-                break;
+            },
+            recv(keypress_rx) -> msg => match msg {
+                Ok(()) => {
+                    // An empty changed-paths slice means "run everything" to
+                    // every `run_session` caller (see `affected_by_changes`),
+                    // so reuse that to force a full rerun on demand instead
+                    // of whatever subset the last diff narrowed things to.
+                    clear_screen();
+                    eprintln!("[tach] ⏩ Forcing a full rerun...\n");
+                    if let Err(e) = run_session(&[]) {
+                        eprintln!("[tach] Run failed: {}", e);
+                    }
+                }
+                Err(_) => {
+                    // stdin closed (e.g. non-interactive session) - keep
+                    // watching for file changes, just without the keypress.
+                }
+            },
+            default(Duration::from_millis(200)) => {
+                // Nothing happened this tick; loop back around so the
+                // `shutdown_requested()` check above is polled even while
+                // idle, instead of blocking on `rx`/`keypress_rx` forever.
             }
         }
     }
@@ -98,79 +156,55 @@ where
     Ok(())
 }
 
-/// Extract Python file paths from a notify event
-fn collect_python_paths(event: &Event) -> Vec<PathBuf> {
+/// Spawn a background thread that reads lines from stdin and emits a signal
+/// each time Enter is pressed, so the watch loop can offer "press Enter to
+/// force a full rerun" without raw terminal mode (which is only entered for
+/// the `--debug` TTY proxy - see `debugger.rs`). Stops quietly once stdin
+/// closes (EOF) or the watch loop exits and drops its receiver.
+fn spawn_keypress_listener() -> Receiver<()> {
+    let (tx, rx) = unbounded();
+    std::thread::spawn(move || {
+        let stdin = std::io::stdin();
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match stdin.lock().read_line(&mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    if tx.send(()).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+    rx
+}
+
+/// Extract Python file paths from a notify event, skipping anything matched
+/// by `ignore` (see `WatchIgnore`).
+fn collect_python_paths(event: &Event, ignore: &WatchIgnore) -> Vec<PathBuf> {
     event
         .paths
         .iter()
         .filter(|p| p.extension() == Some(OsStr::new("py")))
-        .filter(|p| !is_ignored_path(p))
+        .filter(|p| !ignore.is_ignored(p))
         .cloned()
         .collect()
 }
 
-/// Check if a path should be ignored
-fn is_ignored_path(path: &Path) -> bool {
-    let path_str = path.to_string_lossy();
-
-    // Ignore common patterns
-    path_str.contains("__pycache__")
-        || path_str.contains(".pytest_cache")
-        || path_str.contains(".mypy_cache")
-        || path_str.contains(".git")
-        || path_str.contains(".venv")
-        || path_str.contains("/venv/")
-        || path_str.contains("/env/")
-        || path_str.contains("/node_modules/")
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_is_ignored_path() {
-        assert!(is_ignored_path(Path::new("foo/__pycache__/bar.py")));
-        assert!(is_ignored_path(Path::new(".git/hooks/pre-commit.py")));
-        assert!(is_ignored_path(Path::new(".venv/lib/python3.10/site.py")));
-        assert!(!is_ignored_path(Path::new("tests/test_foo.py")));
-        assert!(!is_ignored_path(Path::new("src/models.py")));
-    }
-
-    #[test]
-    fn test_is_ignored_pytest_cache() {
-        assert!(is_ignored_path(Path::new(
-            "project/.pytest_cache/v/cache.py"
-        )));
-    }
-
-    #[test]
-    fn test_is_ignored_mypy_cache() {
-        assert!(is_ignored_path(Path::new(
-            "project/.mypy_cache/3.10/module.py"
-        )));
-    }
-
-    #[test]
-    fn test_is_ignored_venv_variations() {
-        assert!(is_ignored_path(Path::new("/home/user/.venv/lib/site.py")));
-        assert!(is_ignored_path(Path::new("/project/venv/bin/activate.py")));
-        assert!(is_ignored_path(Path::new("/project/env/lib/python.py")));
-    }
-
-    #[test]
-    fn test_is_ignored_node_modules() {
-        assert!(is_ignored_path(Path::new(
-            "/project/node_modules/something.py"
-        )));
-    }
-
-    #[test]
-    fn test_not_ignored_normal_paths() {
-        assert!(!is_ignored_path(Path::new("tests/test_unit.py")));
-        assert!(!is_ignored_path(Path::new("src/app/models.py")));
-        assert!(!is_ignored_path(Path::new("conftest.py")));
-        assert!(!is_ignored_path(Path::new("test_integration.py")));
+    /// A `WatchIgnore` over a fresh, empty temp directory - no `.gitignore`/
+    /// `.tachignore` of its own, so matching falls through to the built-in
+    /// default ruleset (see `ignorefile::default_is_ignored`), matching what
+    /// these tests exercised back when that ruleset was hardcoded here.
+    fn fallback_ignore() -> WatchIgnore {
+        let dir = std::env::temp_dir().join(format!("tach_watch_test_{}", std::process::id()));
+        let _ = std::fs::create_dir_all(&dir);
+        WatchIgnore::load(&dir)
     }
 
     #[test]
@@ -182,7 +216,7 @@ mod tests {
 
     #[test]
     fn test_collect_python_paths_filters_non_py() {
-        use notify::event::{CreateKind, ModifyKind};
+        use notify::event::ModifyKind;
 
         let event = Event {
             kind: notify::EventKind::Modify(ModifyKind::Data(notify::event::DataChange::Content)),
@@ -195,7 +229,7 @@ mod tests {
             attrs: Default::default(),
         };
 
-        let paths = collect_python_paths(&event);
+        let paths = collect_python_paths(&event, &fallback_ignore());
         assert_eq!(paths.len(), 2);
         assert!(paths.iter().all(|p| p.extension().unwrap() == "py"));
     }
@@ -214,7 +248,7 @@ mod tests {
             attrs: Default::default(),
         };
 
-        let paths = collect_python_paths(&event);
+        let paths = collect_python_paths(&event, &fallback_ignore());
         assert_eq!(paths.len(), 1);
         assert_eq!(paths[0], PathBuf::from("tests/test_good.py"));
     }
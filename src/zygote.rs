@@ -1,8 +1,9 @@
 //! Zygote: Fork server with dual-channel IPC
 
 use crate::environment::find_site_packages;
-use crate::logcapture::redirect_output;
+use crate::logcapture::{redirect_output, LogCapture};
 use crate::protocol::{encode_with_length, TestPayload, TestResult, CMD_EXIT, CMD_FORK, MSG_READY};
+use crate::provenance::FileOpenTracker;
 use crate::snapshot::send_fd;
 use anyhow::Result;
 use nix::sys::signal::{signal, SigHandler, Signal};
@@ -44,12 +45,21 @@ fn init_snapshot_mode(sock_path: &str) -> PyResult<bool> {
 
     let pid = std::process::id() as i32;
 
-    // 1. Create UFFD
+    // 1. Create UFFD. Try to opt into write-protect fault support first
+    // (lets the Supervisor do incremental dirty-page resets instead of a
+    // full MADV_DONTNEED every time); if the running kernel doesn't support
+    // it, retry without the feature rather than failing the whole worker.
     let uffd = match UffdBuilder::new()
         .close_on_exec(true)
         .non_blocking(false)
+        .require_features(userfaultfd::FeatureFlags::PAGEFAULT_FLAG_WP)
         .create()
-    {
+        .or_else(|_| {
+            UffdBuilder::new()
+                .close_on_exec(true)
+                .non_blocking(false)
+                .create()
+        }) {
         Ok(u) => u,
         Err(e) => {
             eprintln!(
@@ -155,16 +165,29 @@ pub fn inject_tach_rust_module(py: Python) -> PyResult<()> {
     // Add functions to module
     tach_mod.add_function(wrap_pyfunction!(init_snapshot_mode, &tach_mod)?)?;
     tach_mod.add_function(wrap_pyfunction!(reset_memory, &tach_mod)?)?;
+    tach_mod.add_class::<crate::loader::RegistryImporter>()?;
 
     // Inject into sys.modules so 'import tach_rust' works
     let sys = py.import("sys")?;
     sys.getattr("modules")?.set_item("tach_rust", tach_mod)?;
 
+    // Make `import foo.bar` resolve straight from the registry, no
+    // explicit get_module/load_module call sites required.
+    crate::loader::install_meta_path_importer(py)?;
+
     Ok(())
 }
 
 /// Zygote with separate command and result channels
-pub fn entrypoint(cmd_socket: UnixStream, result_socket: UnixStream) -> Result<()> {
+///
+/// `log_capture` is handed in (rather than re-created here) because it must
+/// be the same instance the Supervisor built: the Zygote only has live fds
+/// for its slots because it inherited them at the fork that created it.
+pub fn entrypoint(
+    cmd_socket: UnixStream,
+    result_socket: UnixStream,
+    log_capture: LogCapture,
+) -> Result<()> {
     // DEAD MAN'S SWITCH (Phase 4.2): If supervisor dies, we die
     // This is the ultimate safety net - no orphaned zygotes
     // Must be the FIRST thing we do, before any resource allocation
@@ -312,12 +335,19 @@ except Exception as e:
                 let (parent_sock, child_sock) = UnixStream::pair()?;
                 let result_tx = result_tx.clone();
 
+                // Self-pipe: lets the parent notice a worker that dies
+                // during post-fork setup instead of only finding out once
+                // its result never arrives.
+                let (spawn_read, spawn_write) = crate::snapshot::create_selfpipe()?;
+
                 match unsafe { fork() } {
                     Ok(ForkResult::Parent { child }) => {
                         drop(child_sock);
                         // Send PID back on command socket
                         cmd_socket.write_all(&child.as_raw().to_le_bytes())?;
 
+                        let spawn_watch_tx = result_tx.clone();
+
                         // Spawn thread to collect this worker's result
                         thread::spawn(move || {
                             let mut socket = parent_sock;
@@ -334,9 +364,60 @@ except Exception as e:
                                 }
                             }
                         });
+
+                        // Spawn thread to wait for the worker to either
+                        // signal it's live (self-pipe closed) or report why
+                        // it never made it that far. Deliberately async and
+                        // decoupled from the cmd_socket pid handshake above:
+                        // on success this can take as long as the
+                        // SIGSTOP/golden-capture/SIGCONT handshake in
+                        // `post_fork_init`, which must not block dispatching
+                        // the *next* test. On failure we can't rely on the
+                        // worker ever sending its own `TestResult`, so we
+                        // forge one here with the decoded errno - the
+                        // Scheduler's result loop can't tell it apart from a
+                        // worker-reported result, and surfaces the real
+                        // failing syscall instead of a generic timeout/crash.
+                        let test_id = payload.test_id;
+                        thread::spawn(move || {
+                            if let Err(e) = crate::snapshot::wait_for_spawn(spawn_read, spawn_write)
+                            {
+                                eprintln!("[zygote] Worker PID {} failed to spawn: {}", child, e);
+                                // EACCES/EPERM out of post-fork setup almost
+                                // always means `isolation::setup_filesystem`
+                                // hit a denied mount (e.g. a `read_paths`/
+                                // `write_paths` entry it has no permission to
+                                // touch) - surface that as STATUS_ERROR,
+                                // distinct from the generic
+                                // STATUS_HARNESS_ERROR bucket used for other
+                                // pre-test setup failures.
+                                let status = if matches!(e.0, nix::errno::Errno::EACCES | nix::errno::Errno::EPERM)
+                                {
+                                    crate::protocol::STATUS_ERROR
+                                } else {
+                                    crate::protocol::STATUS_HARNESS_ERROR
+                                };
+                                let result = TestResult {
+                                    test_id,
+                                    status,
+                                    duration_ns: 0,
+                                    message: format!(
+                                        "worker failed during post-fork setup before running any test: {}",
+                                        e
+                                    ),
+                                    coverage: None,
+                                    read_files: Vec::new(),
+                                };
+                                if let Ok(bytes) = encode_with_length(&result) {
+                                    let _ = spawn_watch_tx.send(bytes);
+                                }
+                            }
+                        });
                     }
                     Ok(ForkResult::Child) => {
                         drop(parent_sock);
+                        drop(spawn_read);
+                        let spawn_write_fd = spawn_write.as_raw_fd();
 
                         // 0. DEAD MAN'S SWITCH (Phase 4.2): If Zygote dies, worker dies
                         // Must be FIRST - before any resource allocation
@@ -352,17 +433,31 @@ except Exception as e:
                         // 2. ISOLATE filesystem and network (Iron Dome)
                         // CRITICAL: Fail hard if isolation fails to protect the host
                         let project_root = std::env::current_dir().unwrap_or_default();
-                        if let Err(e) =
-                            crate::isolation::setup_filesystem(payload.test_id, &project_root)
-                        {
+                        if let Err(e) = crate::isolation::setup_filesystem(
+                            payload.test_id,
+                            &project_root,
+                            &payload.permissions,
+                        ) {
                             eprintln!("[worker] CRITICAL: Isolation failed. Aborting to protect host. Error: {:#}", e);
-                            std::process::exit(1);
+                            crate::snapshot::report_spawn_error(spawn_write_fd, &e);
                         }
 
+                        // 2.5. Start watching this worker's own mount namespace for
+                        // FAN_OPEN events (see `provenance`) - scoped to just this
+                        // worker since isolation already put it in a private
+                        // namespace above. Best-effort: a worker that can't set
+                        // this up (e.g. missing CAP_SYS_ADMIN) still runs the
+                        // test, just without a provenance capture for it.
+                        let file_tracker = FileOpenTracker::start().ok();
+
                         // 3. Re-chdir to pick up the overlay mount on project root
                         // Without this, the CWD handle points to the old mount
                         let _ = std::env::set_current_dir(&project_root);
 
+                        // 3.5. Close every other slot's inherited log fd -
+                        // this worker only gets to touch its own.
+                        log_capture.seal_to_slot(payload.log_slot);
+
                         // 4. Redirect stdout/stderr to memfd
                         if payload.log_fd >= 0 {
                             let _ = redirect_output(payload.log_fd);
@@ -392,8 +487,13 @@ except Exception as e:
                         })
                         .ok(); // Continue even if snapshot fails (graceful degradation)
 
+                        // Setup is complete - close the self-pipe's write end
+                        // so the parent's `wait_for_spawn` sees EOF and knows
+                        // this worker made it live.
+                        drop(spawn_write);
+
                         // 7. Run test
-                        let result = run_worker(&payload);
+                        let result = run_worker(&payload, file_tracker);
 
                         // 4. Flush and send result
                         let _ = std::io::stdout().flush();
@@ -418,8 +518,9 @@ except Exception as e:
     Ok(())
 }
 
-fn run_worker(payload: &TestPayload) -> TestResult {
+fn run_worker(payload: &TestPayload, file_tracker: Option<FileOpenTracker>) -> TestResult {
     use crate::protocol::STATUS_HARNESS_ERROR;
+    use crate::provenance::Capture;
 
     let start = Instant::now();
 
@@ -446,18 +547,37 @@ fn run_worker(payload: &TestPayload) -> TestResult {
 
     let duration_ns = start.elapsed().as_nanos() as u64;
 
+    // Drain whatever this worker opened while the test ran. A tracker that
+    // never started just leaves `read_files` empty - the scheduler simply
+    // won't have provenance for this test id yet, same as a cold cache.
+    // `Capture::Truncated` rides over the wire as `TRUNCATED_SENTINEL` (see
+    // `provenance::Capture::into_wire`) so the supervisor can still record
+    // "always run this one" rather than trusting a partial list.
+    let read_files = file_tracker
+        .map(FileOpenTracker::drain)
+        .map(Capture::into_wire)
+        .unwrap_or_default();
+
     match result {
         Ok((status, _, message)) => TestResult {
             test_id: payload.test_id,
             status,
             duration_ns,
             message,
+            // The harness module (`tach_harness`) isn't vendored in this
+            // tree, so there's nowhere to install the `sys.settrace` hook
+            // that would populate this - `coverage` module below merges
+            // reports when a harness does send them.
+            coverage: None,
+            read_files,
         },
         Err(e) => TestResult {
             test_id: payload.test_id,
             status: STATUS_HARNESS_ERROR,
             duration_ns,
             message: format!("PyO3 Error: {}", e),
+            coverage: None,
+            read_files,
         },
     }
 }